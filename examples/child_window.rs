@@ -55,7 +55,7 @@ fn main() {
                     windows.clear();
                     *control_flow = ControlFlow::Exit;
                 }
-                WindowEvent::CursorEntered { device_id: _ } => {
+                WindowEvent::CursorEntered { .. } => {
                     // On x11, println when the cursor entered in a window even if the child window is created
                     // by some key inputs.
                     // the child windows are always placed at (0, 0) with size (200, 200) in the parent window,
@@ -0,0 +1,100 @@
+#![allow(clippy::single_match)]
+
+use std::time::Duration;
+
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{ElementState, Event, KeyEvent, WindowEvent},
+    event_loop::EventLoop,
+    window::{CursorFrame, CursorIcon, CustomCursor, WindowBuilder},
+};
+
+#[path = "util/fill.rs"]
+mod fill;
+
+/// The pixels of a square ring.
+fn ring(size: u32, red: u8, green: u8, blue: u8) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let border = x < 3 || y < 3 || x >= size - 3 || y >= size - 3;
+            let alpha = if border { 255 } else { 0 };
+            rgba.extend_from_slice(&[red, green, blue, alpha]);
+        }
+    }
+    rgba
+}
+
+/// A cursor drawing a square ring, with its hotspot at the center.
+fn ring_cursor(size: u32, red: u8, green: u8, blue: u8) -> CustomCursor {
+    CustomCursor::from_rgba(ring(size, red, green, blue), size, size, size / 2, size / 2).unwrap()
+}
+
+/// A cursor drawing a ring changing its color.
+fn animated_ring_cursor(size: u32) -> CustomCursor {
+    let frames = [(255, 0, 0), (0, 255, 0), (0, 0, 255)]
+        .into_iter()
+        .map(|(red, green, blue)| {
+            let rgba = ring(size, red, green, blue);
+            CursorFrame::from_rgba(rgba, size, size, Duration::from_millis(250)).unwrap()
+        })
+        .collect();
+
+    CustomCursor::from_frames(frames, size / 2, size / 2).unwrap()
+}
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    window.set_title("Press any key to change the cursor");
+
+    let cursors = [
+        ring_cursor(24, 255, 0, 0),
+        ring_cursor(32, 0, 0, 255),
+        animated_ring_cursor(32),
+    ];
+    let mut cursor_idx = 0;
+
+    event_loop.run(move |event, _, control_flow| {
+        control_flow.set_wait();
+
+        match event {
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                match cursors.get(cursor_idx) {
+                    Some(cursor) => {
+                        println!("Setting cursor to {cursor:?}");
+                        window.set_custom_cursor(cursor);
+                    }
+                    None => {
+                        println!("Setting cursor to \"{:?}\"", CursorIcon::Default);
+                        window.set_cursor_icon(CursorIcon::Default);
+                    }
+                }
+                cursor_idx = (cursor_idx + 1) % (cursors.len() + 1);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                control_flow.set_exit();
+            }
+            Event::RedrawRequested(_) => {
+                fill::fill_window(&window);
+            }
+            _ => (),
+        }
+    });
+}
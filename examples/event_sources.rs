@@ -0,0 +1,56 @@
+#![allow(clippy::single_match)]
+
+// Limit this example to only compatible platforms.
+#[cfg(any(x11_platform, wayland_platform))]
+fn main() {
+    use std::time::{Duration, Instant};
+
+    use simple_logger::SimpleLogger;
+    use winit::{
+        event::{Event, WindowEvent},
+        event_loop::EventLoop,
+        platform::event_sources::EventLoopWindowTargetExtEventSources,
+        window::WindowBuilder,
+    };
+
+    #[path = "util/fill.rs"]
+    mod fill;
+
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new()
+        .with_title("Ticking every second")
+        .build(&event_loop)
+        .unwrap();
+
+    let interval = Duration::from_secs(1);
+    let timer = event_loop
+        .register_timer(Instant::now() + interval, Some(interval))
+        .unwrap();
+    let mut ticks = 0;
+
+    event_loop.run(move |event, _, control_flow| {
+        control_flow.set_wait();
+
+        match event {
+            Event::SourceReady(source) if source == timer => {
+                ticks += 1;
+                println!("Tick {ticks}");
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => control_flow.set_exit(),
+            Event::RedrawRequested(_) => {
+                fill::fill_window(&window);
+            }
+            _ => (),
+        }
+    });
+}
+
+#[cfg(not(any(x11_platform, wayland_platform)))]
+fn main() {
+    println!("This platform doesn't support event sources.");
+}
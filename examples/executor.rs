@@ -0,0 +1,74 @@
+#![allow(clippy::single_match)]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{ElementState, Event, KeyEvent, WindowEvent},
+    event_loop::EventLoop,
+    executor::Executor,
+    window::WindowBuilder,
+};
+
+#[path = "util/fill.rs"]
+mod fill;
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new()
+        .with_title("Press a key, then another one")
+        .build(&event_loop)
+        .unwrap();
+
+    let mut executor = Executor::new(&event_loop);
+    let mut events = executor.spawner().window_events(window.id());
+    let exit = Rc::new(Cell::new(false));
+    let exit_clone = exit.clone();
+
+    executor.spawn(async move {
+        let mut pressed = 0;
+        while let Some(event) = events.next().await {
+            match event {
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            logical_key,
+                            state: ElementState::Pressed,
+                            ..
+                        },
+                    ..
+                } => {
+                    pressed += 1;
+                    println!("Key {pressed} was {logical_key:?}");
+                    if pressed == 2 {
+                        println!("Press a key to exit");
+                    }
+                    if pressed == 3 {
+                        break;
+                    }
+                }
+                WindowEvent::CloseRequested => break,
+                _ => (),
+            }
+        }
+
+        exit_clone.set(true);
+    });
+
+    event_loop.run(move |event, _, control_flow| {
+        control_flow.set_wait();
+
+        if let Event::RedrawRequested(_) = event {
+            fill::fill_window(&window);
+        }
+
+        executor.handle_event(event);
+
+        if exit.get() {
+            control_flow.set_exit();
+        }
+    });
+}
@@ -17,7 +17,7 @@ fn main() {
         .build(&event_loop)
         .unwrap();
 
-    println!("Only supported on macOS at the moment.");
+    println!("Only supported on macOS, X11 and Wayland at the moment.");
 
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Wait;
@@ -42,6 +42,17 @@ fn main() {
                         println!("Rotated clockwise {delta}");
                     }
                 }
+                WindowEvent::TouchpadSwipe {
+                    delta,
+                    fingers,
+                    phase,
+                    ..
+                } => {
+                    println!("Swiped with {fingers} fingers {delta:?} ({phase:?})");
+                }
+                WindowEvent::TouchpadHold { fingers, phase, .. } => {
+                    println!("Held {fingers} fingers ({phase:?})");
+                }
                 _ => (),
             }
         } else if let Event::RedrawRequested(_) = event {
@@ -74,6 +74,7 @@ pub(super) fn fill_window(window: &Window) {
             .buffer_mut()
             .expect("Failed to get the softbuffer buffer");
         buffer.fill(DARK_GRAY);
+        window.pre_present_notify();
         buffer
             .present()
             .expect("Failed to present the softbuffer buffer");
@@ -0,0 +1,65 @@
+#![allow(clippy::single_match)]
+
+// Limit this example to only compatible platforms.
+#[cfg(any(x11_platform, wayland_platform))]
+fn main() {
+    use std::{thread::sleep, time::Duration};
+
+    use simple_logger::SimpleLogger;
+    use winit::{
+        event::{Event, WindowEvent},
+        event_loop::EventLoop,
+        platform::pump_events::{EventLoopExtPumpEvents, PumpStatus},
+        window::WindowBuilder,
+    };
+
+    #[path = "util/fill.rs"]
+    mod fill;
+
+    let mut event_loop = EventLoop::new();
+
+    SimpleLogger::new().init().unwrap();
+    let window = WindowBuilder::new()
+        .with_title("A fantastic window!")
+        .build(&event_loop)
+        .unwrap();
+
+    'main: loop {
+        let timeout = Some(Duration::ZERO);
+        let status = event_loop.pump_events(timeout, |event, _, control_flow| {
+            control_flow.set_wait();
+
+            if let Event::WindowEvent { event, .. } = &event {
+                // Print only Window events to reduce noise
+                println!("{event:?}");
+            }
+
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::CloseRequested,
+                    ..
+                } => {
+                    control_flow.set_exit();
+                }
+                Event::RedrawRequested(_) => {
+                    fill::fill_window(&window);
+                }
+                _ => (),
+            }
+        });
+
+        if let PumpStatus::Exit(exit_code) = status {
+            println!("Exited with {exit_code}");
+            break 'main;
+        }
+
+        // Sleep for 1/60 second to simulate application work
+        println!("Update()");
+        sleep(Duration::from_millis(16));
+    }
+}
+
+#[cfg(not(any(x11_platform, wayland_platform)))]
+fn main() {
+    println!("This platform doesn't support pump_events.");
+}
@@ -0,0 +1,218 @@
+//! Types for interacting with the system clipboard.
+//!
+//! The clipboard is accessed through the [`EventLoopWindowTarget`]. Setting the clipboard content
+//! is done synchronously with [`EventLoopWindowTarget::set_clipboard_content`], however reading
+//! it is asynchronous, since the data has to be transferred from another client. Requests
+//! return an [`AsyncRequestSerial`], and the result is delivered later with an
+//! [`Event::Clipboard`] carrying the same serial.
+//!
+//! ```no_run
+//! use winit::clipboard::{ClipboardContent, ClipboardKind, TEXT_MIME_TYPE};
+//! # use winit::event_loop::EventLoop;
+//! # let event_loop = EventLoop::new();
+//!
+//! let content = ClipboardContent::from_text("Hello from winit");
+//! event_loop
+//!     .set_clipboard_content(ClipboardKind::Clipboard, content)
+//!     .unwrap();
+//!
+//! // The data will arrive with `Event::Clipboard(ClipboardEvent::Data { .. })`.
+//! let _serial = event_loop.request_clipboard_data(ClipboardKind::Clipboard, TEXT_MIME_TYPE);
+//! ```
+//!
+//! [`EventLoopWindowTarget`]: crate::event_loop::EventLoopWindowTarget
+//! [`EventLoopWindowTarget::set_clipboard_content`]: crate::event_loop::EventLoopWindowTarget::set_clipboard_content
+//! [`Event::Clipboard`]: crate::event::Event::Clipboard
+
+use std::sync::Arc;
+
+use crate::event_loop::AsyncRequestSerial;
+
+/// The MIME type used for UTF-8 encoded text.
+pub const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// MIME types and legacy target names which are treated as UTF-8 text.
+pub(crate) const TEXT_MIME_TYPES: &[&str] = &[
+    TEXT_MIME_TYPE,
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+/// The selection to operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardKind {
+    /// The regular clipboard, usually driven by the `Ctrl+C`/`Ctrl+V` shortcuts.
+    Clipboard,
+
+    /// The primary selection, usually holding the last selected text and pasted with the middle
+    /// mouse button.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the compositor to support the `zwp_primary_selection_v1`
+    ///   protocol.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    Primary,
+}
+
+/// Content offered to other clients through the clipboard.
+///
+/// The same data could be offered in several representations, each of them identified by its
+/// MIME type. The order of insertion is preserved and is used as the order of preference.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    data: Vec<(String, Arc<[u8]>)>,
+}
+
+impl ClipboardContent {
+    /// Create empty clipboard content.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create clipboard content holding UTF-8 text.
+    ///
+    /// The text is advertised with [`TEXT_MIME_TYPE`], and the common legacy aliases.
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self::new().with_data(TEXT_MIME_TYPE, text.into().into_bytes())
+    }
+
+    /// Add a representation of the content with the given MIME type.
+    ///
+    /// If the MIME type was already present its data is replaced.
+    pub fn with_data(mut self, mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        let mime_type = mime_type.into();
+        let data: Arc<[u8]> = data.into().into();
+        match self.data.iter_mut().find(|(mime, _)| *mime == mime_type) {
+            Some(entry) => entry.1 = data,
+            None => self.data.push((mime_type, data)),
+        }
+        self
+    }
+
+    /// The MIME types of the content, in the order of preference.
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.data.iter().map(|(mime, _)| mime.as_str())
+    }
+
+    /// Get the data for the given MIME type.
+    pub fn get(&self, mime_type: &str) -> Option<&[u8]> {
+        self.data
+            .iter()
+            .find(|(mime, _)| mime == mime_type)
+            .map(|(_, data)| &**data)
+    }
+
+    /// Whether the content is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// All the MIME types to advertise, including the text aliases.
+    pub(crate) fn advertised_mime_types(&self) -> Vec<String> {
+        let mut mime_types: Vec<String> = self.mime_types().map(String::from).collect();
+        if self.text().is_some() {
+            for alias in TEXT_MIME_TYPES {
+                if !mime_types.iter().any(|mime| mime == alias) {
+                    mime_types.push(String::from(*alias));
+                }
+            }
+        }
+        mime_types
+    }
+
+    /// Resolve the data for the MIME type, taking the text aliases into account.
+    pub(crate) fn resolve(&self, mime_type: &str) -> Option<Arc<[u8]>> {
+        self.data
+            .iter()
+            .find(|(mime, _)| mime == mime_type)
+            .map(|(_, data)| data.clone())
+            .or_else(|| {
+                if TEXT_MIME_TYPES.contains(&mime_type) {
+                    self.text()
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn text(&self) -> Option<Arc<[u8]>> {
+        self.data
+            .iter()
+            .find(|(mime, _)| TEXT_MIME_TYPES.contains(&mime.as_str()))
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// Describes the result of a clipboard request or a change of the clipboard ownership.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardEvent {
+    /// The data requested with [`request_clipboard_data`] has arrived.
+    ///
+    /// [`request_clipboard_data`]: crate::event_loop::EventLoopWindowTarget::request_clipboard_data
+    Data {
+        serial: AsyncRequestSerial,
+        kind: ClipboardKind,
+        mime_type: String,
+        data: Vec<u8>,
+    },
+
+    /// The MIME types requested with [`request_clipboard_mime_types`] have arrived.
+    ///
+    /// [`request_clipboard_mime_types`]: crate::event_loop::EventLoopWindowTarget::request_clipboard_mime_types
+    MimeTypes {
+        serial: AsyncRequestSerial,
+        kind: ClipboardKind,
+        mime_types: Vec<String>,
+    },
+
+    /// The request failed, because the selection was empty, the requested MIME type isn't
+    /// offered, or the owner of the selection failed to transfer the data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The request also fails when the owner stops responding for 5 seconds.
+    RequestFailed {
+        serial: AsyncRequestSerial,
+        kind: ClipboardKind,
+    },
+
+    /// The content previously set by the application was replaced by another client.
+    ContentLost(ClipboardKind),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_aliases() {
+        let content = ClipboardContent::from_text("winit").with_data("image/png", vec![1, 2]);
+        assert_eq!(content.get(TEXT_MIME_TYPE), Some(&b"winit"[..]));
+        assert_eq!(content.get("UTF8_STRING"), None);
+        assert_eq!(
+            content.resolve("UTF8_STRING").as_deref(),
+            Some(&b"winit"[..])
+        );
+        assert_eq!(content.resolve("image/png").as_deref(), Some(&[1, 2][..]));
+        assert_eq!(content.resolve("image/jpeg"), None);
+
+        let advertised = content.advertised_mime_types();
+        assert_eq!(advertised[0], TEXT_MIME_TYPE);
+        assert_eq!(advertised[1], "image/png");
+        assert!(advertised.iter().any(|mime| mime == "STRING"));
+    }
+
+    #[test]
+    fn replace_data() {
+        let content = ClipboardContent::new()
+            .with_data("text/html", "<b>a</b>")
+            .with_data("text/html", "<b>b</b>");
+        assert_eq!(content.mime_types().count(), 1);
+        assert_eq!(content.get("text/html"), Some(&b"<b>b</b>"[..]));
+        assert!(ClipboardContent::new().is_empty());
+    }
+}
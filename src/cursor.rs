@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::{error::Error, fmt};
+
+use crate::icon::{BadIcon, RgbaIcon};
+
+#[derive(Debug)]
+/// An error produced when creating a [`CustomCursor`] with invalid arguments.
+pub enum BadCursor {
+    /// Produced when the `rgba` argument isn't a valid image, see [`BadIcon`].
+    BadImage(BadIcon),
+    /// Produced when the hotspot lies outside of the image.
+    HotspotOutOfBounds {
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    },
+    /// Produced when an animated cursor is created without any frames.
+    NoFrames,
+}
+
+impl fmt::Display for BadCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadCursor::BadImage(e) => fmt::Display::fmt(e, f),
+            BadCursor::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            } => write!(f,
+                "The specified hotspot ({hotspot_x:?}, {hotspot_y:?}) is outside of the cursor image of {width:?}x{height:?} pixels.",
+            ),
+            BadCursor::NoFrames => write!(f, "The animated cursor has no frames."),
+        }
+    }
+}
+
+impl Error for BadCursor {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BadCursor::BadImage(e) => Some(e),
+            BadCursor::HotspotOutOfBounds { .. } | BadCursor::NoFrames => None,
+        }
+    }
+}
+
+impl From<BadIcon> for BadCursor {
+    fn from(error: BadIcon) -> Self {
+        BadCursor::BadImage(error)
+    }
+}
+
+/// A frame of an animated [`CustomCursor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorFrame {
+    pub(crate) image: RgbaIcon,
+    pub(crate) delay: Duration,
+}
+
+impl CursorFrame {
+    /// Creates a frame from 32bpp RGBA data, which is shown for `delay` before the next one.
+    ///
+    /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+    /// `rgba.len() / 4`. Otherwise, this will return a `BadCursor` error. The `delay` is rounded
+    /// down to milliseconds, and is at least a millisecond long.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        delay: Duration,
+    ) -> Result<Self, BadCursor> {
+        Ok(CursorFrame {
+            image: RgbaIcon::from_rgba(rgba, width, height)?,
+            delay: Duration::from_millis(delay.as_millis().max(1) as u64),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CursorImage {
+    /// The frames of the cursor, there's only one unless the cursor is animated.
+    pub(crate) frames: Vec<CursorFrame>,
+    pub(crate) hotspot_x: u32,
+    pub(crate) hotspot_y: u32,
+}
+
+/// A cursor image, which could be used instead of the named [`CursorIcon`]s.
+///
+/// The cursor is cheap to clone, and the resources backing it are created once per event loop,
+/// the first time it's set on one of its windows.
+///
+/// [`CursorIcon`]: crate::window::CursorIcon
+#[derive(Clone)]
+pub struct CustomCursor {
+    pub(crate) inner: Arc<CursorImage>,
+}
+
+impl fmt::Debug for CustomCursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        formatter
+            .debug_struct("CustomCursor")
+            .field("width", &self.inner.frames[0].image.width)
+            .field("height", &self.inner.frames[0].image.height)
+            .field("frames", &self.inner.frames.len())
+            .field("hotspot_x", &self.inner.hotspot_x)
+            .field("hotspot_y", &self.inner.hotspot_y)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for CustomCursor {}
+
+impl CustomCursor {
+    /// Creates a cursor from 32bpp RGBA data, with its hotspot at (`hotspot_x`, `hotspot_y`)
+    /// pixels from the top-left corner of the image.
+    ///
+    /// The length of `rgba` must be divisible by 4, `width * height` must equal `rgba.len() / 4`,
+    /// and the hotspot must lie within the image. Otherwise, this will return a `BadCursor`
+    /// error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<Self, BadCursor> {
+        let frame = CursorFrame {
+            image: RgbaIcon::from_rgba(rgba, width, height)?,
+            delay: Duration::ZERO,
+        };
+        Self::from_frames(vec![frame], hotspot_x, hotspot_y)
+    }
+
+    /// Creates an animated cursor looping over the `frames`, with its hotspot at
+    /// (`hotspot_x`, `hotspot_y`) pixels from the top-left corner of every frame.
+    ///
+    /// There must be at least one frame, and the hotspot must lie within all of them. Otherwise,
+    /// this will return a `BadCursor` error.
+    pub fn from_frames(
+        frames: Vec<CursorFrame>,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<Self, BadCursor> {
+        if frames.is_empty() {
+            return Err(BadCursor::NoFrames);
+        }
+
+        for CursorFrame { image, .. } in &frames {
+            if hotspot_x >= image.width || hotspot_y >= image.height {
+                return Err(BadCursor::HotspotOutOfBounds {
+                    width: image.width,
+                    height: image.height,
+                    hotspot_x,
+                    hotspot_y,
+                });
+            }
+        }
+
+        Ok(CustomCursor {
+            inner: Arc::new(CursorImage {
+                frames,
+                hotspot_x,
+                hotspot_y,
+            }),
+        })
+    }
+}
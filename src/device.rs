@@ -0,0 +1,128 @@
+//! Types describing the user's input devices.
+//!
+//! The devices are listed with
+//! [`EventLoopWindowTarget::available_devices`](crate::event_loop::EventLoopWindowTarget::available_devices),
+//! and their [`DeviceId`] matches the one of the events they produce.
+//!
+//! The devices are grouped in seats, each with its own keyboard focus and cursor, such that
+//! several users may share the same windows. The seat of a device is found with
+//! [`EventLoopWindowTarget::device_seat`](crate::event_loop::EventLoopWindowTarget::device_seat).
+use crate::event::DeviceId;
+
+/// Describes an input device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    pub(crate) id: DeviceId,
+    pub(crate) name: String,
+    pub(crate) kind: DeviceKind,
+    pub(crate) vendor_id: Option<u16>,
+    pub(crate) product_id: Option<u16>,
+    pub(crate) capabilities: DeviceCapabilities,
+    pub(crate) seat: Option<SeatId>,
+}
+
+impl DeviceInfo {
+    /// Returns the identifier of the device, as found in its events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** All the devices of a seat share the same identifier.
+    #[inline]
+    pub fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    /// Returns a human-readable name of the device.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of the device.
+    #[inline]
+    pub fn kind(&self) -> DeviceKind {
+        self.kind
+    }
+
+    /// Returns the USB vendor id of the device, if known.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Read from the `Device Product ID` property set by the input drivers.
+    /// - **Wayland:** Only available for tablets.
+    #[inline]
+    pub fn vendor_id(&self) -> Option<u16> {
+        self.vendor_id
+    }
+
+    /// Returns the USB product id of the device, if known.
+    ///
+    /// See [`DeviceInfo::vendor_id`] for the platform-specific details.
+    #[inline]
+    pub fn product_id(&self) -> Option<u16> {
+        self.product_id
+    }
+
+    /// Returns the kinds of input the device is able to produce.
+    #[inline]
+    pub fn capabilities(&self) -> DeviceCapabilities {
+        self.capabilities
+    }
+
+    /// Returns the seat the device belongs to, if any.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Floating devices, which aren't attached to a master device, have no seat.
+    #[inline]
+    pub fn seat(&self) -> Option<SeatId> {
+        self.seat
+    }
+}
+
+/// Identifier of a seat, a group of input devices used by one user.
+///
+/// Most systems have a single seat, but Wayland compositors may expose several, and X11 has
+/// a seat for each master pointer and keyboard pair created with MPX.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeatId(pub(crate) u64);
+
+/// Describes the kind of an input device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceKind {
+    Keyboard,
+    /// A mouse, a touchpad or any other device moving the cursor.
+    Pointer,
+    Touchscreen,
+    /// A drawing tablet, or one of the tools used on it.
+    Tablet,
+}
+
+bitflags! {
+    /// The kinds of input of a device, see [`DeviceInfo::capabilities`].
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct DeviceCapabilities: u32 {
+        /// The device has keys, reported with [`WindowEvent::KeyboardInput`].
+        ///
+        /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+        const KEYS = 1 << 0;
+        /// The device has buttons, reported with [`WindowEvent::MouseInput`].
+        ///
+        /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+        const BUTTONS = 1 << 1;
+        /// The device moves the cursor.
+        const MOTION = 1 << 2;
+        /// The device scrolls, reported with [`WindowEvent::MouseWheel`].
+        ///
+        /// [`WindowEvent::MouseWheel`]: crate::event::WindowEvent::MouseWheel
+        const SCROLL = 1 << 3;
+        /// The device reports touches, with [`WindowEvent::Touch`].
+        ///
+        /// [`WindowEvent::Touch`]: crate::event::WindowEvent::Touch
+        const TOUCH = 1 << 4;
+        /// The device reports how hard it's pressed.
+        const PRESSURE = 1 << 5;
+        /// The device reports the tilt of a pen.
+        const TILT = 1 << 6;
+    }
+}
@@ -0,0 +1,150 @@
+//! Types for drag and drop.
+//!
+//! A drag hovering one of the windows is reported with [`WindowEvent::DragAndDrop`]. When the drag
+//! enters, the application is told which MIME types the data is offered in, and could then
+//! decide whether to accept the drop with [`EventLoopWindowTarget::accept_drop`], or to reject it
+//! with [`EventLoopWindowTarget::reject_drop`]. The data itself is transferred only when it's
+//! requested with [`EventLoopWindowTarget::request_drop_data`].
+//!
+//! ```no_run
+//! use winit::dnd::{DndAction, DndEvent};
+//! use winit::event::{Event, WindowEvent};
+//! # use winit::event_loop::EventLoop;
+//! # let event_loop = EventLoop::new();
+//!
+//! event_loop.run(move |event, elwt, _| {
+//!     if let Event::WindowEvent {
+//!         event: WindowEvent::DragAndDrop(event),
+//!         ..
+//!     } = event
+//!     {
+//!         match event {
+//!             DndEvent::Entered { mime_types, .. } => {
+//!                 if mime_types.iter().any(|mime_type| mime_type == "text/html") {
+//!                     let _ = elwt.accept_drop("text/html", DndAction::Copy);
+//!                 }
+//!             }
+//!             DndEvent::Dropped { .. } => {
+//!                 let _ = elwt.request_drop_data("text/html");
+//!             }
+//!             DndEvent::Data { data, .. } => {
+//!                 println!("Dropped: {}", String::from_utf8_lossy(&data));
+//!             }
+//!             _ => (),
+//!         }
+//!     }
+//! });
+//! ```
+//!
+//! Data could also be dragged out of a window with [`Window::start_drag`], the outcome of the
+//! drag is then reported with [`WindowEvent::DragSource`].
+//!
+//! [`WindowEvent::DragAndDrop`]: crate::event::WindowEvent::DragAndDrop
+//! [`Window::start_drag`]: crate::window::Window::start_drag
+//! [`WindowEvent::DragSource`]: crate::event::WindowEvent::DragSource
+//! [`EventLoopWindowTarget::accept_drop`]: crate::event_loop::EventLoopWindowTarget::accept_drop
+//! [`EventLoopWindowTarget::reject_drop`]: crate::event_loop::EventLoopWindowTarget::reject_drop
+//! [`EventLoopWindowTarget::request_drop_data`]: crate::event_loop::EventLoopWindowTarget::request_drop_data
+
+use crate::dpi::PhysicalPosition;
+use crate::event_loop::AsyncRequestSerial;
+
+/// The action performed with the dropped data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied.
+    Copy,
+
+    /// The data is moved, the source is expected to delete it once the drop is finished.
+    Move,
+
+    /// The data is linked.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported, treated as [`DndAction::Copy`].
+    Link,
+
+    /// The user chooses the action once the data is dropped.
+    ///
+    /// A drop accepted with it is finished as a copy.
+    Ask,
+}
+
+/// Describes a drag and drop session over the window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DndEvent {
+    /// A drag has entered the window.
+    ///
+    /// The drop is accepted by default only when the data is offered as a list of files, to
+    /// deliver [`WindowEvent::DroppedFile`].
+    ///
+    /// [`WindowEvent::DroppedFile`]: crate::event::WindowEvent::DroppedFile
+    Entered {
+        /// The MIME types the data is offered in.
+        mime_types: Vec<String>,
+
+        /// The position of the drag relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The drag has moved over the window.
+    Moved {
+        /// The position of the drag relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The data was dropped onto the window.
+    ///
+    /// The data must be requested while handling this event, since the drop is finished once all
+    /// of the requested data has arrived.
+    Dropped {
+        /// The position of the drop relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+
+        /// The action chosen for the drop.
+        action: DndAction,
+    },
+
+    /// The drag has left the window without being dropped, or the drop was rejected.
+    Left,
+
+    /// The data requested with [`request_drop_data`] has arrived.
+    ///
+    /// [`request_drop_data`]: crate::event_loop::EventLoopWindowTarget::request_drop_data
+    Data {
+        serial: AsyncRequestSerial,
+        mime_type: String,
+        data: Vec<u8>,
+    },
+
+    /// The data requested with [`request_drop_data`] couldn't be transferred.
+    ///
+    /// [`request_drop_data`]: crate::event_loop::EventLoopWindowTarget::request_drop_data
+    RequestFailed { serial: AsyncRequestSerial },
+}
+
+/// Describes the outcome of a drag started with [`Window::start_drag`].
+///
+/// [`Window::start_drag`]: crate::window::Window::start_drag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DragSourceEvent {
+    /// The data was dropped onto a target, which is now reading it.
+    Dropped {
+        /// The action chosen by the target.
+        action: DndAction,
+    },
+
+    /// The target has finished reading the data.
+    ///
+    /// When the `action` is [`DndAction::Move`], the application is expected to delete the data.
+    Finished {
+        /// The action performed by the target.
+        action: DndAction,
+    },
+
+    /// The drag was cancelled, or the data was dropped where it can't be accepted.
+    Cancelled,
+}
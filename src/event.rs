@@ -36,6 +36,7 @@
 //! [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
 use smol_str::SmolStr;
 use std::path::PathBuf;
+use std::time::Duration;
 #[cfg(not(wasm_platform))]
 use std::time::Instant;
 #[cfg(wasm_platform)]
@@ -44,8 +45,11 @@ use web_time::Instant;
 #[cfg(doc)]
 use crate::window::Window;
 use crate::{
+    clipboard::ClipboardEvent,
+    device::SeatId,
+    dnd::{DndEvent, DragSourceEvent},
     dpi::{PhysicalPosition, PhysicalSize},
-    event_loop::AsyncRequestSerial,
+    event_loop::{AsyncRequestSerial, SourceId},
     keyboard::{self, ModifiersKeyState, ModifiersKeys, ModifiersState},
     platform_impl,
     window::{ActivationToken, Theme, WindowId},
@@ -74,11 +78,30 @@ pub enum Event<'a, T: 'static> {
     DeviceEvent {
         device_id: DeviceId,
         event: DeviceEvent,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
     UserEvent(T),
 
+    /// Emitted when a clipboard request is done or the clipboard content set by the application
+    /// was replaced.
+    ///
+    /// See the [`clipboard`](crate::clipboard) module for more details.
+    Clipboard(ClipboardEvent),
+
+    /// Emitted when an event source registered by the application is ready, that is when its
+    /// file descriptor is readable or its timer has expired.
+    ///
+    /// See the [`event_sources`](crate::platform::event_sources) module for more details.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Never emitted.
+    SourceReady(SourceId),
+
     /// Emitted when the application has been suspended.
     ///
     /// # Portability
@@ -270,9 +293,16 @@ impl<T: Clone> Clone for Event<'static, T> {
                 event: event.clone(),
             },
             UserEvent(event) => UserEvent(event.clone()),
-            DeviceEvent { device_id, event } => DeviceEvent {
+            Clipboard(event) => Clipboard(event.clone()),
+            SourceReady(source) => SourceReady(*source),
+            DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            } => DeviceEvent {
                 device_id: *device_id,
                 event: event.clone(),
+                timestamp: *timestamp,
             },
             NewEvents(cause) => NewEvents(*cause),
             MainEventsCleared => MainEventsCleared,
@@ -292,7 +322,17 @@ impl<'a, T> Event<'a, T> {
         match self {
             UserEvent(_) => Err(self),
             WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
-            DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
+            DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            } => Ok(DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            }),
+            Clipboard(event) => Ok(Clipboard(event)),
+            SourceReady(source) => Ok(SourceReady(source)),
             NewEvents(cause) => Ok(NewEvents(cause)),
             MainEventsCleared => Ok(MainEventsCleared),
             RedrawRequested(wid) => Ok(RedrawRequested(wid)),
@@ -312,7 +352,17 @@ impl<'a, T> Event<'a, T> {
                 .to_static()
                 .map(|event| WindowEvent { window_id, event }),
             UserEvent(event) => Some(UserEvent(event)),
-            DeviceEvent { device_id, event } => Some(DeviceEvent { device_id, event }),
+            DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            } => Some(DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            }),
+            Clipboard(event) => Some(Clipboard(event)),
+            SourceReady(source) => Some(SourceReady(source)),
             NewEvents(cause) => Some(NewEvents(cause)),
             MainEventsCleared => Some(MainEventsCleared),
             RedrawRequested(wid) => Some(RedrawRequested(wid)),
@@ -405,11 +455,39 @@ pub enum WindowEvent<'a> {
     /// hovered.
     HoveredFileCancelled,
 
+    /// A drag and drop session over the window has progressed.
+    ///
+    /// See the [`dnd`](crate::dnd) module for more.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    DragAndDrop(DndEvent),
+
+    /// A drag started from the window with [`Window::start_drag`] has progressed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`Window::start_drag`]: crate::window::Window::start_drag
+    DragSource(DragSourceEvent),
+
     /// The window gained or lost focus.
     ///
     /// The parameter is true if the window has gained focus, and false if it has lost focus.
     Focused(bool),
 
+    /// The keyboard focus of a seat has entered or left the window.
+    ///
+    /// [`WindowEvent::Focused`] tells whether the window has the focus of any seat, while this
+    /// event is sent for each seat, before it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    SeatFocused { seat: SeatId, focused: bool },
+
     /// An event from the keyboard has been received.
     ///
     /// ## Platform-specific
@@ -460,6 +538,9 @@ pub enum WindowEvent<'a> {
         /// limited by the display area and it may have been transformed by the OS to implement effects such as cursor
         /// acceleration, it should not be used to implement non-cursor-like interactions such as 3D camera control.
         position: PhysicalPosition<f64>,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// The cursor has entered the window.
@@ -471,7 +552,12 @@ pub enum WindowEvent<'a> {
     /// [`border`]: https://developer.mozilla.org/en-US/docs/Web/CSS/border
     /// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
-    CursorEntered { device_id: DeviceId },
+    CursorEntered {
+        device_id: DeviceId,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
+    },
 
     /// The cursor has left the window.
     ///
@@ -482,13 +568,49 @@ pub enum WindowEvent<'a> {
     /// [`border`]: https://developer.mozilla.org/en-US/docs/Web/CSS/border
     /// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
-    CursorLeft { device_id: DeviceId },
+    CursorLeft {
+        device_id: DeviceId,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
+    },
 
     /// A mouse wheel movement or touchpad scroll occurred.
+    ///
+    /// The end of a scroll sequence, e.g. lifting the fingers from a touchpad, is reported with
+    /// [`TouchPhase::Ended`] and may come with a zero `delta`.
     MouseWheel {
         device_id: DeviceId,
         delta: MouseScrollDelta,
         phase: TouchPhase,
+
+        /// The kind of device or motion which produced the scroll, if known.
+        ///
+        /// ## Platform-specific
+        ///
+        /// - **X11:** Only reported for button scrolling, since the XInput2 scroll valuators
+        ///   don't carry a source.
+        /// - **Wayland:** Reported when the compositor sends the axis source.
+        /// - **Other platforms:** Always `None`.
+        source: Option<ScrollSource>,
+
+        /// The high-resolution wheel movement along the `x` and `y` axes, in 1/120ths of a
+        /// wheel detent.
+        ///
+        /// A value of `120` is one full detent, so high-resolution wheels may send several
+        /// smaller values instead. The sign follows the one of `delta`.
+        ///
+        /// ## Platform-specific
+        ///
+        /// - **X11:** Derived from the XInput2 scroll valuators.
+        /// - **Wayland:** From `axis_value120` when the compositor supports version 8 of
+        ///   `wl_pointer`, otherwise derived from the discrete steps, so only multiples of `120`
+        ///   are reported.
+        /// - **Other platforms:** Always `None`.
+        value120: Option<(i32, i32)>,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// An mouse button press has been received.
@@ -496,6 +618,9 @@ pub enum WindowEvent<'a> {
         device_id: DeviceId,
         state: ElementState,
         button: MouseButton,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// Touchpad magnification event with two-finger pinch gesture.
@@ -505,11 +630,15 @@ pub enum WindowEvent<'a> {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS**.
+    /// - Only available on **macOS**, **X11** and **Wayland**.
+    /// - **X11:** Requires XInput 2.4.
     TouchpadMagnify {
         device_id: DeviceId,
         delta: f64,
         phase: TouchPhase,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// Smart magnification event.
@@ -523,7 +652,7 @@ pub enum WindowEvent<'a> {
     /// across platforms. It could also be generated by another device.
     ///
     /// Unfortunatly, neither [Windows](https://support.microsoft.com/en-us/windows/touch-gestures-for-windows-a9d28305-4818-a5df-4e2b-e5590f850741)
-    /// nor [Wayland](https://wayland.freedesktop.org/libinput/doc/latest/gestures.html)
+    /// nor [X11 and Wayland](https://wayland.freedesktop.org/libinput/doc/latest/gestures.html)
     /// support this gesture or any other gesture with the same effect.
     ///
     /// ## Platform-specific
@@ -538,11 +667,51 @@ pub enum WindowEvent<'a> {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS**.
+    /// - Only available on **macOS**, **X11** and **Wayland**.
+    /// - **X11:** Requires XInput 2.4.
     TouchpadRotate {
         device_id: DeviceId,
         delta: f32,
         phase: TouchPhase,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
+    },
+
+    /// Touchpad swipe event with a gesture of three or more fingers.
+    ///
+    /// The `delta` is the motion of the center of the fingers since the previous event.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    /// - **X11:** Requires XInput 2.4.
+    TouchpadSwipe {
+        device_id: DeviceId,
+        delta: PhysicalPosition<f64>,
+        fingers: u32,
+        phase: TouchPhase,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
+    },
+
+    /// Touchpad hold event, when fingers rest on the touchpad without moving.
+    ///
+    /// The gesture is [`TouchPhase::Ended`] when the fingers are lifted, and
+    /// [`TouchPhase::Cancelled`] when they start moving or other fingers are added. This is
+    /// commonly used to stop kinetic scrolling.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, with version 3 of the pointer gestures protocol.
+    TouchpadHold {
+        device_id: DeviceId,
+        fingers: u32,
+        phase: TouchPhase,
+
+        /// The time of the event, see [`KeyEvent::timestamp`].
+        timestamp: Option<Instant>,
     },
 
     /// Touchpad pressure event.
@@ -575,6 +744,32 @@ pub enum WindowEvent<'a> {
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
     Touch(Touch),
 
+    /// A pen, such as the stylus of a drawing tablet, was used over the window.
+    ///
+    /// The pen keeps moving the cursor, and its tip and barrel buttons are reported with
+    /// [`WindowEvent::MouseInput`] as well.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only the [`PenTool::Pen`] and [`PenTool::Eraser`] tools are told apart, and the
+    ///   axes are read from the valuators of the tablet's XInput device.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    PenInput {
+        device_id: DeviceId,
+        event: PenEvent,
+    },
+
+    /// A pointing device, either a mouse, a finger or a pen, was used over the window.
+    ///
+    /// This is sent alongside the [`WindowEvent::CursorMoved`], [`WindowEvent::MouseInput`],
+    /// [`WindowEvent::Touch`] and [`WindowEvent::PenInput`] events describing the same input, so
+    /// that all the pointing devices can be handled the same way.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Orbital:** Unsupported.
+    Pointer(Pointer),
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -617,6 +812,28 @@ pub enum WindowEvent<'a> {
     /// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
     Occluded(bool),
+
+    /// A frame of the window was shown on the screen, or discarded.
+    ///
+    /// This is only sent for the frames following a call to
+    /// [`Window::request_presentation_feedback`], once for each call.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only reported for the frames presented through the Present extension, which
+    ///   is the case of most OpenGL and Vulkan drivers, but not of software rendering.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    PresentationFeedback(PresentationFeedback),
+
+    /// The popup was closed by the system, because the user clicked outside of it for instance,
+    /// and should be dropped.
+    ///
+    /// See [`WindowBuilder::with_popup`](crate::window::WindowBuilder::with_popup).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    PopupDismissed,
 }
 
 impl Clone for WindowEvent<'static> {
@@ -634,7 +851,13 @@ impl Clone for WindowEvent<'static> {
             DroppedFile(file) => DroppedFile(file.clone()),
             HoveredFile(file) => HoveredFile(file.clone()),
             HoveredFileCancelled => HoveredFileCancelled,
+            DragAndDrop(event) => DragAndDrop(event.clone()),
+            DragSource(event) => DragSource(*event),
             Focused(f) => Focused(*f),
+            SeatFocused { seat, focused } => SeatFocused {
+                seat: *seat,
+                focused: *focused,
+            },
             KeyboardInput {
                 device_id,
                 event,
@@ -649,42 +872,62 @@ impl Clone for WindowEvent<'static> {
             CursorMoved {
                 device_id,
                 position,
+                timestamp,
             } => CursorMoved {
                 device_id: *device_id,
                 position: *position,
+                timestamp: *timestamp,
             },
-            CursorEntered { device_id } => CursorEntered {
+            CursorEntered {
+                device_id,
+                timestamp,
+            } => CursorEntered {
                 device_id: *device_id,
+                timestamp: *timestamp,
             },
-            CursorLeft { device_id } => CursorLeft {
+            CursorLeft {
+                device_id,
+                timestamp,
+            } => CursorLeft {
                 device_id: *device_id,
+                timestamp: *timestamp,
             },
             MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
+                value120,
+                timestamp,
             } => MouseWheel {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                source: *source,
+                value120: *value120,
+                timestamp: *timestamp,
             },
             MouseInput {
                 device_id,
                 state,
                 button,
+                timestamp,
             } => MouseInput {
                 device_id: *device_id,
                 state: *state,
                 button: *button,
+                timestamp: *timestamp,
             },
             TouchpadMagnify {
                 device_id,
                 delta,
                 phase,
+                timestamp,
             } => TouchpadMagnify {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                timestamp: *timestamp,
             },
             SmartMagnify { device_id } => SmartMagnify {
                 device_id: *device_id,
@@ -693,10 +936,36 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 delta,
                 phase,
+                timestamp,
             } => TouchpadRotate {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                timestamp: *timestamp,
+            },
+            TouchpadSwipe {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            } => TouchpadSwipe {
+                device_id: *device_id,
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: *timestamp,
+            },
+            TouchpadHold {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
+            } => TouchpadHold {
+                device_id: *device_id,
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: *timestamp,
             },
             TouchpadPressure {
                 device_id,
@@ -717,11 +986,18 @@ impl Clone for WindowEvent<'static> {
                 value: *value,
             },
             Touch(touch) => Touch(*touch),
+            Pointer(pointer) => Pointer(*pointer),
+            PenInput { device_id, event } => PenInput {
+                device_id: *device_id,
+                event: *event,
+            },
             ThemeChanged(theme) => ThemeChanged(*theme),
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
             }
             Occluded(occluded) => Occluded(*occluded),
+            PresentationFeedback(feedback) => PresentationFeedback(*feedback),
+            PopupDismissed => PopupDismissed,
         };
     }
 }
@@ -738,7 +1014,10 @@ impl<'a> WindowEvent<'a> {
             DroppedFile(file) => Some(DroppedFile(file)),
             HoveredFile(file) => Some(HoveredFile(file)),
             HoveredFileCancelled => Some(HoveredFileCancelled),
+            DragAndDrop(event) => Some(DragAndDrop(event)),
+            DragSource(event) => Some(DragSource(event)),
             Focused(focused) => Some(Focused(focused)),
+            SeatFocused { seat, focused } => Some(SeatFocused { seat, focused }),
             KeyboardInput {
                 device_id,
                 event,
@@ -753,48 +1032,98 @@ impl<'a> WindowEvent<'a> {
             CursorMoved {
                 device_id,
                 position,
+                timestamp,
             } => Some(CursorMoved {
                 device_id,
                 position,
+                timestamp,
+            }),
+            CursorEntered {
+                device_id,
+                timestamp,
+            } => Some(CursorEntered {
+                device_id,
+                timestamp,
+            }),
+            CursorLeft {
+                device_id,
+                timestamp,
+            } => Some(CursorLeft {
+                device_id,
+                timestamp,
             }),
-            CursorEntered { device_id } => Some(CursorEntered { device_id }),
-            CursorLeft { device_id } => Some(CursorLeft { device_id }),
             MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
+                value120,
+                timestamp,
             } => Some(MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
+                value120,
+                timestamp,
             }),
             MouseInput {
                 device_id,
                 state,
                 button,
+                timestamp,
             } => Some(MouseInput {
                 device_id,
                 state,
                 button,
+                timestamp,
             }),
             TouchpadMagnify {
                 device_id,
                 delta,
                 phase,
+                timestamp,
             } => Some(TouchpadMagnify {
                 device_id,
                 delta,
                 phase,
+                timestamp,
             }),
             SmartMagnify { device_id } => Some(SmartMagnify { device_id }),
             TouchpadRotate {
                 device_id,
                 delta,
                 phase,
+                timestamp,
             } => Some(TouchpadRotate {
                 device_id,
                 delta,
                 phase,
+                timestamp,
+            }),
+            TouchpadSwipe {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            } => Some(TouchpadSwipe {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            }),
+            TouchpadHold {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
+            } => Some(TouchpadHold {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
             }),
             TouchpadPressure {
                 device_id,
@@ -815,9 +1144,13 @@ impl<'a> WindowEvent<'a> {
                 value,
             }),
             Touch(touch) => Some(Touch(touch)),
+            Pointer(pointer) => Some(Pointer(pointer)),
+            PenInput { device_id, event } => Some(PenInput { device_id, event }),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
             ScaleFactorChanged { .. } => None,
             Occluded(occluded) => Some(Occluded(occluded)),
+            PresentationFeedback(feedback) => Some(PresentationFeedback(feedback)),
+            PopupDismissed => Some(PopupDismissed),
         }
     }
 }
@@ -1010,6 +1343,20 @@ pub struct KeyEvent {
     /// this event is the result of one of those repeats.
     pub repeat: bool,
 
+    /// The time at which the input happened, as reported by the platform.
+    ///
+    /// It's on the same monotonic clock as [`Instant::now`], and unlike the time at which the
+    /// event is delivered, it's not delayed by the processing of the previous events, which is
+    /// useful to compensate for the input latency or to detect double clicks. It's `None` if the
+    /// platform doesn't report the time of its events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Wayland:** The time of the events is in milliseconds.
+    /// - **Wayland:** The mouse cursor entering and leaving the window has no time.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    pub timestamp: Option<Instant>,
+
     /// Platform-specific key event information.
     ///
     /// On Windows, Linux and macOS, this type contains the key without modifiers and the text with all
@@ -1208,14 +1555,113 @@ pub struct Touch {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **iOS** 9.0+, **Windows** 8+, and **Web**.
+    /// - Only available on **iOS** 9.0+, **Windows** 8+, **Web**, and **X11**.
     pub force: Option<Force>,
+    /// The size of the contact area, as the major and minor axes of an ellipse in physical
+    /// pixels. May be `None` if the platform doesn't report it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11**, where the size is approximated from the range of the
+    ///   touchscreen's axes and the size of the screen.
+    pub contact_size: Option<(f64, f64)>,
     /// Unique identifier of a finger.
     pub id: u64,
+    /// The time of the event, see [`KeyEvent::timestamp`].
+    pub timestamp: Option<Instant>,
+}
+
+/// Represents a pointer event, see [`WindowEvent::Pointer`].
+///
+/// A mouse or a pen hovering over the window goes through [`PointerPhase::Entered`], any number of
+/// [`PointerPhase::Moved`], [`PointerPhase::Down`] and [`PointerPhase::Up`] and finally
+/// [`PointerPhase::Left`]. A touch starts with [`PointerPhase::Down`] and finishes with
+/// [`PointerPhase::Up`] or [`PointerPhase::Cancelled`].
+///
+/// ## Platform-specific
+///
+/// - **X11:** The `size` is only available for touches, see [`Touch::contact_size`].
+/// - **Wayland:** The `pressure` and `tilt` are only available for pens, and the `size` isn't
+///   available.
+/// - **Web:** Doesn't take into account CSS [`border`], [`padding`], or [`transform`].
+///
+/// [`border`]: https://developer.mozilla.org/en-US/docs/Web/CSS/border
+/// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
+/// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    pub device_id: DeviceId,
+    /// Identifies the pointer among the active pointers of the same device and kind.
+    ///
+    /// For touches, this is the same as [`Touch::id`].
+    pub pointer_id: u64,
+    pub kind: PointerKind,
+    pub phase: PointerPhase,
+    /// The position of the pointer relative to the top-left corner of the window.
+    pub position: PhysicalPosition<f64>,
+    /// The buttons held after the event.
+    ///
+    /// For a [`PointerPhase::Down`] or [`PointerPhase::Up`] event, comparing them with the ones
+    /// of the previous event tells which button changed.
+    pub buttons: PointerButtons,
+    /// How hard the pointer is pressed, between `0.0` and `1.0`, if the device reports it.
+    pub pressure: Option<f64>,
+    /// The tilt of a pen in degrees along the X and Y axes, see [`PenAxes::tilt`].
+    pub tilt: Option<(f64, f64)>,
+    /// The width and the height of the contact area in physical pixels, if the device reports it.
+    pub size: Option<(f64, f64)>,
+    /// The time of the event, see [`KeyEvent::timestamp`].
+    pub timestamp: Option<Instant>,
+}
+
+/// Describes the kind of device behind a [`Pointer`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+    Pen,
+}
+
+/// Describes what happened to a [`Pointer`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PointerPhase {
+    /// The pointer moved over the window.
+    Entered,
+    /// A button was pressed, or the finger or the pen touched the surface.
+    Down,
+    Moved,
+    /// A button was released, or the finger or the pen was lifted from the surface.
+    Up,
+    /// The pointer moved out of the window.
+    Left,
+    /// The system stopped tracking the pointer, like for [`TouchPhase::Cancelled`].
+    Cancelled,
+}
+
+bitflags! {
+    /// The buttons held on a [`Pointer`].
+    ///
+    /// The values match the `buttons` of the DOM `PointerEvent`.
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PointerButtons: u32 {
+        /// The left mouse button, a finger touching the surface or the tip of a pen.
+        const PRIMARY = 1 << 0;
+        /// The right mouse button or the barrel button of a pen.
+        const SECONDARY = 1 << 1;
+        /// The middle mouse button.
+        const AUXILIARY = 1 << 2;
+        const BACK = 1 << 3;
+        const FORWARD = 1 << 4;
+        /// The eraser of a pen touching the surface.
+        const ERASER = 1 << 5;
+    }
 }
 
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -1271,6 +1717,79 @@ impl Force {
     }
 }
 
+/// Describes an input from a pen, see [`WindowEvent::PenInput`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenEvent {
+    /// The pen came into the proximity of the tablet over the window.
+    Entered { tool: PenTool },
+
+    /// The pen has moved, or the state of its axes has changed.
+    Moved {
+        /// The position of the pen relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+        axes: PenAxes,
+    },
+
+    /// The tip of the pen has touched the tablet, or was lifted from it.
+    Tip { state: ElementState },
+
+    /// A button on the barrel of the pen was pressed or released.
+    Button {
+        button: PenButton,
+        state: ElementState,
+    },
+
+    /// The pen left the proximity of the tablet, or moved out of the window.
+    Left,
+}
+
+/// The tool used as a pen.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenTool {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Finger,
+    Mouse,
+    Lens,
+}
+
+/// The state of the axes of a pen.
+///
+/// Axes which the pen doesn't have are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PenAxes {
+    /// How hard the tip is pressed against the tablet, between `0.0` and `1.0`.
+    pub pressure: Option<f64>,
+
+    /// The tilt of the pen away from the normal of the tablet in degrees, along the X and Y axes.
+    ///
+    /// The tilt is positive when the top of the pen leans to the right and towards the user.
+    pub tilt: Option<(f64, f64)>,
+
+    /// The clockwise rotation of the pen around its own axis in degrees, between `0.0` and
+    /// `360.0`.
+    pub rotation: Option<f64>,
+
+    /// The distance between the pen and the tablet, between `0.0` and `1.0`.
+    pub distance: Option<f64>,
+}
+
+/// Describes a button on the barrel of a pen.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenButton {
+    /// The button closest to the tip.
+    Primary,
+    Secondary,
+    Other(u16),
+}
+
 /// Identifier for a specific analog axis on some device.
 pub type AxisId = u32;
 
@@ -1328,3 +1847,49 @@ pub enum MouseScrollDelta {
     /// and move the content right and down (to reveal more things left and up).
     PixelDelta(PhysicalPosition<f64>),
 }
+
+/// Describes what produced a [`WindowEvent::MouseWheel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScrollSource {
+    /// A mouse wheel, scrolling in discrete steps.
+    Wheel,
+
+    /// Fingers on a touch surface, like a touchpad.
+    ///
+    /// The scroll sequence is terminated by a [`TouchPhase::Ended`] event.
+    Finger,
+
+    /// A continuous movement without a terminating event, like a trackpoint or a button-driven
+    /// scroll.
+    Continuous,
+
+    /// The side movement of a tilting mouse wheel.
+    WheelTilt,
+}
+
+/// Describes what happened to a frame, see [`WindowEvent::PresentationFeedback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresentationFeedback {
+    /// The frame was shown on the screen.
+    Presented {
+        /// The time at which the frame started being shown, on the same clock as
+        /// [`Instant::now`].
+        timestamp: Instant,
+
+        /// The refresh interval of the monitor showing the frame, if it has a fixed refresh rate.
+        ///
+        /// The next frame may be shown at `timestamp + refresh` at the earliest.
+        refresh: Option<Duration>,
+
+        /// The counter of vertical blanks of the monitor, if known, to detect the skipped
+        /// refreshes.
+        ///
+        /// It only increases, but may jump when the window changes monitors.
+        sequence: Option<u64>,
+    },
+
+    /// The frame was never shown, being replaced by a newer frame before the next refresh, or
+    /// the window being hidden.
+    Discarded,
+}
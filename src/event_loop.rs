@@ -18,7 +18,15 @@ use std::time::{Duration, Instant};
 #[cfg(wasm_platform)]
 use web_time::{Duration, Instant};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{
+    clipboard::{ClipboardContent, ClipboardKind},
+    device::{DeviceInfo, SeatId},
+    dnd::DndAction,
+    error::ExternalError,
+    event::{DeviceId, Event},
+    monitor::MonitorHandle,
+    platform_impl,
+};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -99,9 +107,10 @@ impl<T> EventLoopBuilder<T> {
     /// ## Platform-specific
     ///
     /// - **Linux:** Backend type can be controlled using an environment variable
-    ///   `WINIT_UNIX_BACKEND`. Legal values are `x11` and `wayland`.
+    ///   `WINIT_UNIX_BACKEND`. Legal values are `x11`, `wayland` and `headless`.
     ///   If it is not set, winit will try to connect to a Wayland connection, and if that fails,
     ///   will fall back on X11. If this variable is set with any other value, winit will panic.
+    ///   Any number of headless event loops can exist at once.
     /// - **Android:** Must be configured with an `AndroidApp` from `android_main()` by calling
     ///     [`.with_android_app(app)`] before calling `.build()`.
     ///
@@ -116,7 +125,13 @@ impl<T> EventLoopBuilder<T> {
     )]
     #[inline]
     pub fn build(&mut self) -> EventLoop<T> {
-        if EVENT_LOOP_CREATED.swap(true, Ordering::Relaxed) {
+        // The headless event loops don't share any global state, so any number of them can exist.
+        #[cfg(any(x11_platform, wayland_platform))]
+        let unique = !self.platform_specific.is_headless();
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        let unique = true;
+
+        if unique && EVENT_LOOP_CREATED.swap(true, Ordering::Relaxed) {
             panic!("Creating EventLoop multiple times is not supported.");
         }
 
@@ -355,6 +370,44 @@ impl<T> EventLoopWindowTarget<T> {
             .map(|inner| MonitorHandle { inner })
     }
 
+    /// Returns the list of the input devices.
+    ///
+    /// The devices come and go along with [`DeviceEvent::Added`] and [`DeviceEvent::Removed`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The virtual core devices, which the window events come from, are listed as well.
+    /// - **Wayland:** The devices are described from the capabilities of the seats and from the
+    ///   tablets. Since the devices of a seat share its identifier, the events are sent with it
+    ///   for each capability and tablet, including the ones present when the event loop starts.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported, no device is listed.
+    ///
+    /// [`DeviceEvent::Added`]: crate::event::DeviceEvent::Added
+    /// [`DeviceEvent::Removed`]: crate::event::DeviceEvent::Removed
+    pub fn available_devices(&self) -> impl Iterator<Item = DeviceInfo> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.available_devices().into_iter();
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Vec::new().into_iter()
+    }
+
+    /// Returns the seat of the device, such as the one of an input event.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The seat is looked up on the X server, each master pointer and keyboard pair
+    ///   being a seat.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported, always returns `None`.
+    #[inline]
+    pub fn device_seat(&self, _device_id: DeviceId) -> Option<SeatId> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.device_seat(_device_id.0);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        None
+    }
+
     /// Change if or when [`DeviceEvent`]s are captured.
     ///
     /// Since the [`DeviceEvent`] capture can lead to high CPU usage for unfocused windows, winit
@@ -370,6 +423,140 @@ impl<T> EventLoopWindowTarget<T> {
         #[cfg(any(x11_platform, wasm_platform, wayland_platform, windows))]
         self.p.listen_device_events(_allowed);
     }
+
+    /// Set the content of the clipboard or the primary selection.
+    ///
+    /// The content is offered to other clients until it's replaced, which is reported with
+    /// [`ClipboardEvent::ContentLost`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The clipboard could only be set after an input event was delivered to one of
+    ///   the application's windows.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`ClipboardEvent::ContentLost`]: crate::clipboard::ClipboardEvent::ContentLost
+    pub fn set_clipboard_content(
+        &self,
+        _kind: ClipboardKind,
+        _content: ClipboardContent,
+    ) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.set_clipboard_content(_kind, _content);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
+
+    /// Request the data of the clipboard or the primary selection in the given MIME type.
+    ///
+    /// The data is delivered with [`ClipboardEvent::Data`] or, on failure,
+    /// [`ClipboardEvent::RequestFailed`], both carrying the returned serial.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`ClipboardEvent::Data`]: crate::clipboard::ClipboardEvent::Data
+    /// [`ClipboardEvent::RequestFailed`]: crate::clipboard::ClipboardEvent::RequestFailed
+    pub fn request_clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.request_clipboard_data(_kind, _mime_type);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
+
+    /// Request the MIME types offered by the clipboard or the primary selection.
+    ///
+    /// The MIME types are delivered with [`ClipboardEvent::MimeTypes`] or, on failure,
+    /// [`ClipboardEvent::RequestFailed`], both carrying the returned serial.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`ClipboardEvent::MimeTypes`]: crate::clipboard::ClipboardEvent::MimeTypes
+    /// [`ClipboardEvent::RequestFailed`]: crate::clipboard::ClipboardEvent::RequestFailed
+    pub fn request_clipboard_mime_types(
+        &self,
+        _kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.request_clipboard_mime_types(_kind);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
+
+    /// Accept the drop of the data currently dragged over one of the windows.
+    ///
+    /// The `mime_type` is the one the application intends to read the data in, and the `action`
+    /// is the one the application prefers. The response could be changed at any point until the
+    /// data is dropped, usually while handling [`DndEvent::Entered`] and [`DndEvent::Moved`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The `mime_type` isn't communicated to the source.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`DndEvent::Entered`]: crate::dnd::DndEvent::Entered
+    /// [`DndEvent::Moved`]: crate::dnd::DndEvent::Moved
+    pub fn accept_drop(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.accept_drop(_mime_type, _action);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
+
+    /// Reject the drop of the data currently dragged over one of the windows.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    pub fn reject_drop(&self) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.reject_drop();
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
+
+    /// Request the data currently dragged over one of the windows in the given MIME type.
+    ///
+    /// The data is delivered with [`DndEvent::Data`] or, on failure, [`DndEvent::RequestFailed`],
+    /// both carrying the returned serial.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`DndEvent::Data`]: crate::dnd::DndEvent::Data
+    /// [`DndEvent::RequestFailed`]: crate::dnd::DndEvent::RequestFailed
+    pub fn request_drop_data(&self, _mime_type: &str) -> Result<AsyncRequestSerial, ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.p.request_drop_data(_mime_type);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(
+            crate::error::NotSupportedError::new(),
+        ))
+    }
 }
 
 unsafe impl<T> HasRawDisplayHandle for EventLoopWindowTarget<T> {
@@ -447,13 +634,13 @@ pub enum DeviceEvents {
 /// containing [`AsyncRequestSerial`] and some closure associated with it.
 /// Then once event is arriving the working list is being traversed and a job
 /// executed and removed from the list.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AsyncRequestSerial {
     serial: u64,
 }
 
 impl AsyncRequestSerial {
-    // TODO(kchibisov) remove `cfg` when the clipboard will be added.
+    // Not every platform has async requests yet.
     #[allow(dead_code)]
     pub(crate) fn get() -> Self {
         static CURRENT_SERIAL: AtomicU64 = AtomicU64::new(0);
@@ -463,3 +650,25 @@ impl AsyncRequestSerial {
         Self { serial }
     }
 }
+
+/// A unique identifier of an event source registered with the event loop.
+///
+/// It's carried by [`Event::SourceReady`] once the source is ready.
+///
+/// See the [`event_sources`] module for more details.
+///
+/// [`event_sources`]: crate::platform::event_sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceId {
+    id: u64,
+}
+
+impl SourceId {
+    // Not every platform has event sources.
+    #[allow(dead_code)]
+    pub(crate) fn get() -> Self {
+        static CURRENT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = CURRENT_ID.fetch_add(1, Ordering::Relaxed);
+        Self { id }
+    }
+}
@@ -0,0 +1,523 @@
+//! An executor running futures on the thread of the event loop, along with streams of its events.
+//!
+//! The [`Executor`] is fed the events from the closure given to [`EventLoop::run`], which it
+//! forwards to the [`EventStream`]s awaited by the futures. The futures are polled once woken up,
+//! the wake ups from other threads sending a [`Wakeup`] to the event loop through an
+//! [`EventLoopProxy`].
+//!
+//! The wake ups reach the closure as [`Event::UserEvent`], holding the user event converted from
+//! [`Wakeup`]. They must be passed to the executor like the other events, but carry nothing for
+//! the application, so they're best ignored when matching the user events.
+//!
+//! ```no_run
+//! use winit::event_loop::EventLoop;
+//! use winit::executor::Executor;
+//! use winit::window::Window;
+//!
+//! let event_loop = EventLoop::new();
+//! let window = Window::new(&event_loop).unwrap();
+//!
+//! let mut executor = Executor::new(&event_loop);
+//! let mut events = executor.spawner().window_events(window.id());
+//! executor.spawn(async move {
+//!     while let Some(event) = events.next().await {
+//!         println!("{event:?}");
+//!     }
+//! });
+//!
+//! event_loop.run(move |event, _, control_flow| {
+//!     control_flow.set_wait();
+//!     executor.handle_event(event);
+//! });
+//! ```
+//!
+//! [`EventLoop::run`]: crate::event_loop::EventLoop::run
+//! [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::event::{DeviceEvent, DeviceId, Event, WindowEvent};
+use crate::event_loop::EventLoop;
+use crate::window::WindowId;
+
+/// The user event sent by the [`Executor`] to wake up the event loop.
+///
+/// The user event type of the event loop must be constructible from it, which is already the
+/// case of `()`. The application receives it as [`Event::UserEvent`], which should be ignored
+/// once passed to [`Executor::handle_event`]. With a custom user event type, a dedicated variant
+/// keeps it apart from the events of the application:
+///
+/// ```
+/// use winit::executor::Wakeup;
+///
+/// enum UserEvent {
+///     Wakeup,
+///     Quit,
+/// }
+///
+/// impl From<Wakeup> for UserEvent {
+///     fn from(_: Wakeup) -> Self {
+///         UserEvent::Wakeup
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Wakeup;
+
+impl From<Wakeup> for () {
+    fn from(_: Wakeup) -> Self {}
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Runs futures on the thread of the event loop.
+///
+/// See the [module-level docs](self) for more information.
+pub struct Executor {
+    spawner: Spawner,
+}
+
+impl Executor {
+    /// Create a new executor for the `event_loop`.
+    pub fn new<T>(event_loop: &EventLoop<T>) -> Self
+    where
+        T: From<Wakeup> + Send + 'static,
+    {
+        let proxy = Mutex::new(event_loop.create_proxy());
+        Self::with_wake_up(Box::new(move || {
+            // The event loop is already gone when the proxy is closed.
+            let _ = proxy.lock().unwrap().send_event(T::from(Wakeup));
+        }))
+    }
+
+    fn with_wake_up(wake_up: Box<dyn Fn() + Send + Sync>) -> Self {
+        let ready = Arc::new(ReadyQueue {
+            tasks: Default::default(),
+            handling_event: AtomicBool::new(false),
+            wake_up,
+        });
+
+        let inner = Inner {
+            ready,
+            next_task: 0,
+            tasks: HashMap::new(),
+            window_streams: Vec::new(),
+            device_streams: Vec::new(),
+        };
+
+        Self {
+            spawner: Spawner {
+                inner: Rc::new(RefCell::new(inner)),
+            },
+        }
+    }
+
+    /// A handle to spawn futures and create event streams, which can be moved into the futures.
+    pub fn spawner(&self) -> Spawner {
+        self.spawner.clone()
+    }
+
+    /// Spawn a future, which is polled on the next event.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.spawner.spawn(future)
+    }
+
+    /// Forward the event to the event streams, and poll the futures which were woken up.
+    ///
+    /// All the events should be passed, so the futures are polled as soon as possible. Events
+    /// referring to data the executor can't keep, like [`WindowEvent::ScaleFactorChanged`], are
+    /// not forwarded.
+    ///
+    /// On [`Event::LoopDestroyed`], the event streams end.
+    pub fn handle_event<T>(&mut self, event: Event<'_, T>) {
+        let ready = self.spawner.inner.borrow().ready.clone();
+
+        // The woken up tasks are polled right after, without waking up the event loop.
+        ready.handling_event.store(true, Ordering::Release);
+
+        let mut inner = self.spawner.inner.borrow_mut();
+        match event {
+            Event::WindowEvent { window_id, event } => {
+                if let Some(event) = event.to_static() {
+                    inner.window_streams.retain(|(stream_window_id, stream)| {
+                        let stream = match stream.upgrade() {
+                            Some(stream) => stream,
+                            None => return false,
+                        };
+                        if *stream_window_id == window_id {
+                            stream.borrow_mut().push(event.clone());
+                        }
+                        true
+                    });
+                }
+            }
+            Event::DeviceEvent {
+                device_id, event, ..
+            } => {
+                inner
+                    .device_streams
+                    .retain(|stream| match stream.upgrade() {
+                        Some(stream) => {
+                            stream.borrow_mut().push((device_id, event.clone()));
+                            true
+                        }
+                        None => false,
+                    });
+            }
+            Event::LoopDestroyed => {
+                for stream in inner.window_streams.drain(..) {
+                    if let Some(stream) = stream.1.upgrade() {
+                        stream.borrow_mut().close();
+                    }
+                }
+                for stream in inner.device_streams.drain(..) {
+                    if let Some(stream) = stream.upgrade() {
+                        stream.borrow_mut().close();
+                    }
+                }
+            }
+            _ => (),
+        }
+        drop(inner);
+
+        // The tasks woken up while being polled are polled on the next event, so a task waking
+        // itself up doesn't block the event loop.
+        let task_ids = mem::take(&mut *ready.tasks.lock().unwrap());
+
+        for task_id in task_ids {
+            // The task is taken out while polled, so it can spawn other tasks.
+            let task = self.spawner.inner.borrow_mut().tasks.remove(&task_id);
+            let (mut task, waker) = match task {
+                Some(task) => task,
+                None => continue,
+            };
+
+            waker.scheduled.store(false, Ordering::Release);
+            let std_waker = Waker::from(waker.clone());
+            let mut cx = Context::from_waker(&std_waker);
+            if task.as_mut().poll(&mut cx).is_pending() {
+                self.spawner
+                    .inner
+                    .borrow_mut()
+                    .tasks
+                    .insert(task_id, (task, waker));
+            }
+        }
+
+        ready.handling_event.store(false, Ordering::Release);
+        if !ready.tasks.lock().unwrap().is_empty() {
+            (ready.wake_up)();
+        }
+    }
+}
+
+impl fmt::Debug for Executor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Executor").finish_non_exhaustive()
+    }
+}
+
+/// A handle to spawn futures onto the [`Executor`] and create event streams.
+#[derive(Clone)]
+pub struct Spawner {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Spawner {
+    /// Spawn a future, which is polled on the next event.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        let mut inner = self.inner.borrow_mut();
+        let task_id = inner.next_task;
+        inner.next_task += 1;
+
+        let waker = Arc::new(TaskWaker {
+            task_id,
+            scheduled: AtomicBool::new(false),
+            ready: inner.ready.clone(),
+        });
+        inner
+            .tasks
+            .insert(task_id, (Box::pin(future), waker.clone()));
+        drop(inner);
+
+        waker.wake();
+    }
+
+    /// A stream of the events of the window with the id `window_id`.
+    ///
+    /// Only the events emitted after the creation of the stream are received.
+    pub fn window_events(&self, window_id: WindowId) -> EventStream<WindowEvent<'static>> {
+        let stream = EventStream::new();
+        self.inner
+            .borrow_mut()
+            .window_streams
+            .push((window_id, Rc::downgrade(&stream.queue)));
+        stream
+    }
+
+    /// A stream of the events of the devices.
+    ///
+    /// Only the events emitted after the creation of the stream are received.
+    pub fn device_events(&self) -> EventStream<(DeviceId, DeviceEvent)> {
+        let stream = EventStream::new();
+        self.inner
+            .borrow_mut()
+            .device_streams
+            .push(Rc::downgrade(&stream.queue));
+        stream
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner").finish_non_exhaustive()
+    }
+}
+
+struct Inner {
+    ready: Arc<ReadyQueue>,
+    next_task: u64,
+    tasks: HashMap<u64, (Task, Arc<TaskWaker>)>,
+    window_streams: Vec<(WindowId, WeakQueue<WindowEvent<'static>>)>,
+    device_streams: Vec<WeakQueue<(DeviceId, DeviceEvent)>>,
+}
+
+/// The tasks woken up, which can be shared across threads.
+struct ReadyQueue {
+    tasks: Mutex<Vec<u64>>,
+
+    /// Whether the executor is handling an event, so the woken up tasks don't require to wake up
+    /// the event loop.
+    handling_event: AtomicBool,
+
+    wake_up: Box<dyn Fn() + Send + Sync>,
+}
+
+struct TaskWaker {
+    task_id: u64,
+
+    /// Whether the task is already in the ready queue.
+    scheduled: AtomicBool,
+
+    ready: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.scheduled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let mut tasks = self.ready.tasks.lock().unwrap();
+        let was_empty = tasks.is_empty();
+        tasks.push(self.task_id);
+        drop(tasks);
+
+        if was_empty && !self.ready.handling_event.load(Ordering::Acquire) {
+            (self.ready.wake_up)();
+        }
+    }
+}
+
+type WeakQueue<E> = Weak<RefCell<Queue<E>>>;
+
+struct Queue<E> {
+    events: VecDeque<E>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+impl<E> Queue<E> {
+    fn push(&mut self, event: E) {
+        self.events.push_back(event);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A stream of events, created by the [`Spawner`].
+///
+/// The stream ends once the event loop is destroyed.
+pub struct EventStream<E> {
+    queue: Rc<RefCell<Queue<E>>>,
+}
+
+impl<E> EventStream<E> {
+    fn new() -> Self {
+        let queue = Queue {
+            events: VecDeque::new(),
+            waker: None,
+            closed: false,
+        };
+        Self {
+            queue: Rc::new(RefCell::new(queue)),
+        }
+    }
+
+    /// Attempt to pull out the next event, registering the current task for wake up if it's
+    /// not available yet.
+    ///
+    /// Returns `Poll::Ready(None)` once the stream has ended.
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        let mut queue = self.queue.borrow_mut();
+        match queue.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if queue.closed => Poll::Ready(None),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// The next event, or `None` once the stream has ended.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Next<'_, E> {
+        Next { stream: self }
+    }
+}
+
+impl<E> fmt::Debug for EventStream<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStream").finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`EventStream::next`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Next<'a, E> {
+    stream: &'a mut EventStream<E>,
+}
+
+impl<E> Future for Next<'_, E> {
+    type Output = Option<E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    use super::*;
+
+    /// A future completing once the flag is set, from any thread.
+    #[derive(Clone, Default)]
+    struct Signal(Arc<Mutex<(bool, Option<Waker>)>>);
+
+    impl Signal {
+        fn set(&self) {
+            let mut state = self.0.lock().unwrap();
+            state.0 = true;
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    impl Future for Signal {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut state = self.0.lock().unwrap();
+            if state.0 {
+                Poll::Ready(())
+            } else {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    fn executor() -> (Executor, Arc<AtomicUsize>) {
+        let wake_ups = Arc::new(AtomicUsize::new(0));
+        let counter = wake_ups.clone();
+        let executor = Executor::with_wake_up(Box::new(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+        (executor, wake_ups)
+    }
+
+    fn handle_event(executor: &mut Executor) {
+        executor.handle_event(Event::<()>::MainEventsCleared);
+    }
+
+    #[test]
+    fn wake_from_another_thread() {
+        let (mut executor, wake_ups) = executor();
+        let signal = Signal::default();
+        let done = Rc::new(Cell::new(false));
+
+        let (task_signal, task_done) = (signal.clone(), done.clone());
+        executor.spawn(async move {
+            task_signal.await;
+            task_done.set(true);
+        });
+        handle_event(&mut executor);
+        assert!(!done.get());
+
+        let wake_ups_before = wake_ups.load(Ordering::Relaxed);
+        thread::spawn(move || signal.set()).join().unwrap();
+        assert_eq!(wake_ups.load(Ordering::Relaxed), wake_ups_before + 1);
+
+        handle_event(&mut executor);
+        assert!(done.get());
+    }
+
+    #[test]
+    fn completed_task_is_dropped() {
+        let (mut executor, _) = executor();
+        let guard = Rc::new(());
+
+        let task_guard = guard.clone();
+        executor.spawn(async move {
+            let _guard = task_guard;
+        });
+        assert_eq!(Rc::strong_count(&guard), 2);
+
+        handle_event(&mut executor);
+        assert_eq!(Rc::strong_count(&guard), 1);
+        assert!(executor.spawner.inner.borrow().tasks.is_empty());
+    }
+
+    #[test]
+    fn wake_after_executor_is_gone() {
+        let (mut executor, wake_ups) = executor();
+        let signal = Signal::default();
+
+        executor.spawn(signal.clone());
+        handle_event(&mut executor);
+        drop(executor);
+
+        // The waker outlives the executor, waking it up only reaches the closed event loop.
+        let wake_ups_before = wake_ups.load(Ordering::Relaxed);
+        thread::spawn(move || signal.set()).join().unwrap();
+        assert_eq!(wake_ups.load(Ordering::Relaxed), wake_ups_before + 1);
+    }
+}
@@ -148,15 +148,22 @@ extern crate serde;
 #[macro_use]
 extern crate bitflags;
 
+pub mod clipboard;
+mod cursor;
+pub mod device;
+pub mod dnd;
 pub mod dpi;
 #[macro_use]
 pub mod error;
 pub mod event;
 pub mod event_loop;
+pub mod executor;
 mod icon;
 pub mod keyboard;
 pub mod monitor;
 mod platform_impl;
+#[cfg(feature = "record")]
+pub mod record;
 pub mod window;
 
 pub mod platform;
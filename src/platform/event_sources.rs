@@ -0,0 +1,94 @@
+//! Event sources of the application, which are polled by the event loop.
+//!
+//! Instead of spawning a thread for each of its sockets, pipes or timers and waking up the event
+//! loop with an [`EventLoopProxy`], the application can register them with the event loop.
+//! [`Event::SourceReady`] is then emitted with the [`SourceId`] of the source once it's ready.
+//!
+//! The event loop itself can be polled from another event loop, such as an epoll instance or an
+//! async runtime, through its file descriptor, see the [`AsRawFd`] implementation of
+//! [`EventLoop`].
+//!
+//! [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+//! [`Event::SourceReady`]: crate::event::Event::SourceReady
+
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+
+use crate::error::ExternalError;
+use crate::event_loop::{EventLoop, EventLoopWindowTarget, SourceId};
+
+/// Additional methods on [`EventLoopWindowTarget`] to register event sources.
+pub trait EventLoopWindowTargetExtEventSources {
+    /// Register the file descriptor `fd` to emit [`Event::SourceReady`] when it's readable.
+    ///
+    /// The file descriptor is level-triggered, so the event is emitted on every iteration of the
+    /// event loop until the data is read. It must be unregistered with
+    /// [`unregister_source`](Self::unregister_source) before being closed.
+    ///
+    /// [`Event::SourceReady`]: crate::event::Event::SourceReady
+    fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError>;
+
+    /// Register a timer to emit [`Event::SourceReady`] at the `deadline`, and then on every
+    /// `interval` if any.
+    ///
+    /// A timer without `interval` is unregistered once it has expired.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **FreeBSD / NetBSD / OpenBSD:** The timer doesn't make the file descriptor of the
+    ///   [`EventLoop`] readable, like the deadline of [`ControlFlow::WaitUntil`].
+    ///
+    /// [`Event::SourceReady`]: crate::event::Event::SourceReady
+    /// [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
+    fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError>;
+
+    /// Unregister the event source, which doesn't emit events anymore.
+    ///
+    /// Does nothing if the source was already unregistered.
+    fn unregister_source(&self, source: SourceId);
+}
+
+impl<T: 'static> EventLoopWindowTargetExtEventSources for EventLoopWindowTarget<T> {
+    #[inline]
+    fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        self.p.register_fd(fd)
+    }
+
+    #[inline]
+    fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        self.p.register_timer(deadline, interval)
+    }
+
+    #[inline]
+    fn unregister_source(&self, source: SourceId) {
+        self.p.unregister_source(source)
+    }
+}
+
+/// The file descriptor of the event loop, which is readable when the event loop has events to
+/// dispatch.
+///
+/// It covers the connection to the display server, the [`EventLoopProxy`], the wake ups of the
+/// windows, and the registered event sources. When it's readable, the events are dispatched with
+/// [`pump_events`].
+///
+/// The deadline of [`ControlFlow::WaitUntil`] and the timers internal to winit, like the key
+/// repeat on Wayland, don't make it readable, so the event loop must also be pumped when they're
+/// due.
+///
+/// [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+/// [`pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+/// [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
+impl<T> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.event_loop.as_raw_fd()
+    }
+}
@@ -0,0 +1,251 @@
+//! The headless backend, running the event loop without a display server.
+//!
+//! It's selected with [`EventLoopBuilderExtHeadless::with_headless`] or by setting the
+//! `WINIT_UNIX_BACKEND` environment variable to `headless`, and is never picked otherwise.
+//!
+//! The windows only exist in memory, on a single virtual monitor of 1920x1080 at the origin,
+//! with a refresh rate of 60Hz and a scale factor of 1. Nothing is rendered, and the raw window
+//! handles are empty. The setters of the [`Window`] take effect right away, and queue the
+//! matching events:
+//!
+//! - The requested size is clamped within the minimum and maximum sizes, queuing
+//!   [`WindowEvent::Resized`] and a redraw when it changes.
+//! - Setting the position queues [`WindowEvent::Moved`] when it changes.
+//! - A maximized window covers the monitor, within its minimum and maximum sizes, and a
+//!   fullscreen one covers the monitor, or has the size of the video mode for
+//!   [`Fullscreen::Exclusive`]. The size and position requested in the meantime are applied once
+//!   the window is restored, and [`Window::request_inner_size`] returns `None`.
+//! - The windows never get the focus on their own, only with [`Window::focus_window`] or
+//!   [`HeadlessInput::focus`].
+//! - Allowing IME queues [`Ime::Enabled`], and disallowing it [`Ime::Disabled`].
+//!
+//! The input is injected with a [`HeadlessInput`], and flows through the event loop like the
+//! one of real devices, with the time of the injection as its timestamp. Unlike with the other
+//! backends, any number of event loops can exist at once, so each test can create its own, even
+//! when the tests run in parallel. They still need the `with_any_thread` of
+//! [`EventLoopBuilderExtX11`] or [`EventLoopBuilderExtWayland`] off the main thread.
+//!
+//! ```no_run
+//! use winit::event::{ElementState, Event, WindowEvent};
+//! use winit::event_loop::EventLoopBuilder;
+//! use winit::keyboard::{Key, KeyCode};
+//! use winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopWindowTargetExtHeadless};
+//! use winit::platform::pump_events::EventLoopExtPumpEvents;
+//! use winit::window::Window;
+//!
+//! let mut event_loop = EventLoopBuilder::new().with_headless().build();
+//! let window = Window::new(&event_loop).unwrap();
+//!
+//! let input = event_loop.headless_input().unwrap();
+//! input.keyboard_input(
+//!     window.id(),
+//!     KeyCode::KeyA,
+//!     Key::Character("a".into()),
+//!     Some("a"),
+//!     ElementState::Pressed,
+//! );
+//!
+//! event_loop.pump_events(Some(std::time::Duration::ZERO), |event, _, _| {
+//!     if let Event::WindowEvent {
+//!         event: WindowEvent::KeyboardInput { event, .. },
+//!         ..
+//!     } = event
+//!     {
+//!         assert_eq!(event.text.as_deref(), Some("a"));
+//!     }
+//! });
+//! ```
+//!
+//! [`Window`]: crate::window::Window
+//! [`Window::request_inner_size`]: crate::window::Window::request_inner_size
+//! [`Window::focus_window`]: crate::window::Window::focus_window
+//! [`WindowEvent::Resized`]: crate::event::WindowEvent::Resized
+//! [`WindowEvent::Moved`]: crate::event::WindowEvent::Moved
+//! [`Fullscreen::Exclusive`]: crate::window::Fullscreen::Exclusive
+//! [`Ime::Enabled`]: crate::event::Ime::Enabled
+//! [`Ime::Disabled`]: crate::event::Ime::Disabled
+//! [`EventLoopBuilderExtX11`]: crate::platform::x11::EventLoopBuilderExtX11
+//! [`EventLoopBuilderExtWayland`]: crate::platform::wayland::EventLoopBuilderExtWayland
+
+use std::fmt;
+
+use smol_str::SmolStr;
+
+use crate::dpi::PhysicalPosition;
+use crate::event::{ElementState, Ime, MouseButton, MouseScrollDelta, TouchPhase};
+use crate::event_loop::{EventLoopBuilder, EventLoopWindowTarget};
+use crate::keyboard::{Key, KeyCode, ModifiersState};
+use crate::platform_impl::{
+    headless, Backend, EventLoopWindowTarget as LinuxEventLoopWindowTarget,
+};
+use crate::window::WindowId;
+
+/// Additional methods on [`EventLoopWindowTarget`] that are specific to the headless backend.
+pub trait EventLoopWindowTargetExtHeadless {
+    /// True if the [`EventLoopWindowTarget`] uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// A handle to inject input into the windows.
+    ///
+    /// Returns `None` if the [`EventLoopWindowTarget`] doesn't use the headless backend.
+    fn headless_input(&self) -> Option<HeadlessInput>;
+}
+
+impl<T> EventLoopWindowTargetExtHeadless for EventLoopWindowTarget<T> {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        self.p.is_headless()
+    }
+
+    #[inline]
+    fn headless_input(&self) -> Option<HeadlessInput> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Headless(ref p) => Some(HeadlessInput { p: p.input() }),
+            _ => None,
+        }
+    }
+}
+
+/// Additional methods on [`EventLoopBuilder`] that are specific to the headless backend.
+pub trait EventLoopBuilderExtHeadless {
+    /// Force using the headless backend.
+    fn with_headless(&mut self) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtHeadless for EventLoopBuilder<T> {
+    #[inline]
+    fn with_headless(&mut self) -> &mut Self {
+        self.platform_specific.forced_backend = Some(Backend::Headless);
+        self
+    }
+}
+
+/// Injects input into the windows of the headless backend.
+///
+/// The events are delivered on the next iteration of the event loop, which is woken up. The
+/// input for a window which doesn't exist anymore is ignored.
+///
+/// It can be sent to other threads, to drive the event loop from there.
+#[derive(Clone)]
+pub struct HeadlessInput {
+    p: headless::Input,
+}
+
+impl HeadlessInput {
+    /// Give the keyboard focus to the window, or take it away.
+    ///
+    /// Only one window has the focus at a time, so focusing a window takes the focus away from
+    /// the other ones, queuing [`WindowEvent::Focused`] for each change.
+    ///
+    /// [`WindowEvent::Focused`]: crate::event::WindowEvent::Focused
+    pub fn focus(&self, window_id: WindowId, focused: bool) {
+        self.p.focus(window_id.0, focused)
+    }
+
+    /// Press or release a key, queuing [`WindowEvent::KeyboardInput`].
+    ///
+    /// The `text` is the text produced by the key, if any, with the modifiers applied. The
+    /// location of the key is derived from the `physical_key`.
+    ///
+    /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+    pub fn keyboard_input(
+        &self,
+        window_id: WindowId,
+        physical_key: KeyCode,
+        logical_key: Key,
+        text: Option<&str>,
+        state: ElementState,
+    ) {
+        self.p.keyboard_input(
+            window_id.0,
+            physical_key,
+            logical_key,
+            text.map(SmolStr::new),
+            state,
+        )
+    }
+
+    /// Change the state of the modifiers, queuing [`WindowEvent::ModifiersChanged`].
+    ///
+    /// [`WindowEvent::ModifiersChanged`]: crate::event::WindowEvent::ModifiersChanged
+    pub fn modifiers(&self, window_id: WindowId, modifiers: ModifiersState) {
+        self.p.modifiers(window_id.0, modifiers)
+    }
+
+    /// Send an event of the input method, queuing [`WindowEvent::Ime`].
+    ///
+    /// The event is ignored unless IME is allowed on the window, see
+    /// [`Window::set_ime_allowed`].
+    ///
+    /// [`WindowEvent::Ime`]: crate::event::WindowEvent::Ime
+    /// [`Window::set_ime_allowed`]: crate::window::Window::set_ime_allowed
+    pub fn ime(&self, window_id: WindowId, ime: Ime) {
+        self.p.ime(window_id.0, ime)
+    }
+
+    /// Move the cursor over the window, queuing [`WindowEvent::CursorMoved`].
+    ///
+    /// [`WindowEvent::CursorEntered`] is queued first when the cursor enters the window, after
+    /// [`WindowEvent::CursorLeft`] for the window it was over. Once the cursor is over the
+    /// window, [`DeviceEvent::MouseMotion`] is queued with the motion.
+    ///
+    /// The grab of the cursor is honored: a confined cursor is kept within the window, and a
+    /// locked cursor stays in place, only queuing [`DeviceEvent::MouseMotion`].
+    ///
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    /// [`WindowEvent::CursorEntered`]: crate::event::WindowEvent::CursorEntered
+    /// [`WindowEvent::CursorLeft`]: crate::event::WindowEvent::CursorLeft
+    /// [`DeviceEvent::MouseMotion`]: crate::event::DeviceEvent::MouseMotion
+    pub fn cursor_moved(&self, window_id: WindowId, position: PhysicalPosition<f64>) {
+        self.p.cursor_moved(window_id.0, position)
+    }
+
+    /// Move the cursor out of the window, queuing [`WindowEvent::CursorLeft`].
+    ///
+    /// A grabbed cursor can't leave the window.
+    ///
+    /// [`WindowEvent::CursorLeft`]: crate::event::WindowEvent::CursorLeft
+    pub fn cursor_left(&self, window_id: WindowId) {
+        self.p.cursor_left(window_id.0)
+    }
+
+    /// Press or release a mouse button, queuing [`WindowEvent::MouseInput`].
+    ///
+    /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+    pub fn mouse_input(&self, window_id: WindowId, button: MouseButton, state: ElementState) {
+        self.p.mouse_input(window_id.0, button, state)
+    }
+
+    /// Scroll the mouse wheel, queuing [`WindowEvent::MouseWheel`].
+    ///
+    /// [`WindowEvent::MouseWheel`]: crate::event::WindowEvent::MouseWheel
+    pub fn mouse_wheel(&self, window_id: WindowId, delta: MouseScrollDelta, phase: TouchPhase) {
+        self.p.mouse_wheel(window_id.0, delta, phase)
+    }
+
+    /// Touch the window with the finger `id`, queuing [`WindowEvent::Touch`].
+    ///
+    /// [`WindowEvent::Touch`]: crate::event::WindowEvent::Touch
+    pub fn touch(
+        &self,
+        window_id: WindowId,
+        id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+    ) {
+        self.p.touch(window_id.0, id, phase, location)
+    }
+
+    /// Ask to close the window, queuing [`WindowEvent::CloseRequested`].
+    ///
+    /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+    pub fn close_requested(&self, window_id: WindowId) {
+        self.p.close_requested(window_id.0)
+    }
+}
+
+impl fmt::Debug for HeadlessInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeadlessInput").finish_non_exhaustive()
+    }
+}
@@ -12,11 +12,18 @@
 //! And the following platform-specific module:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `unix`)
+//! - `event_sources` (available on `unix`)
+//! - `headless` (available on `unix`)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
 #[cfg(android_platform)]
 pub mod android;
+#[cfg(any(x11_platform, wayland_platform))]
+pub mod event_sources;
+#[cfg(any(x11_platform, wayland_platform))]
+pub mod headless;
 #[cfg(ios_platform)]
 pub mod ios;
 #[cfg(macos_platform)]
@@ -35,6 +42,8 @@ pub mod windows;
 pub mod x11;
 
 pub mod modifier_supplement;
+#[cfg(any(x11_platform, wayland_platform))]
+pub mod pump_events;
 #[cfg(any(
     windows_platform,
     macos_platform,
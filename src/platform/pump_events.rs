@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// The status of the event loop after [`EventLoopExtPumpEvents::pump_events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PumpStatus {
+    /// The event loop is still running, and `pump_events` should be called again.
+    Continue,
+    /// The event loop has exited with the given code, after sending [`Event::LoopDestroyed`].
+    Exit(i32),
+}
+
+/// Additional methods on [`EventLoop`] for pumping events within an external event loop.
+pub trait EventLoopExtPumpEvents {
+    /// A type provided by the user that can be passed through [`Event::UserEvent`].
+    type UserEvent;
+
+    /// Runs a single iteration of the event loop, dispatching the pending events to the
+    /// `event_handler`, and returns.
+    ///
+    /// The first call sends [`Event::NewEvents`] with [`StartCause::Init`] and [`Event::Resumed`].
+    /// The next calls wait for new events as requested by the [`ControlFlow`], but never for
+    /// longer than `timeout`, a `timeout` of `None` waiting as long as the [`ControlFlow`] does.
+    /// The [`ControlFlow`] is kept between the calls, so setting it to [`ControlFlow::Poll`]
+    /// makes this return as soon as the pending events are handled. When waiting, a call which
+    /// times out before any new event arrives returns without dispatching anything, and the
+    /// deadline of [`ControlFlow::WaitUntil`] holds across the calls.
+    ///
+    /// Once the [`ControlFlow`] is set to [`ControlFlow::ExitWithCode`], this sends
+    /// [`Event::LoopDestroyed`] and returns [`PumpStatus::Exit`]. Calling this again after that
+    /// starts the event loop anew, like [`EventLoopExtRunReturn::run_return`] does, without
+    /// tearing down the windows.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Wayland:** This returns [`PumpStatus::Exit`] with `1` upon disconnection from
+    ///   the display server.
+    ///
+    /// [`StartCause::Init`]: crate::event::StartCause::Init
+    /// [`EventLoopExtRunReturn::run_return`]: crate::platform::run_return::EventLoopExtRunReturn::run_return
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+    type UserEvent = T;
+
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        ),
+    {
+        self.event_loop.pump_events(timeout, event_handler)
+    }
+}
@@ -62,6 +62,7 @@ impl<T> EventLoopExtStartupNotify for EventLoopWindowTarget<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(_) => env::var(WAYLAND_VAR),
             #[cfg(x11_platform)]
             crate::platform_impl::EventLoopWindowTarget::X(_) => env::var(X11_VAR),
+            crate::platform_impl::EventLoopWindowTarget::Headless(_) => return None,
         }
         .ok()
         .map(ActivationToken::_new)
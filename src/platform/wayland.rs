@@ -3,6 +3,7 @@ use std::os::raw;
 use sctk::reexports::client::Proxy;
 
 use crate::{
+    error::{ExternalError, NotSupportedError},
     event_loop::{EventLoopBuilder, EventLoopWindowTarget},
     monitor::MonitorHandle,
     window::{Window, WindowBuilder},
@@ -43,7 +44,6 @@ impl<T> EventLoopWindowTargetExtWayland for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.connection.display().id().as_ptr() as *mut _)
             }
-            #[cfg(x11_platform)]
             _ => None,
         }
     }
@@ -90,6 +90,33 @@ pub trait WindowExtWayland {
     ///
     /// The pointer will become invalid when the [`Window`] is destroyed.
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
+
+    /// Set whether a child window, created with [`WindowBuilder::with_parent_window`], is
+    /// synchronized with its parent.
+    ///
+    /// A synchronized subsurface is only updated along with its parent, when the parent is
+    /// redrawn, while a desynchronized one is updated on its own, like to play a video. Child
+    /// windows are synchronized by default.
+    ///
+    /// A new child window is stacked above its parent and the child windows created before it.
+    /// It shows up once both it and its parent are redrawn, the redraw of the parent being
+    /// requested when the child window is created.
+    ///
+    /// Returns [`ExternalError::NotSupported`] if the window isn't a child window.
+    fn set_subsurface_sync(&self, sync: bool) -> Result<(), ExternalError>;
+
+    /// Stack a child window right above the `sibling`, another child window of the same parent
+    /// or the parent itself.
+    ///
+    /// Like the position of a child window, the stacking order is applied when the parent is
+    /// next redrawn.
+    ///
+    /// Returns [`ExternalError::NotSupported`] if the window isn't a child window.
+    fn place_subsurface_above(&self, sibling: &Window) -> Result<(), ExternalError>;
+
+    /// Stack a child window right below the `sibling`, see
+    /// [`WindowExtWayland::place_subsurface_above`].
+    fn place_subsurface_below(&self, sibling: &Window) -> Result<(), ExternalError>;
 }
 
 impl WindowExtWayland for Window {
@@ -97,7 +124,6 @@ impl WindowExtWayland for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().id().as_ptr() as *mut _),
-            #[cfg(x11_platform)]
             _ => None,
         }
     }
@@ -106,10 +132,37 @@ impl WindowExtWayland for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().id().as_ptr() as *mut _),
-            #[cfg(x11_platform)]
             _ => None,
         }
     }
+
+    #[inline]
+    fn set_subsurface_sync(&self, sync: bool) -> Result<(), ExternalError> {
+        match self.window {
+            LinuxWindow::Wayland(ref w) => w.set_subsurface_sync(sync),
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    fn place_subsurface_above(&self, sibling: &Window) -> Result<(), ExternalError> {
+        match (&self.window, &sibling.window) {
+            (LinuxWindow::Wayland(w), LinuxWindow::Wayland(sibling)) => {
+                w.place_subsurface(sibling, true)
+            }
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    fn place_subsurface_below(&self, sibling: &Window) -> Result<(), ExternalError> {
+        match (&self.window, &sibling.window) {
+            (LinuxWindow::Wayland(w), LinuxWindow::Wayland(sibling)) => {
+                w.place_subsurface(sibling, false)
+            }
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
 }
 
 /// Additional methods on [`WindowBuilder`] that are specific to Wayland.
@@ -129,6 +129,16 @@ impl RedrawRequester {
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KeyEventExtra {}
 
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        _key_without_modifiers: crate::keyboard::Key,
+        _text_with_all_modifiers: Option<smol_str::SmolStr>,
+    ) -> Self {
+        Self {}
+    }
+}
+
 pub struct EventLoop<T: 'static> {
     android_app: AndroidApp,
     window_target: event_loop::EventLoopWindowTarget<T>,
@@ -392,6 +402,8 @@ impl<T: 'static> EventLoop<T> {
                                         location,
                                         id: pointer.pointer_id() as u64,
                                         force: None,
+                                        contact_size: None,
+                                        timestamp: None,
                                     },
                                 ),
                             };
@@ -438,6 +450,7 @@ impl<T: 'static> EventLoop<T> {
                                         location: keycodes::to_location(keycode),
                                         repeat: key.repeat_count() > 0,
                                         text: None,
+                                        timestamp: None,
                                         platform_specific: KeyEventExtra {},
                                     },
                                     is_synthetic: false,
@@ -253,7 +253,9 @@ impl WinitView {
                     id: touch_id,
                     location: physical_location,
                     force,
+                    contact_size: None,
                     phase,
+                    timestamp: None,
                 }),
             }));
         }
@@ -0,0 +1,15 @@
+use crate::cursor::CustomCursor;
+use crate::window::CursorIcon;
+
+/// The cursor shown over a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectedCursor {
+    Named(CursorIcon),
+    Custom(CustomCursor),
+}
+
+impl Default for SelectedCursor {
+    fn default() -> Self {
+        SelectedCursor::Named(CursorIcon::Default)
+    }
+}
@@ -0,0 +1,90 @@
+//! Drag and drop helpers shared by the X11 and Wayland backends.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    str::Utf8Error,
+};
+
+use percent_encoding::percent_decode;
+
+use crate::{clipboard::ClipboardContent, dnd::DndAction, window::Icon};
+
+/// The MIME type used to transfer the list of files.
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// The request to drag the data out of the window.
+#[derive(Debug)]
+pub struct DragRequest {
+    pub content: ClipboardContent,
+    /// The allowed actions in the order of preference, never empty.
+    pub actions: Vec<DndAction>,
+    pub icon: Option<Icon>,
+}
+
+impl DragRequest {
+    pub fn new(content: ClipboardContent, actions: &[DndAction], icon: Option<Icon>) -> Self {
+        let actions = if actions.is_empty() {
+            vec![DndAction::Copy]
+        } else {
+            actions.to_vec()
+        };
+
+        Self {
+            content,
+            actions,
+            icon,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DndDataParseError {
+    EmptyData,
+    InvalidUtf8(Utf8Error),
+    HostnameSpecified(String),
+    UnexpectedProtocol(String),
+    UnresolvablePath(io::Error),
+}
+
+impl From<Utf8Error> for DndDataParseError {
+    fn from(e: Utf8Error) -> Self {
+        DndDataParseError::InvalidUtf8(e)
+    }
+}
+
+impl From<io::Error> for DndDataParseError {
+    fn from(e: io::Error) -> Self {
+        DndDataParseError::UnresolvablePath(e)
+    }
+}
+
+/// Parse the `text/uri-list` data into the list of paths.
+pub fn parse_uri_list(data: &[u8]) -> Result<Vec<PathBuf>, DndDataParseError> {
+    if !data.is_empty() {
+        let mut path_list = Vec::new();
+        let decoded = percent_decode(data).decode_utf8()?.into_owned();
+        for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
+            // The format is specified as protocol://host/path
+            // However, it's typically simply protocol:///path
+            let path_str = if uri.starts_with("file://") {
+                let path_str = uri.replace("file://", "");
+                if !path_str.starts_with('/') {
+                    // A hostname is specified
+                    // Supporting this case is beyond the scope of my mental health
+                    return Err(DndDataParseError::HostnameSpecified(path_str));
+                }
+                path_str
+            } else {
+                // Only the file protocol is supported
+                return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
+            };
+
+            let path = Path::new(&path_str).canonicalize()?;
+            path_list.push(path);
+        }
+        Ok(path_list)
+    } else {
+        Err(DndDataParseError::EmptyData)
+    }
+}
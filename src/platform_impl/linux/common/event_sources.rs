@@ -0,0 +1,413 @@
+//! The event sources registered by the application, and the file descriptor polling them along
+//! with the sources of the event loop.
+//!
+//! The file descriptor is an epoll instance on Linux and Android, and a kqueue on the BSDs.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use calloop::generic::Generic;
+use calloop::{Interest, LoopHandle, Mode, PostAction, RegistrationToken};
+
+use crate::error::ExternalError;
+use crate::event_loop::SourceId;
+use crate::platform_impl::OsError;
+
+/// Wakes up the event loop, including when it's polled through [`EventSources::as_raw_fd`].
+#[derive(Debug)]
+pub struct Waker {
+    reader: UnixStream,
+    writer: UnixStream,
+}
+
+impl Waker {
+    fn new() -> io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        writer.set_nonblocking(true)?;
+        Ok(Self { reader, writer })
+    }
+
+    /// Wake up the event loop.
+    pub fn ping(&self) {
+        // The write only fails when the socket is full, which still wakes up.
+        let _ = (&self.writer).write(&[0]);
+    }
+
+    fn reset(&self) {
+        let mut buffer = [0u8; 64];
+        while let Ok(1..) = (&self.reader).read(&mut buffer) {}
+    }
+}
+
+/// A timer backed by a file descriptor, so it can be polled from outside of the event loop.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+struct TimerFd {
+    fd: OwnedFd,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl TimerFd {
+    fn new(deadline: Instant, interval: Option<Duration>) -> io::Result<Self> {
+        let fd = unsafe {
+            libc::timerfd_create(
+                libc::CLOCK_MONOTONIC,
+                libc::TFD_CLOEXEC | libc::TFD_NONBLOCK,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let timer = Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        };
+
+        // A zero value disarms the timer, so a deadline in the past expires right away instead.
+        let value = deadline
+            .saturating_duration_since(Instant::now())
+            .max(Duration::from_nanos(1));
+        let spec = libc::itimerspec {
+            it_interval: timespec(interval.unwrap_or(Duration::ZERO)),
+            it_value: timespec(value),
+        };
+        if unsafe { libc::timerfd_settime(fd, 0, &spec, std::ptr::null_mut()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(timer)
+    }
+
+    fn reset(&self) {
+        let mut expirations = 0u64;
+        unsafe {
+            libc::read(
+                self.fd.as_raw_fd(),
+                &mut expirations as *mut u64 as *mut _,
+                std::mem::size_of::<u64>(),
+            )
+        };
+    }
+}
+
+impl AsRawFd for Waker {
+    fn as_raw_fd(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl AsRawFd for TimerFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs() as _,
+        tv_nsec: duration.subsec_nanos() as _,
+    }
+}
+
+/// Polls the file descriptors of the sources, becoming readable when any of them is.
+#[derive(Debug)]
+struct Poller {
+    fd: OwnedFd,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Poller {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    fn add(&self, fd: RawFd) -> io::Result<()> {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: fd as u64,
+        };
+        if unsafe { libc::epoll_ctl(self.fd.as_raw_fd(), libc::EPOLL_CTL_ADD, fd, &mut event) } < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    fn remove(&self, fd: RawFd) {
+        unsafe {
+            libc::epoll_ctl(
+                self.fd.as_raw_fd(),
+                libc::EPOLL_CTL_DEL,
+                fd,
+                std::ptr::null_mut(),
+            )
+        };
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+impl Poller {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::kqueue() };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { fd })
+    }
+
+    fn add(&self, fd: RawFd) -> io::Result<()> {
+        self.change(fd, true)
+    }
+
+    fn remove(&self, fd: RawFd) {
+        let _ = self.change(fd, false);
+    }
+
+    fn change(&self, fd: RawFd, add: bool) -> io::Result<()> {
+        // The layout of `kevent` differs between the BSDs, the other fields are zeroed.
+        let mut change: libc::kevent = unsafe { std::mem::zeroed() };
+        change.ident = fd as _;
+        change.filter = libc::EVFILT_READ;
+        change.flags = if add { libc::EV_ADD } else { libc::EV_DELETE };
+        let result = unsafe {
+            libc::kevent(
+                self.fd.as_raw_fd(),
+                &change,
+                1,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null(),
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+impl AsRawFd for Poller {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// The registration of a source with the event loop.
+struct Source {
+    token: RegistrationToken,
+
+    /// The file descriptor of the application, which must be removed from the poller, unlike
+    /// the ones owned by winit which are closed with the source.
+    fd: Option<RawFd>,
+}
+
+/// The event sources of the application.
+pub struct EventSources<D> {
+    loop_handle: LoopHandle<'static, D>,
+
+    /// Polls the sources along with the event loop.
+    poller: Poller,
+
+    waker: Arc<Waker>,
+
+    sources: Rc<RefCell<HashMap<SourceId, Source>>>,
+
+    /// Queues the event of a ready source.
+    on_ready: fn(&mut D, SourceId),
+}
+
+impl<D> EventSources<D> {
+    /// The waker of the event loop.
+    ///
+    /// It must be pinged when waking up the event loop from the windows or the proxies, so the
+    /// file descriptor of the event loop becomes readable as well.
+    pub fn waker(&self) -> &Arc<Waker> {
+        &self.waker
+    }
+}
+
+impl<D: 'static> EventSources<D> {
+    /// Create the event sources, polling the `connection_fd` of the display server along with
+    /// them if any.
+    pub fn new(
+        loop_handle: LoopHandle<'static, D>,
+        connection_fd: Option<RawFd>,
+        on_ready: fn(&mut D, SourceId),
+    ) -> io::Result<Self> {
+        let poller = Poller::new()?;
+        let waker = Arc::new(Waker::new()?);
+        let source = Generic::new(waker.as_raw_fd(), Interest::READ, Mode::Level);
+        let waker_clone = waker.clone();
+        loop_handle
+            .insert_source(source, move |_, _, _| {
+                waker_clone.reset();
+                Ok(PostAction::Continue)
+            })
+            .map_err(|error| io::Error::from(error.error))?;
+
+        let event_sources = Self {
+            loop_handle,
+            poller,
+            waker,
+            sources: Default::default(),
+            on_ready,
+        };
+        if let Some(connection_fd) = connection_fd {
+            event_sources.poller.add(connection_fd)?;
+        }
+        event_sources.poller.add(event_sources.waker.as_raw_fd())?;
+
+        Ok(event_sources)
+    }
+
+    pub fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        let id = SourceId::get();
+        let on_ready = self.on_ready;
+        let source = Generic::new(fd, Interest::READ, Mode::Level);
+        let token = self
+            .loop_handle
+            .insert_source(source, move |_, _, data| {
+                on_ready(data, id);
+                Ok(PostAction::Continue)
+            })
+            .map_err(|error| os_error(error.error.into()))?;
+
+        if let Err(error) = self.poller.add(fd) {
+            self.loop_handle.remove(token);
+            return Err(os_error(error));
+        }
+
+        let source = Source {
+            token,
+            fd: Some(fd),
+        };
+        self.sources.borrow_mut().insert(id, source);
+        Ok(id)
+    }
+
+    pub fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        let id = SourceId::get();
+        let on_ready = self.on_ready;
+        let sources = Rc::downgrade(&self.sources);
+        let on_expired = move |data: &mut D| {
+            on_ready(data, id);
+
+            // The timer is removed from the event loop once it has expired.
+            if interval.is_none() {
+                if let Some(sources) = sources.upgrade() {
+                    sources.borrow_mut().remove(&id);
+                }
+            }
+        };
+
+        let token = self.insert_timer(deadline, interval, on_expired)?;
+        let source = Source { token, fd: None };
+        self.sources.borrow_mut().insert(id, source);
+        Ok(id)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn insert_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+        on_expired: impl Fn(&mut D) + 'static,
+    ) -> Result<RegistrationToken, ExternalError> {
+        let timer = TimerFd::new(deadline, interval).map_err(os_error)?;
+        let timer_fd = timer.as_raw_fd();
+
+        let source = Generic::new(timer, Interest::READ, Mode::Level);
+        let token = self
+            .loop_handle
+            .insert_source(source, move |_, timer, data| {
+                timer.reset();
+                on_expired(data);
+
+                // The timer is closed when removed, which also removes it from the poller.
+                match interval {
+                    Some(_) => Ok(PostAction::Continue),
+                    None => Ok(PostAction::Remove),
+                }
+            })
+            .map_err(|error| os_error(error.error.into()))?;
+
+        if let Err(error) = self.poller.add(timer_fd) {
+            self.loop_handle.remove(token);
+            return Err(os_error(error));
+        }
+
+        Ok(token)
+    }
+
+    /// There's no timer file descriptor to poll on the BSDs, so the timers are only part of the
+    /// event loop.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn insert_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+        on_expired: impl Fn(&mut D) + 'static,
+    ) -> Result<RegistrationToken, ExternalError> {
+        use calloop::timer::{TimeoutAction, Timer};
+
+        self.loop_handle
+            .insert_source(Timer::from_deadline(deadline), move |deadline, _, data| {
+                on_expired(data);
+
+                match interval {
+                    Some(interval) => TimeoutAction::ToInstant(deadline + interval),
+                    None => TimeoutAction::Drop,
+                }
+            })
+            .map_err(|error| os_error(error.error.into()))
+    }
+
+    pub fn unregister_source(&self, id: SourceId) {
+        let source = match self.sources.borrow_mut().remove(&id) {
+            Some(source) => source,
+            None => return,
+        };
+
+        if let Some(fd) = source.fd {
+            self.poller.remove(fd);
+        }
+        self.loop_handle.remove(source.token);
+    }
+}
+
+impl<D> AsRawFd for EventSources<D> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.poller.as_raw_fd()
+    }
+}
+
+fn os_error(error: io::Error) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::IoError(error.into())))
+}
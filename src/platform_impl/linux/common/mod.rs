@@ -1,2 +1,7 @@
+pub mod cursor;
+pub mod dnd;
+pub mod event_sources;
 pub mod keymap;
+pub mod modal;
+pub mod timestamp;
 pub mod xkb_state;
@@ -0,0 +1,110 @@
+//! The owners of modal dialogs, whose input is blocked while the dialogs are shown.
+
+use crate::event::{ElementState, PenEvent, PointerPhase, TouchPhase, WindowEvent};
+
+/// Whether the event is input from the user, which isn't delivered to the owners of modal
+/// dialogs.
+///
+/// The releases are still delivered, since the press which opened the dialog would otherwise
+/// stay held on the owner.
+pub fn is_user_input(event: &WindowEvent<'_>) -> bool {
+    match event {
+        WindowEvent::KeyboardInput { event, .. } => event.state == ElementState::Pressed,
+        WindowEvent::MouseInput { state, .. } => *state == ElementState::Pressed,
+        WindowEvent::Touch(touch) => {
+            !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled)
+        }
+        WindowEvent::Pointer(pointer) => !matches!(
+            pointer.phase,
+            PointerPhase::Up | PointerPhase::Left | PointerPhase::Cancelled
+        ),
+        WindowEvent::PenInput { event, .. } => !matches!(
+            event,
+            PenEvent::Tip {
+                state: ElementState::Released
+            } | PenEvent::Button {
+                state: ElementState::Released,
+                ..
+            } | PenEvent::Left
+        ),
+        event => matches!(
+            event,
+            WindowEvent::Ime(_)
+                | WindowEvent::CursorMoved { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::TouchpadMagnify { .. }
+                | WindowEvent::SmartMagnify { .. }
+                | WindowEvent::TouchpadRotate { .. }
+                | WindowEvent::TouchpadSwipe { .. }
+                | WindowEvent::TouchpadHold { .. }
+                | WindowEvent::TouchpadPressure { .. }
+                | WindowEvent::AxisMotion { .. }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dpi::PhysicalPosition;
+    use crate::event::{DeviceId, MouseButton, Pointer, PointerButtons, PointerKind, Touch};
+
+    #[test]
+    fn press_open_modal_release() {
+        let device_id = unsafe { DeviceId::dummy() };
+        let mouse_input = |state| WindowEvent::MouseInput {
+            device_id,
+            state,
+            button: MouseButton::Left,
+            timestamp: None,
+        };
+        let touch = |phase| {
+            WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location: PhysicalPosition::new(0., 0.),
+                force: None,
+                contact_size: None,
+                id: 0,
+                timestamp: None,
+            })
+        };
+        let pointer = |phase| {
+            WindowEvent::Pointer(Pointer {
+                device_id,
+                pointer_id: 0,
+                kind: PointerKind::Mouse,
+                phase,
+                position: PhysicalPosition::new(0., 0.),
+                buttons: PointerButtons::empty(),
+                pressure: None,
+                tilt: None,
+                size: None,
+                timestamp: None,
+            })
+        };
+
+        let pen = |event| WindowEvent::PenInput { device_id, event };
+
+        // The dialog is opened by a press, the new presses are then blocked.
+        assert!(is_user_input(&mouse_input(ElementState::Pressed)));
+        assert!(is_user_input(&touch(TouchPhase::Started)));
+        assert!(is_user_input(&touch(TouchPhase::Moved)));
+        assert!(is_user_input(&pointer(PointerPhase::Down)));
+        assert!(is_user_input(&pen(PenEvent::Tip {
+            state: ElementState::Pressed
+        })));
+
+        // The releases reach the owner, so nothing stays held on it.
+        assert!(!is_user_input(&mouse_input(ElementState::Released)));
+        assert!(!is_user_input(&touch(TouchPhase::Ended)));
+        assert!(!is_user_input(&touch(TouchPhase::Cancelled)));
+        assert!(!is_user_input(&pointer(PointerPhase::Up)));
+        assert!(!is_user_input(&pointer(PointerPhase::Left)));
+        assert!(!is_user_input(&pen(PenEvent::Tip {
+            state: ElementState::Released
+        })));
+        assert!(!is_user_input(&pen(PenEvent::Left)));
+    }
+}
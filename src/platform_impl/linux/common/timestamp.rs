@@ -0,0 +1,48 @@
+//! The conversion of the time of the input events into an [`Instant`].
+//!
+//! Both X11 and Wayland report the time of the events on `CLOCK_MONOTONIC`, which is also the
+//! clock of [`Instant`] on Linux and BSD, so it's converted by measuring how long ago the event
+//! happened.
+
+use std::time::{Duration, Instant};
+
+/// The time of an event, in milliseconds wrapping around every 49 days.
+pub fn from_millis(time: u32) -> Instant {
+    let (instant, now) = now();
+
+    // The time of the event is only compared with the same precision, and when it's ahead of the
+    // clock the event is considered to have just happened.
+    let elapsed = (now.as_millis() as u32).wrapping_sub(time);
+    if elapsed > u32::MAX / 2 {
+        return instant;
+    }
+
+    instant
+        .checked_sub(Duration::from_millis(elapsed as u64))
+        .unwrap_or(instant)
+}
+
+/// The time of an event, in microseconds.
+pub fn from_micros(time: u64) -> Instant {
+    from_monotonic(Duration::from_micros(time))
+}
+
+/// The time of an event, with the full precision of `CLOCK_MONOTONIC`.
+pub fn from_monotonic(time: Duration) -> Instant {
+    let (instant, now) = now();
+    let elapsed = now.saturating_sub(time);
+    instant.checked_sub(elapsed).unwrap_or(instant)
+}
+
+/// The current time as an [`Instant`] and on `CLOCK_MONOTONIC`.
+fn now() -> (Instant, Duration) {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) };
+    (
+        Instant::now(),
+        Duration::new(time.tv_sec as u64, time.tv_nsec as u32),
+    )
+}
@@ -5,6 +5,7 @@ use std::os::raw::c_char;
 use std::os::unix::ffi::OsStringExt;
 use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 use once_cell::sync::Lazy;
 use smol_str::SmolStr;
@@ -373,6 +374,7 @@ impl KbdState {
         keycode: u32,
         state: ElementState,
         repeat: bool,
+        timestamp: Instant,
     ) -> KeyEvent {
         let mut event =
             KeyEventResults::new(self, keycode, !repeat && state == ElementState::Pressed);
@@ -394,6 +396,7 @@ impl KbdState {
             location,
             state,
             repeat,
+            timestamp: Some(timestamp),
             platform_specific,
         }
     }
@@ -0,0 +1,559 @@
+//! The headless event loop.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::process;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::SendError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use raw_window_handle::{DrmDisplayHandle, RawDisplayHandle};
+
+use crate::clipboard::{ClipboardContent, ClipboardKind};
+use crate::device::{DeviceCapabilities, DeviceInfo, DeviceKind};
+use crate::dnd::DndAction;
+use crate::error::{ExternalError, NotSupportedError};
+use crate::event::{DeviceEvent, Event, StartCause, WindowEvent};
+use crate::event_loop::{
+    AsyncRequestSerial, ControlFlow, EventLoopClosed,
+    EventLoopWindowTarget as RootEventLoopWindowTarget, SourceId,
+};
+use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::platform::common::event_sources::{EventSources, Waker};
+use crate::platform_impl::platform::sticky_exit_callback;
+use crate::platform_impl::{
+    EventLoopWindowTarget as PlatformEventLoopWindowTarget, MonitorHandle as PlatformMonitorHandle,
+};
+use crate::window::WindowId as RootWindowId;
+
+use super::window::WindowState;
+use super::{Input, MonitorHandle, WindowId};
+
+/// The state shared by the event loop, its windows and the input handles.
+pub struct Shared {
+    /// The events queued by the windows and the injected input.
+    events: Mutex<Vec<Event<'static, ()>>>,
+
+    /// The state of the windows.
+    pub windows: Mutex<HashMap<WindowId, Arc<Mutex<WindowState>>>>,
+
+    /// Wakes up the event loop when events are queued.
+    pub waker: Arc<Waker>,
+
+    next_window_id: AtomicU64,
+}
+
+impl Shared {
+    pub fn next_window_id(&self) -> WindowId {
+        WindowId(self.next_window_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn window(&self, window_id: WindowId) -> Option<Arc<Mutex<WindowState>>> {
+        self.windows.lock().unwrap().get(&window_id).cloned()
+    }
+
+    /// Queue a window event, and wake up the event loop.
+    pub fn push_window_event(&self, window_id: WindowId, event: WindowEvent<'static>) {
+        self.events.lock().unwrap().push(Event::WindowEvent {
+            window_id: RootWindowId(window_id),
+            event,
+        });
+        self.waker.ping();
+    }
+
+    /// Queue a device event, and wake up the event loop.
+    pub fn push_device_event(&self, event: DeviceEvent) {
+        self.events.lock().unwrap().push(Event::DeviceEvent {
+            device_id: super::root_device_id(),
+            event,
+            timestamp: Some(Instant::now()),
+        });
+        self.waker.ping();
+    }
+}
+
+/// The data of the calloop event loop.
+#[derive(Default)]
+pub struct LoopState {
+    source_events: Vec<SourceId>,
+}
+
+/// The headless event loop.
+pub struct EventLoop<T: 'static> {
+    /// Sender of user events.
+    user_events_sender: calloop::channel::Sender<T>,
+
+    /// Pending events from the user.
+    pending_user_events: Rc<RefCell<Vec<T>>>,
+
+    /// Event loop window target.
+    window_target: RootEventLoopWindowTarget<T>,
+
+    /// The control flow of the running loop, `None` until it's started.
+    control_flow: Option<ControlFlow>,
+
+    /// The state of the calloop event loop.
+    state: LoopState,
+
+    // XXX preallocate certian structures to avoid allocating on each loop iteration.
+    window_ids: Vec<WindowId>,
+    events: Vec<Event<'static, ()>>,
+
+    /// Calloop's event loop.
+    event_loop: calloop::EventLoop<'static, LoopState>,
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> io::Result<EventLoop<T>> {
+        let event_loop = calloop::EventLoop::<LoopState>::try_new()?;
+
+        // Setup the user proxy.
+        let pending_user_events = Rc::new(RefCell::new(Vec::new()));
+        let pending_user_events_clone = pending_user_events.clone();
+        let (user_events_sender, user_events_channel) = calloop::channel::channel();
+        event_loop
+            .handle()
+            .insert_source(user_events_channel, move |event, _, _| {
+                if let calloop::channel::Event::Msg(msg) = event {
+                    pending_user_events_clone.borrow_mut().push(msg);
+                }
+            })
+            .map_err(|error| io::Error::from(error.error))?;
+
+        // There's no connection to poll, only the event loop's awakener and the event sources
+        // of the application.
+        let event_sources = EventSources::new(
+            event_loop.handle(),
+            None,
+            |state: &mut LoopState, source| state.source_events.push(source),
+        )?;
+
+        let shared = Shared {
+            events: Default::default(),
+            windows: Default::default(),
+            waker: event_sources.waker().clone(),
+            next_window_id: AtomicU64::new(1),
+        };
+
+        let window_target = EventLoopWindowTarget {
+            shared: Arc::new(shared),
+            event_sources,
+            _marker: PhantomData,
+        };
+
+        Ok(Self {
+            user_events_sender,
+            pending_user_events,
+            window_target: RootEventLoopWindowTarget {
+                p: PlatformEventLoopWindowTarget::Headless(window_target),
+                _marker: PhantomData,
+            },
+            control_flow: None,
+            state: LoopState::default(),
+            window_ids: Vec::new(),
+            events: Vec::new(),
+            event_loop,
+        })
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow) + 'static,
+    {
+        let exit_code = self.run_return(callback);
+        process::exit(exit_code);
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        // Start the loop anew.
+        self.control_flow = None;
+
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                break code;
+            }
+        }
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = match self.control_flow.take() {
+            Some(control_flow) => control_flow,
+            None => {
+                let mut control_flow = ControlFlow::Poll;
+
+                callback(
+                    Event::NewEvents(StartCause::Init),
+                    &self.window_target,
+                    &mut control_flow,
+                );
+                callback(Event::Resumed, &self.window_target, &mut control_flow);
+
+                control_flow
+            }
+        };
+
+        if let Err(code) = self.single_iteration(&mut control_flow, timeout, &mut callback) {
+            control_flow = ControlFlow::ExitWithCode(code);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = control_flow {
+            callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+            PumpStatus::Exit(code)
+        } else {
+            self.control_flow = Some(control_flow);
+            PumpStatus::Continue
+        }
+    }
+
+    fn single_iteration<F>(
+        &mut self,
+        control_flow: &mut ControlFlow,
+        timeout: Option<Duration>,
+        callback: &mut F,
+    ) -> Result<(), i32>
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        match *control_flow {
+            ControlFlow::ExitWithCode(_) => return Ok(()),
+            ControlFlow::Poll => {
+                self.loop_dispatch(Some(Duration::ZERO))?;
+
+                callback(
+                    Event::NewEvents(StartCause::Poll),
+                    &self.window_target,
+                    control_flow,
+                );
+            }
+            ControlFlow::Wait => {
+                self.loop_dispatch(timeout)?;
+
+                // Don't run an empty iteration when the timeout of `pump_events` has passed.
+                if !self.has_pending() {
+                    return Ok(());
+                }
+
+                callback(
+                    Event::NewEvents(StartCause::WaitCancelled {
+                        start: Instant::now(),
+                        requested_resume: None,
+                    }),
+                    &self.window_target,
+                    control_flow,
+                );
+            }
+            ControlFlow::WaitUntil(deadline) => {
+                let start = Instant::now();
+
+                // Compute the amount of time we'll block for.
+                let duration = deadline.saturating_duration_since(start);
+                let duration = timeout.map_or(duration, |timeout| duration.min(timeout));
+
+                self.loop_dispatch(Some(duration))?;
+
+                let now = Instant::now();
+                if now < deadline && !self.has_pending() {
+                    return Ok(());
+                }
+
+                if now < deadline {
+                    callback(
+                        Event::NewEvents(StartCause::WaitCancelled {
+                            start,
+                            requested_resume: Some(deadline),
+                        }),
+                        &self.window_target,
+                        control_flow,
+                    )
+                } else {
+                    callback(
+                        Event::NewEvents(StartCause::ResumeTimeReached {
+                            start,
+                            requested_resume: deadline,
+                        }),
+                        &self.window_target,
+                        control_flow,
+                    )
+                }
+            }
+        }
+
+        // Reuse the structures preallocated for the loop iterations.
+        let mut window_ids = mem::take(&mut self.window_ids);
+        let mut events = mem::take(&mut self.events);
+
+        // Handle pending user events.
+        for user_event in self.pending_user_events.borrow_mut().drain(..) {
+            sticky_exit_callback(
+                Event::UserEvent(user_event),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Handle the ready event sources.
+        for source in mem::take(&mut self.state.source_events) {
+            sticky_exit_callback(
+                Event::SourceReady(source),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Handle the events of the windows and the injected input.
+        let shared = self.shared().clone();
+        events.append(&mut shared.events.lock().unwrap());
+        for event in events.drain(..) {
+            let event = event.map_nonuser_event().unwrap();
+            sticky_exit_callback(event, &self.window_target, control_flow, callback);
+        }
+
+        // Send events cleared.
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        // Collect the windows requesting a redraw.
+        window_ids.extend(
+            shared
+                .windows
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, window)| window.lock().unwrap().take_redraw_requested())
+                .map(|(window_id, _)| *window_id),
+        );
+        window_ids.sort_unstable();
+
+        for window_id in window_ids.drain(..) {
+            sticky_exit_callback(
+                Event::RedrawRequested(RootWindowId(window_id)),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        self.window_ids = window_ids;
+        self.events = events;
+
+        Ok(())
+    }
+
+    /// Whether there's anything to deliver to the application in a loop iteration.
+    fn has_pending(&self) -> bool {
+        let shared = self.shared();
+        !self.pending_user_events.borrow().is_empty()
+            || !self.state.source_events.is_empty()
+            || !shared.events.lock().unwrap().is_empty()
+            || shared
+                .windows
+                .lock()
+                .unwrap()
+                .values()
+                .any(|window| window.lock().unwrap().redraw_requested())
+    }
+
+    #[inline]
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+            waker: self.shared().waker.clone(),
+        }
+    }
+
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.headless_target().as_raw_fd()
+    }
+
+    #[inline]
+    pub fn window_target(&self) -> &RootEventLoopWindowTarget<T> {
+        &self.window_target
+    }
+
+    fn headless_target(&self) -> &EventLoopWindowTarget<T> {
+        match &self.window_target.p {
+            PlatformEventLoopWindowTarget::Headless(window_target) => window_target,
+            _ => unreachable!(),
+        }
+    }
+
+    fn shared(&self) -> &Arc<Shared> {
+        &self.headless_target().shared
+    }
+
+    fn loop_dispatch(&mut self, timeout: Option<Duration>) -> Result<(), i32> {
+        self.event_loop
+            .dispatch(timeout, &mut self.state)
+            .map_err(|error| {
+                error!("Error dispatching event loop: {}", error);
+                io::Error::from(error).raw_os_error().unwrap_or(1)
+            })
+    }
+}
+
+/// A handle that can be sent across the threads and used to wake up the `EventLoop`.
+pub struct EventLoopProxy<T: 'static> {
+    user_events_sender: calloop::channel::Sender<T>,
+    waker: Arc<Waker>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_events_sender
+            .send(event)
+            .map_err(|SendError(error)| EventLoopClosed(error))?;
+        self.waker.ping();
+        Ok(())
+    }
+}
+
+pub struct EventLoopWindowTarget<T> {
+    /// The state shared with the windows and the input handles.
+    pub shared: Arc<Shared>,
+
+    /// The event sources of the application.
+    event_sources: EventSources<LoopState>,
+
+    _marker: PhantomData<T>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        // There's nothing to render to, so the handle is empty.
+        RawDisplayHandle::Drm(DrmDisplayHandle::empty())
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![MonitorHandle]
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<PlatformMonitorHandle> {
+        Some(PlatformMonitorHandle::Headless(MonitorHandle))
+    }
+
+    /// A handle to inject input into the windows.
+    pub fn input(&self) -> Input {
+        Input::new(self.shared.clone())
+    }
+
+    pub fn available_devices(&self) -> Vec<DeviceInfo> {
+        // The injected input comes from a single virtual keyboard and mouse.
+        vec![
+            DeviceInfo {
+                id: super::root_device_id(),
+                name: String::from("Headless keyboard"),
+                kind: DeviceKind::Keyboard,
+                vendor_id: None,
+                product_id: None,
+                capabilities: DeviceCapabilities::KEYS,
+                seat: None,
+            },
+            DeviceInfo {
+                id: super::root_device_id(),
+                name: String::from("Headless pointer"),
+                kind: DeviceKind::Pointer,
+                vendor_id: None,
+                product_id: None,
+                capabilities: DeviceCapabilities::BUTTONS
+                    | DeviceCapabilities::MOTION
+                    | DeviceCapabilities::SCROLL
+                    | DeviceCapabilities::TOUCH,
+                seat: None,
+            },
+        ]
+    }
+
+    pub fn set_clipboard_content(
+        &self,
+        _kind: ClipboardKind,
+        _content: ClipboardContent,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn request_clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn request_clipboard_mime_types(
+        &self,
+        _kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn accept_drop(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn reject_drop(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn request_drop_data(&self, _mime_type: &str) -> Result<AsyncRequestSerial, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_fd(fd)
+    }
+
+    pub fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_timer(deadline, interval)
+    }
+
+    pub fn unregister_source(&self, source: SourceId) {
+        self.event_sources.unregister_source(source)
+    }
+}
+
+impl<T> AsRawFd for EventLoopWindowTarget<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.event_sources.as_raw_fd()
+    }
+}
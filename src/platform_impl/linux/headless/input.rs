@@ -0,0 +1,304 @@
+//! The input injected into the headless windows.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use smol_str::SmolStr;
+
+use crate::dpi::PhysicalPosition;
+use crate::event::{
+    DeviceEvent, ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+    WindowEvent,
+};
+use crate::keyboard::{Key, KeyCode, KeyLocation, ModifiersState};
+use crate::platform_impl::KeyEventExtra;
+use crate::window::CursorGrabMode;
+
+use super::event_loop::Shared;
+use super::WindowId;
+
+/// Injects the input into the windows, which is delivered as if it came from the devices.
+///
+/// The input for a window which doesn't exist anymore is ignored.
+#[derive(Clone)]
+pub struct Input {
+    shared: Arc<Shared>,
+}
+
+impl Input {
+    pub(super) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    pub fn focus(&self, window_id: WindowId, focused: bool) {
+        let windows: Vec<_> = self
+            .shared
+            .windows
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(window_id, window)| (*window_id, window.clone()))
+            .collect();
+
+        // Only one window has the focus at a time.
+        for (other_window_id, window) in windows {
+            let mut window = window.lock().unwrap();
+            let has_focus = if other_window_id == window_id {
+                focused
+            } else {
+                window.has_focus && !focused
+            };
+
+            if window.has_focus != has_focus {
+                window.has_focus = has_focus;
+                self.shared
+                    .push_window_event(other_window_id, WindowEvent::Focused(has_focus));
+            }
+        }
+    }
+
+    pub fn keyboard_input(
+        &self,
+        window_id: WindowId,
+        physical_key: KeyCode,
+        logical_key: Key,
+        text: Option<SmolStr>,
+        state: ElementState,
+    ) {
+        if self.shared.window(window_id).is_none() {
+            return;
+        }
+
+        let event = KeyEvent {
+            physical_key,
+            logical_key: logical_key.clone(),
+            text: text.clone(),
+            location: key_location(physical_key),
+            state,
+            repeat: false,
+            timestamp: Some(Instant::now()),
+            platform_specific: KeyEventExtra {
+                key_without_modifiers: logical_key,
+                text_with_all_modifiers: text,
+            },
+        };
+
+        self.shared.push_window_event(
+            window_id,
+            WindowEvent::KeyboardInput {
+                device_id: super::root_device_id(),
+                event,
+                is_synthetic: false,
+            },
+        );
+    }
+
+    pub fn modifiers(&self, window_id: WindowId, modifiers: ModifiersState) {
+        if self.shared.window(window_id).is_some() {
+            self.shared
+                .push_window_event(window_id, WindowEvent::ModifiersChanged(modifiers.into()));
+        }
+    }
+
+    pub fn ime(&self, window_id: WindowId, ime: Ime) {
+        let window = match self.shared.window(window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // Like with an input method, the events are only delivered once IME is allowed.
+        if window.lock().unwrap().ime_allowed {
+            self.shared
+                .push_window_event(window_id, WindowEvent::Ime(ime));
+        }
+    }
+
+    pub fn cursor_moved(&self, window_id: WindowId, position: PhysicalPosition<f64>) {
+        let window = match self.shared.window(window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // The cursor is only over one window at a time.
+        let windows: Vec<_> = self
+            .shared
+            .windows
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(other_window_id, _)| **other_window_id != window_id)
+            .map(|(other_window_id, window)| (*other_window_id, window.clone()))
+            .collect();
+        for (other_window_id, window) in windows {
+            self.leave(other_window_id, &mut window.lock().unwrap().cursor_position);
+        }
+
+        let mut window = window.lock().unwrap();
+        let last_position = match window.cursor_position {
+            Some(last_position) => {
+                let delta = (position.x - last_position.x, position.y - last_position.y);
+                self.shared
+                    .push_device_event(DeviceEvent::MouseMotion { delta });
+                last_position
+            }
+            None => {
+                self.shared.push_window_event(
+                    window_id,
+                    WindowEvent::CursorEntered {
+                        device_id: super::root_device_id(),
+                        timestamp: Some(Instant::now()),
+                    },
+                );
+                position
+            }
+        };
+
+        let position = match window.cursor_grab {
+            CursorGrabMode::None => position,
+            CursorGrabMode::Confined => PhysicalPosition::new(
+                position.x.max(0.).min(window.size.width as f64),
+                position.y.max(0.).min(window.size.height as f64),
+            ),
+            // The cursor stays in place, only the motion is delivered.
+            CursorGrabMode::Locked => last_position,
+        };
+        window.cursor_position = Some(position);
+
+        if window.cursor_grab != CursorGrabMode::Locked {
+            self.shared.push_window_event(
+                window_id,
+                WindowEvent::CursorMoved {
+                    device_id: super::root_device_id(),
+                    position,
+                    timestamp: Some(Instant::now()),
+                },
+            );
+        }
+    }
+
+    pub fn cursor_left(&self, window_id: WindowId) {
+        let window = match self.shared.window(window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // A grabbed cursor can't leave the window.
+        let mut window = window.lock().unwrap();
+        if window.cursor_grab == CursorGrabMode::None {
+            self.leave(window_id, &mut window.cursor_position);
+        }
+    }
+
+    pub fn mouse_input(&self, window_id: WindowId, button: MouseButton, state: ElementState) {
+        if self.shared.window(window_id).is_some() {
+            self.shared.push_window_event(
+                window_id,
+                WindowEvent::MouseInput {
+                    device_id: super::root_device_id(),
+                    state,
+                    button,
+                    timestamp: Some(Instant::now()),
+                },
+            );
+        }
+    }
+
+    pub fn mouse_wheel(&self, window_id: WindowId, delta: MouseScrollDelta, phase: TouchPhase) {
+        if self.shared.window(window_id).is_some() {
+            self.shared.push_window_event(
+                window_id,
+                WindowEvent::MouseWheel {
+                    device_id: super::root_device_id(),
+                    delta,
+                    phase,
+                    source: None,
+                    value120: None,
+                    timestamp: Some(Instant::now()),
+                },
+            );
+        }
+    }
+
+    pub fn touch(
+        &self,
+        window_id: WindowId,
+        id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+    ) {
+        if self.shared.window(window_id).is_some() {
+            let touch = Touch {
+                device_id: super::root_device_id(),
+                phase,
+                location,
+                force: None,
+                contact_size: None,
+                id,
+                timestamp: Some(Instant::now()),
+            };
+            self.shared
+                .push_window_event(window_id, WindowEvent::Touch(touch));
+        }
+    }
+
+    pub fn close_requested(&self, window_id: WindowId) {
+        if self.shared.window(window_id).is_some() {
+            self.shared
+                .push_window_event(window_id, WindowEvent::CloseRequested);
+        }
+    }
+
+    fn leave(&self, window_id: WindowId, cursor_position: &mut Option<PhysicalPosition<f64>>) {
+        if cursor_position.take().is_some() {
+            self.shared.push_window_event(
+                window_id,
+                WindowEvent::CursorLeft {
+                    device_id: super::root_device_id(),
+                    timestamp: Some(Instant::now()),
+                },
+            );
+        }
+    }
+}
+
+fn key_location(physical_key: KeyCode) -> KeyLocation {
+    match physical_key {
+        KeyCode::AltLeft | KeyCode::ControlLeft | KeyCode::ShiftLeft | KeyCode::SuperLeft => {
+            KeyLocation::Left
+        }
+        KeyCode::AltRight | KeyCode::ControlRight | KeyCode::ShiftRight | KeyCode::SuperRight => {
+            KeyLocation::Right
+        }
+        KeyCode::Numpad0
+        | KeyCode::Numpad1
+        | KeyCode::Numpad2
+        | KeyCode::Numpad3
+        | KeyCode::Numpad4
+        | KeyCode::Numpad5
+        | KeyCode::Numpad6
+        | KeyCode::Numpad7
+        | KeyCode::Numpad8
+        | KeyCode::Numpad9
+        | KeyCode::NumpadAdd
+        | KeyCode::NumpadBackspace
+        | KeyCode::NumpadClear
+        | KeyCode::NumpadClearEntry
+        | KeyCode::NumpadComma
+        | KeyCode::NumpadDecimal
+        | KeyCode::NumpadDivide
+        | KeyCode::NumpadEnter
+        | KeyCode::NumpadEqual
+        | KeyCode::NumpadHash
+        | KeyCode::NumpadMemoryAdd
+        | KeyCode::NumpadMemoryClear
+        | KeyCode::NumpadMemoryRecall
+        | KeyCode::NumpadMemoryStore
+        | KeyCode::NumpadMemorySubtract
+        | KeyCode::NumpadMultiply
+        | KeyCode::NumpadParenLeft
+        | KeyCode::NumpadParenRight
+        | KeyCode::NumpadStar
+        | KeyCode::NumpadSubtract => KeyLocation::Numpad,
+        _ => KeyLocation::Standard,
+    }
+}
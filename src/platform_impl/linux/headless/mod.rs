@@ -0,0 +1,25 @@
+//! Winit's headless backend.
+//!
+//! The windows and the monitor only exist in memory, and the input is injected by the
+//! application through [`Input`], which makes it possible to run the event loop without a
+//! display server, in automated tests for example.
+
+pub use crate::platform_impl::platform::WindowId;
+pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use input::Input;
+pub use monitor::{MonitorHandle, VideoMode};
+pub use window::Window;
+
+mod event_loop;
+mod input;
+mod monitor;
+mod window;
+
+/// Dummy device id, since the input is injected by the application.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId;
+
+#[inline]
+fn root_device_id() -> crate::event::DeviceId {
+    crate::event::DeviceId(crate::platform_impl::DeviceId::Headless(DeviceId))
+}
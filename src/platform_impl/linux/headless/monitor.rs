@@ -0,0 +1,79 @@
+use std::iter;
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform_impl::platform::{
+    MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode,
+};
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+const REFRESH_RATE_MILLIHERTZ: u32 = 60_000;
+
+/// The virtual monitor, which is the only one of the headless backend.
+///
+/// Its size is 1920x1080 at the origin, with a refresh rate of 60Hz and a scale factor of 1.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonitorHandle;
+
+impl MonitorHandle {
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some(String::from("Headless"))
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        0
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(WIDTH, HEIGHT)
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(0, 0)
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        Some(REFRESH_RATE_MILLIHERTZ)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        1.
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = PlatformVideoMode> {
+        iter::once(PlatformVideoMode::Headless(VideoMode))
+    }
+}
+
+/// The only video mode of the virtual monitor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode;
+
+impl VideoMode {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(WIDTH, HEIGHT)
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        32
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        REFRESH_RATE_MILLIHERTZ
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> PlatformMonitorHandle {
+        PlatformMonitorHandle::Headless(MonitorHandle)
+    }
+}
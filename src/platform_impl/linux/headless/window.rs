@@ -0,0 +1,522 @@
+//! The headless window.
+
+use std::sync::{Arc, Mutex};
+
+use raw_window_handle::{DrmDisplayHandle, DrmWindowHandle, RawDisplayHandle, RawWindowHandle};
+
+use crate::clipboard::ClipboardContent;
+use crate::cursor::CustomCursor;
+use crate::device::SeatId;
+use crate::dnd::DndAction;
+use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::event::{Ime, WindowEvent};
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::{
+    Fullscreen, MonitorHandle as PlatformMonitorHandle,
+    PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
+};
+use crate::window::{
+    CursorGrabMode, CursorIcon, Icon, ImePurpose, ResizeDirection, Theme, WindowAttributes,
+    WindowButtons,
+};
+
+use super::event_loop::Shared;
+use super::{EventLoopWindowTarget, Input, MonitorHandle, WindowId};
+
+/// The headless window.
+pub struct Window {
+    /// Window id.
+    window_id: WindowId,
+
+    /// The state of the window.
+    window_state: Arc<Mutex<WindowState>>,
+
+    /// The state shared with the event loop.
+    shared: Arc<Shared>,
+}
+
+impl Window {
+    pub(crate) fn new<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        attributes: WindowAttributes,
+        _platform_attributes: PlatformAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = event_loop_window_target.shared.clone();
+        let window_id = shared.next_window_id();
+
+        // The scale factor of the virtual monitor is 1.
+        let size = attributes
+            .inner_size
+            .map(|size| size.to_physical(1.))
+            .unwrap_or(PhysicalSize::new(800, 600));
+        let position = attributes
+            .position
+            .map(|position| position.to_physical(1.))
+            .unwrap_or_default();
+
+        let mut window_state = WindowState {
+            title: attributes.title,
+            floating_position: position,
+            floating_size: size,
+            position,
+            size,
+            min_size: attributes.min_inner_size.map(|size| size.to_physical(1.)),
+            max_size: attributes.max_inner_size.map(|size| size.to_physical(1.)),
+            resize_increments: attributes
+                .resize_increments
+                .map(|size| size.to_physical(1.)),
+            resizable: attributes.resizable,
+            enabled_buttons: attributes.enabled_buttons,
+            decorated: attributes.decorations,
+            visible: attributes.visible,
+            minimized: false,
+            maximized: attributes.maximized,
+            fullscreen: attributes.fullscreen.map(Into::into),
+            theme: attributes.preferred_theme,
+            cursor_grab: CursorGrabMode::None,
+            cursor_position: None,
+            ime_allowed: false,
+            has_focus: false,
+            redraw_requested: true,
+        };
+        window_state.apply_geometry();
+
+        let window_state = Arc::new(Mutex::new(window_state));
+        shared
+            .windows
+            .lock()
+            .unwrap()
+            .insert(window_id, window_state.clone());
+
+        // Wake-up event loop, so it'll send initial redraw requested.
+        shared.waker.ping();
+
+        Ok(Self {
+            window_id,
+            window_state,
+            shared,
+        })
+    }
+
+    /// Update the state of the window, and queue the events for the changes of its geometry.
+    fn configure<R>(&self, f: impl FnOnce(&mut WindowState) -> R) -> R {
+        let mut state = self.window_state.lock().unwrap();
+        let (position, size) = (state.position, state.size);
+
+        let result = f(&mut state);
+        state.apply_geometry();
+
+        if state.position != position {
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::Moved(state.position));
+        }
+
+        if state.size != size {
+            state.redraw_requested = true;
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::Resized(state.size));
+        }
+
+        result
+    }
+}
+
+impl Window {
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.window_id
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.window_state.lock().unwrap().title = title.to_owned();
+    }
+
+    #[inline]
+    pub fn set_transparent(&self, _transparent: bool) {}
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        self.window_state.lock().unwrap().visible = visible;
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(self.window_state.lock().unwrap().visible)
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.window_state.lock().unwrap().position)
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        // The decorations aren't drawn, so the inner and outer geometries are the same.
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        self.configure(|state| state.floating_position = position.to_physical(1.));
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.window_state.lock().unwrap().size
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+        self.configure(|state| {
+            state.floating_size = size.to_physical(1.);
+            state.is_floating()
+        })
+        .then(|| self.inner_size())
+    }
+
+    #[inline]
+    pub fn request_activation_token(&self) -> Result<AsyncRequestSerial, NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, min_size: Option<Size>) {
+        self.configure(|state| state.min_size = min_size.map(|size| size.to_physical(1.)));
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, max_size: Option<Size>) {
+        self.configure(|state| state.max_size = max_size.map(|size| size.to_physical(1.)));
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        self.window_state.lock().unwrap().resize_increments
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.window_state.lock().unwrap().resize_increments =
+            increments.map(|size| size.to_physical(1.));
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.window_state.lock().unwrap().resizable = resizable;
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.window_state.lock().unwrap().resizable
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.window_state.lock().unwrap().enabled_buttons = buttons;
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.window_state.lock().unwrap().enabled_buttons
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, _cursor: CursorIcon) {}
+
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: CustomCursor) {}
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.window_state.lock().unwrap().cursor_grab = mode;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_icon(
+        &self,
+        _seat: SeatId,
+        _cursor: CursorIcon,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_grab(
+        &self,
+        _seat: SeatId,
+        _mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _content: ClipboardContent,
+        _actions: &[DndAction],
+        _icon: Option<Icon>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        MonitorHandle.scale_factor()
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        // The cursor is only moved while it's over the window, without emitting events.
+        let mut state = self.window_state.lock().unwrap();
+        if state.cursor_position.is_some() {
+            state.cursor_position = Some(position.to_physical(1.));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.configure(|state| state.maximized = maximized);
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.window_state.lock().unwrap().maximized
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.window_state.lock().unwrap().minimized = minimized;
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        Some(self.window_state.lock().unwrap().minimized)
+    }
+
+    #[inline]
+    pub(crate) fn fullscreen(&self) -> Option<Fullscreen> {
+        self.window_state.lock().unwrap().fullscreen.clone()
+    }
+
+    #[inline]
+    pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        self.configure(|state| state.fullscreen = fullscreen);
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorate: bool) {
+        self.window_state.lock().unwrap().decorated = decorate;
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.window_state.lock().unwrap().decorated
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        let mut state = self.window_state.lock().unwrap();
+        if state.ime_allowed == allowed {
+            return;
+        }
+
+        state.ime_allowed = allowed;
+        let event = if allowed { Ime::Enabled } else { Ime::Disabled };
+        self.shared
+            .push_window_event(self.window_id, WindowEvent::Ime(event));
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+
+    #[inline]
+    pub fn focus_window(&self) {
+        Input::new(self.shared.clone()).focus(self.window_id, true);
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.window_state.lock().unwrap().redraw_requested = true;
+        self.shared.waker.ping();
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {}
+
+    #[inline]
+    pub fn request_presentation_feedback(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> MonitorHandle {
+        MonitorHandle
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![MonitorHandle]
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<PlatformMonitorHandle> {
+        Some(PlatformMonitorHandle::Headless(MonitorHandle))
+    }
+
+    #[inline]
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        // There's nothing to render to, so the handle is empty.
+        RawWindowHandle::Drm(DrmWindowHandle::empty())
+    }
+
+    #[inline]
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Drm(DrmDisplayHandle::empty())
+    }
+
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        self.window_state.lock().unwrap().theme = theme;
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.window_state.lock().unwrap().theme
+    }
+
+    #[inline]
+    pub fn has_focus(&self) -> bool {
+        self.window_state.lock().unwrap().has_focus
+    }
+
+    #[inline]
+    pub fn title(&self) -> String {
+        self.window_state.lock().unwrap().title.clone()
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.windows.lock().unwrap().remove(&self.window_id);
+        self.shared
+            .push_window_event(self.window_id, WindowEvent::Destroyed);
+    }
+}
+
+/// The state of the window.
+pub struct WindowState {
+    title: String,
+
+    /// The geometry of the window when it's neither maximized nor fullscreen.
+    floating_position: PhysicalPosition<i32>,
+    floating_size: PhysicalSize<u32>,
+
+    /// The current geometry of the window.
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+
+    min_size: Option<PhysicalSize<u32>>,
+    max_size: Option<PhysicalSize<u32>>,
+    resize_increments: Option<PhysicalSize<u32>>,
+
+    resizable: bool,
+    enabled_buttons: WindowButtons,
+    decorated: bool,
+    visible: bool,
+    minimized: bool,
+    maximized: bool,
+    fullscreen: Option<Fullscreen>,
+    theme: Option<Theme>,
+
+    pub cursor_grab: CursorGrabMode,
+
+    /// The position of the cursor, `None` when it's not over the window.
+    pub cursor_position: Option<PhysicalPosition<f64>>,
+
+    pub ime_allowed: bool,
+    pub has_focus: bool,
+    redraw_requested: bool,
+}
+
+impl WindowState {
+    #[inline]
+    pub fn redraw_requested(&self) -> bool {
+        self.redraw_requested
+    }
+
+    #[inline]
+    pub fn take_redraw_requested(&mut self) -> bool {
+        std::mem::take(&mut self.redraw_requested)
+    }
+
+    /// Whether the window is neither maximized nor fullscreen.
+    #[inline]
+    fn is_floating(&self) -> bool {
+        !self.maximized && self.fullscreen.is_none()
+    }
+
+    /// Compute the geometry of the window, which covers the monitor when fullscreen or
+    /// maximized.
+    fn apply_geometry(&mut self) {
+        let (position, size) = match &self.fullscreen {
+            Some(Fullscreen::Exclusive(video_mode)) => {
+                (MonitorHandle.position(), video_mode.size())
+            }
+            Some(Fullscreen::Borderless(_)) => (MonitorHandle.position(), MonitorHandle.size()),
+            None if self.maximized => (
+                MonitorHandle.position(),
+                self.clamp_size(MonitorHandle.size()),
+            ),
+            None => (self.floating_position, self.clamp_size(self.floating_size)),
+        };
+
+        self.position = position;
+        self.size = size;
+    }
+
+    /// Clamp the size within the minimum and maximum sizes.
+    fn clamp_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let mut size = size;
+        if let Some(max_size) = self.max_size {
+            size.width = size.width.min(max_size.width);
+            size.height = size.height.min(max_size.height);
+        }
+        if let Some(min_size) = self.min_size {
+            size.width = size.width.max(min_size.width);
+            size.height = size.height.max(min_size.height);
+        }
+
+        PhysicalSize::new(size.width.max(1), size.height.max(1))
+    }
+}
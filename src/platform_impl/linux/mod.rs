@@ -3,17 +3,17 @@
 #[cfg(all(not(x11_platform), not(wayland_platform)))]
 compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
 
-#[cfg(wayland_platform)]
 use std::error::Error;
 
-use std::{collections::VecDeque, env, fmt};
-#[cfg(x11_platform)]
 use std::{
-    ffi::CStr,
-    mem::MaybeUninit,
-    os::raw::*,
-    sync::{Arc, Mutex},
+    collections::VecDeque,
+    env, fmt, io,
+    os::unix::io::RawFd,
+    sync::Arc,
+    time::{Duration, Instant},
 };
+#[cfg(x11_platform)]
+use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Mutex};
 
 #[cfg(x11_platform)]
 use once_cell::sync::Lazy;
@@ -27,16 +27,23 @@ use self::x11::{ffi::XVisualInfo, util::WindowType as XWindowType, X11Error, XCo
 #[cfg(x11_platform)]
 use crate::platform::x11::XlibErrorHook;
 use crate::{
+    clipboard::{ClipboardContent, ClipboardKind},
+    cursor::CustomCursor,
+    device::{DeviceInfo, SeatId},
+    dnd::DndAction,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event::{Event, KeyEvent},
     event_loop::{
         AsyncRequestSerial, ControlFlow, DeviceEvents, EventLoopClosed,
-        EventLoopWindowTarget as RootELW,
+        EventLoopWindowTarget as RootELW, SourceId,
     },
     icon::Icon,
     keyboard::{Key, KeyCode},
-    platform::{modifier_supplement::KeyEventExtModifierSupplement, scancode::KeyCodeExtScancode},
+    platform::{
+        modifier_supplement::KeyEventExtModifierSupplement, pump_events::PumpStatus,
+        scancode::KeyCodeExtScancode,
+    },
     window::{
         ActivationToken, CursorGrabMode, CursorIcon, ImePurpose, ResizeDirection, Theme,
         UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
@@ -47,6 +54,7 @@ pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 pub(self) use crate::platform_impl::Fullscreen;
 
 pub mod common;
+pub mod headless;
 #[cfg(wayland_platform)]
 pub mod wayland;
 #[cfg(x11_platform)]
@@ -54,9 +62,9 @@ pub mod x11;
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland and headless. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
-/// and if it fails will fallback on x11.
+/// and if it fails will fallback on x11. The headless backend is only used when requested.
 ///
 /// If this variable is set with any other value, winit will panic.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
@@ -67,6 +75,7 @@ pub(crate) enum Backend {
     X,
     #[cfg(wayland_platform)]
     Wayland,
+    Headless,
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -75,6 +84,16 @@ pub(crate) struct PlatformSpecificEventLoopAttributes {
     pub(crate) any_thread: bool,
 }
 
+impl PlatformSpecificEventLoopAttributes {
+    /// Whether the headless backend is requested, through the builder or the environment.
+    pub(crate) fn is_headless(&self) -> bool {
+        match self.forced_backend {
+            Some(backend) => backend == Backend::Headless,
+            None => env::var(BACKEND_PREFERENCE_ENV_VAR).map_or(false, |var| var == "headless"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ApplicationName {
     pub general: String,
@@ -134,6 +153,7 @@ pub enum OsError {
     XMisc(&'static str),
     #[cfg(wayland_platform)]
     WaylandMisc(&'static str),
+    IoError(Arc<io::Error>),
 }
 
 impl fmt::Display for OsError {
@@ -145,6 +165,7 @@ impl fmt::Display for OsError {
             OsError::XMisc(e) => _f.pad(e),
             #[cfg(wayland_platform)]
             OsError::WaylandMisc(e) => _f.pad(e),
+            OsError::IoError(ref e) => fmt::Display::fmt(e, _f),
         }
     }
 }
@@ -154,6 +175,7 @@ pub(crate) enum Window {
     X(x11::Window),
     #[cfg(wayland_platform)]
     Wayland(wayland::Window),
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -183,6 +205,7 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(wayland_platform)]
     Wayland(wayland::DeviceId),
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -200,6 +223,7 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(wayland_platform)]
     Wayland(wayland::MonitorHandle),
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -208,6 +232,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -218,6 +243,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -226,6 +252,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $x,
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -273,6 +300,7 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(wayland_platform)]
     Wayland(wayland::VideoMode),
+    Headless(headless::VideoMode),
 }
 
 impl VideoMode {
@@ -313,6 +341,9 @@ impl Window {
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs, pl_attribs).map(Window::Headless)
+            }
         }
     }
 
@@ -323,6 +354,7 @@ impl Window {
             Self::Wayland(window) => window.id(),
             #[cfg(x11_platform)]
             Self::X(window) => window.id(),
+            Self::Headless(window) => window.id(),
         }
     }
 
@@ -426,11 +458,34 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_cursor_icon(cursor))
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        x11_or_wayland!(match self; Window(w) => w.set_custom_cursor(cursor))
+    }
+
     #[inline]
     pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(mode))
     }
 
+    #[inline]
+    pub fn set_seat_cursor_icon(
+        &self,
+        seat: SeatId,
+        cursor: CursorIcon,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_seat_cursor_icon(seat, cursor))
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_grab(
+        &self,
+        seat: SeatId,
+        mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_seat_cursor_grab(seat, mode))
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         x11_or_wayland!(match self; Window(window) => window.set_cursor_visible(visible))
@@ -446,6 +501,16 @@ impl Window {
         x11_or_wayland!(match self; Window(window) => window.drag_resize_window(direction))
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        content: ClipboardContent,
+        actions: &[DndAction],
+        icon: Option<Icon>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.start_drag(content, actions, icon))
+    }
+
     #[inline]
     pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
         x11_or_wayland!(match self; Window(w) => w.set_cursor_hittest(hittest))
@@ -508,6 +573,7 @@ impl Window {
             Window::X(ref w) => w.set_window_level(_level),
             #[cfg(wayland_platform)]
             Window::Wayland(_) => (),
+            Window::Headless(_) => (),
         }
     }
 
@@ -518,6 +584,7 @@ impl Window {
             Window::X(ref w) => w.set_window_icon(_window_icon),
             #[cfg(wayland_platform)]
             Window::Wayland(_) => (),
+            Window::Headless(_) => (),
         }
     }
 
@@ -548,6 +615,7 @@ impl Window {
             Window::X(ref w) => w.focus_window(),
             #[cfg(wayland_platform)]
             Window::Wayland(_) => (),
+            Window::Headless(ref w) => w.focus_window(),
         }
     }
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
@@ -556,6 +624,7 @@ impl Window {
             Window::X(ref w) => w.request_user_attention(request_type),
             #[cfg(wayland_platform)]
             Window::Wayland(ref w) => w.request_user_attention(request_type),
+            Window::Headless(_) => (),
         }
     }
 
@@ -564,6 +633,16 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.request_redraw())
     }
 
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        x11_or_wayland!(match self; Window(w) => w.pre_present_notify())
+    }
+
+    #[inline]
+    pub fn request_presentation_feedback(&self) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.request_presentation_feedback())
+    }
+
     #[inline]
     pub fn current_monitor(&self) -> Option<MonitorHandle> {
         match self {
@@ -577,6 +656,10 @@ impl Window {
                 let current_monitor = MonitorHandle::Wayland(window.current_monitor()?);
                 Some(current_monitor)
             }
+            Window::Headless(ref window) => {
+                let current_monitor = MonitorHandle::Headless(window.current_monitor());
+                Some(current_monitor)
+            }
         }
     }
 
@@ -595,6 +678,11 @@ impl Window {
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -608,6 +696,7 @@ impl Window {
             }
             #[cfg(wayland_platform)]
             Window::Wayland(ref window) => window.primary_monitor(),
+            Window::Headless(ref window) => window.primary_monitor(),
         }
     }
 
@@ -647,6 +736,19 @@ pub struct KeyEventExtra {
     pub text_with_all_modifiers: Option<SmolStr>,
 }
 
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        key_without_modifiers: Key,
+        text_with_all_modifiers: Option<SmolStr>,
+    ) -> Self {
+        Self {
+            key_without_modifiers,
+            text_with_all_modifiers,
+        }
+    }
+}
+
 impl KeyEventExtModifierSupplement for KeyEvent {
     #[inline]
     fn text_with_all_modifiers(&self) -> Option<&str> {
@@ -724,6 +826,7 @@ pub enum EventLoop<T: 'static> {
     Wayland(Box<wayland::EventLoop<T>>),
     #[cfg(x11_platform)]
     X(x11::EventLoop<T>),
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -731,6 +834,7 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(wayland_platform)]
     Wayland(wayland::EventLoopProxy<T>),
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -750,50 +854,62 @@ impl<T: 'static> EventLoop<T> {
             );
         }
 
+        match Self::new_any_thread(attributes) {
+            Ok(event_loop) => event_loop,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Create the event loop of the requested backend, or of the first available one.
+    fn new_any_thread(
+        attributes: &PlatformSpecificEventLoopAttributes,
+    ) -> Result<Self, Box<dyn Error>> {
         #[cfg(x11_platform)]
         if attributes.forced_backend == Some(Backend::X) {
-            // TODO: Propagate
-            return EventLoop::new_x11_any_thread().unwrap();
+            return EventLoop::new_x11_any_thread()
+                .map_err(|err| format!("Failed to initialize X11 backend: {err}").into());
         }
 
         #[cfg(wayland_platform)]
         if attributes.forced_backend == Some(Backend::Wayland) {
-            // TODO: Propagate
-            return EventLoop::new_wayland_any_thread().expect("failed to open Wayland connection");
+            return EventLoop::new_wayland_any_thread()
+                .map_err(|err| format!("Failed to open Wayland connection: {err}").into());
+        }
+
+        if attributes.forced_backend == Some(Backend::Headless) {
+            return EventLoop::new_headless_any_thread()
+                .map_err(|err| format!("Failed to initialize headless backend: {err}").into());
         }
 
         if let Ok(env_var) = env::var(BACKEND_PREFERENCE_ENV_VAR) {
-            match env_var.as_str() {
-                "x11" => {
-                    // TODO: propagate
-                    #[cfg(x11_platform)]
-                    return EventLoop::new_x11_any_thread()
-                        .expect("Failed to initialize X11 backend");
-                    #[cfg(not(x11_platform))]
-                    panic!("x11 feature is not enabled")
-                }
-                "wayland" => {
-                    #[cfg(wayland_platform)]
-                    return EventLoop::new_wayland_any_thread()
-                        .expect("Failed to initialize Wayland backend");
-                    #[cfg(not(wayland_platform))]
-                    panic!("wayland feature is not enabled");
-                }
-                _ => panic!(
-                    "Unknown environment variable value for {BACKEND_PREFERENCE_ENV_VAR}, try one of `x11`,`wayland`",
-                ),
-            }
+            return match env_var.as_str() {
+                #[cfg(x11_platform)]
+                "x11" => EventLoop::new_x11_any_thread()
+                    .map_err(|err| format!("Failed to initialize X11 backend: {err}").into()),
+                #[cfg(not(x11_platform))]
+                "x11" => Err("x11 feature is not enabled".into()),
+                #[cfg(wayland_platform)]
+                "wayland" => EventLoop::new_wayland_any_thread()
+                    .map_err(|err| format!("Failed to initialize Wayland backend: {err}").into()),
+                #[cfg(not(wayland_platform))]
+                "wayland" => Err("wayland feature is not enabled".into()),
+                "headless" => EventLoop::new_headless_any_thread()
+                    .map_err(|err| format!("Failed to initialize headless backend: {err}").into()),
+                _ => Err(format!(
+                    "Unknown environment variable value for {BACKEND_PREFERENCE_ENV_VAR}, try one of `x11`,`wayland`,`headless`",
+                ).into()),
+            };
         }
 
         #[cfg(wayland_platform)]
         let wayland_err = match EventLoop::new_wayland_any_thread() {
-            Ok(event_loop) => return event_loop,
+            Ok(event_loop) => return Ok(event_loop),
             Err(err) => err,
         };
 
         #[cfg(x11_platform)]
         let x11_err = match EventLoop::new_x11_any_thread() {
-            Ok(event_loop) => return event_loop,
+            Ok(event_loop) => return Ok(event_loop),
             Err(err) => err,
         };
 
@@ -802,9 +918,9 @@ impl<T: 'static> EventLoop<T> {
         #[cfg(not(x11_platform))]
         let x11_err = "backend disabled";
 
-        panic!(
+        Err(format!(
             "Failed to initialize any backend! Wayland status: {wayland_err:?} X11 status: {x11_err:?}",
-        );
+        ).into())
     }
 
     #[cfg(wayland_platform)]
@@ -822,6 +938,10 @@ impl<T: 'static> EventLoop<T> {
         Ok(EventLoop::X(x11::EventLoop::new(xconn)))
     }
 
+    fn new_headless_any_thread() -> Result<EventLoop<T>, io::Error> {
+        headless::EventLoop::new().map(EventLoop::Headless)
+    }
+
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
@@ -833,6 +953,13 @@ impl<T: 'static> EventLoop<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.run_return(callback))
     }
 
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+    where
+        F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.pump_events(timeout, callback))
+    }
+
     pub fn run<F>(self, callback: F) -> !
     where
         F: 'static + FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
@@ -843,6 +970,10 @@ impl<T: 'static> EventLoop<T> {
     pub fn window_target(&self) -> &crate::event_loop::EventLoopWindowTarget<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.window_target())
     }
+
+    pub fn as_raw_fd(&self) -> RawFd {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.as_raw_fd())
+    }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
@@ -856,19 +987,35 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(x11_platform)]
     X(x11::EventLoopWindowTarget<T>),
+    Headless(headless::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
+    #[cfg(wayland_platform)]
     #[inline]
     pub fn is_wayland(&self) -> bool {
         match *self {
             #[cfg(wayland_platform)]
             EventLoopWindowTarget::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[cfg(x11_platform)]
+    #[inline]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(x11_platform)]
+            EventLoopWindowTarget::X(_) => true,
             _ => false,
         }
     }
 
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        matches!(*self, EventLoopWindowTarget::Headless(_))
+    }
+
     #[inline]
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         match *self {
@@ -885,6 +1032,11 @@ impl<T> EventLoopWindowTarget<T> {
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -898,6 +1050,7 @@ impl<T> EventLoopWindowTarget<T> {
                 let primary_monitor = MonitorHandle::X(evlp.x_connection().primary_monitor());
                 Some(primary_monitor)
             }
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.primary_monitor(),
         }
     }
 
@@ -908,14 +1061,93 @@ impl<T> EventLoopWindowTarget<T> {
             EventLoopWindowTarget::Wayland(_) => (),
             #[cfg(x11_platform)]
             EventLoopWindowTarget::X(ref evlp) => evlp.set_listen_device_events(_allowed),
+            EventLoopWindowTarget::Headless(_) => (),
         }
     }
 
+    #[inline]
+    pub fn available_devices(&self) -> Vec<DeviceInfo> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.available_devices())
+    }
+
+    #[inline]
+    pub fn device_seat(&self, device_id: DeviceId) -> Option<SeatId> {
+        match (self, device_id) {
+            #[cfg(wayland_platform)]
+            (_, DeviceId::Wayland(device_id)) => Some(device_id.seat()),
+            #[cfg(x11_platform)]
+            (Self::X(evlp), DeviceId::X(device_id)) => evlp.device_seat(device_id),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn set_clipboard_content(
+        &self,
+        kind: ClipboardKind,
+        content: ClipboardContent,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.set_clipboard_content(kind, content))
+    }
+
+    #[inline]
+    pub fn request_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.request_clipboard_data(kind, mime_type))
+    }
+
+    #[inline]
+    pub fn request_clipboard_mime_types(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.request_clipboard_mime_types(kind))
+    }
+
+    #[inline]
+    pub fn accept_drop(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.accept_drop(mime_type, action))
+    }
+
+    #[inline]
+    pub fn reject_drop(&self) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.reject_drop())
+    }
+
+    #[inline]
+    pub fn request_drop_data(&self, mime_type: &str) -> Result<AsyncRequestSerial, ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.request_drop_data(mime_type))
+    }
+
     pub fn raw_display_handle(&self) -> raw_window_handle::RawDisplayHandle {
         x11_or_wayland!(match self; Self(evlp) => evlp.raw_display_handle())
     }
 }
 
+impl<T: 'static> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.register_fd(fd))
+    }
+
+    #[inline]
+    pub fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.register_timer(deadline, interval))
+    }
+
+    #[inline]
+    pub fn unregister_source(&self, source: SourceId) {
+        x11_or_wayland!(match self; Self(evlp) => evlp.unregister_source(source))
+    }
+}
+
 fn sticky_exit_callback<T, F>(
     evt: Event<'_, T>,
     target: &RootELW<T>,
@@ -5,9 +5,11 @@ use std::error::Error;
 use std::io::Result as IOResult;
 use std::marker::PhantomData;
 use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::process;
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use raw_window_handle::{RawDisplayHandle, WaylandDisplayHandle};
@@ -16,9 +18,17 @@ use sctk::reexports::calloop;
 use sctk::reexports::client::globals;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle, WaylandSource};
 
+use crate::clipboard::{ClipboardContent, ClipboardKind};
+use crate::device::DeviceInfo;
+use crate::dnd::DndAction;
 use crate::dpi::{LogicalSize, PhysicalSize};
+use crate::error::ExternalError;
 use crate::event::{Event, StartCause, WindowEvent};
-use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
+use crate::event_loop::{
+    AsyncRequestSerial, ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget, SourceId,
+};
+use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::platform::common::event_sources::{EventSources, Waker};
 use crate::platform_impl::platform::sticky_exit_callback;
 use crate::platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget;
 
@@ -29,6 +39,7 @@ pub use proxy::EventLoopProxy;
 use sink::EventSink;
 
 use super::state::{WindowCompositorUpdate, WinitState};
+use super::window::FrameCallbackState;
 use super::{DeviceId, WindowId};
 
 type WaylandDispatcher = calloop::Dispatcher<'static, WaylandSource<WinitState>, WinitState>;
@@ -53,6 +64,17 @@ pub struct EventLoop<T: 'static> {
     /// Event loop window target.
     window_target: RootEventLoopWindowTarget<T>,
 
+    /// The timer waking up the event loop for the next frame of the animated cursors.
+    cursor_animation_timer: Option<(Instant, calloop::RegistrationToken)>,
+
+    /// The control flow of the running loop, `None` until it's started.
+    control_flow: Option<ControlFlow>,
+
+    // XXX preallocate certian structures to avoid allocating on each loop iteration.
+    window_ids: Vec<WindowId>,
+    compositor_updates: Vec<WindowCompositorUpdate>,
+    buffer_sink: EventSink,
+
     // XXX drop after everything else, just to be safe.
     /// Calloop's event loop.
     event_loop: calloop::EventLoop<'static, WinitState>,
@@ -77,7 +99,9 @@ impl<T: 'static> EventLoop<T> {
         let wayland_source = WaylandSource::new(event_queue)?;
         let wayland_dispatcher =
             calloop::Dispatcher::new(wayland_source, |_, queue, winit_state| {
-                queue.dispatch_pending(winit_state)
+                let dispatched = queue.dispatch_pending(winit_state)?;
+                winit_state.wayland_events_dispatched |= dispatched > 0;
+                Ok(dispatched)
             });
 
         event_loop
@@ -96,18 +120,20 @@ impl<T: 'static> EventLoop<T> {
                 }
             })?;
 
-        // An event's loop awakener to wake up for window events from winit's windows.
-        let (event_loop_awakener, event_loop_awakener_source) = calloop::ping::make_ping()?;
-        event_loop
-            .handle()
-            .insert_source(event_loop_awakener_source, move |_, _, _| {
-                // No extra handling is required, we just need to wake-up.
-            })?;
+        // The event sources of the application, along with the event loop's awakener to wake up
+        // for window events from winit's windows.
+        let connection_fd = connection.prepare_read()?.connection_fd().as_raw_fd();
+        let event_sources = EventSources::new(
+            event_loop.handle(),
+            Some(connection_fd),
+            |state: &mut WinitState, source| state.events_sink.push_source_event(source),
+        )?;
 
         let window_target = EventLoopWindowTarget {
             connection: connection.clone(),
             wayland_dispatcher: wayland_dispatcher.clone(),
-            event_loop_awakener,
+            event_loop_awakener: event_sources.waker().clone(),
+            event_sources,
             queue_handle,
             state: RefCell::new(winit_state),
             _marker: PhantomData,
@@ -119,6 +145,11 @@ impl<T: 'static> EventLoop<T> {
             user_events_sender,
             pending_user_events,
             event_loop,
+            cursor_animation_timer: None,
+            control_flow: None,
+            window_ids: Vec::new(),
+            compositor_updates: Vec::new(),
+            buffer_sink: EventSink::default(),
             window_target: RootEventLoopWindowTarget {
                 p: PlatformEventLoopWindowTarget::Wayland(window_target),
                 _marker: PhantomData,
@@ -140,22 +171,38 @@ impl<T: 'static> EventLoop<T> {
     where
         F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        let mut control_flow = ControlFlow::Poll;
+        // Start the loop anew.
+        self.control_flow = None;
 
-        // XXX preallocate certian structures to avoid allocating on each loop iteration.
-        let mut window_ids = Vec::<WindowId>::new();
-        let mut compositor_updates = Vec::<WindowCompositorUpdate>::new();
-        let mut buffer_sink = EventSink::new();
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                break code;
+            }
+        }
+    }
 
-        callback(
-            Event::NewEvents(StartCause::Init),
-            &self.window_target,
-            &mut control_flow,
-        );
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = match self.control_flow.take() {
+            Some(control_flow) => control_flow,
+            None => {
+                let mut control_flow = ControlFlow::Poll;
+
+                callback(
+                    Event::NewEvents(StartCause::Init),
+                    &self.window_target,
+                    &mut control_flow,
+                );
 
-        // XXX For consistency all platforms must emit a 'Resumed' event even though Wayland
-        // applications don't themselves have a formal suspend/resume lifecycle.
-        callback(Event::Resumed, &self.window_target, &mut control_flow);
+                // XXX For consistency all platforms must emit a 'Resumed' event even though Wayland
+                // applications don't themselves have a formal suspend/resume lifecycle.
+                callback(Event::Resumed, &self.window_target, &mut control_flow);
+
+                control_flow
+            }
+        };
 
         // XXX We break on errors from dispatches, since if we've got protocol error
         // libwayland-client/wayland-rs will inform us anyway, but crashing downstream is not
@@ -163,300 +210,379 @@ impl<T: 'static> EventLoop<T> {
         // communicate an error that something was terminated, but winit doesn't provide us
         // with an API to do that via some event.
         // Still, we set the exit code to the error's OS error code, or to 1 if not possible.
-        let exit_code = loop {
-            // Flush the connection.
-            let _ = self.connection.flush();
-
-            // During the run of the user callback, some other code monitoring and reading the
-            // Wayland socket may have been run (mesa for example does this with vsync), if that
-            // is the case, some events may have been enqueued in our event queue.
-            //
-            // If some messages are there, the event loop needs to behave as if it was instantly
-            // woken up by messages arriving from the Wayland socket, to avoid delaying the
-            // dispatch of these events until we're woken up again.
-            let instant_wakeup = {
-                let mut wayland_source = self.wayland_dispatcher.as_source_mut();
-                let queue = wayland_source.queue();
-                let state = match &mut self.window_target.p {
-                    PlatformEventLoopWindowTarget::Wayland(window_target) => {
-                        window_target.state.get_mut()
-                    }
-                    #[cfg(x11_platform)]
-                    _ => unreachable!(),
-                };
+        if let Err(code) = self.single_iteration(&mut control_flow, timeout, &mut callback) {
+            control_flow = ControlFlow::ExitWithCode(code);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = control_flow {
+            callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+            PumpStatus::Exit(code)
+        } else {
+            self.control_flow = Some(control_flow);
+            PumpStatus::Continue
+        }
+    }
 
-                match queue.dispatch_pending(state) {
-                    Ok(dispatched) => dispatched > 0,
-                    Err(error) => {
-                        error!("Error dispatching wayland queue: {}", error);
-                        break 1;
-                    }
+    fn single_iteration<F>(
+        &mut self,
+        control_flow: &mut ControlFlow,
+        timeout: Option<Duration>,
+        callback: &mut F,
+    ) -> Result<(), i32>
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        // Flush the connection.
+        let _ = self.connection.flush();
+
+        // During the run of the user callback, some other code monitoring and reading the
+        // Wayland socket may have been run (mesa for example does this with vsync), if that
+        // is the case, some events may have been enqueued in our event queue.
+        //
+        // If some messages are there, the event loop needs to behave as if it was instantly
+        // woken up by messages arriving from the Wayland socket, to avoid delaying the
+        // dispatch of these events until we're woken up again.
+        let instant_wakeup = {
+            let mut wayland_source = self.wayland_dispatcher.as_source_mut();
+            let queue = wayland_source.queue();
+            let state = match &mut self.window_target.p {
+                PlatformEventLoopWindowTarget::Wayland(window_target) => {
+                    window_target.state.get_mut()
                 }
+                _ => unreachable!(),
             };
 
-            match control_flow {
-                ControlFlow::ExitWithCode(code) => break code,
-                ControlFlow::Poll => {
-                    // Non-blocking dispatch.
-                    let timeout = Duration::ZERO;
-                    if let Err(error) = self.loop_dispatch(Some(timeout)) {
-                        break error.raw_os_error().unwrap_or(1);
-                    }
+            match queue.dispatch_pending(state) {
+                Ok(dispatched) => dispatched > 0,
+                Err(error) => {
+                    error!("Error dispatching wayland queue: {}", error);
+                    return Err(1);
+                }
+            }
+        };
 
-                    callback(
-                        Event::NewEvents(StartCause::Poll),
-                        &self.window_target,
-                        &mut control_flow,
-                    );
+        match *control_flow {
+            ControlFlow::ExitWithCode(_) => return Ok(()),
+            ControlFlow::Poll => {
+                // Non-blocking dispatch.
+                let timeout = Duration::ZERO;
+                if let Err(error) = self.loop_dispatch(Some(timeout)) {
+                    return Err(error.raw_os_error().unwrap_or(1));
                 }
-                ControlFlow::Wait => {
-                    let timeout = if instant_wakeup {
-                        Some(Duration::ZERO)
-                    } else {
-                        None
-                    };
 
-                    if let Err(error) = self.loop_dispatch(timeout) {
-                        break error.raw_os_error().unwrap_or(1);
-                    }
+                callback(
+                    Event::NewEvents(StartCause::Poll),
+                    &self.window_target,
+                    control_flow,
+                );
+            }
+            ControlFlow::Wait => {
+                let timeout = if instant_wakeup {
+                    Some(Duration::ZERO)
+                } else {
+                    timeout
+                };
 
+                if let Err(error) = self.loop_dispatch(timeout) {
+                    return Err(error.raw_os_error().unwrap_or(1));
+                }
+
+                // Don't run an empty iteration when the loop was woken up for nothing, or the
+                // timeout of `pump_events` has passed.
+                if !instant_wakeup && !self.has_pending() {
+                    return Ok(());
+                }
+
+                callback(
+                    Event::NewEvents(StartCause::WaitCancelled {
+                        start: Instant::now(),
+                        requested_resume: None,
+                    }),
+                    &self.window_target,
+                    control_flow,
+                );
+            }
+            ControlFlow::WaitUntil(deadline) => {
+                let start = Instant::now();
+
+                // Compute the amount of time we'll block for.
+                let duration = if deadline > start && !instant_wakeup {
+                    deadline - start
+                } else {
+                    Duration::ZERO
+                };
+                let duration = timeout.map_or(duration, |timeout| duration.min(timeout));
+
+                if let Err(error) = self.loop_dispatch(Some(duration)) {
+                    return Err(error.raw_os_error().unwrap_or(1));
+                }
+
+                let now = Instant::now();
+                if now < deadline && !instant_wakeup && !self.has_pending() {
+                    return Ok(());
+                }
+
+                if now < deadline {
                     callback(
                         Event::NewEvents(StartCause::WaitCancelled {
-                            start: Instant::now(),
-                            requested_resume: None,
+                            start,
+                            requested_resume: Some(deadline),
                         }),
                         &self.window_target,
-                        &mut control_flow,
-                    );
+                        control_flow,
+                    )
+                } else {
+                    callback(
+                        Event::NewEvents(StartCause::ResumeTimeReached {
+                            start,
+                            requested_resume: deadline,
+                        }),
+                        &self.window_target,
+                        control_flow,
+                    )
                 }
-                ControlFlow::WaitUntil(deadline) => {
-                    let start = Instant::now();
+            }
+        }
 
-                    // Compute the amount of time we'll block for.
-                    let duration = if deadline > start && !instant_wakeup {
-                        deadline - start
-                    } else {
-                        Duration::ZERO
-                    };
+        // Reuse the structures preallocated for the loop iterations.
+        let mut window_ids = mem::take(&mut self.window_ids);
+        let mut compositor_updates = mem::take(&mut self.compositor_updates);
+        let mut buffer_sink = mem::take(&mut self.buffer_sink);
+        self.with_state(|state| state.wayland_events_dispatched = false);
 
-                    if let Err(error) = self.loop_dispatch(Some(duration)) {
-                        break error.raw_os_error().unwrap_or(1);
-                    }
+        // Handle pending user events. We don't need back buffer, since we can't dispatch
+        // user events indirectly via callback to the user.
+        for user_event in self.pending_user_events.borrow_mut().drain(..) {
+            sticky_exit_callback(
+                Event::UserEvent(user_event),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Drain the pending compositor updates.
+        self.with_state(|state| compositor_updates.append(&mut state.window_compositor_updates));
+
+        for mut compositor_update in compositor_updates.drain(..) {
+            let window_id = compositor_update.window_id;
+            if let Some(scale_factor) = compositor_update.scale_factor {
+                let mut physical_size = self.with_state(|state| {
+                    let windows = state.windows.get_mut();
+                    let mut window = windows.get(&window_id).unwrap().lock().unwrap();
+
+                    // Set the new scale factor.
+                    window.set_scale_factor(scale_factor);
+                    let window_size = compositor_update.size.unwrap_or(window.inner_size());
+                    logical_to_physical_rounded(window_size, scale_factor)
+                });
 
-                    let now = Instant::now();
-
-                    if now < deadline {
-                        callback(
-                            Event::NewEvents(StartCause::WaitCancelled {
-                                start,
-                                requested_resume: Some(deadline),
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
-                    } else {
-                        callback(
-                            Event::NewEvents(StartCause::ResumeTimeReached {
-                                start,
-                                requested_resume: deadline,
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
-                    }
-                }
-            }
+                // Stash the old window size.
+                let old_physical_size = physical_size;
 
-            // Handle pending user events. We don't need back buffer, since we can't dispatch
-            // user events indirectly via callback to the user.
-            for user_event in self.pending_user_events.borrow_mut().drain(..) {
                 sticky_exit_callback(
-                    Event::UserEvent(user_event),
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size: &mut physical_size,
+                        },
+                    },
                     &self.window_target,
-                    &mut control_flow,
-                    &mut callback,
+                    control_flow,
+                    callback,
                 );
-            }
 
-            // Drain the pending compositor updates.
-            self.with_state(|state| {
-                compositor_updates.append(&mut state.window_compositor_updates)
-            });
+                let new_logical_size = physical_size.to_logical(scale_factor);
 
-            for mut compositor_update in compositor_updates.drain(..) {
-                let window_id = compositor_update.window_id;
-                if let Some(scale_factor) = compositor_update.scale_factor {
-                    let mut physical_size = self.with_state(|state| {
+                // Resize the window when user altered the size.
+                if old_physical_size != physical_size {
+                    self.with_state(|state| {
                         let windows = state.windows.get_mut();
                         let mut window = windows.get(&window_id).unwrap().lock().unwrap();
-
-                        // Set the new scale factor.
-                        window.set_scale_factor(scale_factor);
-                        let window_size = compositor_update.size.unwrap_or(window.inner_size());
-                        logical_to_physical_rounded(window_size, scale_factor)
+                        window.resize(new_logical_size);
                     });
-
-                    // Stash the old window size.
-                    let old_physical_size = physical_size;
-
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(window_id),
-                            event: WindowEvent::ScaleFactorChanged {
-                                scale_factor,
-                                new_inner_size: &mut physical_size,
-                            },
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-
-                    let new_logical_size = physical_size.to_logical(scale_factor);
-
-                    // Resize the window when user altered the size.
-                    if old_physical_size != physical_size {
-                        self.with_state(|state| {
-                            let windows = state.windows.get_mut();
-                            let mut window = windows.get(&window_id).unwrap().lock().unwrap();
-                            window.resize(new_logical_size);
-                        });
-                    }
-
-                    // Make it queue resize.
-                    compositor_update.size = Some(new_logical_size);
                 }
 
-                if let Some(size) = compositor_update.size.take() {
-                    let physical_size = self.with_state(|state| {
-                        let windows = state.windows.get_mut();
-                        let window = windows.get(&window_id).unwrap().lock().unwrap();
+                // Make it queue resize.
+                compositor_update.size = Some(new_logical_size);
+            }
 
-                        let scale_factor = window.scale_factor();
-                        let physical_size = logical_to_physical_rounded(size, scale_factor);
+            if let Some(size) = compositor_update.size.take() {
+                let physical_size = self.with_state(|state| {
+                    let windows = state.windows.get_mut();
+                    let window = windows.get(&window_id).unwrap().lock().unwrap();
 
-                        // TODO could probably bring back size reporting optimization.
+                    let scale_factor = window.scale_factor();
+                    let physical_size = logical_to_physical_rounded(size, scale_factor);
 
-                        // Mark the window as needed a redraw.
-                        state
-                            .window_requests
-                            .get_mut()
-                            .get_mut(&window_id)
-                            .unwrap()
-                            .redraw_requested
-                            .store(true, Ordering::Relaxed);
+                    // TODO could probably bring back size reporting optimization.
 
-                        physical_size
-                    });
+                    // Mark the window as needed a redraw.
+                    state
+                        .window_requests
+                        .get_mut()
+                        .get_mut(&window_id)
+                        .unwrap()
+                        .redraw_requested
+                        .store(true, Ordering::Relaxed);
 
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(window_id),
-                            event: WindowEvent::Resized(physical_size),
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+                    physical_size
+                });
 
-                if compositor_update.close_window {
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(window_id),
-                            event: WindowEvent::CloseRequested,
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: WindowEvent::Resized(physical_size),
+                    },
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
             }
 
-            // Push the events directly from the window.
-            self.with_state(|state| {
-                buffer_sink.append(&mut state.window_events_sink.lock().unwrap());
-            });
-            for event in buffer_sink.drain() {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
+            if compositor_update.close_window {
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: WindowEvent::CloseRequested,
+                    },
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
             }
-
-            // Handle non-synthetic events.
-            self.with_state(|state| {
-                buffer_sink.append(&mut state.events_sink);
-            });
-            for event in buffer_sink.drain() {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
+        }
+
+        // Start the drags requested by the windows.
+        self.with_state(|state| {
+            let drags: Vec<_> = state
+                .window_requests
+                .get_mut()
+                .iter()
+                .filter_map(|(window_id, requests)| Some((*window_id, requests.take_drag()?)))
+                .collect();
+            for (window_id, request) in drags {
+                state.start_drag(window_id, request);
+            }
+        });
+
+        // Push the events directly from the window.
+        self.with_state(|state| {
+            buffer_sink.append(&mut state.window_events_sink.lock().unwrap());
+        });
+        for event in buffer_sink.drain() {
+            let event = event.map_nonuser_event().unwrap();
+            if self.with_state(|state| state.is_input_blocked(&event)) {
+                continue;
+            }
+            sticky_exit_callback(event, &self.window_target, control_flow, callback);
+        }
+
+        // Handle non-synthetic events.
+        self.with_state(|state| {
+            buffer_sink.append(&mut state.events_sink);
+        });
+        for event in buffer_sink.drain() {
+            let event = event.map_nonuser_event().unwrap();
+            if self.with_state(|state| state.is_input_blocked(&event)) {
+                continue;
             }
+            sticky_exit_callback(event, &self.window_target, control_flow, callback);
+        }
 
-            // Send events cleared.
-            sticky_exit_callback(
-                Event::MainEventsCleared,
-                &self.window_target,
-                &mut control_flow,
-                &mut callback,
-            );
+        // Finish the drop once the data requested while handling it has arrived.
+        self.with_state(|state| state.finish_drop());
 
-            // Collect the window ids
-            self.with_state(|state| {
-                window_ids.extend(state.window_requests.get_mut().keys());
-            });
+        // Send events cleared.
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
 
-            for window_id in window_ids.drain(..) {
-                let request_redraw = self.with_state(|state| {
-                    let window_requests = state.window_requests.get_mut();
-                    if window_requests.get(&window_id).unwrap().take_closed() {
-                        mem::drop(window_requests.remove(&window_id));
-                        mem::drop(state.windows.get_mut().remove(&window_id));
-                        false
+        // Collect the window ids
+        self.with_state(|state| {
+            window_ids.extend(state.window_requests.get_mut().keys());
+        });
+
+        for window_id in window_ids.drain(..) {
+            let request_redraw = self.with_state(|state| {
+                let window_requests = state.window_requests.get_mut();
+                if window_requests.get(&window_id).unwrap().take_closed() {
+                    mem::drop(window_requests.remove(&window_id));
+                    mem::drop(state.windows.get_mut().remove(&window_id));
+                    false
+                } else {
+                    let mut window = state
+                        .windows
+                        .get_mut()
+                        .get_mut(&window_id)
+                        .unwrap()
+                        .lock()
+                        .unwrap();
+
+                    // The decorations are redrawn right away, they don't wait for the frame
+                    // callback of the window.
+                    let frame_dirty = window.refresh_frame();
+                    let requests = window_requests.get(&window_id).unwrap();
+
+                    // Hold the redraw requests until the compositor is ready for a new frame.
+                    if window.begin_redraw() {
+                        requests.take_redraw_requested() || frame_dirty
                     } else {
-                        let mut redraw_requested = window_requests
-                            .get(&window_id)
-                            .unwrap()
-                            .take_redraw_requested();
-
-                        // Redraw the frames while at it.
-                        redraw_requested |= state
-                            .windows
-                            .get_mut()
-                            .get_mut(&window_id)
-                            .unwrap()
-                            .lock()
-                            .unwrap()
-                            .refresh_frame();
-
-                        redraw_requested
+                        if frame_dirty {
+                            requests.redraw_requested.store(true, Ordering::Relaxed);
+                        }
+                        false
                     }
-                });
-
-                if request_redraw {
-                    sticky_exit_callback(
-                        Event::RedrawRequested(crate::window::WindowId(window_id)),
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
                 }
+            });
+
+            if request_redraw {
+                sticky_exit_callback(
+                    Event::RedrawRequested(crate::window::WindowId(window_id)),
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
             }
+        }
 
-            // Send RedrawEventCleared.
-            sticky_exit_callback(
-                Event::RedrawEventsCleared,
-                &self.window_target,
-                &mut control_flow,
-                &mut callback,
-            );
-        };
+        // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        self.window_ids = window_ids;
+        self.compositor_updates = compositor_updates;
+        self.buffer_sink = buffer_sink;
 
-        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
-        exit_code
+        Ok(())
     }
 
     #[inline]
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
-        EventLoopProxy::new(self.user_events_sender.clone())
+        let waker = match &self.window_target.p {
+            PlatformEventLoopWindowTarget::Wayland(window_target) => {
+                window_target.event_loop_awakener.clone()
+            }
+            _ => unreachable!(),
+        };
+        EventLoopProxy::new(self.user_events_sender.clone(), waker)
+    }
+
+    pub fn as_raw_fd(&self) -> RawFd {
+        match &self.window_target.p {
+            PlatformEventLoopWindowTarget::Wayland(window_target) => {
+                window_target.event_sources.as_raw_fd()
+            }
+            _ => unreachable!(),
+        }
     }
 
     #[inline]
@@ -464,33 +590,107 @@ impl<T: 'static> EventLoop<T> {
         &self.window_target
     }
 
+    /// Whether there's anything to deliver to the application in a loop iteration.
+    fn has_pending(&mut self) -> bool {
+        if !self.pending_user_events.borrow().is_empty() {
+            return true;
+        }
+
+        self.with_state(|state| {
+            let windows = state.windows.get_mut();
+            let window_requests = state
+                .window_requests
+                .get_mut()
+                .iter()
+                .any(|(id, requests)| {
+                    let redraw_requested = requests.redraw_requested.load(Ordering::Relaxed)
+                        && windows.get(id).map_or(false, |window| {
+                            window.lock().unwrap().frame_callback_state()
+                                != FrameCallbackState::Requested
+                        });
+                    redraw_requested
+                        || requests.closed.load(Ordering::Relaxed)
+                        || requests.drag.lock().unwrap().is_some()
+                });
+
+            state.wayland_events_dispatched
+                || window_requests
+                || !state.window_compositor_updates.is_empty()
+                || !state.events_sink.is_empty()
+                || !state.window_events_sink.lock().unwrap().is_empty()
+        })
+    }
+
     fn with_state<'a, U: 'a, F: FnOnce(&'a mut WinitState) -> U>(&'a mut self, callback: F) -> U {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(x11_platform)]
             _ => unreachable!(),
         };
 
         callback(state)
     }
 
+    /// Advance the animated cursors, and schedule a wake up for their next frame.
+    fn animate_cursors(&mut self) {
+        let deadline = self.with_state(|state| {
+            let now = Instant::now();
+            state
+                .windows
+                .get_mut()
+                .values()
+                .filter_map(|window| window.lock().unwrap().animate_cursor(now))
+                .min()
+        });
+        self.schedule_cursor_animation(deadline);
+    }
+
+    fn schedule_cursor_animation(&mut self, deadline: Option<Instant>) {
+        if self.cursor_animation_timer.map(|(deadline, _)| deadline) == deadline {
+            return;
+        }
+
+        // Timers never fire early, the one which is due was already dropped.
+        if let Some((old_deadline, token)) = self.cursor_animation_timer.take() {
+            if old_deadline > Instant::now() {
+                self.event_loop.handle().remove(token);
+            }
+        }
+
+        self.cursor_animation_timer = deadline.and_then(|deadline| {
+            // Waking up is enough, since the cursors are animated after each dispatch.
+            let timer = calloop::timer::Timer::from_deadline(deadline);
+            self.event_loop
+                .handle()
+                .insert_source(timer, |_, _, _| calloop::timer::TimeoutAction::Drop)
+                .ok()
+                .map(|token| (deadline, token))
+        });
+    }
+
     fn loop_dispatch<D: Into<Option<std::time::Duration>>>(&mut self, timeout: D) -> IOResult<()> {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
         self.event_loop.dispatch(timeout, state).map_err(|error| {
             error!("Error dispatching event loop: {}", error);
-            error.into()
-        })
+            std::io::Error::from(error)
+        })?;
+
+        // Animate the cursors here rather than in the iteration, since the iteration is skipped
+        // when there's nothing to deliver, like after `set_custom_cursor` or a frame timer.
+        self.animate_cursors();
+        Ok(())
     }
 }
 
 pub struct EventLoopWindowTarget<T> {
     /// The event loop wakeup source.
-    pub event_loop_awakener: calloop::ping::Ping,
+    pub event_loop_awakener: Arc<Waker>,
+
+    /// The event sources of the application.
+    pub event_sources: EventSources<WinitState>,
 
     /// The main queue used by the event loop.
     pub queue_handle: QueueHandle<WinitState>,
@@ -514,6 +714,78 @@ impl<T> EventLoopWindowTarget<T> {
         display_handle.display = self.connection.display().id().as_ptr() as *mut _;
         RawDisplayHandle::Wayland(display_handle)
     }
+
+    pub fn available_devices(&self) -> Vec<DeviceInfo> {
+        self.state.borrow().available_devices()
+    }
+
+    pub fn set_clipboard_content(
+        &self,
+        kind: ClipboardKind,
+        content: ClipboardContent,
+    ) -> Result<(), ExternalError> {
+        self.state
+            .borrow_mut()
+            .set_clipboard_content(kind, content, &self.queue_handle)
+    }
+
+    pub fn request_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        let serial = self
+            .state
+            .borrow_mut()
+            .request_clipboard_data(kind, mime_type)?;
+
+        // Wake up the event loop, since the request could fail right away.
+        self.event_loop_awakener.ping();
+        Ok(serial)
+    }
+
+    pub fn request_clipboard_mime_types(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        let serial = self.state.borrow_mut().request_clipboard_mime_types(kind)?;
+
+        // Wake up the event loop to deliver the response.
+        self.event_loop_awakener.ping();
+        Ok(serial)
+    }
+
+    pub fn accept_drop(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        self.state.borrow_mut().accept_drop(mime_type, action)
+    }
+
+    pub fn reject_drop(&self) -> Result<(), ExternalError> {
+        self.state.borrow_mut().reject_drop()
+    }
+
+    pub fn request_drop_data(&self, mime_type: &str) -> Result<AsyncRequestSerial, ExternalError> {
+        let serial = self.state.borrow_mut().request_drop_data(mime_type)?;
+
+        // Wake up the event loop, since the request could fail right away.
+        self.event_loop_awakener.ping();
+        Ok(serial)
+    }
+
+    pub fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_fd(fd)
+    }
+
+    pub fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_timer(deadline, interval)
+    }
+
+    pub fn unregister_source(&self, source: SourceId) {
+        self.event_sources.unregister_source(source)
+    }
 }
 
 // The default routine does floor, but we need round on Wayland.
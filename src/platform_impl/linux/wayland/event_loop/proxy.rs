@@ -1,32 +1,41 @@
 //! An event loop proxy.
 
 use std::sync::mpsc::SendError;
+use std::sync::Arc;
 
 use sctk::reexports::calloop::channel::Sender;
 
 use crate::event_loop::EventLoopClosed;
+use crate::platform_impl::platform::common::event_sources::Waker;
 
 /// A handle that can be sent across the threads and used to wake up the `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
     user_events_sender: Sender<T>,
+    waker: Arc<Waker>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
             user_events_sender: self.user_events_sender.clone(),
+            waker: self.waker.clone(),
         }
     }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
-    pub fn new(user_events_sender: Sender<T>) -> Self {
-        Self { user_events_sender }
+    pub fn new(user_events_sender: Sender<T>, waker: Arc<Waker>) -> Self {
+        Self {
+            user_events_sender,
+            waker,
+        }
     }
 
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
         self.user_events_sender
             .send(event)
-            .map_err(|SendError(error)| EventLoopClosed(error))
+            .map_err(|SendError(error)| EventLoopClosed(error))?;
+        self.waker.ping();
+        Ok(())
     }
 }
@@ -1,8 +1,11 @@
 //! An event loop's sink to deliver events from the Wayland event callbacks.
 
+use std::time::Instant;
 use std::vec::Drain;
 
+use crate::clipboard::ClipboardEvent;
 use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
+use crate::event_loop::SourceId;
 use crate::platform_impl::platform::DeviceId as PlatformDeviceId;
 use crate::window::WindowId as RootWindowId;
 
@@ -22,10 +25,16 @@ impl EventSink {
 
     /// Add new device event to a queue.
     #[inline]
-    pub fn push_device_event(&mut self, event: DeviceEvent, device_id: DeviceId) {
+    pub fn push_device_event(
+        &mut self,
+        event: DeviceEvent,
+        device_id: DeviceId,
+        timestamp: Option<Instant>,
+    ) {
         self.window_events.push(Event::DeviceEvent {
             event,
             device_id: RootDeviceId(PlatformDeviceId::Wayland(device_id)),
+            timestamp,
         });
     }
 
@@ -38,6 +47,23 @@ impl EventSink {
         });
     }
 
+    /// Add new clipboard event to a queue.
+    #[inline]
+    pub fn push_clipboard_event(&mut self, event: ClipboardEvent) {
+        self.window_events.push(Event::Clipboard(event));
+    }
+
+    /// Add new event of a ready event source to a queue.
+    #[inline]
+    pub fn push_source_event(&mut self, source: SourceId) {
+        self.window_events.push(Event::SourceReady(source));
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.window_events.is_empty()
+    }
+
     #[inline]
     pub fn append(&mut self, other: &mut Self) {
         self.window_events.append(&mut other.window_events);
@@ -2,9 +2,11 @@
 
 //! Winit's Wayland backend.
 
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Proxy;
 
+use crate::device::SeatId;
 pub use crate::platform_impl::platform::WindowId;
 pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 pub use output::{MonitorHandle, VideoMode};
@@ -17,14 +19,40 @@ mod state;
 mod types;
 mod window;
 
-/// Dummy device id, since Wayland doesn't have device events.
+/// The device id of all the devices of a seat, since Wayland doesn't expose the devices.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct DeviceId;
+pub struct DeviceId(u32);
 
 impl DeviceId {
     pub const unsafe fn dummy() -> Self {
-        DeviceId
+        DeviceId(0)
     }
+
+    /// Get the DeviceId of the devices of the seat.
+    #[inline]
+    fn from_seat(seat: &WlSeat) -> Self {
+        DeviceId(seat.id().protocol_id())
+    }
+
+    /// The identifier of the seat of the devices.
+    #[inline]
+    pub fn seat(&self) -> SeatId {
+        SeatId(self.0 as u64)
+    }
+}
+
+/// Get the SeatId of the seat.
+#[inline]
+fn make_seat_id(seat: &WlSeat) -> SeatId {
+    DeviceId::from_seat(seat).seat()
+}
+
+/// Get the root DeviceId of the devices of the seat.
+#[inline]
+fn make_device_id(seat: &WlSeat) -> crate::event::DeviceId {
+    crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+        DeviceId::from_seat(seat),
+    ))
 }
 
 /// Get the WindowId out of the surface.
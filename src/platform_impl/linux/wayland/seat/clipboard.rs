@@ -0,0 +1,323 @@
+//! The clipboard and primary selection handling.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+use sctk::reexports::client::backend::ObjectId;
+use sctk::reexports::client::{Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1;
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1;
+
+use sctk::data_device_manager::data_device::DataDeviceDataExt;
+use sctk::data_device_manager::data_offer::{DataOfferError, SelectionOffer as DataSelectionOffer};
+use sctk::data_device_manager::data_source::CopyPasteSource;
+use sctk::data_device_manager::WritePipe;
+
+use crate::clipboard::{ClipboardContent, ClipboardEvent, ClipboardKind, TEXT_MIME_TYPES};
+use crate::error::{ExternalError, NotSupportedError};
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::types::primary_selection::{
+    PrimarySelectionDeviceData, PrimarySelectionOfferData,
+};
+use crate::platform_impl::OsError;
+
+use super::data_device;
+use super::WinitSeatState;
+
+/// The content offered by the application.
+#[derive(Debug, Default)]
+pub struct ClipboardState {
+    /// The source of the clipboard along with its content.
+    clipboard: Option<(CopyPasteSource, ClipboardContent)>,
+
+    /// The source of the primary selection along with its content.
+    primary: Option<(ZwpPrimarySelectionSourceV1, ClipboardContent)>,
+}
+
+impl ClipboardState {
+    fn content(&self, kind: ClipboardKind) -> Option<&ClipboardContent> {
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard.as_ref().map(|(_, content)| content),
+            ClipboardKind::Primary => self.primary.as_ref().map(|(_, content)| content),
+        }
+    }
+
+    fn set_primary(&mut self, primary: Option<(ZwpPrimarySelectionSourceV1, ClipboardContent)>) {
+        if let Some((source, _)) = std::mem::replace(&mut self.primary, primary) {
+            source.destroy();
+        }
+    }
+}
+
+/// The selection offered by another client, or by us.
+enum SelectionOffer {
+    Clipboard(DataSelectionOffer),
+    Primary(ZwpPrimarySelectionOfferV1),
+}
+
+impl SelectionOffer {
+    /// Ask the owner of the selection to write the data into the returned pipe.
+    fn receive(&self, mime_type: String) -> io::Result<File> {
+        match self {
+            SelectionOffer::Clipboard(offer) => {
+                let pipe = offer.receive(mime_type).map_err(|err| match err {
+                    DataOfferError::Io(err) => err,
+                    err => io::Error::new(io::ErrorKind::Other, err),
+                })?;
+                Ok(unsafe { File::from_raw_fd(pipe.into_raw_fd()) })
+            }
+            SelectionOffer::Primary(offer) => {
+                let (read, write) = data_device::pipe()?;
+                offer.receive(mime_type, write.as_raw_fd());
+                Ok(read)
+            }
+        }
+    }
+}
+
+impl WinitState {
+    pub fn set_clipboard_content(
+        &mut self,
+        kind: ClipboardKind,
+        content: ClipboardContent,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<(), ExternalError> {
+        let (seat_state, serial) = match self.latest_input_serial.as_ref() {
+            Some((seat, serial)) => match self.seats.get(seat) {
+                Some(seat_state) => (seat_state, *serial),
+                None => return Err(no_input_serial()),
+            },
+            None => return Err(no_input_serial()),
+        };
+
+        let mime_types = content.advertised_mime_types();
+        match kind {
+            ClipboardKind::Clipboard => {
+                let (manager, device) = match (
+                    self.data_device_manager_state.as_ref(),
+                    seat_state.data_device.as_ref(),
+                ) {
+                    (Some(manager), Some(device)) => (manager, device),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+
+                if content.is_empty() {
+                    device.unset_selection(serial);
+                    self.clipboard_state.clipboard = None;
+                } else {
+                    let source = manager.create_copy_paste_source(
+                        queue_handle,
+                        mime_types.iter().map(String::as_str),
+                    );
+                    source.set_selection(device, serial);
+                    self.clipboard_state.clipboard = Some((source, content));
+                }
+            }
+            ClipboardKind::Primary => {
+                let (manager, device) = match (
+                    self.primary_selection_state.as_ref(),
+                    seat_state.primary_selection_device.as_ref(),
+                ) {
+                    (Some(manager), Some(device)) => (manager, device),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+
+                if content.is_empty() {
+                    device.set_selection(None, serial);
+                    self.clipboard_state.set_primary(None);
+                } else {
+                    let source =
+                        manager.create_source(mime_types.iter().map(String::as_str), queue_handle);
+                    device.set_selection(Some(&source), serial);
+                    self.clipboard_state.set_primary(Some((source, content)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn request_clipboard_data(
+        &mut self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        self.ensure_clipboard_supported(kind)?;
+
+        let serial = AsyncRequestSerial::get();
+        let offer = match self.selection_offer(kind) {
+            Some((offer, mime_types)) => {
+                // Legacy clients may not offer the MIME type we use for the text.
+                let offered = if mime_types.iter().any(|offered| offered == mime_type) {
+                    Some(mime_type.to_owned())
+                } else if TEXT_MIME_TYPES.contains(&mime_type) {
+                    mime_types
+                        .into_iter()
+                        .find(|offered| TEXT_MIME_TYPES.contains(&offered.as_str()))
+                } else {
+                    None
+                };
+
+                offered.map(|offered| (offer, offered))
+            }
+            None => None,
+        };
+
+        let file = offer.and_then(|(offer, offered)| match offer.receive(offered) {
+            Ok(file) => Some(file),
+            Err(err) => {
+                warn!("Failed to receive the selection: {err}");
+                None
+            }
+        });
+
+        let file = match file {
+            Some(file) => file,
+            None => {
+                self.events_sink
+                    .push_clipboard_event(ClipboardEvent::RequestFailed { serial, kind });
+                return Ok(serial);
+            }
+        };
+
+        let mime_type = mime_type.to_owned();
+        data_device::read_pipe(&self.loop_handle, file, move |state, result| {
+            let event = match result {
+                Ok(data) => ClipboardEvent::Data {
+                    serial,
+                    kind,
+                    mime_type,
+                    data,
+                },
+                Err(err) => {
+                    warn!("Failed to read the selection: {err}");
+                    ClipboardEvent::RequestFailed { serial, kind }
+                }
+            };
+
+            state.events_sink.push_clipboard_event(event);
+        });
+
+        Ok(serial)
+    }
+
+    pub fn request_clipboard_mime_types(
+        &mut self,
+        kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        self.ensure_clipboard_supported(kind)?;
+
+        let serial = AsyncRequestSerial::get();
+        let event = match self.selection_offer(kind) {
+            Some((_, mime_types)) => ClipboardEvent::MimeTypes {
+                serial,
+                kind,
+                mime_types,
+            },
+            None => ClipboardEvent::RequestFailed { serial, kind },
+        };
+
+        self.events_sink.push_clipboard_event(event);
+        Ok(serial)
+    }
+
+    /// Send the content set by the application into the pipe.
+    pub fn send_clipboard_content(
+        &mut self,
+        kind: ClipboardKind,
+        mime_type: &str,
+        pipe: WritePipe,
+    ) {
+        // Dropping the pipe without writing will result in empty data on the other end.
+        if let Some(data) = self
+            .clipboard_state
+            .content(kind)
+            .and_then(|content| content.resolve(mime_type))
+        {
+            data_device::write_pipe(&self.loop_handle, pipe, data);
+        }
+    }
+
+    /// The source of the selection was replaced by another client.
+    pub fn clipboard_cancelled(&mut self, kind: ClipboardKind, source_id: &ObjectId) {
+        let is_current = match kind {
+            ClipboardKind::Clipboard => self
+                .clipboard_state
+                .clipboard
+                .as_ref()
+                .map_or(false, |(source, _)| source.inner().id() == *source_id),
+            ClipboardKind::Primary => self
+                .clipboard_state
+                .primary
+                .as_ref()
+                .map_or(false, |(source, _)| source.id() == *source_id),
+        };
+
+        if !is_current {
+            return;
+        }
+
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard_state.clipboard = None,
+            ClipboardKind::Primary => self.clipboard_state.set_primary(None),
+        }
+
+        self.events_sink
+            .push_clipboard_event(ClipboardEvent::ContentLost(kind));
+    }
+
+    fn ensure_clipboard_supported(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        let supported = match kind {
+            ClipboardKind::Clipboard => self.data_device_manager_state.is_some(),
+            ClipboardKind::Primary => self.primary_selection_state.is_some(),
+        };
+
+        if supported {
+            Ok(())
+        } else {
+            Err(ExternalError::NotSupported(NotSupportedError::new()))
+        }
+    }
+
+    /// The seat used to access the selection.
+    fn clipboard_seat(&self) -> Option<&WinitSeatState> {
+        self.latest_input_serial
+            .as_ref()
+            .and_then(|(seat, _)| self.seats.get(seat))
+            .or_else(|| {
+                self.seats.values().find(|seat_state| {
+                    seat_state.data_device.is_some()
+                        || seat_state.primary_selection_device.is_some()
+                })
+            })
+    }
+
+    /// The current selection offer along with its MIME types.
+    fn selection_offer(&self, kind: ClipboardKind) -> Option<(SelectionOffer, Vec<String>)> {
+        let seat_state = self.clipboard_seat()?;
+        match kind {
+            ClipboardKind::Clipboard => {
+                let device = seat_state.data_device.as_ref()?;
+                let offer = device.selection_offer()?;
+                Some((
+                    SelectionOffer::Clipboard(offer),
+                    device.selection_mime_types(),
+                ))
+            }
+            ClipboardKind::Primary => {
+                let device = seat_state.primary_selection_device.as_ref()?;
+                let offer = device.data::<PrimarySelectionDeviceData>()?.offer()?;
+                let mime_types = offer.data::<PrimarySelectionOfferData>()?.mime_types();
+                Some((SelectionOffer::Primary(offer), mime_types))
+            }
+        }
+    }
+}
+
+fn no_input_serial() -> ExternalError {
+    ExternalError::Os(os_error!(OsError::WaylandMisc(
+        "no input event was received to set the selection"
+    )))
+}
@@ -0,0 +1,230 @@
+//! The data device handling, used for the clipboard and drag and drop.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::sync::Arc;
+
+use sctk::reexports::calloop::generic::Generic;
+use sctk::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction;
+use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
+use sctk::reexports::client::{Connection, Proxy, QueueHandle};
+
+use sctk::data_device_manager::data_device::{DataDevice, DataDeviceHandler};
+use sctk::data_device_manager::data_offer::{DataDeviceOffer, DataOfferHandler, DragOffer};
+use sctk::data_device_manager::data_source::DataSourceHandler;
+use sctk::data_device_manager::WritePipe;
+
+use crate::clipboard::ClipboardKind;
+use crate::platform_impl::wayland::state::WinitState;
+
+impl DataDeviceHandler for WinitState {
+    fn enter(&mut self, _: &Connection, _: &QueueHandle<Self>, data_device: DataDevice) {
+        self.dnd_enter(&data_device);
+    }
+
+    fn leave(&mut self, _: &Connection, _: &QueueHandle<Self>, _: DataDevice) {
+        self.dnd_leave();
+    }
+
+    fn motion(&mut self, _: &Connection, _: &QueueHandle<Self>, data_device: DataDevice) {
+        self.dnd_motion(&data_device);
+    }
+
+    fn selection(&mut self, _: &Connection, _: &QueueHandle<Self>, _: DataDevice) {
+        // The selection offer is stored inside the data device and is queried on demand.
+    }
+
+    fn drop_performed(&mut self, _: &Connection, _: &QueueHandle<Self>, data_device: DataDevice) {
+        self.dnd_drop(&data_device);
+    }
+}
+
+impl DataOfferHandler for WinitState {
+    fn offer(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &mut DataDeviceOffer, _: String) {
+    }
+
+    fn source_actions(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &mut DragOffer,
+        _: DndAction,
+    ) {
+    }
+
+    fn selected_action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &mut DragOffer,
+        _: DndAction,
+    ) {
+    }
+}
+
+impl DataSourceHandler for WinitState {
+    fn accept_mime(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlDataSource,
+        _: Option<String>,
+    ) {
+    }
+
+    fn send_request(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        mime_type: String,
+        pipe: WritePipe,
+    ) {
+        if self.is_drag_source(source) {
+            self.send_drag_content(&mime_type, pipe);
+        } else {
+            self.send_clipboard_content(ClipboardKind::Clipboard, &mime_type, pipe);
+        }
+    }
+
+    fn cancelled(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        if self.is_drag_source(source) {
+            self.drag_cancelled();
+        } else {
+            self.clipboard_cancelled(ClipboardKind::Clipboard, &source.id());
+        }
+    }
+
+    fn dnd_dropped(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        if self.is_drag_source(source) {
+            self.drag_dropped();
+        }
+    }
+
+    fn dnd_finished(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        if self.is_drag_source(source) {
+            self.drag_finished();
+        }
+    }
+
+    fn action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        action: DndAction,
+    ) {
+        if self.is_drag_source(source) {
+            self.drag_action_changed(action);
+        }
+    }
+}
+
+/// Create a pipe for the data transfer, returning the read and write ends.
+pub fn pipe() -> io::Result<(File, File)> {
+    let mut fds: [RawFd; 2] = [-1; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    unsafe { Ok((File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]))) }
+}
+
+/// Read everything from the `file` without blocking the event loop.
+///
+/// The `callback` is called once the other end of the pipe is closed.
+pub fn read_pipe<F>(
+    loop_handle: &LoopHandle<'static, WinitState>,
+    file: impl IntoRawFd,
+    callback: F,
+) where
+    F: FnOnce(&mut WinitState, io::Result<Vec<u8>>) + 'static,
+{
+    let file = unsafe { File::from_raw_fd(file.into_raw_fd()) };
+    if let Err(err) = set_nonblocking(&file) {
+        warn!("Failed to make the pipe non-blocking: {err}");
+    }
+
+    let mut callback = Some(callback);
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+    let source = Generic::new(file, Interest::READ, Mode::Level);
+    let result = loop_handle.insert_source(source, move |_, file, state| {
+        let result = loop {
+            match file.read(&mut buffer) {
+                Ok(0) => break Ok(std::mem::take(&mut data)),
+                Ok(len) => data.extend_from_slice(&buffer[..len]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(PostAction::Continue)
+                }
+                Err(err) => break Err(err),
+            }
+        };
+
+        if let Some(callback) = callback.take() {
+            callback(state, result);
+        }
+
+        Ok(PostAction::Remove)
+    });
+
+    if let Err(err) = result {
+        warn!("Failed to register the pipe for reading: {}", err.error);
+    }
+}
+
+/// Write the `data` into the `file` without blocking the event loop.
+pub fn write_pipe(
+    loop_handle: &LoopHandle<'static, WinitState>,
+    file: impl IntoRawFd,
+    data: Arc<[u8]>,
+) {
+    let file = unsafe { File::from_raw_fd(file.into_raw_fd()) };
+    if let Err(err) = set_nonblocking(&file) {
+        warn!("Failed to make the pipe non-blocking: {err}");
+    }
+
+    let mut written = 0;
+    let source = Generic::new(file, Interest::WRITE, Mode::Level);
+    let result = loop_handle.insert_source(source, move |_, file, _| {
+        while written < data.len() {
+            match file.write(&data[written..]) {
+                Ok(len) => written += len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(PostAction::Continue)
+                }
+                Err(err) => {
+                    warn!("Failed to write the data into the pipe: {err}");
+                    break;
+                }
+            }
+        }
+
+        Ok(PostAction::Remove)
+    });
+
+    if let Err(err) = result {
+        warn!("Failed to register the pipe for writing: {}", err.error);
+    }
+}
+
+fn set_nonblocking(file: &File) -> io::Result<()> {
+    let fd = file.as_raw_fd();
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+sctk::delegate_data_device_manager!(WinitState);
+sctk::delegate_data_device!(WinitState);
+sctk::delegate_data_offer!(WinitState);
+sctk::delegate_data_source!(WinitState);
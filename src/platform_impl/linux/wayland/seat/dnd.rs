@@ -0,0 +1,609 @@
+//! The drag and drop handling.
+
+use std::io;
+use std::path::PathBuf;
+
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
+use sctk::reexports::client::protocol::wl_shm;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Proxy;
+
+use sctk::data_device_manager::data_device::{DataDevice, DataDeviceDataExt};
+use sctk::data_device_manager::data_offer::DragOffer;
+use sctk::data_device_manager::data_source::DragSource as SctkDragSource;
+use sctk::data_device_manager::WritePipe;
+use sctk::shell::WaylandSurface;
+use sctk::shm::slot::{Buffer, SlotPool};
+use sctk::shm::Shm;
+
+use crate::clipboard::ClipboardContent;
+use crate::dnd::{DndAction, DndEvent, DragSourceEvent};
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::error::ExternalError;
+use crate::event::WindowEvent;
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::platform::common::dnd::{self, DragRequest, URI_LIST_MIME_TYPE};
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
+use crate::platform_impl::{OsError, PlatformIcon};
+
+use super::{data_device, WinitPointerDataExt};
+
+/// The drag and drop offer hovering one of our windows.
+#[derive(Debug)]
+pub struct DndOffer {
+    /// The offer itself.
+    offer: DragOffer,
+
+    /// The window the offer is hovering.
+    window_id: WindowId,
+
+    /// The action accepted by us, `None` when the drop is rejected.
+    action: Option<DndAction>,
+
+    /// Whether the data is offered as a list of files.
+    has_files: bool,
+
+    /// The files from the offer, once they were read.
+    paths: Option<Vec<PathBuf>>,
+
+    /// The number of the data requests which haven't completed yet.
+    pending_requests: usize,
+
+    /// Whether the offer was dropped onto the window.
+    dropped: bool,
+}
+
+/// The drag of the data out of one of our windows.
+#[derive(Debug)]
+pub struct DragSource {
+    /// The source itself, the drag is cancelled once it's dropped.
+    source: SctkDragSource,
+
+    /// The window the data is dragged out of.
+    window_id: WindowId,
+
+    /// The dragged data.
+    content: ClipboardContent,
+
+    /// The action chosen by the target.
+    action: DndAction,
+
+    /// The surface showing the icon, along with its buffer.
+    icon: Option<(WlSurface, SlotPool, Buffer)>,
+}
+
+impl Drop for DragSource {
+    fn drop(&mut self) {
+        if let Some((surface, ..)) = self.icon.take() {
+            surface.destroy();
+        }
+    }
+}
+
+impl DndOffer {
+    /// Send the response to the compositor.
+    fn set_action(&mut self, mime_type: Option<String>, action: Option<DndAction>) {
+        let wl_action = action.map_or(WlDndAction::empty(), wl_dnd_action);
+        self.offer.accept_mime_type(self.offer.serial, mime_type);
+        self.offer.set_actions(wl_action, wl_action);
+        self.action = action;
+    }
+}
+
+impl WinitState {
+    pub fn dnd_enter(&mut self, data_device: &DataDevice) {
+        let offer = match data_device.drag_offer() {
+            Some(offer) => offer,
+            None => return,
+        };
+
+        // Drop the offer which wasn't properly finished.
+        if let Some(dnd_offer) = self.dnd_offer.take() {
+            dnd_offer.offer.destroy();
+        }
+
+        let window_id = wayland::make_wid(&offer.surface);
+        let position = match self.dnd_position(&offer) {
+            Some(position) => position,
+            None => {
+                offer.destroy();
+                return;
+            }
+        };
+
+        let mime_types = data_device.drag_mime_types();
+        let has_files = mime_types
+            .iter()
+            .any(|mime_type| mime_type == URI_LIST_MIME_TYPE);
+
+        let mut dnd_offer = DndOffer {
+            offer,
+            window_id,
+            action: None,
+            has_files,
+            paths: None,
+            pending_requests: 0,
+            dropped: false,
+        };
+
+        // Accept the files by default.
+        if has_files {
+            dnd_offer.set_action(Some(URI_LIST_MIME_TYPE.to_owned()), Some(DndAction::Copy));
+        } else {
+            dnd_offer.set_action(None, None);
+        }
+
+        self.dnd_offer = Some(dnd_offer);
+
+        // Read the files right away, so we could emit `HoveredFile`.
+        if has_files {
+            self.receive_dnd_data(None, URI_LIST_MIME_TYPE);
+        }
+
+        self.events_sink.push_window_event(
+            WindowEvent::DragAndDrop(DndEvent::Entered {
+                mime_types,
+                position,
+            }),
+            window_id,
+        );
+    }
+
+    pub fn dnd_motion(&mut self, data_device: &DataDevice) {
+        let offer = match data_device.drag_offer() {
+            Some(offer) => offer,
+            None => return,
+        };
+
+        let window_id = match self.dnd_offer.as_ref() {
+            Some(dnd_offer) if dnd_offer.offer == offer => dnd_offer.window_id,
+            _ => return,
+        };
+
+        if let Some(position) = self.dnd_position(&offer) {
+            self.events_sink.push_window_event(
+                WindowEvent::DragAndDrop(DndEvent::Moved { position }),
+                window_id,
+            );
+        }
+    }
+
+    pub fn dnd_leave(&mut self) {
+        // The dropped offer is kept around until its data is read.
+        if self
+            .dnd_offer
+            .as_ref()
+            .map_or(true, |dnd_offer| dnd_offer.dropped)
+        {
+            return;
+        }
+
+        let dnd_offer = self.dnd_offer.take().unwrap();
+        if dnd_offer.has_files {
+            self.events_sink
+                .push_window_event(WindowEvent::HoveredFileCancelled, dnd_offer.window_id);
+        }
+
+        self.events_sink.push_window_event(
+            WindowEvent::DragAndDrop(DndEvent::Left),
+            dnd_offer.window_id,
+        );
+
+        dnd_offer.offer.destroy();
+    }
+
+    pub fn dnd_drop(&mut self, data_device: &DataDevice) {
+        let position = match data_device
+            .drag_offer()
+            .and_then(|offer| self.dnd_position(&offer))
+        {
+            Some(position) => position,
+            None => return,
+        };
+
+        let dnd_offer = match self.dnd_offer.as_mut() {
+            Some(dnd_offer) => dnd_offer,
+            None => return,
+        };
+
+        let window_id = dnd_offer.window_id;
+        let action = match dnd_offer.action {
+            Some(action) => action,
+            None => {
+                let dnd_offer = self.dnd_offer.take().unwrap();
+                dnd_offer.offer.destroy();
+                self.events_sink
+                    .push_window_event(WindowEvent::DragAndDrop(DndEvent::Left), window_id);
+                return;
+            }
+        };
+
+        dnd_offer.dropped = true;
+        let paths = dnd_offer.paths.take();
+
+        // The data is requested while handling this event.
+        self.events_sink.push_window_event(
+            WindowEvent::DragAndDrop(DndEvent::Dropped { position, action }),
+            window_id,
+        );
+
+        for path in paths.into_iter().flatten() {
+            self.events_sink
+                .push_window_event(WindowEvent::DroppedFile(path), window_id);
+        }
+    }
+
+    /// Finish the drop once all the requested data has arrived.
+    ///
+    /// This must be called after the events were delivered to the application, since it could
+    /// request the data while handling `DndEvent::Dropped`.
+    pub fn finish_drop(&mut self) {
+        match self.dnd_offer.as_ref() {
+            Some(dnd_offer) if dnd_offer.dropped && dnd_offer.pending_requests == 0 => (),
+            _ => return,
+        }
+
+        let dnd_offer = self.dnd_offer.take().unwrap();
+
+        // The `ask` action isn't final, the data is copied once the drop is accepted.
+        if dnd_offer.action == Some(DndAction::Ask) {
+            dnd_offer
+                .offer
+                .set_actions(WlDndAction::Copy, WlDndAction::Copy);
+        }
+
+        dnd_offer.offer.finish();
+        dnd_offer.offer.destroy();
+    }
+
+    pub fn accept_drop(&mut self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        let dnd_offer = self.dnd_offer.as_mut().ok_or_else(no_drag_error)?;
+        if !dnd_offer.dropped {
+            dnd_offer.set_action(Some(mime_type.to_owned()), Some(action));
+        }
+
+        Ok(())
+    }
+
+    pub fn reject_drop(&mut self) -> Result<(), ExternalError> {
+        let dnd_offer = self.dnd_offer.as_mut().ok_or_else(no_drag_error)?;
+        if !dnd_offer.dropped {
+            dnd_offer.set_action(None, None);
+        }
+
+        Ok(())
+    }
+
+    pub fn request_drop_data(
+        &mut self,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        if self.dnd_offer.is_none() {
+            return Err(no_drag_error());
+        }
+
+        let serial = AsyncRequestSerial::get();
+        self.receive_dnd_data(Some(serial), mime_type);
+        Ok(serial)
+    }
+
+    /// Read the data from the current offer, `serial` is `None` for the list of files.
+    fn receive_dnd_data(&mut self, serial: Option<AsyncRequestSerial>, mime_type: &str) {
+        let dnd_offer = match self.dnd_offer.as_mut() {
+            Some(dnd_offer) => dnd_offer,
+            None => return,
+        };
+
+        match dnd_offer.offer.receive(mime_type.to_owned()) {
+            Ok(pipe) => {
+                dnd_offer.pending_requests += 1;
+                let offer = dnd_offer.offer.clone();
+                let mime_type = mime_type.to_owned();
+                data_device::read_pipe(&self.loop_handle, pipe, move |state, result| {
+                    state.dnd_data_received(&offer, serial, mime_type, result)
+                });
+            }
+            Err(err) => {
+                warn!("Failed to receive the dragged data: {err}");
+                if let Some(serial) = serial {
+                    let window_id = dnd_offer.window_id;
+                    self.events_sink.push_window_event(
+                        WindowEvent::DragAndDrop(DndEvent::RequestFailed { serial }),
+                        window_id,
+                    );
+                }
+            }
+        }
+    }
+
+    fn dnd_data_received(
+        &mut self,
+        offer: &DragOffer,
+        serial: Option<AsyncRequestSerial>,
+        mime_type: String,
+        result: io::Result<Vec<u8>>,
+    ) {
+        let dnd_offer = match self.dnd_offer.as_mut() {
+            Some(dnd_offer) if dnd_offer.offer == *offer => dnd_offer,
+            _ => return,
+        };
+
+        dnd_offer.pending_requests -= 1;
+        let window_id = dnd_offer.window_id;
+
+        let serial = match serial {
+            Some(serial) => serial,
+            None => {
+                let paths = match result {
+                    Ok(data) => dnd::parse_uri_list(&data).unwrap_or_else(|err| {
+                        warn!("Failed to parse the dropped files: {err:?}");
+                        Vec::new()
+                    }),
+                    Err(err) => {
+                        warn!("Failed to read the dropped files: {err}");
+                        Vec::new()
+                    }
+                };
+
+                for path in &paths {
+                    self.events_sink
+                        .push_window_event(WindowEvent::HoveredFile(path.clone()), window_id);
+                }
+
+                if dnd_offer.dropped {
+                    for path in paths {
+                        self.events_sink
+                            .push_window_event(WindowEvent::DroppedFile(path), window_id);
+                    }
+                } else {
+                    dnd_offer.paths = Some(paths);
+                }
+
+                return;
+            }
+        };
+
+        let event = match result {
+            Ok(data) => DndEvent::Data {
+                serial,
+                mime_type,
+                data,
+            },
+            Err(err) => {
+                warn!("Failed to read the dragged data: {err}");
+                DndEvent::RequestFailed { serial }
+            }
+        };
+
+        self.events_sink
+            .push_window_event(WindowEvent::DragAndDrop(event), window_id);
+    }
+
+    /// The position of the offer in the physical coordinates of the window.
+    fn dnd_position(&mut self, offer: &DragOffer) -> Option<PhysicalPosition<f64>> {
+        let window_id = wayland::make_wid(&offer.surface);
+        let scale_factor = self
+            .windows
+            .get_mut()
+            .get(&window_id)?
+            .lock()
+            .unwrap()
+            .scale_factor();
+        Some(LogicalPosition::new(offer.x, offer.y).to_physical(scale_factor))
+    }
+}
+
+impl WinitState {
+    /// Start the drag requested by the window.
+    pub fn start_drag(&mut self, window_id: WindowId, request: DragRequest) {
+        // Cancel the previous drag.
+        if let Some(drag_source) = self.drag_source.take() {
+            self.events_sink.push_window_event(
+                WindowEvent::DragSource(DragSourceEvent::Cancelled),
+                drag_source.window_id,
+            );
+        }
+
+        match self.create_drag_source(window_id, request) {
+            Some(drag_source) => self.drag_source = Some(drag_source),
+            None => self.events_sink.push_window_event(
+                WindowEvent::DragSource(DragSourceEvent::Cancelled),
+                window_id,
+            ),
+        }
+    }
+
+    fn create_drag_source(
+        &mut self,
+        window_id: WindowId,
+        request: DragRequest,
+    ) -> Option<DragSource> {
+        let manager = self.data_device_manager_state.as_ref()?;
+        let window_state = self.windows.get_mut().get(&window_id)?.lock().unwrap();
+
+        // The drag is started with the pointer holding the button in the window, the latest press
+        // wins when several seats do so.
+        let (seat, serial) = window_state
+            .pointers
+            .iter()
+            .filter_map(|pointer| pointer.upgrade())
+            .filter_map(|pointer| {
+                let data = pointer.pointer().winit_data();
+                data.has_pressed_buttons()
+                    .then(|| (data.seat().clone(), data.latest_button_serial()))
+            })
+            .max_by_key(|(_, serial)| *serial)?;
+        let device = self.seats.get(&seat.id())?.data_device.as_ref()?;
+
+        let actions = request
+            .actions
+            .iter()
+            .fold(WlDndAction::empty(), |actions, &action| {
+                actions | wl_dnd_action(action)
+            });
+        let mime_types = request.content.advertised_mime_types();
+        let source = manager.create_drag_and_drop_source(
+            &window_state.queue_handle,
+            mime_types.iter().map(String::as_str),
+            actions,
+        );
+
+        let icon = request.icon.and_then(|icon| {
+            let surface = self
+                .compositor_state
+                .create_surface(&window_state.queue_handle);
+            let (pool, buffer) = match create_icon_buffer(&self.shm, &icon.inner) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    warn!("Failed to create the drag icon: {err}");
+                    surface.destroy();
+                    return None;
+                }
+            };
+
+            if let Err(err) = buffer.attach_to(&surface) {
+                warn!("Failed to attach the drag icon: {err:?}");
+                surface.destroy();
+                return None;
+            }
+
+            surface.damage_buffer(0, 0, icon.inner.width as i32, icon.inner.height as i32);
+            surface.commit();
+            Some((surface, pool, buffer))
+        });
+
+        let origin = window_state.window.wl_surface();
+        source.start_drag(
+            device,
+            origin,
+            icon.as_ref().map(|(surface, ..)| surface),
+            serial,
+        );
+
+        Some(DragSource {
+            source,
+            window_id,
+            content: request.content,
+            action: request.actions[0],
+            icon,
+        })
+    }
+
+    /// Whether the source belongs to the current drag.
+    pub fn is_drag_source(&self, source: &WlDataSource) -> bool {
+        self.drag_source
+            .as_ref()
+            .map_or(false, |drag_source| drag_source.source.inner() == source)
+    }
+
+    /// Send the dragged data into the pipe.
+    pub fn send_drag_content(&mut self, mime_type: &str, pipe: WritePipe) {
+        // Dropping the pipe without writing will result in empty data on the other end.
+        if let Some(data) = self
+            .drag_source
+            .as_ref()
+            .and_then(|drag_source| drag_source.content.resolve(mime_type))
+        {
+            data_device::write_pipe(&self.loop_handle, pipe, data);
+        }
+    }
+
+    pub fn drag_action_changed(&mut self, action: WlDndAction) {
+        if let Some(drag_source) = self.drag_source.as_mut() {
+            if let Some(action) = winit_dnd_action(action) {
+                drag_source.action = action;
+            }
+        }
+    }
+
+    pub fn drag_dropped(&mut self) {
+        if let Some(drag_source) = self.drag_source.as_mut() {
+            // The icon is no longer shown.
+            if let Some((surface, ..)) = drag_source.icon.take() {
+                surface.destroy();
+            }
+
+            self.events_sink.push_window_event(
+                WindowEvent::DragSource(DragSourceEvent::Dropped {
+                    action: drag_source.action,
+                }),
+                drag_source.window_id,
+            );
+        }
+    }
+
+    pub fn drag_finished(&mut self) {
+        if let Some(drag_source) = self.drag_source.take() {
+            self.events_sink.push_window_event(
+                WindowEvent::DragSource(DragSourceEvent::Finished {
+                    action: drag_source.action,
+                }),
+                drag_source.window_id,
+            );
+        }
+    }
+
+    pub fn drag_cancelled(&mut self) {
+        if let Some(drag_source) = self.drag_source.take() {
+            self.events_sink.push_window_event(
+                WindowEvent::DragSource(DragSourceEvent::Cancelled),
+                drag_source.window_id,
+            );
+        }
+    }
+}
+
+/// Create the buffer holding the premultiplied pixels of the icon.
+fn create_icon_buffer(
+    shm: &Shm,
+    icon: &PlatformIcon,
+) -> Result<(SlotPool, Buffer), Box<dyn std::error::Error>> {
+    let (width, height) = (icon.width as i32, icon.height as i32);
+    let mut pool = SlotPool::new(icon.rgba.len(), shm)?;
+    let (buffer, canvas) =
+        pool.create_buffer(width, height, width * 4, wl_shm::Format::Argb8888)?;
+
+    for (dst, src) in canvas.chunks_exact_mut(4).zip(icon.rgba.chunks_exact(4)) {
+        let alpha = src[3] as u32;
+        let premultiply = |channel: u8| (channel as u32 * alpha / 255) as u8;
+        let pixel = u32::from_be_bytes([
+            src[3],
+            premultiply(src[0]),
+            premultiply(src[1]),
+            premultiply(src[2]),
+        ]);
+        dst.copy_from_slice(&pixel.to_le_bytes());
+    }
+
+    Ok((pool, buffer))
+}
+
+fn wl_dnd_action(action: DndAction) -> WlDndAction {
+    match action {
+        DndAction::Copy => WlDndAction::Copy,
+        DndAction::Move => WlDndAction::Move,
+        // There's no link action on Wayland.
+        DndAction::Link => WlDndAction::Copy,
+        DndAction::Ask => WlDndAction::Ask,
+    }
+}
+
+fn winit_dnd_action(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else if action.contains(WlDndAction::Ask) {
+        Some(DndAction::Ask)
+    } else {
+        None
+    }
+}
+
+fn no_drag_error() -> ExternalError {
+    ExternalError::Os(os_error!(OsError::WaylandMisc(
+        "no drag and drop session is active"
+    )))
+}
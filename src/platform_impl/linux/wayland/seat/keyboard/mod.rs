@@ -1,7 +1,7 @@
 //! The keyboard input handling.
 
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use calloop::timer::{TimeoutAction, Timer};
 use calloop::{LoopHandle, RegistrationToken};
@@ -17,11 +17,12 @@ use sctk::reexports::client::{Connection, Dispatch, Proxy, QueueHandle, WEnum};
 use crate::event::{ElementState, WindowEvent};
 use crate::keyboard::ModifiersState;
 
+use crate::platform_impl::common::timestamp;
 use crate::platform_impl::common::xkb_state::KbdState;
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::seat::WinitSeatState;
 use crate::platform_impl::wayland::state::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+use crate::platform_impl::wayland::{self, WindowId};
 
 impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
     fn event(
@@ -32,6 +33,11 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
         _: &Connection,
         _: &QueueHandle<WinitState>,
     ) {
+        // Keyboard serials are required to set the selection.
+        if let WlKeyboardEvent::Enter { serial, .. } | WlKeyboardEvent::Key { serial, .. } = event {
+            state.latest_input_serial = Some((data.seat.id(), serial));
+        }
+
         let seat_state = match state.seats.get_mut(&data.seat.id()) {
             Some(seat_state) => seat_state,
             None => return,
@@ -60,19 +66,31 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
             WlKeyboardEvent::Enter { surface, .. } => {
                 let window_id = wayland::make_wid(&surface);
 
+                let seat = wayland::make_seat_id(&data.seat);
+
                 // Mark the window as focused.
-                match state.windows.get_mut().get(&window_id) {
-                    Some(window) => window.lock().unwrap().set_has_focus(true),
+                let focus_changed = match state.windows.get_mut().get(&window_id) {
+                    Some(window) => window.lock().unwrap().set_seat_focus(seat, true),
                     None => return,
                 };
 
                 // Drop the repeat, if there were any.
                 seat_state.keyboard_state.as_mut().unwrap().current_repeat = None;
 
-                // The keyboard focus is considered as general focus.
-                state
-                    .events_sink
-                    .push_window_event(WindowEvent::Focused(true), window_id);
+                state.events_sink.push_window_event(
+                    WindowEvent::SeatFocused {
+                        seat,
+                        focused: true,
+                    },
+                    window_id,
+                );
+
+                // The keyboard focus of any seat is considered as general focus.
+                if focus_changed {
+                    state
+                        .events_sink
+                        .push_window_event(WindowEvent::Focused(true), window_id);
+                }
 
                 *data.window_id.lock().unwrap() = Some(window_id);
 
@@ -91,10 +109,12 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 // window of for the window which just went gone.
                 seat_state.keyboard_state.as_mut().unwrap().current_repeat = None;
 
+                let seat = wayland::make_seat_id(&data.seat);
+
                 // NOTE: The check whether the window exists is essential as we might get a
                 // nil surface, regardless of what protocol says.
-                match state.windows.get_mut().get(&window_id) {
-                    Some(window) => window.lock().unwrap().set_has_focus(false),
+                let focus_changed = match state.windows.get_mut().get(&window_id) {
+                    Some(window) => window.lock().unwrap().set_seat_focus(seat, false),
                     None => return,
                 };
 
@@ -108,13 +128,24 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 // anyway.
                 *data.window_id.lock().unwrap() = None;
 
-                state
-                    .events_sink
-                    .push_window_event(WindowEvent::Focused(false), window_id);
+                state.events_sink.push_window_event(
+                    WindowEvent::SeatFocused {
+                        seat,
+                        focused: false,
+                    },
+                    window_id,
+                );
+
+                if focus_changed {
+                    state
+                        .events_sink
+                        .push_window_event(WindowEvent::Focused(false), window_id);
+                }
             }
             WlKeyboardEvent::Key {
                 key,
                 state: key_state,
+                time,
                 ..
             } if key_state == WEnum::Value(WlKeyState::Pressed) => {
                 let key = key + 8;
@@ -126,6 +157,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     key,
                     ElementState::Pressed,
                     false,
+                    timestamp::from_millis(time),
                 );
 
                 let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
@@ -150,7 +182,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 let wl_keyboard = wl_keyboard.clone();
                 keyboard_state.repeat_token = keyboard_state
                     .loop_handle
-                    .insert_source(timer, move |_, _, state| {
+                    .insert_source(timer, move |deadline, _, state| {
                         let data = wl_keyboard.data::<KeyboardData>().unwrap();
                         let seat_state = state.seats.get_mut(&data.seat.id()).unwrap();
 
@@ -169,6 +201,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                             repeat_keycode,
                             ElementState::Pressed,
                             true,
+                            deadline,
                         );
 
                         // NOTE: the gap could change dynamically while repeat is going.
@@ -182,6 +215,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
             WlKeyboardEvent::Key {
                 key,
                 state: key_state,
+                time,
                 ..
             } if key_state == WEnum::Value(WlKeyState::Released) => {
                 let key = key + 8;
@@ -193,6 +227,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     key,
                     ElementState::Released,
                     false,
+                    timestamp::from_millis(time),
                 );
 
                 let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
@@ -349,6 +384,7 @@ fn key_input(
     keycode: u32,
     state: ElementState,
     repeat: bool,
+    timestamp: Instant,
 ) {
     let window_id = match *data.window_id.lock().unwrap() {
         Some(window_id) => window_id,
@@ -357,10 +393,10 @@ fn key_input(
 
     let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
 
-    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    let device_id = wayland::make_device_id(&data.seat);
     let event = keyboard_state
         .xkb_state
-        .process_key_event(keycode, state, repeat);
+        .process_key_event(keycode, state, repeat, timestamp);
 
     event_sink.push_window_event(
         WindowEvent::KeyboardInput {
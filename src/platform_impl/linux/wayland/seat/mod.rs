@@ -4,35 +4,57 @@ use std::sync::Arc;
 
 use fnv::FnvHashMap;
 
-use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_registry::WlRegistry;
+use sctk::reexports::client::protocol::wl_seat::{self, WlSeat};
 use sctk::reexports::client::protocol::wl_touch::WlTouch;
-use sctk::reexports::client::{Connection, Proxy, QueueHandle};
+use sctk::reexports::client::{Connection, Dispatch, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1;
 use sctk::reexports::protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_seat_v2::ZwpTabletSeatV2;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 
-use sctk::seat::pointer::{ThemeSpec, ThemedPointer};
+use sctk::data_device_manager::data_device::DataDevice;
+use sctk::globals::GlobalData;
 use sctk::seat::{Capability as SeatCapability, SeatHandler, SeatState};
 
+use crate::device::{DeviceCapabilities, DeviceInfo, DeviceKind};
+use crate::event::DeviceEvent;
 use crate::keyboard::ModifiersState;
 use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::DeviceId;
 
+mod clipboard;
+mod data_device;
+mod dnd;
 mod keyboard;
 mod pointer;
+mod tablet;
 mod text_input;
 mod touch;
 
+pub use clipboard::ClipboardState;
+pub use dnd::{DndOffer, DragSource};
+pub use pointer::pointer_gestures::PointerGesturesState;
 pub use pointer::relative_pointer::RelativePointerState;
-pub use pointer::{PointerConstraintsState, WinitPointerData, WinitPointerDataExt};
+pub use pointer::{
+    CustomCursorCache, PointerConstraintsState, ThemedPointer, WinitPointerData,
+    WinitPointerDataExt,
+};
+pub use tablet::{TabletInfo, TabletManagerState};
 pub use text_input::{TextInputState, ZwpTextInputV3Ext};
 
 use keyboard::{KeyboardData, KeyboardState};
+use pointer::pointer_gestures::PointerGestures;
 use text_input::TextInputData;
 use touch::TouchPoint;
 
 #[derive(Debug)]
 pub struct WinitSeatState {
+    /// The seat bound at version 8 to get the high-resolution wheel steps on the pointer.
+    pointer_seat: Option<WlSeat>,
+
     /// The pointer bound on the seat.
-    pointer: Option<Arc<ThemedPointer<WinitPointerData>>>,
+    pointer: Option<Arc<ThemedPointer>>,
 
     /// The touch bound on the seat.
     touch: Option<WlTouch>,
@@ -46,6 +68,9 @@ pub struct WinitSeatState {
     /// The relative pointer bound on the seat.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
+    /// The touchpad gestures bound on the seat.
+    pointer_gestures: Option<PointerGestures>,
+
     /// The keyboard bound on the seat.
     keyboard_state: Option<KeyboardState>,
 
@@ -54,20 +79,89 @@ pub struct WinitSeatState {
 
     /// Wether we have pending modifiers.
     modifiers_pending: bool,
+
+    /// The data device bound on the seat, used for the clipboard.
+    data_device: Option<DataDevice>,
+
+    /// The primary selection device bound on the seat.
+    primary_selection_device: Option<ZwpPrimarySelectionDeviceV1>,
+
+    /// The tablet seat bound on the seat, used for the pens.
+    tablet_seat: Option<ZwpTabletSeatV2>,
 }
 
 impl WinitSeatState {
-    pub fn new() -> Self {
+    pub fn new(pointer_seat: Option<WlSeat>) -> Self {
         Self {
+            pointer_seat,
             pointer: None,
             touch: None,
             relative_pointer: None,
+            pointer_gestures: None,
             text_input: None,
             touch_map: Default::default(),
             keyboard_state: None,
             modifiers: ModifiersState::empty(),
             modifiers_pending: false,
+            data_device: None,
+            primary_selection_device: None,
+            tablet_seat: None,
+        }
+    }
+}
+
+impl WinitState {
+    /// Describe the devices from the capabilities of the seats and from the tablets.
+    pub fn available_devices(&self) -> Vec<DeviceInfo> {
+        let mut devices = Vec::new();
+        for seat in self.seat_state.seats() {
+            let info = match self.seat_state.info(&seat) {
+                Some(info) => info,
+                None => continue,
+            };
+
+            let id = DeviceId::from_seat(&seat);
+
+            let seat_name = info.name.as_deref().unwrap_or("seat");
+            let mut push_device = |kind, name, capabilities| {
+                devices.push(DeviceInfo {
+                    id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(id)),
+                    name: format!("{seat_name} {name}"),
+                    kind,
+                    vendor_id: None,
+                    product_id: None,
+                    capabilities,
+                    seat: Some(id.seat()),
+                })
+            };
+            if info.has_keyboard {
+                push_device(DeviceKind::Keyboard, "keyboard", DeviceCapabilities::KEYS);
+            }
+            if info.has_pointer {
+                push_device(
+                    DeviceKind::Pointer,
+                    "pointer",
+                    DeviceCapabilities::BUTTONS
+                        | DeviceCapabilities::MOTION
+                        | DeviceCapabilities::SCROLL,
+                );
+            }
+            if info.has_touch {
+                push_device(DeviceKind::Touchscreen, "touch", DeviceCapabilities::TOUCH);
+            }
         }
+
+        devices.extend(self.tablets.values().map(|tablet| DeviceInfo {
+            id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(tablet.device_id)),
+            name: tablet.name.clone(),
+            kind: DeviceKind::Tablet,
+            vendor_id: tablet.ids.map(|(vendor_id, _)| vendor_id),
+            product_id: tablet.ids.map(|(_, product_id)| product_id),
+            capabilities: tablet.capabilities,
+            seat: Some(tablet.device_id.seat()),
+        }));
+
+        devices
     }
 }
 
@@ -88,34 +182,43 @@ impl SeatHandler for WinitState {
         match capability {
             SeatCapability::Touch if seat_state.touch.is_none() => {
                 seat_state.touch = self.seat_state.get_touch(queue_handle, &seat).ok();
+                self.events_sink.push_device_event(
+                    DeviceEvent::Added,
+                    DeviceId::from_seat(&seat),
+                    None,
+                );
             }
             SeatCapability::Keyboard if seat_state.keyboard_state.is_none() => {
                 let keyboard = seat.get_keyboard(queue_handle, KeyboardData::new(seat.clone()));
                 seat_state.keyboard_state =
                     Some(KeyboardState::new(keyboard, self.loop_handle.clone()));
+                self.events_sink.push_device_event(
+                    DeviceEvent::Added,
+                    DeviceId::from_seat(&seat),
+                    None,
+                );
             }
             SeatCapability::Pointer if seat_state.pointer.is_none() => {
                 let surface = self.compositor_state.create_surface(queue_handle);
                 let surface_id = surface.id();
                 let pointer_data = WinitPointerData::new(seat.clone(), surface);
-                let themed_pointer = self
-                    .seat_state
-                    .get_pointer_with_theme_and_data(
-                        queue_handle,
-                        &seat,
-                        ThemeSpec::System,
-                        pointer_data,
-                    )
-                    .expect("failed to create pointer with present capability.");
+                let pointer_seat = seat_state.pointer_seat.as_ref().unwrap_or(&seat);
+                let themed_pointer =
+                    ThemedPointer::new(pointer_seat.get_pointer(queue_handle, pointer_data));
 
                 seat_state.relative_pointer = self.relative_pointer.as_ref().map(|manager| {
                     manager.get_relative_pointer(
                         themed_pointer.pointer(),
                         queue_handle,
-                        sctk::globals::GlobalData,
+                        DeviceId::from_seat(&seat),
                     )
                 });
 
+                seat_state.pointer_gestures = self
+                    .pointer_gestures
+                    .as_ref()
+                    .map(|manager| manager.get_gestures(themed_pointer.pointer(), queue_handle));
+
                 let themed_pointer = Arc::new(themed_pointer);
 
                 // Register cursor surface.
@@ -123,6 +226,11 @@ impl SeatHandler for WinitState {
                     .insert(surface_id, themed_pointer.clone());
 
                 seat_state.pointer = Some(themed_pointer);
+                self.events_sink.push_device_event(
+                    DeviceEvent::Added,
+                    DeviceId::from_seat(&seat),
+                    None,
+                );
             }
             _ => (),
         }
@@ -139,6 +247,26 @@ impl SeatHandler for WinitState {
                 TextInputData::default(),
             )));
         }
+
+        if seat_state.data_device.is_none() {
+            seat_state.data_device = self
+                .data_device_manager_state
+                .as_ref()
+                .map(|manager| manager.get_data_device(queue_handle, &seat));
+        }
+
+        if seat_state.primary_selection_device.is_none() {
+            seat_state.primary_selection_device = self
+                .primary_selection_state
+                .as_ref()
+                .map(|manager| manager.get_device(&seat, queue_handle));
+        }
+
+        if seat_state.tablet_seat.is_none() {
+            seat_state.tablet_seat = self.tablet_manager.as_ref().map(|manager| {
+                manager.get_tablet_seat(&seat, queue_handle, DeviceId::from_seat(&seat))
+            });
+        }
     }
 
     fn remove_capability(
@@ -163,6 +291,10 @@ impl SeatHandler for WinitState {
                     relative_pointer.destroy();
                 }
 
+                if let Some(pointer_gestures) = seat_state.pointer_gestures.take() {
+                    pointer_gestures.destroy();
+                }
+
                 if let Some(pointer) = seat_state.pointer.take() {
                     let pointer_data = pointer.pointer().winit_data();
 
@@ -185,6 +317,17 @@ impl SeatHandler for WinitState {
             _ => (),
         }
 
+        if matches!(
+            capability,
+            SeatCapability::Touch | SeatCapability::Pointer | SeatCapability::Keyboard
+        ) {
+            self.events_sink.push_device_event(
+                DeviceEvent::Removed,
+                DeviceId::from_seat(&seat),
+                None,
+            );
+        }
+
         if let Some(text_input) = seat_state.text_input.take() {
             text_input.destroy();
         }
@@ -193,10 +336,24 @@ impl SeatHandler for WinitState {
     fn new_seat(
         &mut self,
         _connection: &Connection,
-        _queue_handle: &QueueHandle<Self>,
+        queue_handle: &QueueHandle<Self>,
         seat: WlSeat,
     ) {
-        self.seats.insert(seat.id(), WinitSeatState::new());
+        // The global of the new seat was just added to the registry.
+        let pointer_seat = self
+            .registry_state
+            .globals_by_interface(WlSeat::interface().name)
+            .last()
+            .and_then(|global| {
+                bind_pointer_seat(
+                    self.registry_state.registry(),
+                    queue_handle,
+                    global.name,
+                    global.version,
+                )
+            });
+        self.seats
+            .insert(seat.id(), WinitSeatState::new(pointer_seat));
     }
 
     fn remove_seat(
@@ -205,7 +362,58 @@ impl SeatHandler for WinitState {
         _queue_handle: &QueueHandle<Self>,
         seat: WlSeat,
     ) {
-        let _ = self.seats.remove(&seat.id());
+        if let Some(seat_state) = self.seats.remove(&seat.id()) {
+            // The capabilities aren't always removed before the seat.
+            if seat_state.pointer.is_some()
+                || seat_state.keyboard_state.is_some()
+                || seat_state.touch.is_some()
+            {
+                self.events_sink.push_device_event(
+                    DeviceEvent::Removed,
+                    DeviceId::from_seat(&seat),
+                    None,
+                );
+            }
+            if let Some(device) = seat_state.data_device {
+                device.release();
+            }
+
+            if let Some(device) = seat_state.primary_selection_device {
+                device.destroy();
+            }
+
+            if let Some(tablet_seat) = seat_state.tablet_seat {
+                tablet_seat.destroy();
+            }
+
+            if let Some(pointer_seat) = seat_state.pointer_seat {
+                pointer_seat.release();
+            }
+        }
+    }
+}
+
+/// Bind the seat again at version 8 when the compositor supports it, since `sctk` binds the seats
+/// up to version 7 and the pointers inherit the version of their seat.
+pub fn bind_pointer_seat(
+    registry: &WlRegistry,
+    queue_handle: &QueueHandle<WinitState>,
+    name: u32,
+    version: u32,
+) -> Option<WlSeat> {
+    (version >= 8).then(|| registry.bind(name, 8, queue_handle, GlobalData))
+}
+
+// The capabilities and the name are tracked on the seat bound by `sctk`.
+impl Dispatch<WlSeat, GlobalData> for WinitState {
+    fn event(
+        _state: &mut WinitState,
+        _seat: &WlSeat,
+        _event: wl_seat::Event,
+        _data: &GlobalData,
+        _connection: &Connection,
+        _queue_handle: &QueueHandle<WinitState>,
+    ) {
     }
 }
 
@@ -1,32 +1,45 @@
 //! The pointer events.
 
 use std::ops::Deref;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
 
 use sctk::reexports::client::delegate_dispatch;
-use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_pointer::{self, WlPointer};
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::{self, WlShm};
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
-use sctk::reexports::client::{Connection, Proxy, QueueHandle, Dispatch};
+use sctk::reexports::client::{Connection, Proxy, QueueHandle, Dispatch, WEnum};
 use sctk::reexports::protocols::wp::pointer_constraints::zv1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
 use sctk::reexports::protocols::wp::pointer_constraints::zv1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
 use sctk::reexports::protocols::wp::pointer_constraints::zv1::client::zwp_pointer_constraints_v1::{Lifetime, ZwpPointerConstraintsV1};
 use sctk::reexports::client::globals::{BindError, GlobalList};
 
 use sctk::compositor::SurfaceData;
-use sctk::globals::GlobalData;
+use sctk::error::GlobalError;
+use sctk::globals::{GlobalData, ProvidesBoundGlobal};
 use sctk::seat::pointer::{PointerData, PointerDataExt};
 use sctk::seat::pointer::{PointerEvent, PointerEventKind, PointerHandler};
 use sctk::seat::SeatState;
 use sctk::shell::xdg::frame::FrameClick;
+use sctk::shm::slot::{Buffer, SlotPool};
 
+use crate::cursor::{CursorFrame, CursorImage, CustomCursor};
 use crate::dpi::{LogicalPosition, PhysicalPosition};
-use crate::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+use crate::event::{
+    ElementState, MouseButton, MouseScrollDelta, Pointer, PointerButtons, PointerKind,
+    PointerPhase, ScrollSource, TouchPhase, WindowEvent,
+};
 
+use crate::platform_impl::common::timestamp;
 use crate::platform_impl::wayland::state::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+use crate::platform_impl::wayland::{self, WindowId};
 
+pub mod pointer_gestures;
 pub mod relative_pointer;
+mod themed_pointer;
+
+pub use themed_pointer::ThemedPointer;
 
 impl PointerHandler for WinitState {
     fn pointer_frame(
@@ -39,7 +52,7 @@ impl PointerHandler for WinitState {
         let seat = pointer.winit_data().seat();
         let seat_state = self.seats.get(&seat.id()).unwrap();
 
-        let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+        let device_id = wayland::make_device_id(seat);
 
         for event in events {
             let surface = &event.surface;
@@ -115,23 +128,42 @@ impl PointerHandler for WinitState {
                 }
                 // Regular events on the main surface.
                 PointerEventKind::Enter { .. } => {
-                    self.events_sink
-                        .push_window_event(WindowEvent::CursorEntered { device_id }, window_id);
+                    // The enter event has no time.
+                    self.events_sink.push_window_event(
+                        WindowEvent::CursorEntered {
+                            device_id,
+                            timestamp: None,
+                        },
+                        window_id,
+                    );
 
                     if let Some(pointer) = seat_state.pointer.as_ref().map(Arc::downgrade) {
                         window.pointer_entered(pointer);
                     }
 
-                    // Set the currently focused surface.
-                    pointer.winit_data().inner.lock().unwrap().surface = Some(window_id);
+                    // Set the currently focused surface, the buttons aren't known on enter.
+                    let mut pointer_data = pointer.winit_data().inner.lock().unwrap();
+                    pointer_data.surface = Some(window_id);
+                    pointer_data.buttons = PointerButtons::empty();
 
                     self.events_sink.push_window_event(
                         WindowEvent::CursorMoved {
                             device_id,
                             position,
+                            timestamp: None,
                         },
                         window_id,
                     );
+                    self.events_sink.push_window_event(
+                        mouse_pointer(
+                            device_id,
+                            PointerPhase::Entered,
+                            position,
+                            pointer_data.buttons,
+                            None,
+                        ),
+                        window_id,
+                    );
                 }
                 PointerEventKind::Leave { .. } => {
                     if let Some(pointer) = seat_state.pointer.as_ref().map(Arc::downgrade) {
@@ -139,54 +171,106 @@ impl PointerHandler for WinitState {
                     }
 
                     // Remove the active surface.
-                    pointer.winit_data().inner.lock().unwrap().surface = None;
+                    let mut pointer_data = pointer.winit_data().inner.lock().unwrap();
+                    pointer_data.surface = None;
 
-                    self.events_sink
-                        .push_window_event(WindowEvent::CursorLeft { device_id }, window_id);
+                    // The leave event has no time either.
+                    self.events_sink.push_window_event(
+                        WindowEvent::CursorLeft {
+                            device_id,
+                            timestamp: None,
+                        },
+                        window_id,
+                    );
+                    self.events_sink.push_window_event(
+                        mouse_pointer(
+                            device_id,
+                            PointerPhase::Left,
+                            position,
+                            pointer_data.buttons,
+                            None,
+                        ),
+                        window_id,
+                    );
                 }
-                PointerEventKind::Motion { .. } => {
+                PointerEventKind::Motion { time } => {
+                    let timestamp = Some(timestamp::from_millis(time));
                     self.events_sink.push_window_event(
                         WindowEvent::CursorMoved {
                             device_id,
                             position,
+                            timestamp,
                         },
                         window_id,
                     );
+
+                    let buttons = pointer.winit_data().inner.lock().unwrap().buttons;
+                    self.events_sink.push_window_event(
+                        mouse_pointer(device_id, PointerPhase::Moved, position, buttons, timestamp),
+                        window_id,
+                    );
                 }
-                ref kind @ PointerEventKind::Press { button, serial, .. }
-                | ref kind @ PointerEventKind::Release { button, serial, .. } => {
+                ref kind @ PointerEventKind::Press {
+                    button,
+                    serial,
+                    time,
+                }
+                | ref kind @ PointerEventKind::Release {
+                    button,
+                    serial,
+                    time,
+                } => {
                     // Update the last button serial.
-                    pointer
-                        .winit_data()
-                        .inner
-                        .lock()
-                        .unwrap()
-                        .latest_button_serial = serial;
+                    let mut pointer_data = pointer.winit_data().inner.lock().unwrap();
+                    pointer_data.latest_button_serial = serial;
+
+                    // The button serial could be used to set the selection.
+                    self.latest_input_serial = Some((seat.id(), serial));
 
                     let button = wayland_button_to_winit(button);
-                    let state = if matches!(kind, PointerEventKind::Press { .. }) {
-                        ElementState::Pressed
+                    let timestamp = Some(timestamp::from_millis(time));
+                    let (state, phase) = if matches!(kind, PointerEventKind::Press { .. }) {
+                        pointer_data.buttons.insert(pointer_button(button));
+                        (ElementState::Pressed, PointerPhase::Down)
                     } else {
-                        ElementState::Released
+                        pointer_data.buttons.remove(pointer_button(button));
+                        (ElementState::Released, PointerPhase::Up)
                     };
                     self.events_sink.push_window_event(
                         WindowEvent::MouseInput {
                             device_id,
                             state,
                             button,
+                            timestamp,
                         },
                         window_id,
                     );
+                    self.events_sink.push_window_event(
+                        mouse_pointer(device_id, phase, position, pointer_data.buttons, timestamp),
+                        window_id,
+                    );
                 }
                 PointerEventKind::Axis {
+                    time,
                     horizontal,
                     vertical,
-                    ..
+                    source,
                 } => {
                     // Get the current phase.
                     let mut pointer_data = pointer.winit_data().inner.lock().unwrap();
 
-                    let has_discrete_scroll = horizontal.discrete != 0 || vertical.discrete != 0;
+                    // The wheel steps come in fractions of `120` from version 8 of `wl_pointer`,
+                    // the older versions only send the whole steps.
+                    let value120 = if pointer.version() >= 8 {
+                        pointer_data.value120
+                    } else if horizontal.discrete != 0 || vertical.discrete != 0 {
+                        Some((horizontal.discrete * 120, vertical.discrete * 120))
+                    } else {
+                        None
+                    };
+                    // XXX Wayland sign convention is the inverse of winit.
+                    let value120 = value120.map(|(x, y)| (-x, -y));
+                    let has_discrete_scroll = value120.is_some();
 
                     // Figure out what to do about start/ended phases here.
                     //
@@ -208,12 +292,8 @@ impl PointerHandler for WinitState {
 
                     // Mice events have both pixel and discrete delta's at the same time. So prefer
                     // the descrite values if they are present.
-                    let delta = if has_discrete_scroll {
-                        // XXX Wayland sign convention is the inverse of winit.
-                        MouseScrollDelta::LineDelta(
-                            (-horizontal.discrete) as f32,
-                            (-vertical.discrete) as f32,
-                        )
+                    let delta = if let Some((x, y)) = value120 {
+                        MouseScrollDelta::LineDelta(x as f32 / 120., y as f32 / 120.)
                     } else {
                         // XXX Wayland sign convention is the inverse of winit.
                         MouseScrollDelta::PixelDelta(
@@ -222,11 +302,21 @@ impl PointerHandler for WinitState {
                         )
                     };
 
+                    let source = source.map(|source| match source {
+                        wl_pointer::AxisSource::Finger => ScrollSource::Finger,
+                        wl_pointer::AxisSource::Continuous => ScrollSource::Continuous,
+                        wl_pointer::AxisSource::WheelTilt => ScrollSource::WheelTilt,
+                        _ => ScrollSource::Wheel,
+                    });
+
                     self.events_sink.push_window_event(
                         WindowEvent::MouseWheel {
                             device_id,
                             delta,
                             phase,
+                            source,
+                            value120,
+                            timestamp: Some(timestamp::from_millis(time)),
                         },
                         window_id,
                     )
@@ -302,8 +392,8 @@ impl WinitPointerData {
     }
 
     pub fn unconfine_pointer(&self) {
-        let inner = self.inner.lock().unwrap();
-        if let Some(confined_pointer) = inner.confined_pointer.as_ref() {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(confined_pointer) = inner.confined_pointer.take() {
             confined_pointer.destroy();
         }
     }
@@ -328,6 +418,11 @@ impl WinitPointerData {
         self.inner.lock().unwrap().latest_button_serial
     }
 
+    /// Whether any button is held on the pointer.
+    pub fn has_pressed_buttons(&self) -> bool {
+        !self.inner.lock().unwrap().buttons.is_empty()
+    }
+
     /// Last enter serial.
     pub fn latest_enter_serial(&self) -> u32 {
         self.sctk_data.latest_enter_serial().unwrap_or_default()
@@ -347,6 +442,81 @@ impl Drop for WinitPointerData {
     }
 }
 
+/// The buffers of the custom cursors, shared by all the windows of the event loop.
+#[derive(Debug)]
+pub struct CustomCursorCache {
+    shm: WlShm,
+    pool: Option<SlotPool>,
+    buffers: Vec<(Weak<CursorImage>, Vec<Buffer>)>,
+}
+
+impl CustomCursorCache {
+    pub fn new(shm: WlShm) -> Self {
+        Self {
+            shm,
+            pool: None,
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Get the buffer holding the premultiplied pixels of the cursor's `frame`, creating the
+    /// buffers of all the frames on first use.
+    pub fn buffer(&mut self, cursor: &CustomCursor, frame: usize) -> Option<&Buffer> {
+        // Drop the buffers of the cursors which can't be set anymore.
+        self.buffers.retain(|(image, _)| image.strong_count() > 0);
+
+        let index = match self
+            .buffers
+            .iter()
+            .position(|(image, _)| image.as_ptr() == Arc::as_ptr(&cursor.inner))
+        {
+            Some(index) => index,
+            None => {
+                let frames = &cursor.inner.frames;
+                if self.pool.is_none() {
+                    let len = frames.iter().map(|frame| frame.image.rgba.len()).sum();
+                    self.pool = Some(SlotPool::new(len, self).ok()?);
+                }
+
+                let pool = self.pool.as_mut().unwrap();
+                let mut buffers = Vec::with_capacity(frames.len());
+                for CursorFrame { image, .. } in frames {
+                    let (width, height) = (image.width as i32, image.height as i32);
+                    let (buffer, canvas) = pool
+                        .create_buffer(width, height, width * 4, wl_shm::Format::Argb8888)
+                        .ok()?;
+
+                    for (dst, src) in canvas.chunks_exact_mut(4).zip(image.rgba.chunks_exact(4)) {
+                        let alpha = src[3] as u32;
+                        let premultiply = |channel: u8| (channel as u32 * alpha / 255) as u8;
+                        let pixel = u32::from_be_bytes([
+                            src[3],
+                            premultiply(src[0]),
+                            premultiply(src[1]),
+                            premultiply(src[2]),
+                        ]);
+                        dst.copy_from_slice(&pixel.to_le_bytes());
+                    }
+
+                    buffers.push(buffer);
+                }
+
+                self.buffers.push((Arc::downgrade(&cursor.inner), buffers));
+                self.buffers.len() - 1
+            }
+        };
+
+        self.buffers[index].1.get(frame)
+    }
+}
+
+// The pool is created from the shm of the cache once the first cursor is set.
+impl ProvidesBoundGlobal<WlShm, 1> for CustomCursorCache {
+    fn bound_global(&self) -> Result<WlShm, GlobalError> {
+        Ok(self.shm.clone())
+    }
+}
+
 impl PointerDataExt for WinitPointerData {
     fn pointer_data(&self) -> &PointerData {
         &self.sctk_data
@@ -369,6 +539,12 @@ pub struct WinitPointerDataInner {
 
     /// Current axis phase.
     phase: TouchPhase,
+
+    /// The buttons held since the pointer entered the surface.
+    buttons: PointerButtons,
+
+    /// The high-resolution wheel steps of the current frame.
+    value120: Option<(i32, i32)>,
 }
 
 impl Drop for WinitPointerDataInner {
@@ -391,10 +567,46 @@ impl Default for WinitPointerDataInner {
             confined_pointer: None,
             latest_button_serial: 0,
             phase: TouchPhase::Ended,
+            buttons: PointerButtons::empty(),
+            value120: None,
         }
     }
 }
 
+/// Build the pointer event of the mouse.
+fn mouse_pointer(
+    device_id: crate::event::DeviceId,
+    phase: PointerPhase,
+    position: PhysicalPosition<f64>,
+    buttons: PointerButtons,
+    timestamp: Option<Instant>,
+) -> WindowEvent<'static> {
+    WindowEvent::Pointer(Pointer {
+        device_id,
+        pointer_id: 0,
+        kind: PointerKind::Mouse,
+        phase,
+        position,
+        buttons,
+        pressure: None,
+        tilt: None,
+        size: None,
+        timestamp,
+    })
+}
+
+/// Convert the winit button into the pointer buttons.
+fn pointer_button(button: MouseButton) -> PointerButtons {
+    match button {
+        MouseButton::Left => PointerButtons::PRIMARY,
+        MouseButton::Right => PointerButtons::SECONDARY,
+        MouseButton::Middle => PointerButtons::AUXILIARY,
+        MouseButton::Back => PointerButtons::BACK,
+        MouseButton::Forward => PointerButtons::FORWARD,
+        MouseButton::Other(_) => PointerButtons::empty(),
+    }
+}
+
 /// Convert the Wayland button into winit.
 fn wayland_button_to_winit(button: u32) -> MouseButton {
     // These values are coming from <linux/input-event-codes.h>.
@@ -427,6 +639,46 @@ impl WinitPointerDataExt for WlPointer {
     }
 }
 
+impl Dispatch<WlPointer, WinitPointerData> for WinitState {
+    fn event(
+        state: &mut WinitState,
+        pointer: &WlPointer,
+        event: wl_pointer::Event,
+        data: &WinitPointerData,
+        connection: &Connection,
+        queue_handle: &QueueHandle<WinitState>,
+    ) {
+        match event {
+            // `sctk` doesn't handle the high-resolution wheel steps, so keep them for the axis
+            // events of the frame.
+            wl_pointer::Event::AxisValue120 { axis, value120 } => {
+                let mut inner = data.inner.lock().unwrap();
+                let steps = inner.value120.get_or_insert((0, 0));
+                match axis {
+                    WEnum::Value(wl_pointer::Axis::HorizontalScroll) => steps.0 += value120,
+                    WEnum::Value(wl_pointer::Axis::VerticalScroll) => steps.1 += value120,
+                    _ => (),
+                }
+            }
+            event => {
+                let is_frame = matches!(event, wl_pointer::Event::Frame);
+                <SeatState as Dispatch<WlPointer, WinitPointerData, WinitState>>::event(
+                    state,
+                    pointer,
+                    event,
+                    data,
+                    connection,
+                    queue_handle,
+                );
+
+                if is_frame {
+                    data.inner.lock().unwrap().value120 = None;
+                }
+            }
+        }
+    }
+}
+
 pub struct PointerConstraintsState {
     pointer_constraints: ZwpPointerConstraintsV1,
 }
@@ -486,7 +738,6 @@ impl Dispatch<ZwpConfinedPointerV1, GlobalData, WinitState> for PointerConstrain
     }
 }
 
-delegate_dispatch!(WinitState: [ WlPointer: WinitPointerData] => SeatState);
 delegate_dispatch!(WinitState: [ZwpPointerConstraintsV1: GlobalData] => PointerConstraintsState);
 delegate_dispatch!(WinitState: [ZwpLockedPointerV1: GlobalData] => PointerConstraintsState);
 delegate_dispatch!(WinitState: [ZwpConfinedPointerV1: GlobalData] => PointerConstraintsState);
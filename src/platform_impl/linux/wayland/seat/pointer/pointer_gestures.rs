@@ -0,0 +1,295 @@
+//! Touchpad gestures.
+
+use std::sync::Mutex;
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::{delegate_dispatch, Dispatch, Proxy};
+use sctk::reexports::client::{Connection, QueueHandle};
+use sctk::reexports::protocols::wp::pointer_gestures::zv1::client::{
+    zwp_pointer_gesture_hold_v1::{self, ZwpPointerGestureHoldV1},
+    zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+    zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+    zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::dpi::LogicalPosition;
+use crate::event::{TouchPhase, WindowEvent};
+use crate::platform_impl::common::timestamp;
+use crate::platform_impl::wayland::seat::WinitPointerDataExt;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
+
+/// Wrapper around the pointer gestures manager.
+pub struct PointerGesturesState {
+    manager: ZwpPointerGesturesV1,
+}
+
+impl PointerGesturesState {
+    /// Create new pointer gestures manager.
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=3, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the gestures of the pointer.
+    pub fn get_gestures(
+        &self,
+        pointer: &WlPointer,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> PointerGestures {
+        let device_id = wayland::make_device_id(pointer.winit_data().seat());
+        let swipe =
+            self.manager
+                .get_swipe_gesture(pointer, queue_handle, GestureData::new(device_id));
+        let pinch =
+            self.manager
+                .get_pinch_gesture(pointer, queue_handle, GestureData::new(device_id));
+        let hold = (self.manager.version() >= 3).then(|| {
+            self.manager
+                .get_hold_gesture(pointer, queue_handle, GestureData::new(device_id))
+        });
+
+        PointerGestures { swipe, pinch, hold }
+    }
+}
+
+/// The gestures bound on the pointer.
+#[derive(Debug)]
+pub struct PointerGestures {
+    swipe: ZwpPointerGestureSwipeV1,
+    pinch: ZwpPointerGesturePinchV1,
+    hold: Option<ZwpPointerGestureHoldV1>,
+}
+
+impl PointerGestures {
+    pub fn destroy(self) {
+        self.swipe.destroy();
+        self.pinch.destroy();
+        if let Some(hold) = self.hold {
+            hold.destroy();
+        }
+    }
+}
+
+/// The state of the gesture, which is needed for its updates.
+#[derive(Debug)]
+pub struct GestureData {
+    /// The device id of the seat of the pointer.
+    device_id: crate::event::DeviceId,
+
+    inner: Mutex<GestureDataInner>,
+}
+
+impl GestureData {
+    fn new(device_id: crate::event::DeviceId) -> Self {
+        Self {
+            device_id,
+            inner: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct GestureDataInner {
+    /// The window the gesture has begun on.
+    window_id: Option<WindowId>,
+
+    /// The number of fingers of the gesture.
+    fingers: u32,
+
+    /// The latest scale of the pinch, relative to its beginning.
+    scale: f64,
+}
+
+impl Dispatch<ZwpPointerGesturesV1, GlobalData, WinitState> for PointerGesturesState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPointerGesturesV1,
+        _event: <ZwpPointerGesturesV1 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpPointerGestureSwipeV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureSwipeV1,
+        event: <ZwpPointerGestureSwipeV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+        let (window_id, delta, phase, time) = match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin {
+                time,
+                surface,
+                fingers,
+                ..
+            } => {
+                let window_id = wayland::make_wid(&surface);
+                inner.window_id = Some(window_id);
+                inner.fingers = fingers;
+                (window_id, (0., 0.), TouchPhase::Started, time)
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update { time, dx, dy } => match inner.window_id {
+                Some(window_id) => (window_id, (dx, dy), TouchPhase::Moved, time),
+                None => return,
+            },
+            zwp_pointer_gesture_swipe_v1::Event::End {
+                time, cancelled, ..
+            } => match inner.window_id.take() {
+                Some(window_id) => (window_id, (0., 0.), end_phase(cancelled), time),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let scale_factor = match state.windows.get_mut().get(&window_id) {
+            Some(window) => window.lock().unwrap().scale_factor(),
+            None => return,
+        };
+
+        state.events_sink.push_window_event(
+            WindowEvent::TouchpadSwipe {
+                device_id: data.device_id,
+                delta: LogicalPosition::new(delta.0, delta.1).to_physical(scale_factor),
+                fingers: inner.fingers,
+                phase,
+                timestamp: Some(timestamp::from_millis(time)),
+            },
+            window_id,
+        );
+    }
+}
+
+impl Dispatch<ZwpPointerGesturePinchV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGesturePinchV1,
+        event: <ZwpPointerGesturePinchV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+        let (window_id, magnify, rotate, phase, time) = match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin { time, surface, .. } => {
+                let window_id = wayland::make_wid(&surface);
+                inner.window_id = Some(window_id);
+                inner.scale = 1.;
+                (window_id, 0., 0., TouchPhase::Started, time)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update {
+                time,
+                scale,
+                rotation,
+                ..
+            } => {
+                let window_id = match inner.window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                // The scale is relative to the beginning of the pinch, but the rotation is
+                // clockwise relative to the previous event.
+                let magnify = scale - inner.scale;
+                inner.scale = scale;
+                (window_id, magnify, -rotation, TouchPhase::Moved, time)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::End {
+                time, cancelled, ..
+            } => match inner.window_id.take() {
+                Some(window_id) => (window_id, 0., 0., end_phase(cancelled), time),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let device_id = data.device_id;
+        let timestamp = Some(timestamp::from_millis(time));
+        state.events_sink.push_window_event(
+            WindowEvent::TouchpadMagnify {
+                device_id,
+                delta: magnify,
+                phase,
+                timestamp,
+            },
+            window_id,
+        );
+        state.events_sink.push_window_event(
+            WindowEvent::TouchpadRotate {
+                device_id,
+                delta: rotate as f32,
+                phase,
+                timestamp,
+            },
+            window_id,
+        );
+    }
+}
+
+impl Dispatch<ZwpPointerGestureHoldV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureHoldV1,
+        event: <ZwpPointerGestureHoldV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+        let (window_id, phase, time) = match event {
+            zwp_pointer_gesture_hold_v1::Event::Begin {
+                time,
+                surface,
+                fingers,
+                ..
+            } => {
+                let window_id = wayland::make_wid(&surface);
+                inner.window_id = Some(window_id);
+                inner.fingers = fingers;
+                (window_id, TouchPhase::Started, time)
+            }
+            zwp_pointer_gesture_hold_v1::Event::End {
+                time, cancelled, ..
+            } => match inner.window_id.take() {
+                Some(window_id) => (window_id, end_phase(cancelled), time),
+                None => return,
+            },
+            _ => return,
+        };
+
+        state.events_sink.push_window_event(
+            WindowEvent::TouchpadHold {
+                device_id: data.device_id,
+                fingers: inner.fingers,
+                phase,
+                timestamp: Some(timestamp::from_millis(time)),
+            },
+            window_id,
+        );
+    }
+}
+
+fn end_phase(cancelled: i32) -> TouchPhase {
+    if cancelled != 0 {
+        TouchPhase::Cancelled
+    } else {
+        TouchPhase::Ended
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpPointerGesturesV1: GlobalData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureSwipeV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGesturePinchV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureHoldV1: GestureData] => PointerGesturesState);
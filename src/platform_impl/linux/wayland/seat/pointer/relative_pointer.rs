@@ -13,7 +13,9 @@ use sctk::reexports::protocols::wp::relative_pointer::zv1::{
 use sctk::globals::GlobalData;
 
 use crate::event::DeviceEvent;
+use crate::platform_impl::common::timestamp;
 use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::DeviceId;
 
 /// Wrapper around the relative pointer.
 pub struct RelativePointerState {
@@ -51,30 +53,34 @@ impl Dispatch<ZwpRelativePointerManagerV1, GlobalData, WinitState> for RelativeP
     }
 }
 
-impl Dispatch<ZwpRelativePointerV1, GlobalData, WinitState> for RelativePointerState {
+impl Dispatch<ZwpRelativePointerV1, DeviceId, WinitState> for RelativePointerState {
     fn event(
         state: &mut WinitState,
         _proxy: &ZwpRelativePointerV1,
         event: <ZwpRelativePointerV1 as wayland_client::Proxy>::Event,
-        _data: &GlobalData,
+        data: &DeviceId,
         _conn: &Connection,
         _qhandle: &QueueHandle<WinitState>,
     ) {
         if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            utime_hi,
+            utime_lo,
             dx_unaccel,
             dy_unaccel,
             ..
         } = event
         {
+            let time = (utime_hi as u64) << 32 | utime_lo as u64;
             state.events_sink.push_device_event(
                 DeviceEvent::MouseMotion {
                     delta: (dx_unaccel, dy_unaccel),
                 },
-                super::DeviceId,
+                *data,
+                Some(timestamp::from_micros(time)),
             );
         }
     }
 }
 
-delegate_dispatch!(WinitState: [ZwpRelativePointerV1: GlobalData] => RelativePointerState);
+delegate_dispatch!(WinitState: [ZwpRelativePointerV1: DeviceId] => RelativePointerState);
 delegate_dispatch!(WinitState: [ZwpRelativePointerManagerV1: GlobalData] => RelativePointerState);
@@ -0,0 +1,130 @@
+//! Themed pointer.
+//!
+//! The pointer of `sctk` can only be created from the seats it binds, which stop at version 7,
+//! while `axis_value120` needs version 8 of `wl_pointer`.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::env;
+use std::sync::Mutex;
+
+use sctk::reexports::client::backend::InvalidId;
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Connection, Proxy};
+use sctk::seat::pointer::{PointerDataExt, PointerThemeError};
+use wayland_cursor::{Cursor, CursorTheme};
+
+use super::WinitPointerData;
+
+/// The pointer with the cursor theme of the system.
+#[derive(Debug)]
+pub struct ThemedPointer {
+    pointer: WlPointer,
+    themes: Mutex<Themes>,
+}
+
+impl ThemedPointer {
+    pub fn new(pointer: WlPointer) -> Self {
+        Self {
+            pointer,
+            themes: Mutex::new(Themes::new()),
+        }
+    }
+
+    /// Set the named cursor of the theme on the pointer.
+    pub fn set_cursor(
+        &self,
+        connection: &Connection,
+        name: &str,
+        shm: &WlShm,
+        surface: &WlSurface,
+        scale: i32,
+    ) -> Result<(), PointerThemeError> {
+        let mut themes = self.themes.lock().unwrap();
+
+        let cursor = themes
+            .get_cursor(connection, name, scale as u32, shm)
+            .map_err(PointerThemeError::InvalidId)?
+            .ok_or(PointerThemeError::CursorNotFound)?;
+
+        let image = &cursor[0];
+        let (width, height) = image.dimensions();
+        let (hotspot_x, hotspot_y) = image.hotspot();
+
+        surface.set_buffer_scale(scale);
+        surface.attach(Some(image), 0, 0);
+
+        if surface.version() >= 4 {
+            surface.damage_buffer(0, 0, width as i32, height as i32);
+        } else {
+            surface.damage(0, 0, width as i32 / scale, height as i32 / scale);
+        }
+
+        surface.commit();
+
+        let serial = self
+            .pointer
+            .data::<WinitPointerData>()
+            .and_then(|data| data.pointer_data().latest_enter_serial())
+            .ok_or(PointerThemeError::MissingEnterSerial)?;
+
+        self.pointer.set_cursor(
+            serial,
+            Some(surface),
+            hotspot_x as i32 / scale,
+            hotspot_y as i32 / scale,
+        );
+
+        Ok(())
+    }
+
+    pub fn pointer(&self) -> &WlPointer {
+        &self.pointer
+    }
+}
+
+/// The cursor themes loaded for each scale.
+#[derive(Debug)]
+struct Themes {
+    name: String,
+    size: u32,
+    themes: HashMap<u32, CursorTheme>,
+}
+
+impl Themes {
+    /// Use the theme from `XCURSOR_THEME` and `XCURSOR_SIZE`.
+    fn new() -> Self {
+        let name = env::var("XCURSOR_THEME").unwrap_or_else(|_| String::from("default"));
+        let size = env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(24);
+
+        Self {
+            name,
+            size,
+            themes: HashMap::new(),
+        }
+    }
+
+    fn get_cursor(
+        &mut self,
+        connection: &Connection,
+        name: &str,
+        scale: u32,
+        shm: &WlShm,
+    ) -> Result<Option<&Cursor>, InvalidId> {
+        let theme = match self.themes.entry(scale) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(CursorTheme::load_from_name(
+                connection,
+                shm.clone(),
+                &self.name,
+                self.size * scale,
+            )?),
+        };
+
+        Ok(theme.get_cursor(name))
+    }
+}
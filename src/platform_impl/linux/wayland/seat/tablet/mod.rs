@@ -0,0 +1,568 @@
+//! The tablet events.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::{delegate_dispatch, event_created_child, WEnum};
+use sctk::reexports::client::{Connection, Dispatch, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_group_v2::{
+    self, ZwpTabletPadGroupV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_v2::{
+    self, ZwpTabletPadV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_seat_v2::{
+    self, ZwpTabletSeatV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::{
+    ButtonState, Capability, Event as ToolEvent, Type as ToolType, ZwpTabletToolV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_v2::{
+    Event as TabletEvent, ZwpTabletV2,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::device::DeviceCapabilities;
+use crate::dpi::LogicalPosition;
+use crate::event::{
+    DeviceEvent, ElementState, MouseButton, PenAxes, PenButton, PenEvent, PenTool, Pointer,
+    PointerButtons, PointerKind, PointerPhase, WindowEvent,
+};
+
+use crate::platform_impl::common::timestamp;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+
+// The buttons on the barrel of the pen, from `linux/input-event-codes.h`.
+const BTN_STYLUS: u32 = 0x14b;
+const BTN_STYLUS2: u32 = 0x14c;
+
+/// The maximum value of the pressure and distance axes.
+const AXIS_MAX: f64 = 65535.;
+
+pub struct TabletManagerState {
+    tablet_manager: ZwpTabletManagerV2,
+}
+
+impl TabletManagerState {
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let tablet_manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self { tablet_manager })
+    }
+}
+
+impl Deref for TabletManagerState {
+    type Target = ZwpTabletManagerV2;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tablet_manager
+    }
+}
+
+/// The description of a tablet.
+#[derive(Debug)]
+pub struct TabletInfo {
+    /// The device id of the seat of the tablet.
+    pub device_id: DeviceId,
+
+    /// The name of the tablet.
+    pub name: String,
+
+    /// The USB vendor and product ids of the tablet.
+    pub ids: Option<(u16, u16)>,
+
+    /// The kinds of input of the tablet, gathered from the capabilities of the tools.
+    pub capabilities: DeviceCapabilities,
+}
+
+/// The state of the tool, which is accumulated until the frame.
+#[derive(Debug, Default)]
+pub struct TabletToolData {
+    inner: Mutex<TabletToolDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct TabletToolDataInner {
+    /// The device id of the seat of the tool.
+    device_id: Option<DeviceId>,
+
+    /// The kind of the tool.
+    tool: Option<PenTool>,
+
+    /// The axes the tool has, with their latest values.
+    axes: PenAxes,
+
+    /// The window the tool is over.
+    window_id: Option<WindowId>,
+
+    /// The latest position of the tool on the window.
+    position: LogicalPosition<f64>,
+
+    /// Whether the tool came into proximity during the frame.
+    entered: bool,
+
+    /// Whether the position or the axes have changed during the frame.
+    moved: bool,
+
+    /// The change of the tip during the frame.
+    tip: Option<ElementState>,
+
+    /// The buttons changed during the frame.
+    buttons: Vec<(u32, ElementState)>,
+
+    /// Whether the tool went out of proximity during the frame.
+    left: bool,
+
+    /// The buttons held since the tool came into proximity.
+    pointer_buttons: PointerButtons,
+}
+
+impl Dispatch<ZwpTabletManagerV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletManagerV2,
+        _event: <ZwpTabletManagerV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletSeatV2, DeviceId, WinitState> for TabletManagerState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpTabletSeatV2,
+        event: <ZwpTabletSeatV2 as Proxy>::Event,
+        data: &DeviceId,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        // Remember the seat of the tablets and tools, which their own events don't tell.
+        match event {
+            zwp_tablet_seat_v2::Event::TabletAdded { id } => {
+                let tablet = TabletInfo {
+                    device_id: *data,
+                    name: String::new(),
+                    ids: None,
+                    capabilities: DeviceCapabilities::BUTTONS | DeviceCapabilities::MOTION,
+                };
+                state.tablets.insert(id.id(), tablet);
+            }
+            zwp_tablet_seat_v2::Event::ToolAdded { id } => {
+                if let Some(tool_data) = id.data::<TabletToolData>() {
+                    tool_data.inner.lock().unwrap().device_id = Some(*data);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    event_created_child!(WinitState, ZwpTabletSeatV2, [
+        zwp_tablet_seat_v2::EVT_TABLET_ADDED_OPCODE => (ZwpTabletV2, GlobalData),
+        zwp_tablet_seat_v2::EVT_TOOL_ADDED_OPCODE => (ZwpTabletToolV2, TabletToolData::default()),
+        zwp_tablet_seat_v2::EVT_PAD_ADDED_OPCODE => (ZwpTabletPadV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        state: &mut WinitState,
+        tablet: &ZwpTabletV2,
+        event: <ZwpTabletV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        match event {
+            TabletEvent::Name { name } => {
+                if let Some(info) = state.tablets.get_mut(&tablet.id()) {
+                    info.name = name;
+                }
+            }
+            TabletEvent::Id { vid, pid } => {
+                if let Some(info) = state.tablets.get_mut(&tablet.id()) {
+                    info.ids = Some((vid as u16, pid as u16));
+                }
+            }
+            TabletEvent::Done => {
+                if let Some(info) = state.tablets.get(&tablet.id()) {
+                    state
+                        .events_sink
+                        .push_device_event(DeviceEvent::Added, info.device_id, None);
+                }
+            }
+            TabletEvent::Removed => {
+                if let Some(info) = state.tablets.remove(&tablet.id()) {
+                    state
+                        .events_sink
+                        .push_device_event(DeviceEvent::Removed, info.device_id, None);
+                }
+                tablet.destroy();
+            }
+            _ => (),
+        }
+    }
+}
+
+// The pads aren't used, but their objects still need to be handled.
+
+impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        pad: &ZwpTabletPadV2,
+        event: <ZwpTabletPadV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        if let zwp_tablet_pad_v2::Event::Removed = event {
+            pad.destroy();
+        }
+    }
+
+    event_created_child!(WinitState, ZwpTabletPadV2, [
+        zwp_tablet_pad_v2::EVT_GROUP_OPCODE => (ZwpTabletPadGroupV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletPadGroupV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadGroupV2,
+        _event: <ZwpTabletPadGroupV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+
+    event_created_child!(WinitState, ZwpTabletPadGroupV2, [
+        zwp_tablet_pad_group_v2::EVT_RING_OPCODE => (ZwpTabletPadRingV2, GlobalData),
+        zwp_tablet_pad_group_v2::EVT_STRIP_OPCODE => (ZwpTabletPadStripV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletPadRingV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadRingV2,
+        _event: <ZwpTabletPadRingV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletPadStripV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadStripV2,
+        _event: <ZwpTabletPadStripV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletToolV2, TabletToolData, WinitState> for TabletManagerState {
+    fn event(
+        state: &mut WinitState,
+        tool: &ZwpTabletToolV2,
+        event: <ZwpTabletToolV2 as Proxy>::Event,
+        data: &TabletToolData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+        match event {
+            ToolEvent::Type {
+                tool_type: WEnum::Value(tool_type),
+            } => {
+                inner.tool = Some(match tool_type {
+                    ToolType::Eraser => PenTool::Eraser,
+                    ToolType::Brush => PenTool::Brush,
+                    ToolType::Pencil => PenTool::Pencil,
+                    ToolType::Airbrush => PenTool::Airbrush,
+                    ToolType::Finger => PenTool::Finger,
+                    ToolType::Mouse => PenTool::Mouse,
+                    ToolType::Lens => PenTool::Lens,
+                    _ => PenTool::Pen,
+                });
+            }
+            ToolEvent::Capability {
+                capability: WEnum::Value(capability),
+            } => {
+                let capabilities = match capability {
+                    Capability::Pressure => {
+                        inner.axes.pressure = Some(0.);
+                        DeviceCapabilities::PRESSURE
+                    }
+                    Capability::Tilt => {
+                        inner.axes.tilt = Some((0., 0.));
+                        DeviceCapabilities::TILT
+                    }
+                    Capability::Rotation => {
+                        inner.axes.rotation = Some(0.);
+                        DeviceCapabilities::empty()
+                    }
+                    Capability::Distance => {
+                        inner.axes.distance = Some(0.);
+                        DeviceCapabilities::empty()
+                    }
+                    _ => DeviceCapabilities::empty(),
+                };
+
+                // The tools can be used on any tablet of their seat.
+                for info in state.tablets.values_mut() {
+                    if Some(info.device_id) == inner.device_id {
+                        info.capabilities |= capabilities;
+                    }
+                }
+            }
+            ToolEvent::Removed => tool.destroy(),
+            ToolEvent::ProximityIn { surface, .. } => {
+                inner.window_id = Some(wayland::make_wid(&surface));
+                inner.entered = true;
+            }
+            ToolEvent::ProximityOut => inner.left = true,
+            ToolEvent::Down { .. } => inner.tip = Some(ElementState::Pressed),
+            ToolEvent::Up => inner.tip = Some(ElementState::Released),
+            ToolEvent::Motion { x, y } => {
+                inner.position = LogicalPosition::new(x, y);
+                inner.moved = true;
+            }
+            ToolEvent::Pressure { pressure } => {
+                inner.axes.pressure = Some(pressure as f64 / AXIS_MAX);
+                inner.moved = true;
+            }
+            ToolEvent::Distance { distance } => {
+                inner.axes.distance = Some(distance as f64 / AXIS_MAX);
+                inner.moved = true;
+            }
+            ToolEvent::Tilt { tilt_x, tilt_y } => {
+                inner.axes.tilt = Some((tilt_x, tilt_y));
+                inner.moved = true;
+            }
+            ToolEvent::Rotation { degrees } => {
+                inner.axes.rotation = Some(degrees.rem_euclid(360.));
+                inner.moved = true;
+            }
+            ToolEvent::Button {
+                button,
+                state: WEnum::Value(button_state),
+                ..
+            } => {
+                let button_state = match button_state {
+                    ButtonState::Pressed => ElementState::Pressed,
+                    _ => ElementState::Released,
+                };
+                inner.buttons.push((button, button_state));
+            }
+            ToolEvent::Frame { time } => {
+                let window_id = match inner.window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                if inner.left {
+                    inner.window_id = None;
+                }
+
+                let scale_factor = match state.windows.get_mut().get(&window_id) {
+                    Some(window) => window.lock().unwrap().scale_factor(),
+                    None => {
+                        inner.reset();
+                        return;
+                    }
+                };
+
+                inner.push_events(state, window_id, scale_factor, timestamp::from_millis(time));
+                inner.reset();
+            }
+            _ => (),
+        }
+    }
+}
+
+impl TabletToolDataInner {
+    /// Push the events accumulated during the frame, emulating the pointer along the way.
+    fn push_events(
+        &mut self,
+        state: &mut WinitState,
+        window_id: WindowId,
+        scale_factor: f64,
+        timestamp: Instant,
+    ) {
+        let device_id = match self.device_id {
+            Some(device_id) => {
+                crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(device_id))
+            }
+            None => return,
+        };
+        let mut push_event = |event| state.events_sink.push_window_event(event, window_id);
+        let position = self.position.to_physical(scale_factor);
+        let tool = self.tool.unwrap_or(PenTool::Pen);
+        let axes = self.axes;
+        let pointer = |phase, buttons| {
+            WindowEvent::Pointer(Pointer {
+                device_id,
+                pointer_id: 0,
+                kind: PointerKind::Pen,
+                phase,
+                position,
+                buttons,
+                pressure: axes.pressure,
+                tilt: axes.tilt,
+                size: None,
+                timestamp: Some(timestamp),
+            })
+        };
+
+        if self.entered {
+            push_event(WindowEvent::CursorEntered {
+                device_id,
+                timestamp: Some(timestamp),
+            });
+            push_event(WindowEvent::PenInput {
+                device_id,
+                event: PenEvent::Entered { tool },
+            });
+            push_event(pointer(PointerPhase::Entered, self.pointer_buttons));
+        }
+
+        if self.moved || self.entered {
+            push_event(WindowEvent::CursorMoved {
+                device_id,
+                position,
+                timestamp: Some(timestamp),
+            });
+            push_event(WindowEvent::PenInput {
+                device_id,
+                event: PenEvent::Moved {
+                    position,
+                    axes: self.axes,
+                },
+            });
+        }
+
+        if self.moved && !self.entered {
+            push_event(pointer(PointerPhase::Moved, self.pointer_buttons));
+        }
+
+        if let Some(state) = self.tip {
+            push_event(WindowEvent::MouseInput {
+                device_id,
+                state,
+                button: MouseButton::Left,
+                timestamp: Some(timestamp),
+            });
+            push_event(WindowEvent::PenInput {
+                device_id,
+                event: PenEvent::Tip { state },
+            });
+
+            let tip = if tool == PenTool::Eraser {
+                PointerButtons::ERASER
+            } else {
+                PointerButtons::PRIMARY
+            };
+            let phase = pointer_phase(&mut self.pointer_buttons, tip, state);
+            push_event(pointer(phase, self.pointer_buttons));
+        }
+
+        for &(button, state) in &self.buttons {
+            // The barrel buttons are usually bound to the middle and right mouse buttons.
+            let (button, mouse_button, pointer_button) = match button {
+                BTN_STYLUS => (
+                    PenButton::Primary,
+                    MouseButton::Middle,
+                    PointerButtons::AUXILIARY,
+                ),
+                BTN_STYLUS2 => (
+                    PenButton::Secondary,
+                    MouseButton::Right,
+                    PointerButtons::SECONDARY,
+                ),
+                button => (
+                    PenButton::Other(button as u16),
+                    MouseButton::Other(button as u16),
+                    PointerButtons::empty(),
+                ),
+            };
+            push_event(WindowEvent::MouseInput {
+                device_id,
+                state,
+                button: mouse_button,
+                timestamp: Some(timestamp),
+            });
+            push_event(WindowEvent::PenInput {
+                device_id,
+                event: PenEvent::Button { button, state },
+            });
+
+            let phase = pointer_phase(&mut self.pointer_buttons, pointer_button, state);
+            push_event(pointer(phase, self.pointer_buttons));
+        }
+
+        if self.left {
+            push_event(WindowEvent::PenInput {
+                device_id,
+                event: PenEvent::Left,
+            });
+            push_event(WindowEvent::CursorLeft {
+                device_id,
+                timestamp: Some(timestamp),
+            });
+            push_event(pointer(PointerPhase::Left, self.pointer_buttons));
+            self.pointer_buttons = PointerButtons::empty();
+        }
+    }
+
+    /// Reset the state accumulated during the frame.
+    fn reset(&mut self) {
+        self.entered = false;
+        self.moved = false;
+        self.tip = None;
+        self.buttons.clear();
+        self.left = false;
+    }
+}
+
+/// Update the held buttons, returning the phase of the pointer event.
+fn pointer_phase(
+    buttons: &mut PointerButtons,
+    button: PointerButtons,
+    state: ElementState,
+) -> PointerPhase {
+    match state {
+        ElementState::Pressed => {
+            buttons.insert(button);
+            PointerPhase::Down
+        }
+        ElementState::Released => {
+            buttons.remove(button);
+            PointerPhase::Up
+        }
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpTabletManagerV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletSeatV2: DeviceId] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletToolV2: TabletToolData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadGroupV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadRingV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadStripV2: GlobalData] => TabletManagerState);
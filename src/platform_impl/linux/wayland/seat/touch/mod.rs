@@ -1,5 +1,7 @@
 //! Touch handling.
 
+use std::time::Instant;
+
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::protocol::wl_touch::WlTouch;
@@ -7,11 +9,14 @@ use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 
 use sctk::seat::touch::{TouchData, TouchHandler};
 
-use crate::dpi::LogicalPosition;
-use crate::event::{Touch, TouchPhase, WindowEvent};
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{
+    DeviceId, Pointer, PointerButtons, PointerKind, PointerPhase, Touch, TouchPhase, WindowEvent,
+};
 
+use crate::platform_impl::common::timestamp;
+use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::state::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId};
 
 impl TouchHandler for WinitState {
     fn down(
@@ -20,7 +25,7 @@ impl TouchHandler for WinitState {
         _: &QueueHandle<Self>,
         touch: &WlTouch,
         _: u32,
-        _: u32,
+        time: u32,
         surface: WlSurface,
         id: i32,
         position: (f64, f64),
@@ -33,6 +38,7 @@ impl TouchHandler for WinitState {
 
         let location = LogicalPosition::<f64>::from(position);
 
+        let device_id = wayland::make_device_id(touch.seat());
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
 
         // Update the state of the point.
@@ -42,16 +48,26 @@ impl TouchHandler for WinitState {
 
         self.events_sink.push_window_event(
             WindowEvent::Touch(Touch {
-                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                    DeviceId,
-                )),
+                device_id,
                 phase: TouchPhase::Started,
                 location: location.to_physical(scale_factor),
                 force: None,
+                contact_size: None,
                 id: id as u64,
+                timestamp: Some(timestamp::from_millis(time)),
             }),
             window_id,
         );
+        self.events_sink.push_window_event(
+            touch_pointer(
+                device_id,
+                id,
+                PointerPhase::Down,
+                location.to_physical(scale_factor),
+                Some(timestamp::from_millis(time)),
+            ),
+            window_id,
+        );
     }
 
     fn up(
@@ -60,9 +76,10 @@ impl TouchHandler for WinitState {
         _: &QueueHandle<Self>,
         touch: &WlTouch,
         _: u32,
-        _: u32,
+        time: u32,
         id: i32,
     ) {
+        let device_id = wayland::make_device_id(touch.seat());
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
 
         // Remove the touch point.
@@ -79,16 +96,26 @@ impl TouchHandler for WinitState {
 
         self.events_sink.push_window_event(
             WindowEvent::Touch(Touch {
-                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                    DeviceId,
-                )),
+                device_id,
                 phase: TouchPhase::Ended,
                 location: touch_point.location.to_physical(scale_factor),
                 force: None,
+                contact_size: None,
                 id: id as u64,
+                timestamp: Some(timestamp::from_millis(time)),
             }),
             window_id,
         );
+        self.events_sink.push_window_event(
+            touch_pointer(
+                device_id,
+                id,
+                PointerPhase::Up,
+                touch_point.location.to_physical(scale_factor),
+                Some(timestamp::from_millis(time)),
+            ),
+            window_id,
+        );
     }
 
     fn motion(
@@ -96,10 +123,11 @@ impl TouchHandler for WinitState {
         _: &Connection,
         _: &QueueHandle<Self>,
         touch: &WlTouch,
-        _: u32,
+        time: u32,
         id: i32,
         position: (f64, f64),
     ) {
+        let device_id = wayland::make_device_id(touch.seat());
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
 
         // Remove the touch point.
@@ -118,19 +146,30 @@ impl TouchHandler for WinitState {
 
         self.events_sink.push_window_event(
             WindowEvent::Touch(Touch {
-                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                    DeviceId,
-                )),
+                device_id,
                 phase: TouchPhase::Cancelled,
                 location: touch_point.location.to_physical(scale_factor),
                 force: None,
+                contact_size: None,
                 id: id as u64,
+                timestamp: Some(timestamp::from_millis(time)),
             }),
             window_id,
         );
+        self.events_sink.push_window_event(
+            touch_pointer(
+                device_id,
+                id,
+                PointerPhase::Moved,
+                touch_point.location.to_physical(scale_factor),
+                Some(timestamp::from_millis(time)),
+            ),
+            window_id,
+        );
     }
 
     fn cancel(&mut self, _: &Connection, _: &QueueHandle<Self>, touch: &WlTouch) {
+        let device_id = wayland::make_device_id(touch.seat());
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
 
         for (id, touch_point) in seat_state.touch_map.drain() {
@@ -144,16 +183,20 @@ impl TouchHandler for WinitState {
 
             self.events_sink.push_window_event(
                 WindowEvent::Touch(Touch {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
+                    device_id,
                     phase: TouchPhase::Cancelled,
                     location,
                     force: None,
+                    contact_size: None,
                     id: id as u64,
+                    timestamp: None,
                 }),
                 window_id,
             );
+            self.events_sink.push_window_event(
+                touch_pointer(device_id, id, PointerPhase::Cancelled, location, None),
+                window_id,
+            );
         }
     }
 
@@ -174,6 +217,34 @@ impl TouchHandler for WinitState {
     }
 }
 
+/// Build the pointer event of a touch point.
+fn touch_pointer(
+    device_id: DeviceId,
+    id: i32,
+    phase: PointerPhase,
+    position: PhysicalPosition<f64>,
+    timestamp: Option<Instant>,
+) -> WindowEvent<'static> {
+    // The finger touches the surface until it's lifted.
+    let buttons = match phase {
+        PointerPhase::Down | PointerPhase::Moved => PointerButtons::PRIMARY,
+        _ => PointerButtons::empty(),
+    };
+
+    WindowEvent::Pointer(Pointer {
+        device_id,
+        pointer_id: id as u64,
+        kind: PointerKind::Touch,
+        phase,
+        position,
+        buttons,
+        pressure: None,
+        tilt: None,
+        size: None,
+        timestamp,
+    })
+}
+
 /// The state of the touch point.
 #[derive(Debug)]
 pub struct TouchPoint {
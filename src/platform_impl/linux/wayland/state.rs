@@ -8,14 +8,16 @@ use sctk::reexports::calloop::LoopHandle;
 use sctk::reexports::client::backend::ObjectId;
 use sctk::reexports::client::globals::GlobalList;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 
 use sctk::compositor::{CompositorHandler, CompositorState};
+use sctk::data_device_manager::DataDeviceManagerState;
 use sctk::output::{OutputHandler, OutputState};
 use sctk::registry::{ProvidesRegistryState, RegistryState};
-use sctk::seat::pointer::ThemedPointer;
 use sctk::seat::SeatState;
+use sctk::shell::xdg::popup::{Popup, PopupConfigure, PopupHandler};
 use sctk::shell::xdg::window::{Window, WindowConfigure, WindowHandler};
 use sctk::shell::xdg::XdgShell;
 use sctk::shell::WaylandSurface;
@@ -23,14 +25,19 @@ use sctk::shm::{Shm, ShmHandler};
 use sctk::subcompositor::SubcompositorState;
 
 use crate::dpi::LogicalSize;
+use crate::event::{Event, WindowEvent};
+use crate::platform_impl::platform::common::modal;
 
 use super::event_loop::sink::EventSink;
 use super::output::MonitorHandle;
 use super::seat::{
-    PointerConstraintsState, RelativePointerState, TextInputState, WinitPointerData,
-    WinitPointerDataExt, WinitSeatState,
+    self, ClipboardState, CustomCursorCache, DndOffer, DragSource, PointerConstraintsState,
+    PointerGesturesState, RelativePointerState, TabletInfo, TabletManagerState, TextInputState,
+    ThemedPointer, WinitPointerDataExt, WinitSeatState,
 };
+use super::types::primary_selection::PrimarySelectionState;
 use super::types::wp_fractional_scaling::FractionalScalingManager;
+use super::types::wp_presentation::PresentationState;
 use super::types::wp_viewporter::ViewporterState;
 use super::types::xdg_activation::XdgActivationState;
 use super::window::{WindowRequests, WindowState};
@@ -75,11 +82,17 @@ pub struct WinitState {
     pub seats: FnvHashMap<ObjectId, WinitSeatState>,
 
     /// Currently present cursor surfaces.
-    pub pointer_surfaces: FnvHashMap<ObjectId, Arc<ThemedPointer<WinitPointerData>>>,
+    pub pointer_surfaces: FnvHashMap<ObjectId, Arc<ThemedPointer>>,
 
     /// The state of the text input on the client.
     pub text_input_state: Option<TextInputState>,
 
+    /// The tablet manager, used to get the pen input.
+    pub tablet_manager: Option<TabletManagerState>,
+
+    /// The description of the present tablets.
+    pub tablets: FnvHashMap<ObjectId, TabletInfo>,
+
     /// Observed monitors.
     pub monitors: Arc<Mutex<Vec<MonitorHandle>>>,
 
@@ -87,21 +100,51 @@ pub struct WinitState {
     /// event loop run.
     pub events_sink: EventSink,
 
+    /// Whether the Wayland events were dispatched since the last loop iteration.
+    pub wayland_events_dispatched: bool,
+
     /// Xdg activation.
     pub xdg_activation: Option<XdgActivationState>,
 
     /// Relative pointer.
     pub relative_pointer: Option<RelativePointerState>,
 
+    /// Touchpad gestures.
+    pub pointer_gestures: Option<PointerGesturesState>,
+
     /// Pointer constraints to handle pointer locking and confining.
     pub pointer_constraints: Option<Arc<PointerConstraintsState>>,
 
+    /// The buffers of the custom cursors.
+    pub custom_cursors: Arc<Mutex<CustomCursorCache>>,
+
     /// Viewporter state on the given window.
     pub viewporter_state: Option<ViewporterState>,
 
     /// Fractional scaling manager.
     pub fractional_scaling_manager: Option<FractionalScalingManager>,
 
+    /// The presentation timing feedback.
+    pub presentation: Option<PresentationState>,
+
+    /// The data device manager used for the clipboard.
+    pub data_device_manager_state: Option<DataDeviceManagerState>,
+
+    /// The primary selection manager.
+    pub primary_selection_state: Option<PrimarySelectionState>,
+
+    /// The content offered by us through the clipboard and the primary selection.
+    pub clipboard_state: ClipboardState,
+
+    /// The seat and the serial of the latest input event, used to set the selection.
+    pub latest_input_serial: Option<(ObjectId, u32)>,
+
+    /// The drag and drop offer hovering one of the windows.
+    pub dnd_offer: Option<DndOffer>,
+
+    /// The drag of the data out of one of the windows.
+    pub drag_source: Option<DragSource>,
+
     /// Loop handle to re-register event sources, such as keyboard repeat.
     pub loop_handle: LoopHandle<'static, Self>,
 }
@@ -125,9 +168,19 @@ impl WinitState {
 
         let seat_state = SeatState::new(globals, queue_handle);
 
+        // `sctk` binds the seats in the order of the globals.
+        let seat_globals = globals.contents().with_list(|globals| {
+            globals
+                .iter()
+                .filter(|global| global.interface == WlSeat::interface().name)
+                .map(|global| (global.name, global.version))
+                .collect::<Vec<_>>()
+        });
         let mut seats = FnvHashMap::default();
-        for seat in seat_state.seats() {
-            seats.insert(seat.id(), WinitSeatState::new());
+        for (seat, (name, version)) in seat_state.seats().zip(seat_globals) {
+            let pointer_seat =
+                seat::bind_pointer_seat(globals.registry(), queue_handle, name, version);
+            seats.insert(seat.id(), WinitSeatState::new(pointer_seat));
         }
 
         let (viewporter_state, fractional_scaling_manager) =
@@ -137,13 +190,16 @@ impl WinitState {
                 (None, None)
             };
 
+        let shm = Shm::bind(globals, queue_handle)?;
+        let custom_cursors = Arc::new(Mutex::new(CustomCursorCache::new(shm.wl_shm().clone())));
+
         Ok(Self {
             registry_state,
             compositor_state: Arc::new(compositor_state),
             subcompositor_state: Arc::new(subcompositor_state),
             output_state,
             seat_state,
-            shm: Shm::bind(globals, queue_handle)?,
+            shm,
 
             xdg_shell: XdgShell::bind(globals, queue_handle)?,
             xdg_activation: XdgActivationState::bind(globals, queue_handle).ok(),
@@ -154,18 +210,31 @@ impl WinitState {
             window_events_sink: Default::default(),
             viewporter_state,
             fractional_scaling_manager,
+            presentation: PresentationState::bind(globals, queue_handle).ok(),
+
+            data_device_manager_state: DataDeviceManagerState::bind(globals, queue_handle).ok(),
+            primary_selection_state: PrimarySelectionState::bind(globals, queue_handle).ok(),
+            clipboard_state: Default::default(),
+            latest_input_serial: None,
+            dnd_offer: None,
+            drag_source: None,
 
             seats,
             text_input_state: TextInputState::new(globals, queue_handle).ok(),
+            tablet_manager: TabletManagerState::new(globals, queue_handle).ok(),
+            tablets: Default::default(),
 
             relative_pointer: RelativePointerState::new(globals, queue_handle).ok(),
+            pointer_gestures: PointerGesturesState::new(globals, queue_handle).ok(),
             pointer_constraints: PointerConstraintsState::new(globals, queue_handle)
                 .map(Arc::new)
                 .ok(),
+            custom_cursors,
             pointer_surfaces: Default::default(),
 
             monitors: Arc::new(Mutex::new(monitors)),
             events_sink: EventSink::new(),
+            wayland_events_dispatched: false,
             loop_handle,
         })
     }
@@ -214,6 +283,18 @@ impl WinitState {
         }
     }
 
+    /// Whether the event is input for a window blocked by a modal dialog.
+    pub fn is_input_blocked<T>(&mut self, event: &Event<'_, T>) -> bool {
+        match event {
+            Event::WindowEvent { window_id, event } if modal::is_user_input(event) => self
+                .windows
+                .get_mut()
+                .get(&window_id.0)
+                .map_or(false, |window| window.lock().unwrap().is_input_disabled()),
+            _ => false,
+        }
+    }
+
     pub fn queue_close(updates: &mut Vec<WindowCompositorUpdate>, window_id: WindowId) {
         let pos = if let Some(pos) = updates
             .iter()
@@ -279,6 +360,47 @@ impl WindowHandler for WinitState {
     }
 }
 
+impl PopupHandler for WinitState {
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        popup: &Popup,
+        configure: PopupConfigure,
+    ) {
+        let window_id = super::make_wid(popup.wl_surface());
+
+        let pos = if let Some(pos) = self
+            .window_compositor_updates
+            .iter()
+            .position(|update| update.window_id == window_id)
+        {
+            pos
+        } else {
+            self.window_compositor_updates
+                .push(WindowCompositorUpdate::new(window_id));
+            self.window_compositor_updates.len() - 1
+        };
+
+        let new_size = self
+            .windows
+            .get_mut()
+            .get_mut(&window_id)
+            .expect("got configure for dead popup.")
+            .lock()
+            .unwrap()
+            .configure_popup(configure);
+
+        self.window_compositor_updates[pos].size = Some(new_size);
+    }
+
+    fn done(&mut self, _: &Connection, _: &QueueHandle<Self>, popup: &Popup) {
+        let window_id = super::make_wid(popup.wl_surface());
+        self.events_sink
+            .push_window_event(WindowEvent::PopupDismissed, window_id);
+    }
+}
+
 impl OutputHandler for WinitState {
     fn output_state(&mut self) -> &mut OutputState {
         &mut self.output_state
@@ -321,7 +443,16 @@ impl CompositorHandler for WinitState {
         self.scale_factor_changed(surface, scale_factor as f64, true)
     }
 
-    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlSurface, _: u32) {}
+    fn frame(&mut self, _: &Connection, _: &QueueHandle<Self>, surface: &WlSurface, _: u32) {
+        let window_id = super::make_wid(surface);
+        let window = match self.windows.get_mut().get(&window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        // The pending redraw requests are processed along with the callback.
+        window.lock().unwrap().frame_callback_received();
+    }
 }
 
 impl ProvidesRegistryState for WinitState {
@@ -366,3 +497,4 @@ sctk::delegate_registry!(WinitState);
 sctk::delegate_shm!(WinitState);
 sctk::delegate_xdg_shell!(WinitState);
 sctk::delegate_xdg_window!(WinitState);
+sctk::delegate_xdg_popup!(WinitState);
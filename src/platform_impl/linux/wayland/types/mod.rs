@@ -1,5 +1,7 @@
 //! Wayland protocol implementation boilerplate.
 
+pub mod primary_selection;
 pub mod wp_fractional_scaling;
+pub mod wp_presentation;
 pub mod wp_viewporter;
 pub mod xdg_activation;
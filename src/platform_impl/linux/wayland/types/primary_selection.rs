@@ -0,0 +1,169 @@
+//! Handling of the primary selection.
+
+use std::sync::Mutex;
+
+use sctk::reexports::client::delegate_dispatch;
+use sctk::reexports::client::event_created_child;
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::Dispatch;
+use sctk::reexports::client::{Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_device_v1::{
+    Event as DeviceEvent, ZwpPrimarySelectionDeviceV1,
+};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_offer_v1::{
+    Event as OfferEvent, ZwpPrimarySelectionOfferV1,
+};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_source_v1::{
+    Event as SourceEvent, ZwpPrimarySelectionSourceV1,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::clipboard::ClipboardKind;
+use crate::platform_impl::wayland::state::WinitState;
+
+pub struct PrimarySelectionState {
+    manager: ZwpPrimarySelectionDeviceManagerV1,
+}
+
+impl PrimarySelectionState {
+    pub fn bind(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    pub fn get_device(
+        &self,
+        seat: &WlSeat,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> ZwpPrimarySelectionDeviceV1 {
+        self.manager
+            .get_device(seat, queue_handle, PrimarySelectionDeviceData::default())
+    }
+
+    pub fn create_source<'a>(
+        &self,
+        mime_types: impl IntoIterator<Item = &'a str>,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> ZwpPrimarySelectionSourceV1 {
+        let source = self.manager.create_source(queue_handle, GlobalData);
+        for mime_type in mime_types {
+            source.offer(mime_type.to_owned());
+        }
+        source
+    }
+}
+
+/// The data associated with the primary selection device.
+#[derive(Debug, Default)]
+pub struct PrimarySelectionDeviceData {
+    /// The current selection offer.
+    offer: Mutex<Option<ZwpPrimarySelectionOfferV1>>,
+}
+
+impl PrimarySelectionDeviceData {
+    /// The current selection offer.
+    pub fn offer(&self) -> Option<ZwpPrimarySelectionOfferV1> {
+        self.offer.lock().unwrap().clone()
+    }
+}
+
+/// The data associated with the primary selection offer.
+#[derive(Debug, Default)]
+pub struct PrimarySelectionOfferData {
+    mime_types: Mutex<Vec<String>>,
+}
+
+impl PrimarySelectionOfferData {
+    /// The MIME types advertised by the offer.
+    pub fn mime_types(&self) -> Vec<String> {
+        self.mime_types.lock().unwrap().clone()
+    }
+}
+
+impl Dispatch<ZwpPrimarySelectionDeviceManagerV1, GlobalData, WinitState>
+    for PrimarySelectionState
+{
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPrimarySelectionDeviceManagerV1,
+        _event: <ZwpPrimarySelectionDeviceManagerV1 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpPrimarySelectionDeviceV1, PrimarySelectionDeviceData, WinitState>
+    for PrimarySelectionState
+{
+    event_created_child!(WinitState, ZwpPrimarySelectionDeviceV1, [
+        0 => (ZwpPrimarySelectionOfferV1, PrimarySelectionOfferData::default())
+    ]);
+
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPrimarySelectionDeviceV1,
+        event: <ZwpPrimarySelectionDeviceV1 as Proxy>::Event,
+        data: &PrimarySelectionDeviceData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        if let DeviceEvent::Selection { id } = event {
+            let mut offer = data.offer.lock().unwrap();
+            if let Some(old_offer) = offer.take() {
+                old_offer.destroy();
+            }
+            *offer = id;
+        }
+    }
+}
+
+impl Dispatch<ZwpPrimarySelectionOfferV1, PrimarySelectionOfferData, WinitState>
+    for PrimarySelectionState
+{
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPrimarySelectionOfferV1,
+        event: <ZwpPrimarySelectionOfferV1 as Proxy>::Event,
+        data: &PrimarySelectionOfferData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        if let OfferEvent::Offer { mime_type } = event {
+            data.mime_types.lock().unwrap().push(mime_type);
+        }
+    }
+}
+
+impl Dispatch<ZwpPrimarySelectionSourceV1, GlobalData, WinitState> for PrimarySelectionState {
+    fn event(
+        state: &mut WinitState,
+        source: &ZwpPrimarySelectionSourceV1,
+        event: <ZwpPrimarySelectionSourceV1 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        match event {
+            SourceEvent::Send { mime_type, fd } => {
+                state.send_clipboard_content(ClipboardKind::Primary, &mime_type, fd.into());
+            }
+            SourceEvent::Cancelled => {
+                state.clipboard_cancelled(ClipboardKind::Primary, &source.id());
+            }
+            _ => (),
+        }
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpPrimarySelectionDeviceManagerV1: GlobalData] => PrimarySelectionState);
+delegate_dispatch!(WinitState: [ZwpPrimarySelectionDeviceV1: PrimarySelectionDeviceData] => PrimarySelectionState);
+delegate_dispatch!(WinitState: [ZwpPrimarySelectionOfferV1: PrimarySelectionOfferData] => PrimarySelectionState);
+delegate_dispatch!(WinitState: [ZwpPrimarySelectionSourceV1: GlobalData] => PrimarySelectionState);
@@ -0,0 +1,116 @@
+//! Handling of the wp-presentation, which reports when the frames are shown.
+
+use std::time::{Duration, Instant};
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::Dispatch;
+use sctk::reexports::client::{delegate_dispatch, Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::presentation_time::client::wp_presentation::{
+    Event as PresentationEvent, WpPresentation,
+};
+use sctk::reexports::protocols::wp::presentation_time::client::wp_presentation_feedback::{
+    Event as FeedbackEvent, WpPresentationFeedback,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::event::{PresentationFeedback, WindowEvent};
+use crate::platform_impl::platform::common::timestamp;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::WindowId;
+
+#[derive(Debug)]
+pub struct PresentationState {
+    presentation: WpPresentation,
+
+    /// The clock of the presentation timestamps.
+    clock_id: Option<u32>,
+}
+
+impl PresentationState {
+    pub fn bind(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let presentation = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self {
+            presentation,
+            clock_id: None,
+        })
+    }
+
+    pub fn global(&self) -> &WpPresentation {
+        &self.presentation
+    }
+}
+
+impl Dispatch<WpPresentation, GlobalData, WinitState> for PresentationState {
+    fn event(
+        state: &mut WinitState,
+        _: &WpPresentation,
+        event: <WpPresentation as Proxy>::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        if let PresentationEvent::ClockId { clk_id } = event {
+            if let Some(presentation) = state.presentation.as_mut() {
+                presentation.clock_id = Some(clk_id);
+            }
+        }
+    }
+}
+
+impl Dispatch<WpPresentationFeedback, WindowId, WinitState> for PresentationState {
+    fn event(
+        state: &mut WinitState,
+        _: &WpPresentationFeedback,
+        event: <WpPresentationFeedback as Proxy>::Event,
+        window_id: &WindowId,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        let feedback = match event {
+            FeedbackEvent::Presented {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+                refresh,
+                seq_hi,
+                seq_lo,
+                ..
+            } => {
+                let clock_id = state
+                    .presentation
+                    .as_ref()
+                    .and_then(|presentation| presentation.clock_id);
+
+                // The compositors use `CLOCK_MONOTONIC` in practice, the other clocks can't be
+                // converted and the time of the event is used instead.
+                let timestamp = if clock_id == Some(libc::CLOCK_MONOTONIC as u32) {
+                    let secs = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                    timestamp::from_monotonic(Duration::new(secs, tv_nsec))
+                } else {
+                    Instant::now()
+                };
+
+                // Both are zero when they aren't known.
+                let sequence = (u64::from(seq_hi) << 32) | u64::from(seq_lo);
+                PresentationFeedback::Presented {
+                    timestamp,
+                    refresh: (refresh != 0).then(|| Duration::from_nanos(refresh.into())),
+                    sequence: (sequence != 0).then_some(sequence),
+                }
+            }
+            FeedbackEvent::Discarded => PresentationFeedback::Discarded,
+            _ => return,
+        };
+
+        state
+            .events_sink
+            .push_window_event(WindowEvent::PresentationFeedback(feedback), *window_id);
+    }
+}
+
+delegate_dispatch!(WinitState: [WpPresentation: GlobalData] => PresentationState);
+delegate_dispatch!(WinitState: [WpPresentationFeedback: WindowId] => PresentationState);
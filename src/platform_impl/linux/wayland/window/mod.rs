@@ -7,29 +7,37 @@ use raw_window_handle::{
     RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
 };
 
-use sctk::reexports::calloop;
 use sctk::reexports::client::protocol::wl_display::WlDisplay;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Proxy;
 use sctk::reexports::client::QueueHandle;
+use sctk::reexports::protocols::xdg::shell::client::xdg_positioner::{Anchor, Gravity};
 
 use sctk::compositor::{CompositorState, Region, SurfaceData};
 use sctk::reexports::protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1;
-use sctk::shell::xdg::window::Window as SctkWindow;
+use sctk::shell::xdg::popup::Popup;
 use sctk::shell::xdg::window::WindowDecorations;
+use sctk::shell::xdg::XdgPositioner;
 use sctk::shell::WaylandSurface;
 
+use crate::clipboard::ClipboardContent;
+use crate::cursor::CustomCursor;
+use crate::device::SeatId;
+use crate::dnd::DndAction;
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
 use crate::event::{Ime, WindowEvent};
 use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::platform::common::cursor::SelectedCursor;
+use crate::platform_impl::platform::common::dnd::DragRequest;
+use crate::platform_impl::platform::common::event_sources::Waker;
 use crate::platform_impl::{
     Fullscreen, MonitorHandle as PlatformMonitorHandle, OsError,
     PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
 };
 use crate::window::{
-    CursorGrabMode, CursorIcon, ImePurpose, ResizeDirection, Theme, UserAttentionType,
-    WindowAttributes, WindowButtons,
+    CursorGrabMode, CursorIcon, Icon, ImePurpose, PopupAnchor, PopupPlacement, ResizeDirection,
+    Theme, UserAttentionType, WindowAttributes, WindowButtons,
 };
 
 use super::event_loop::sink::EventSink;
@@ -40,12 +48,12 @@ use super::{EventLoopWindowTarget, WindowId};
 
 mod state;
 
-pub use state::WindowState;
+pub use state::{FrameCallbackState, Subsurface, WindowRole, WindowState};
 
 /// The Wayland window.
 pub struct Window {
-    /// Reference to the underlying SCTK window.
-    window: SctkWindow,
+    /// Reference to the underlying SCTK window or popup.
+    window: WindowRole,
 
     /// Window id.
     window_id: WindowId,
@@ -75,7 +83,7 @@ pub struct Window {
     monitors: Arc<Mutex<Vec<MonitorHandle>>>,
 
     /// Source to wake-up the event-loop for window requests.
-    event_loop_awakener: calloop::ping::Ping,
+    event_loop_awakener: Arc<Waker>,
 
     /// The event sink to deliver sythetic events.
     window_events_sink: Arc<Mutex<EventSink>>,
@@ -92,7 +100,6 @@ impl Window {
 
         let monitors = state.monitors.clone();
 
-        let surface = state.compositor_state.create_surface(&queue_handle);
         let compositor = state.compositor_state.clone();
         let xdg_activation = state
             .xdg_activation
@@ -114,10 +121,114 @@ impl Window {
             WindowDecorations::RequestClient
         };
 
-        let window =
-            state
-                .xdg_shell
-                .create_window(surface.clone(), default_decorations, &queue_handle);
+        // Popups are attached to their parent, which must still exist.
+        let popup = attributes.popup.and_then(|(parent, placement)| {
+            let parent = state
+                .windows
+                .get_mut()
+                .get(&parent.0)
+                .filter(|parent| parent.lock().unwrap().window.xdg_surface().is_some())
+                .cloned();
+            if parent.is_none() {
+                warn!("Invalid popup parent, creating a regular window");
+            }
+            Some((parent?, placement))
+        });
+
+        // Child windows are subsurfaces of a window of this event loop.
+        let subsurface_parent = attributes.parent_window.and_then(|parent| {
+            let parent = match parent {
+                RawWindowHandle::Wayland(handle) => state
+                    .windows
+                    .get_mut()
+                    .values()
+                    .find(|window_state| {
+                        let window_state = window_state.lock().unwrap();
+                        window_state.window.wl_surface().id().as_ptr() as *mut _ == handle.surface
+                    })
+                    .cloned(),
+                _ => None,
+            };
+            if parent.is_none() {
+                warn!("Invalid parent window, creating a regular window");
+            }
+            parent
+        });
+        let mut subsurface_position = None;
+
+        let (window, size) = if let Some((parent, placement)) = popup {
+            let surface = state.compositor_state.create_surface(&queue_handle);
+            let parent = parent.lock().unwrap();
+            let scale_factor = parent.scale_factor();
+            let size = attributes
+                .inner_size
+                .map(|size| size.to_logical::<u32>(scale_factor))
+                .unwrap_or(size);
+
+            let positioner = XdgPositioner::new(&state.xdg_shell).map_err(|_| {
+                os_error!(OsError::WaylandMisc(
+                    "failed to create the popup positioner."
+                ))
+            })?;
+            set_popup_placement(&positioner, &placement, size, &parent);
+
+            let popup = Popup::from_surface(
+                parent.window.xdg_surface(),
+                &positioner,
+                &queue_handle,
+                surface.clone(),
+                &state.xdg_shell,
+            )
+            .map_err(|_| os_error!(OsError::WaylandMisc("failed to create the popup.")))?;
+
+            // The grab needs the serial of the latest input event, like the click opening a menu.
+            if placement.grab {
+                let seat = state
+                    .latest_input_serial
+                    .as_ref()
+                    .and_then(|(seat, serial)| {
+                        let seat = state.seat_state.seats().find(|s| &s.id() == seat)?;
+                        Some((seat, *serial))
+                    });
+                match seat {
+                    Some((seat, serial)) => popup.xdg_popup().grab(&seat, serial),
+                    None => warn!("No input event to grab the popup with"),
+                }
+            }
+
+            (WindowRole::Popup(popup), size)
+        } else if let Some(parent) = subsurface_parent {
+            let parent = parent.lock().unwrap();
+            let scale_factor = parent.scale_factor();
+            let size = attributes
+                .inner_size
+                .map(|size| size.to_logical::<u32>(scale_factor))
+                .unwrap_or(size);
+            subsurface_position = attributes
+                .position
+                .map(|position| position.to_logical::<i32>(scale_factor));
+
+            let (subsurface, surface) = state
+                .subcompositor_state
+                .create_subsurface(parent.window.wl_surface().clone(), &queue_handle);
+            let subsurface = Subsurface::new(subsurface, surface);
+
+            // The subsurface only shows up once its parent commits, so redraw the parent.
+            let parent_id = super::make_wid(parent.window.wl_surface());
+            if let Some(requests) = state.window_requests.get_mut().get(&parent_id) {
+                requests.redraw_requested.store(true, Ordering::Relaxed);
+            }
+
+            (WindowRole::Subsurface(Arc::new(subsurface)), size)
+        } else {
+            let surface = state.compositor_state.create_surface(&queue_handle);
+            let window =
+                state
+                    .xdg_shell
+                    .create_window(surface.clone(), default_decorations, &queue_handle);
+            (WindowRole::Toplevel(window), size)
+        };
+        let surface = window.wl_surface().clone();
 
         let mut window_state = WindowState::new(
             event_loop_window_target.connection.clone(),
@@ -131,12 +242,40 @@ impl Window {
         // Set transparency hint.
         window_state.set_transparent(attributes.transparent);
 
+        // Subsurfaces aren't configured, so apply their size and position right away.
+        if window.subsurface().is_some() {
+            window_state.resize(size);
+            if let Some(position) = subsurface_position {
+                window_state.set_subsurface_position(position);
+            }
+        }
+
         // Set the decorations hint.
         window_state.set_decorate(attributes.decorations);
 
         // Set the app_id.
-        if let Some(name) = platform_attributes.name.map(|name| name.general) {
-            window.set_app_id(name);
+        if let (Some(toplevel), Some(name)) = (
+            window.toplevel(),
+            platform_attributes.name.map(|name| name.general),
+        ) {
+            toplevel.set_app_id(name);
+        }
+
+        // Dialogs are kept above their owner, which must be a top-level.
+        if let (Some(toplevel), Some(owner)) = (window.toplevel(), attributes.owner) {
+            let owner = state.windows.get_mut().get(&owner.0).cloned();
+            let owner_toplevel = owner
+                .as_ref()
+                .and_then(|owner| owner.lock().unwrap().window.toplevel().cloned());
+            match (owner, owner_toplevel) {
+                (Some(owner), Some(owner_toplevel)) => {
+                    toplevel.set_parent(Some(&owner_toplevel));
+                    if attributes.modal {
+                        window_state.set_modal_owner(&owner);
+                    }
+                }
+                _ => warn!("The owner of the dialog isn't a top-level window"),
+            }
         }
 
         // Set the window title.
@@ -147,27 +286,30 @@ impl Window {
         let max_size = attributes.max_inner_size.map(|size| size.to_logical(1.));
         window_state.set_min_inner_size(min_size);
         window_state.set_max_inner_size(max_size);
+        let increments = attributes.resize_increments.map(|size| size.to_logical(1.));
+        window_state.set_resize_increments(increments);
 
         // Non-resizable implies that the min and max sizes are set to the same value.
         window_state.set_resizable(attributes.resizable);
 
         // Set startup mode.
-        match attributes.fullscreen.map(Into::into) {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland");
-            }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let output = monitor.and_then(|monitor| match monitor {
-                    PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
-                });
-
-                window.set_fullscreen(output.as_ref())
-            }
-            _ if attributes.maximized => window.set_maximized(),
-            _ => (),
-        };
+        if let Some(toplevel) = window.toplevel() {
+            match attributes.fullscreen.map(Into::into) {
+                Some(Fullscreen::Exclusive(_)) => {
+                    warn!("`Fullscreen::Exclusive` is ignored on Wayland");
+                }
+                Some(Fullscreen::Borderless(monitor)) => {
+                    let output = monitor.and_then(|monitor| match monitor {
+                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
+                        _ => None,
+                    });
+
+                    toplevel.set_fullscreen(output.as_ref())
+                }
+                _ if attributes.maximized => toplevel.set_maximized(),
+                _ => (),
+            };
+        }
 
         // Activate the window when the token is passed.
         if let (Some(xdg_activation), Some(token)) = (
@@ -178,7 +320,7 @@ impl Window {
         }
 
         // XXX Do initial commit.
-        window.commit();
+        window.wl_surface().commit();
 
         // Add the window and window requests into the state.
         let window_state = Arc::new(Mutex::new(window_state));
@@ -191,6 +333,7 @@ impl Window {
         let window_requests = WindowRequests {
             redraw_requested: AtomicBool::new(true),
             closed: AtomicBool::new(false),
+            drag: Mutex::new(None),
         };
         let window_requests = Arc::new(window_requests);
         state
@@ -265,17 +408,76 @@ impl Window {
 
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        self.inner_position()
     }
 
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        // Only the position of the subsurfaces, relative to their parent, is known.
+        let window_state = self.window_state.lock().unwrap();
+        let scale_factor = window_state.scale_factor();
+        window_state
+            .subsurface_position()
+            .map(|position| position.to_physical(scale_factor))
+            .ok_or_else(NotSupportedError::new)
     }
 
     #[inline]
-    pub fn set_outer_position(&self, _: Position) {
-        // Not possible on Wayland.
+    pub fn set_outer_position(&self, position: Position) {
+        // Only subsurfaces can be moved on Wayland.
+        let mut window_state = self.window_state.lock().unwrap();
+        let scale_factor = window_state.scale_factor();
+        window_state.set_subsurface_position(position.to_logical(scale_factor));
+    }
+
+    /// Set whether the subsurface is synchronized with the commits of its parent.
+    #[inline]
+    pub fn set_subsurface_sync(&self, sync: bool) -> Result<(), ExternalError> {
+        let subsurface = self
+            .window
+            .subsurface()
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+
+        if sync {
+            subsurface.set_sync();
+        } else {
+            subsurface.set_desync();
+        }
+
+        Ok(())
+    }
+
+    /// Stack the subsurface right above or below the sibling, which may also be its parent.
+    pub fn place_subsurface(&self, sibling: &Window, above: bool) -> Result<(), ExternalError> {
+        let subsurface = self
+            .window
+            .subsurface()
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+
+        let parent = self
+            .surface()
+            .data::<SurfaceData>()
+            .and_then(|data| data.parent_surface());
+        let sibling_parent = sibling
+            .surface()
+            .data::<SurfaceData>()
+            .and_then(|data| data.parent_surface());
+        if parent.is_none()
+            || (parent != Some(sibling.surface()) && parent != sibling_parent)
+            || sibling.surface() == self.surface()
+        {
+            return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "the subsurface can only be placed relative to its parent and siblings."
+            ))));
+        }
+
+        if above {
+            subsurface.place_above(sibling.surface());
+        } else {
+            subsurface.place_below(sibling.surface());
+        }
+
+        Ok(())
     }
 
     #[inline]
@@ -293,6 +495,25 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        self.window_state.lock().unwrap().request_frame_callback();
+    }
+
+    #[inline]
+    pub fn request_presentation_feedback(&self) -> Result<(), ExternalError> {
+        if self
+            .window_state
+            .lock()
+            .unwrap()
+            .request_presentation_feedback()
+        {
+            Ok(())
+        } else {
+            Err(ExternalError::NotSupported(NotSupportedError::new()))
+        }
+    }
+
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
         let window_state = self.window_state.lock().unwrap();
@@ -335,12 +556,31 @@ impl Window {
 
     #[inline]
     pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
-        None
+        let window_state = self.window_state.lock().unwrap();
+        let scale_factor = window_state.scale_factor();
+        window_state
+            .resize_increments()
+            .map(|increments| increments.to_physical(scale_factor))
     }
 
     #[inline]
-    pub fn set_resize_increments(&self, _increments: Option<Size>) {
-        warn!("`set_resize_increments` is not implemented for Wayland");
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        let mut window_state = self.window_state.lock().unwrap();
+        let scale_factor = window_state.scale_factor();
+        let increments = increments.map(|size| size.to_logical(scale_factor));
+
+        // Snap the current size to the new increments.
+        if window_state.set_resize_increments(increments) {
+            let size = window_state.inner_size().to_physical(scale_factor);
+            drop(window_state);
+
+            self.window_events_sink
+                .lock()
+                .unwrap()
+                .push_window_event(WindowEvent::Resized(size), self.window_id);
+            self.request_redraw();
+            self.event_loop_awakener.ping();
+        }
     }
 
     #[inline]
@@ -414,7 +654,9 @@ impl Window {
             return;
         }
 
-        self.window.set_minimized();
+        if let Some(toplevel) = self.window.toplevel() {
+            toplevel.set_minimized();
+        }
     }
 
     #[inline]
@@ -430,10 +672,15 @@ impl Window {
 
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
+        let toplevel = match self.window.toplevel() {
+            Some(toplevel) => toplevel,
+            None => return,
+        };
+
         if maximized {
-            self.window.set_maximized()
+            toplevel.set_maximized()
         } else {
-            self.window.unset_maximized()
+            toplevel.unset_maximized()
         }
     }
 
@@ -458,6 +705,11 @@ impl Window {
 
     #[inline]
     pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let toplevel = match self.window.toplevel() {
+            Some(toplevel) => toplevel,
+            None => return,
+        };
+
         match fullscreen {
             Some(Fullscreen::Exclusive(_)) => {
                 warn!("`Fullscreen::Exclusive` is ignored on Wayland");
@@ -465,19 +717,45 @@ impl Window {
             Some(Fullscreen::Borderless(monitor)) => {
                 let output = monitor.and_then(|monitor| match monitor {
                     PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
+                    _ => None,
                 });
 
-                self.window.set_fullscreen(output.as_ref())
+                toplevel.set_fullscreen(output.as_ref())
             }
-            None => self.window.unset_fullscreen(),
+            None => toplevel.unset_fullscreen(),
         }
     }
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        self.window_state.lock().unwrap().set_cursor(cursor);
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_window_cursor(SelectedCursor::Named(cursor));
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_icon(
+        &self,
+        seat: SeatId,
+        cursor: CursorIcon,
+    ) -> Result<(), ExternalError> {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_seat_cursor(seat, cursor);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_window_cursor(SelectedCursor::Custom(cursor));
+
+        // Wake up the event loop to animate the cursor.
+        self.event_loop_awakener.ping();
     }
 
     #[inline]
@@ -486,6 +764,9 @@ impl Window {
             .lock()
             .unwrap()
             .set_cursor_visible(visible);
+
+        // Wake up the event loop to animate the cursor.
+        self.event_loop_awakener.ping();
     }
 
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
@@ -535,6 +816,18 @@ impl Window {
         self.window_state.lock().unwrap().set_cursor_grab(mode)
     }
 
+    #[inline]
+    pub fn set_seat_cursor_grab(
+        &self,
+        seat: SeatId,
+        mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_seat_cursor_grab(seat, mode)
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
         let scale_factor = self.scale_factor();
@@ -552,6 +845,18 @@ impl Window {
         self.window_state.lock().unwrap().drag_window()
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        content: ClipboardContent,
+        actions: &[DndAction],
+        icon: Option<Icon>,
+    ) -> Result<(), ExternalError> {
+        *self.window_requests.drag.lock().unwrap() = Some(DragRequest::new(content, actions, icon));
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
         let surface = self.window.wl_surface();
@@ -658,8 +963,59 @@ impl Window {
     }
 }
 
+/// Describe the placement of the popup relative to its parent to the positioner.
+fn set_popup_placement(
+    positioner: &XdgPositioner,
+    placement: &PopupPlacement,
+    size: LogicalSize<u32>,
+    parent: &WindowState,
+) {
+    let scale_factor = parent.scale_factor();
+    let anchor_position = placement.anchor_position.to_logical::<i32>(scale_factor);
+    let anchor_size = placement.anchor_size.to_logical::<i32>(scale_factor);
+
+    // The anchor rectangle is relative to the window geometry, which includes the frame.
+    let (offset_x, offset_y) = parent.frame_offset();
+
+    positioner.set_size(size.width as i32, size.height as i32);
+    positioner.set_anchor_rect(
+        anchor_position.x + offset_x,
+        anchor_position.y + offset_y,
+        // The rectangle must be at least 1x1.
+        anchor_size.width.max(1),
+        anchor_size.height.max(1),
+    );
+    positioner.set_anchor(match placement.anchor {
+        PopupAnchor::Center => Anchor::None,
+        PopupAnchor::Top => Anchor::Top,
+        PopupAnchor::Bottom => Anchor::Bottom,
+        PopupAnchor::Left => Anchor::Left,
+        PopupAnchor::Right => Anchor::Right,
+        PopupAnchor::TopLeft => Anchor::TopLeft,
+        PopupAnchor::TopRight => Anchor::TopRight,
+        PopupAnchor::BottomLeft => Anchor::BottomLeft,
+        PopupAnchor::BottomRight => Anchor::BottomRight,
+    });
+    positioner.set_gravity(match placement.gravity {
+        PopupAnchor::Center => Gravity::None,
+        PopupAnchor::Top => Gravity::Top,
+        PopupAnchor::Bottom => Gravity::Bottom,
+        PopupAnchor::Left => Gravity::Left,
+        PopupAnchor::Right => Gravity::Right,
+        PopupAnchor::TopLeft => Gravity::TopLeft,
+        PopupAnchor::TopRight => Gravity::TopRight,
+        PopupAnchor::BottomLeft => Gravity::BottomLeft,
+        PopupAnchor::BottomRight => Gravity::BottomRight,
+    });
+    // The flags match the ones of the protocol.
+    positioner.set_constraint_adjustment(placement.adjustment.bits());
+}
+
 impl Drop for Window {
     fn drop(&mut self) {
+        // The owner of a modal dialog is unblocked once the dialog is gone.
+        self.window_state.lock().unwrap().release_modal_owner();
+
         self.window_requests.closed.store(true, Ordering::Relaxed);
         self.event_loop_awakener.ping();
     }
@@ -673,6 +1029,9 @@ pub struct WindowRequests {
 
     /// Redraw Requested.
     pub redraw_requested: AtomicBool,
+
+    /// The drag requested to start.
+    pub drag: Mutex<Option<DragRequest>>,
 }
 
 impl WindowRequests {
@@ -683,6 +1042,10 @@ impl WindowRequests {
     pub fn take_redraw_requested(&self) -> bool {
         self.redraw_requested.swap(false, Ordering::Relaxed)
     }
+
+    pub fn take_drag(&self) -> Option<DragRequest> {
+        self.drag.lock().unwrap().take()
+    }
 }
 
 impl TryFrom<&str> for Theme {
@@ -2,35 +2,43 @@
 
 use std::mem::ManuallyDrop;
 use std::num::NonZeroU32;
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
 
 use log::warn;
 
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_subsurface::WlSubsurface;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 use sctk::reexports::protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
+use sctk::reexports::protocols::wp::presentation_time::client::wp_presentation::WpPresentation;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 use sctk::reexports::protocols::wp::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::xdg::shell::client::xdg_surface;
 use sctk::reexports::protocols::xdg::shell::client::xdg_toplevel::ResizeEdge;
 
 use sctk::compositor::{CompositorState, Region, SurfaceData};
-use sctk::seat::pointer::ThemedPointer;
 use sctk::shell::xdg::frame::{DecorationsFrame, FrameAction, FrameClick};
+use sctk::shell::xdg::popup::{Popup, PopupConfigure};
 use sctk::shell::xdg::window::{DecorationMode, Window, WindowConfigure};
 use sctk::shell::xdg::XdgSurface;
 use sctk::shell::WaylandSurface;
 use sctk::shm::Shm;
 use sctk::subcompositor::SubcompositorState;
 
+use crate::device::SeatId;
 use crate::dpi::{LogicalPosition, LogicalSize};
 use crate::error::{ExternalError, NotSupportedError};
+use crate::platform_impl::platform::common::cursor::SelectedCursor;
 use crate::platform_impl::WindowId;
 use crate::window::{CursorGrabMode, CursorIcon, ImePurpose, ResizeDirection, Theme};
 
+use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::seat::{
-    PointerConstraintsState, WinitPointerData, WinitPointerDataExt, ZwpTextInputV3Ext,
+    CustomCursorCache, PointerConstraintsState, ThemedPointer, WinitPointerData,
+    WinitPointerDataExt, ZwpTextInputV3Ext,
 };
 use crate::platform_impl::wayland::state::{WindowCompositorUpdate, WinitState};
 
@@ -42,13 +50,83 @@ pub type WinitFrame = sctk::shell::xdg::frame::fallback_frame::FallbackFrame<Win
 // Minimum window inner size.
 const MIN_WINDOW_SIZE: LogicalSize<u32> = LogicalSize::new(2, 1);
 
+/// The role of the window surface, either a top-level, a popup or a subsurface.
+#[derive(Debug, Clone)]
+pub enum WindowRole {
+    Toplevel(Window),
+    Popup(Popup),
+    Subsurface(Arc<Subsurface>),
+}
+
+impl WindowRole {
+    /// The top-level window, if the surface has this role.
+    #[inline]
+    pub fn toplevel(&self) -> Option<&Window> {
+        match self {
+            WindowRole::Toplevel(window) => Some(window),
+            _ => None,
+        }
+    }
+
+    /// The subsurface, if the surface has this role.
+    #[inline]
+    pub fn subsurface(&self) -> Option<&WlSubsurface> {
+        match self {
+            WindowRole::Subsurface(subsurface) => Some(&subsurface.subsurface),
+            _ => None,
+        }
+    }
+
+    /// The `xdg_surface` of top-levels and popups, subsurfaces don't have one.
+    #[inline]
+    pub fn xdg_surface(&self) -> Option<&xdg_surface::XdgSurface> {
+        match self {
+            WindowRole::Toplevel(window) => Some(window.xdg_surface()),
+            WindowRole::Popup(popup) => Some(popup.xdg_surface()),
+            WindowRole::Subsurface(_) => None,
+        }
+    }
+}
+
+impl WaylandSurface for WindowRole {
+    fn wl_surface(&self) -> &WlSurface {
+        match self {
+            WindowRole::Toplevel(window) => window.wl_surface(),
+            WindowRole::Popup(popup) => popup.wl_surface(),
+            WindowRole::Subsurface(subsurface) => &subsurface.surface,
+        }
+    }
+}
+
+/// A surface placed inside of its parent surface.
+#[derive(Debug)]
+pub struct Subsurface {
+    subsurface: WlSubsurface,
+    surface: WlSurface,
+}
+
+impl Subsurface {
+    pub fn new(subsurface: WlSubsurface, surface: WlSurface) -> Self {
+        Self {
+            subsurface,
+            surface,
+        }
+    }
+}
+
+impl Drop for Subsurface {
+    fn drop(&mut self) {
+        self.subsurface.destroy();
+    }
+}
+
 /// The state of the window which is being updated from the [`WinitState`].
 pub struct WindowState {
     /// The connection to Wayland server.
     pub connection: Connection,
 
-    /// The underlying SCTK window.
-    pub window: ManuallyDrop<Window>,
+    /// The underlying SCTK window or popup.
+    pub window: ManuallyDrop<WindowRole>,
 
     /// The window frame, which is created from the configure request.
     frame: Option<WinitFrame>,
@@ -59,15 +137,42 @@ pub struct WindowState {
     /// The last received configure.
     pub last_configure: Option<WindowConfigure>,
 
+    /// Whether the initial configure was received.
+    configured: bool,
+
+    /// The position of the subsurface relative to its parent.
+    subsurface_position: LogicalPosition<i32>,
+
+    /// The owner of the modal dialog, whose input is blocked until the dialog is dropped.
+    modal_owner: Option<Weak<Mutex<WindowState>>>,
+
+    /// The number of modal dialogs blocking the input of the window.
+    modal_dialogs: usize,
+
     /// The pointers observed on the window.
-    pub pointers: Vec<Weak<ThemedPointer<WinitPointerData>>>,
+    pub pointers: Vec<Weak<ThemedPointer>>,
+
+    /// The selected cursor.
+    pub cursor: SelectedCursor,
+
+    /// The buffers of the custom cursors.
+    custom_cursors: Arc<Mutex<CustomCursorCache>>,
+
+    /// The frame of the animated cursor which is shown.
+    cursor_frame: usize,
 
-    /// Cursor icon.
-    pub cursor_icon: CursorIcon,
+    /// When the next frame of the animated cursor is due.
+    cursor_frame_deadline: Option<Instant>,
 
     /// Wether the cursor is visible.
     pub cursor_visible: bool,
 
+    /// The cursors of the seats, replacing the one of the window.
+    seat_cursors: Vec<(SeatId, CursorIcon)>,
+
+    /// The grabbing modes of the seats, replacing the one of the window.
+    seat_grabs: Vec<(SeatId, CursorGrabMode)>,
+
     /// Pointer constraints to lock/confine pointer.
     pub pointer_constraints: Option<Arc<PointerConstraintsState>>,
 
@@ -83,8 +188,8 @@ pub struct WindowState {
     /// Whether the frame is resizable.
     resizable: bool,
 
-    /// Whether the window has focus.
-    has_focus: bool,
+    /// The seats whose keyboard focus is on the window.
+    focused_seats: Vec<SeatId>,
 
     /// The scale factor of the window.
     scale_factor: f64,
@@ -120,6 +225,15 @@ pub struct WindowState {
     min_inner_size: LogicalSize<u32>,
     max_inner_size: Option<LogicalSize<u32>>,
 
+    /// The increments the size is snapped to, when no states were applied to the window.
+    resize_increments: Option<LogicalSize<u32>>,
+
+    /// The base of the resize increments, which is the min size without the borders.
+    base_size: LogicalSize<u32>,
+
+    /// The max size without the borders, which bounds the snapped sizes.
+    base_max_size: Option<LogicalSize<u32>>,
+
     /// The size of the window when no states were applied to it. The primary use for it
     /// is to fallback to original window size, before it was maximized, if the compositor
     /// sends `None` for the new size in the configure.
@@ -132,6 +246,38 @@ pub struct WindowState {
     ///
     /// The value is the serial of the event triggered moved.
     has_pending_move: Option<u32>,
+
+    /// The state of the frame callback, which paces the redraws.
+    frame_callback_state: FrameCallbackState,
+
+    /// The presentation time, to report when the frames are shown.
+    presentation: Option<WpPresentation>,
+}
+
+/// The state of the frame callback.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FrameCallbackState {
+    /// No frame callback was requested.
+    #[default]
+    None,
+    /// The frame callback was requested, but not yet received.
+    Requested,
+    /// The frame callback was received, but not yet processed.
+    Received,
+}
+
+impl FrameCallbackState {
+    /// Start delivering the redraws, unless a frame callback is still pending.
+    ///
+    /// Returns whether the redraws can be delivered, the state being reset when they can.
+    pub fn begin_redraw(&mut self) -> bool {
+        if *self == FrameCallbackState::Requested {
+            false
+        } else {
+            *self = FrameCallbackState::None;
+            true
+        }
+    }
 }
 
 /// The state of the cursor grabs.
@@ -155,10 +301,7 @@ impl GrabState {
 
 impl WindowState {
     /// Apply closure on the given pointer.
-    fn apply_on_poiner<F: Fn(&ThemedPointer<WinitPointerData>, &WinitPointerData)>(
-        &self,
-        callback: F,
-    ) {
+    fn apply_on_poiner<F: Fn(&ThemedPointer, &WinitPointerData)>(&self, callback: F) {
         self.pointers
             .iter()
             .filter_map(Weak::upgrade)
@@ -230,11 +373,27 @@ impl WindowState {
 
         // XXX Set the configure before doing a resize.
         self.last_configure = Some(configure);
+        self.configured = true;
 
         // XXX Update the new size right away.
         self.resize(new_size);
 
-        new_size
+        // The size could be snapped to the resize increments.
+        self.size
+    }
+
+    /// Apply the configure of a popup, which is always sized by the compositor.
+    pub fn configure_popup(&mut self, configure: PopupConfigure) -> LogicalSize<u32> {
+        let new_size = if configure.width > 0 && configure.height > 0 {
+            (configure.width as u32, configure.height as u32).into()
+        } else {
+            self.size
+        };
+
+        self.configured = true;
+        self.resize(new_size);
+
+        self.size
     }
 
     #[inline]
@@ -244,7 +403,10 @@ impl WindowState {
 
     /// Start interacting drag resize.
     pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
 
         // TODO(kchibisov) handle touch serials.
         self.apply_on_poiner(|_, data| {
@@ -258,7 +420,10 @@ impl WindowState {
 
     /// Start the window drag.
     pub fn drag_window(&self) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
         // TODO(kchibisov) handle touch serials.
         self.apply_on_poiner(|_, data| {
             let serial = data.latest_button_serial();
@@ -279,14 +444,16 @@ impl WindowState {
         window_id: WindowId,
         updates: &mut Vec<WindowCompositorUpdate>,
     ) -> Option<bool> {
+        // Popups never have a frame.
+        let window = self.window.toplevel()?;
         match self.frame.as_mut()?.on_click(click, pressed)? {
-            FrameAction::Minimize => self.window.set_minimized(),
-            FrameAction::Maximize => self.window.set_maximized(),
-            FrameAction::UnMaximize => self.window.unset_maximized(),
+            FrameAction::Minimize => window.set_minimized(),
+            FrameAction::Maximize => window.set_maximized(),
+            FrameAction::UnMaximize => window.unset_maximized(),
             FrameAction::Close => WinitState::queue_close(updates, window_id),
             FrameAction::Move => self.has_pending_move = Some(serial),
-            FrameAction::Resize(edge) => self.window.resize(seat, serial, edge),
-            FrameAction::ShowMenu(x, y) => self.window.show_window_menu(seat, serial, (x, y)),
+            FrameAction::Resize(edge) => window.resize(seat, serial, edge),
+            FrameAction::ShowMenu(x, y) => window.show_window_menu(seat, serial, (x, y)),
         };
 
         Some(false)
@@ -314,7 +481,9 @@ impl WindowState {
             // If we have a cursor change, that means that cursor is over the decorations,
             // so try to apply move.
             if let Some(serial) = cursor.is_some().then_some(serial).flatten() {
-                self.window.move_(seat, serial);
+                if let Some(window) = self.window.toplevel() {
+                    window.move_(seat, serial);
+                }
                 None
             } else {
                 cursor
@@ -352,10 +521,10 @@ impl WindowState {
         }
     }
 
-    /// Whether the window is focused.
+    /// Whether the window is focused by any seat.
     #[inline]
     pub fn has_focus(&self) -> bool {
-        self.has_focus
+        !self.focused_seats.is_empty()
     }
 
     /// Whether the IME is allowed.
@@ -373,7 +542,7 @@ impl WindowState {
     /// Whether the window received initial configure event from the compositor.
     #[inline]
     pub fn is_configured(&self) -> bool {
-        self.last_configure.is_some()
+        self.configured
     }
 
     #[inline]
@@ -397,7 +566,7 @@ impl WindowState {
         queue_handle: &QueueHandle<WinitState>,
         winit_state: &WinitState,
         size: LogicalSize<u32>,
-        window: Window,
+        window: WindowRole,
         theme: Option<Theme>,
     ) -> Self {
         let compositor = winit_state.compositor_state.clone();
@@ -410,6 +579,10 @@ impl WindowState {
             .fractional_scaling_manager
             .as_ref()
             .map(|fsm| fsm.fractional_scaling(window.wl_surface(), queue_handle));
+        let presentation = winit_state
+            .presentation
+            .as_ref()
+            .map(|presentation| presentation.global().clone());
 
         Self {
             compositor,
@@ -418,18 +591,33 @@ impl WindowState {
             csd_fails: false,
             decorate: true,
             cursor_grab_mode: GrabState::new(),
-            cursor_icon: CursorIcon::Default,
+            cursor: SelectedCursor::default(),
+            custom_cursors: winit_state.custom_cursors.clone(),
+            cursor_frame: 0,
+            cursor_frame_deadline: None,
             cursor_visible: true,
+            seat_cursors: Vec::new(),
+            seat_grabs: Vec::new(),
             fractional_scale,
             frame: None,
-            has_focus: false,
+            focused_seats: Vec::new(),
+            frame_callback_state: FrameCallbackState::None,
             ime_allowed: false,
             ime_purpose: ImePurpose::Normal,
             last_configure: None,
+            // Subsurfaces are sized by the client alone.
+            configured: matches!(window, WindowRole::Subsurface(_)),
+            subsurface_position: LogicalPosition::new(0, 0),
+            modal_owner: None,
+            modal_dialogs: 0,
             max_inner_size: None,
             min_inner_size: MIN_WINDOW_SIZE,
+            resize_increments: None,
+            base_size: MIN_WINDOW_SIZE,
+            base_max_size: None,
             pointer_constraints,
             pointers: Default::default(),
+            presentation,
             queue_handle: queue_handle.clone(),
             scale_factor: 1.,
             shm: winit_state.shm.wl_shm().clone(),
@@ -454,8 +642,48 @@ impl WindowState {
             .unwrap_or(self.size)
     }
 
+    /// Block the input of the owner of the modal dialog.
+    pub fn set_modal_owner(&mut self, owner: &Arc<Mutex<WindowState>>) {
+        owner.lock().unwrap().modal_dialogs += 1;
+        self.modal_owner = Some(Arc::downgrade(owner));
+    }
+
+    /// Unblock the input of the owner of the modal dialog.
+    pub fn release_modal_owner(&mut self) {
+        if let Some(owner) = self.modal_owner.take().and_then(|owner| owner.upgrade()) {
+            owner.lock().unwrap().modal_dialogs -= 1;
+        }
+    }
+
+    /// Whether the input of the window is blocked by a modal dialog.
+    #[inline]
+    pub fn is_input_disabled(&self) -> bool {
+        self.modal_dialogs > 0
+    }
+
+    /// Get the position of the subsurface relative to its parent.
+    #[inline]
+    pub fn subsurface_position(&self) -> Option<LogicalPosition<i32>> {
+        self.window.subsurface().map(|_| self.subsurface_position)
+    }
+
+    /// Move the subsurface, which is applied on the next commit of its parent.
+    pub fn set_subsurface_position(&mut self, position: LogicalPosition<i32>) {
+        if let Some(subsurface) = self.window.subsurface() {
+            subsurface.set_position(position.x, position.y);
+            self.subsurface_position = position;
+        }
+    }
+
+    /// Get the offset of the surface from the window geometry, which includes the frame.
+    #[inline]
+    pub fn frame_offset(&self) -> (i32, i32) {
+        let (x, y) = self.frame.as_ref().map_or((0, 0), |frame| frame.location());
+        (-x, -y)
+    }
+
     /// Register pointer on the top-level.
-    pub fn pointer_entered(&mut self, added: Weak<ThemedPointer<WinitPointerData>>) {
+    pub fn pointer_entered(&mut self, added: Weak<ThemedPointer>) {
         self.pointers.push(added);
         self.reload_cursor_style();
 
@@ -464,7 +692,7 @@ impl WindowState {
     }
 
     /// Pointer has left the top-level.
-    pub fn pointer_left(&mut self, removed: Weak<ThemedPointer<WinitPointerData>>) {
+    pub fn pointer_left(&mut self, removed: Weak<ThemedPointer>) {
         let mut new_pointers = Vec::new();
         for pointer in self.pointers.drain(..) {
             if let Some(pointer) = pointer.upgrade() {
@@ -477,6 +705,46 @@ impl WindowState {
         self.pointers = new_pointers;
     }
 
+    /// Get the current state of the frame callback.
+    #[inline]
+    pub fn frame_callback_state(&self) -> FrameCallbackState {
+        self.frame_callback_state
+    }
+
+    /// The frame callback was received, but not yet processed.
+    #[inline]
+    pub fn frame_callback_received(&mut self) {
+        self.frame_callback_state = FrameCallbackState::Received;
+    }
+
+    /// Start delivering the redraws, unless a frame callback is still pending.
+    #[inline]
+    pub fn begin_redraw(&mut self) -> bool {
+        self.frame_callback_state.begin_redraw()
+    }
+
+    /// Request a frame callback, unless one is already pending, to pace the redraws.
+    pub fn request_frame_callback(&mut self) {
+        if self.frame_callback_state != FrameCallbackState::Requested {
+            let surface = self.window.wl_surface();
+            surface.frame(&self.queue_handle, surface.clone());
+            self.frame_callback_state = FrameCallbackState::Requested;
+        }
+    }
+
+    /// Request the presentation feedback of the next commit, returning `false` when the
+    /// compositor doesn't support it.
+    pub fn request_presentation_feedback(&self) -> bool {
+        let presentation = match self.presentation.as_ref() {
+            Some(presentation) => presentation,
+            None => return false,
+        };
+
+        let surface = self.window.wl_surface();
+        presentation.feedback(surface, &self.queue_handle, wayland::make_wid(surface));
+        true
+    }
+
     /// Refresh the decorations frame if it's present returning whether the client should redraw.
     pub fn refresh_frame(&mut self) -> bool {
         if let Some(frame) = self.frame.as_mut() {
@@ -493,7 +761,7 @@ impl WindowState {
     /// Reload the cursor style on the given window.
     pub fn reload_cursor_style(&mut self) {
         if self.cursor_visible {
-            self.set_cursor(self.cursor_icon);
+            self.set_cursor(self.cursor.clone());
         } else {
             self.set_cursor_visible(self.cursor_visible);
         }
@@ -515,6 +783,19 @@ impl WindowState {
 
     /// Resize the window to the new inner size.
     pub fn resize(&mut self, inner_size: LogicalSize<u32>) {
+        // Snap the size to the resize increments, unless the compositor requires the exact size.
+        let inner_size = match self.resize_increments {
+            Some(increments)
+                if self
+                    .last_configure
+                    .as_ref()
+                    .map_or(true, Self::is_stateless) =>
+            {
+                snap_to_increments(inner_size, self.base_size, increments, self.base_max_size)
+            }
+            _ => inner_size,
+        };
+
         self.size = inner_size;
 
         // Update the stateless size.
@@ -544,12 +825,14 @@ impl WindowState {
         self.reload_transparency_hint();
 
         // Set the window geometry.
-        self.window.xdg_surface().set_window_geometry(
-            x,
-            y,
-            outer_size.width as i32,
-            outer_size.height as i32,
-        );
+        if let Some(xdg_surface) = self.window.xdg_surface() {
+            xdg_surface.set_window_geometry(
+                x,
+                y,
+                outer_size.width as i32,
+                outer_size.height as i32,
+            );
+        }
 
         // Update the target viewport, this is used if and only if fractional scaling is in use.
         if let Some(viewport) = self.viewport.as_ref() {
@@ -564,33 +847,132 @@ impl WindowState {
         self.scale_factor
     }
 
-    /// Set the cursor icon.
-    ///
-    /// Providing `None` will hide the cursor.
-    pub fn set_cursor(&mut self, cursor_icon: CursorIcon) {
-        self.cursor_icon = cursor_icon;
+    /// Set the cursor of the window, replacing the ones of the seats.
+    pub fn set_window_cursor(&mut self, cursor: SelectedCursor) {
+        self.seat_cursors.clear();
+        self.set_cursor(cursor);
+    }
+
+    /// Set the cursor.
+    pub fn set_cursor(&mut self, cursor: SelectedCursor) {
+        if cursor != self.cursor {
+            self.cursor_frame = 0;
+            self.cursor_frame_deadline = None;
+        }
+        self.cursor = cursor;
 
         if !self.cursor_visible {
             return;
         }
 
-        self.apply_on_poiner(|pointer, data| {
-            let surface = data.cursor_surface();
-            let scale_factor = surface.data::<SurfaceData>().unwrap().scale_factor();
-
-            if pointer
-                .set_cursor(
-                    &self.connection,
-                    cursor_icon.name(),
-                    &self.shm,
-                    surface,
-                    scale_factor,
-                )
-                .is_err()
+        match self.cursor {
+            SelectedCursor::Named(cursor_icon) => self.apply_on_poiner(|pointer, data| {
+                let cursor_icon = self.seat_cursor(data).unwrap_or(cursor_icon);
+                self.set_named_cursor(pointer, data, cursor_icon);
+            }),
+            SelectedCursor::Custom(ref cursor) => {
+                let mut custom_cursors = self.custom_cursors.lock().unwrap();
+                let buffer = match custom_cursors.buffer(cursor, self.cursor_frame) {
+                    Some(buffer) => buffer,
+                    None => {
+                        warn!("Failed to create the buffer for {:?}", cursor);
+                        return;
+                    }
+                };
+
+                let image = &cursor.inner.frames[self.cursor_frame].image;
+                self.apply_on_poiner(|pointer, data| {
+                    if let Some(cursor_icon) = self.seat_cursor(data) {
+                        return self.set_named_cursor(pointer, data, cursor_icon);
+                    }
+
+                    // The image is drawn at its logical size.
+                    let surface = data.cursor_surface();
+                    surface.set_buffer_scale(1);
+                    surface.attach(Some(buffer.wl_buffer()), 0, 0);
+                    surface.damage_buffer(0, 0, image.width as i32, image.height as i32);
+                    surface.commit();
+
+                    pointer.pointer().set_cursor(
+                        data.latest_enter_serial(),
+                        Some(surface),
+                        cursor.inner.hotspot_x as i32,
+                        cursor.inner.hotspot_y as i32,
+                    );
+                })
+            }
+        }
+    }
+
+    /// Set the named cursor on the pointer.
+    fn set_named_cursor(
+        &self,
+        pointer: &ThemedPointer,
+        data: &WinitPointerData,
+        cursor_icon: CursorIcon,
+    ) {
+        let surface = data.cursor_surface();
+        let scale_factor = surface.data::<SurfaceData>().unwrap().scale_factor();
+
+        if pointer
+            .set_cursor(
+                &self.connection,
+                cursor_icon.name(),
+                &self.shm,
+                surface,
+                scale_factor,
+            )
+            .is_err()
+        {
+            warn!("Failed to set cursor to {:?}", cursor_icon);
+        }
+    }
+
+    /// The cursor of the seat of the pointer, if it has its own.
+    fn seat_cursor(&self, data: &WinitPointerData) -> Option<CursorIcon> {
+        let seat = wayland::make_seat_id(data.seat());
+        self.seat_cursors
+            .iter()
+            .find_map(|&(cursor_seat, cursor_icon)| (cursor_seat == seat).then_some(cursor_icon))
+    }
+
+    /// Set the cursor of the seat, until the one of the window is set again.
+    pub fn set_seat_cursor(&mut self, seat: SeatId, cursor_icon: CursorIcon) {
+        self.seat_cursors
+            .retain(|(cursor_seat, _)| *cursor_seat != seat);
+        self.seat_cursors.push((seat, cursor_icon));
+        self.reload_cursor_style();
+    }
+
+    /// Show the next frame of the animated cursor once it's due, returning when the following
+    /// one is.
+    pub fn animate_cursor(&mut self, now: Instant) -> Option<Instant> {
+        let cursor = match self.cursor {
+            SelectedCursor::Custom(ref cursor)
+                if cursor.inner.frames.len() > 1
+                    && self.cursor_visible
+                    && !self.pointers.is_empty() =>
             {
-                warn!("Failed to set cursor to {:?}", cursor_icon);
+                cursor.clone()
             }
-        })
+            _ => {
+                self.cursor_frame_deadline = None;
+                return None;
+            }
+        };
+
+        match self.cursor_frame_deadline {
+            Some(deadline) if deadline > now => return Some(deadline),
+            Some(_) => {
+                self.cursor_frame = (self.cursor_frame + 1) % cursor.inner.frames.len();
+                self.set_cursor(SelectedCursor::Custom(cursor.clone()));
+            }
+            None => (),
+        }
+
+        let deadline = now + cursor.inner.frames[self.cursor_frame].delay;
+        self.cursor_frame_deadline = Some(deadline);
+        Some(deadline)
     }
 
     /// Set maximum inner window size.
@@ -599,6 +981,7 @@ impl WindowState {
         let mut size = size.unwrap_or(MIN_WINDOW_SIZE);
         size.width = size.width.max(MIN_WINDOW_SIZE.width);
         size.height = size.height.max(MIN_WINDOW_SIZE.height);
+        self.base_size = size;
 
         // Add the borders.
         let size = self
@@ -608,11 +991,14 @@ impl WindowState {
             .unwrap_or(size);
 
         self.min_inner_size = size;
-        self.window.set_min_size(Some(size.into()));
+        if let Some(window) = self.window.toplevel() {
+            window.set_min_size(Some(size.into()));
+        }
     }
 
     /// Set maximum inner window size.
     pub fn set_max_inner_size(&mut self, size: Option<LogicalSize<u32>>) {
+        self.base_max_size = size;
         let size = size.map(|size| {
             self.frame
                 .as_ref()
@@ -621,7 +1007,26 @@ impl WindowState {
         });
 
         self.max_inner_size = size;
-        self.window.set_max_size(size.map(Into::into));
+        if let Some(window) = self.window.toplevel() {
+            window.set_max_size(size.map(Into::into));
+        }
+    }
+
+    /// Set the increments the size is snapped to, starting from the min size.
+    ///
+    /// The current size is snapped to the new increments, returns whether it has changed.
+    pub fn set_resize_increments(&mut self, increments: Option<LogicalSize<u32>>) -> bool {
+        self.resize_increments = increments;
+
+        let size = self.size;
+        self.resize(size);
+        self.size != size
+    }
+
+    /// The increments the size is snapped to.
+    #[inline]
+    pub fn resize_increments(&self) -> Option<LogicalSize<u32>> {
+        self.resize_increments
     }
 
     /// Set the CSD theme.
@@ -641,8 +1046,26 @@ impl WindowState {
 
     /// Set the cursor grabbing state on the top-level.
     pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) -> Result<(), ExternalError> {
-        // Replace the user grabbing mode.
+        // Replace the user grabbing mode, along with the ones of the seats.
         self.cursor_grab_mode.user_grab_mode = mode;
+        self.seat_grabs.clear();
+        self.set_cursor_grab_inner(mode)
+    }
+
+    /// Set the cursor grabbing state of the seat, until the one of the window is set again.
+    pub fn set_seat_cursor_grab(
+        &mut self,
+        seat: SeatId,
+        mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        if self.pointer_constraints.is_none() && mode != CursorGrabMode::None {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.seat_grabs.retain(|(grab_seat, _)| *grab_seat != seat);
+        self.seat_grabs.push((seat, mode));
+
+        let mode = self.cursor_grab_mode.user_grab_mode;
         self.set_cursor_grab_inner(mode)
     }
 
@@ -661,32 +1084,33 @@ impl WindowState {
         };
 
         // Replace the current mode.
-        let old_mode = std::mem::replace(&mut self.cursor_grab_mode.current_grab_mode, mode);
-
-        match old_mode {
-            CursorGrabMode::None => (),
-            CursorGrabMode::Confined => self.apply_on_poiner(|_, data| {
-                data.unconfine_pointer();
-            }),
-            CursorGrabMode::Locked => {
-                self.apply_on_poiner(|_, data| data.unlock_pointer());
-            }
-        }
+        self.cursor_grab_mode.current_grab_mode = mode;
 
         let surface = self.window.wl_surface();
-        match mode {
-            CursorGrabMode::Locked => self.apply_on_poiner(|pointer, data| {
-                let pointer = pointer.pointer();
-                data.lock_pointer(pointer_constraints, surface, pointer, &self.queue_handle)
-            }),
-            CursorGrabMode::Confined => self.apply_on_poiner(|pointer, data| {
-                let pointer = pointer.pointer();
-                data.confine_pointer(pointer_constraints, surface, pointer, &self.queue_handle)
-            }),
-            CursorGrabMode::None => {
-                // Current lock/confine was already removed.
+        self.apply_on_poiner(|pointer, data| {
+            // The seats with their own mode keep it.
+            let seat = wayland::make_seat_id(data.seat());
+            let mode = self
+                .seat_grabs
+                .iter()
+                .find_map(|&(grab_seat, mode)| (grab_seat == seat).then_some(mode))
+                .unwrap_or(mode);
+
+            // Remove the current lock/confine before applying the mode.
+            data.unlock_pointer();
+            data.unconfine_pointer();
+
+            let pointer = pointer.pointer();
+            match mode {
+                CursorGrabMode::Locked => {
+                    data.lock_pointer(pointer_constraints, surface, pointer, &self.queue_handle)
+                }
+                CursorGrabMode::Confined => {
+                    data.confine_pointer(pointer_constraints, surface, pointer, &self.queue_handle)
+                }
+                CursorGrabMode::None => (),
             }
-        }
+        });
 
         Ok(())
     }
@@ -718,7 +1142,7 @@ impl WindowState {
         self.cursor_visible = cursor_visible;
 
         if self.cursor_visible {
-            self.set_cursor(self.cursor_icon);
+            self.set_cursor(self.cursor.clone());
         } else {
             for pointer in self.pointers.iter().filter_map(|pointer| pointer.upgrade()) {
                 let latest_enter_serial = pointer.pointer().winit_data().latest_enter_serial();
@@ -739,6 +1163,12 @@ impl WindowState {
 
         self.decorate = decorate;
 
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            // Popups are never decorated.
+            None => return,
+        };
+
         match self
             .last_configure
             .as_ref()
@@ -746,12 +1176,9 @@ impl WindowState {
         {
             Some(DecorationMode::Server) if !self.decorate => {
                 // To disable decorations we should request client and hide the frame.
-                self.window
-                    .request_decoration_mode(Some(DecorationMode::Client))
+                window.request_decoration_mode(Some(DecorationMode::Client))
             }
-            _ if self.decorate => self
-                .window
-                .request_decoration_mode(Some(DecorationMode::Server)),
+            _ if self.decorate => window.request_decoration_mode(Some(DecorationMode::Server)),
             _ => (),
         }
 
@@ -762,12 +1189,19 @@ impl WindowState {
         }
     }
 
-    /// Mark that the window has focus.
+    /// Mark that the keyboard focus of the seat has entered or left the window, returning whether
+    /// the focus of the window as a whole has changed.
     ///
     /// Should be used from routine that sends focused event.
-    #[inline]
-    pub fn set_has_focus(&mut self, has_focus: bool) {
-        self.has_focus = has_focus;
+    pub fn set_seat_focus(&mut self, seat: SeatId, focused: bool) -> bool {
+        let had_focus = self.has_focus();
+        self.focused_seats
+            .retain(|focused_seat| *focused_seat != seat);
+        if focused {
+            self.focused_seats.push(seat);
+        }
+
+        had_focus != self.has_focus()
     }
 
     /// Returns `true` if the requested state was applied.
@@ -847,7 +1281,9 @@ impl WindowState {
             frame.set_title(&title);
         }
 
-        self.window.set_title(&title);
+        if let Some(window) = self.window.toplevel() {
+            window.set_title(&title);
+        }
         self.title = title;
     }
 
@@ -907,6 +1343,39 @@ impl From<ResizeDirection> for ResizeEdge {
     }
 }
 
+/// Snap the size down to the `increments` counted from the `base` size, after bounding it by the
+/// `base` and `max` sizes. The axes with a zero increment are only bounded.
+fn snap_to_increments(
+    size: LogicalSize<u32>,
+    base: LogicalSize<u32>,
+    increments: LogicalSize<u32>,
+    max: Option<LogicalSize<u32>>,
+) -> LogicalSize<u32> {
+    let snap = |size: u32, base: u32, max: Option<u32>, increment: u32| {
+        let size = max.map_or(size, |max| size.min(max)).max(base);
+        if increment > 0 {
+            size - (size - base) % increment
+        } else {
+            size
+        }
+    };
+
+    LogicalSize::new(
+        snap(
+            size.width,
+            base.width,
+            max.map(|max| max.width),
+            increments.width,
+        ),
+        snap(
+            size.height,
+            base.height,
+            max.map(|max| max.height),
+            increments.height,
+        ),
+    )
+}
+
 // XXX rust doesn't allow from `Option`.
 #[cfg(feature = "sctk-adwaita")]
 fn into_sctk_adwaita_config(theme: Option<Theme>) -> sctk_adwaita::FrameConfig {
@@ -916,3 +1385,61 @@ fn into_sctk_adwaita_config(theme: Option<Theme>) -> sctk_adwaita::FrameConfig {
         None => sctk_adwaita::FrameConfig::auto(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redraws_are_held_until_the_frame_callback() {
+        let mut state = FrameCallbackState::default();
+        assert!(state.begin_redraw());
+        assert_eq!(state, FrameCallbackState::None);
+
+        // The redraws are held while the frame callback is pending.
+        state = FrameCallbackState::Requested;
+        assert!(!state.begin_redraw());
+        assert!(!state.begin_redraw());
+        assert_eq!(state, FrameCallbackState::Requested);
+
+        // The received frame callback releases them, once.
+        state = FrameCallbackState::Received;
+        assert!(state.begin_redraw());
+        assert_eq!(state, FrameCallbackState::None);
+    }
+
+    #[test]
+    fn snaps_from_the_base_size() {
+        let base = LogicalSize::new(100, 50);
+        let increments = LogicalSize::new(10, 20);
+        let snap = |width, height| {
+            snap_to_increments(LogicalSize::new(width, height), base, increments, None)
+        };
+
+        assert_eq!(snap(100, 50), LogicalSize::new(100, 50));
+        assert_eq!(snap(109, 69), LogicalSize::new(100, 50));
+        assert_eq!(snap(115, 95), LogicalSize::new(110, 90));
+    }
+
+    #[test]
+    fn snapped_size_is_bounded() {
+        let base = LogicalSize::new(100, 50);
+        let increments = LogicalSize::new(10, 20);
+        let max = Some(LogicalSize::new(135, 95));
+        let snap = |width, height| {
+            snap_to_increments(LogicalSize::new(width, height), base, increments, max)
+        };
+
+        assert_eq!(snap(20, 10), LogicalSize::new(100, 50));
+        assert_eq!(snap(500, 500), LogicalSize::new(130, 90));
+    }
+
+    #[test]
+    fn zero_increments_are_not_snapped() {
+        let base = LogicalSize::new(100, 50);
+        let increments = LogicalSize::new(0, 20);
+        let size = snap_to_increments(LogicalSize::new(123, 77), base, increments, None);
+
+        assert_eq!(size, LogicalSize::new(123, 70));
+    }
+}
@@ -13,7 +13,7 @@ macro_rules! atom_manager {
 
         /// Indices into the `Atoms` struct.
         #[derive(Copy, Clone, Debug)]
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
         pub(crate) enum AtomName {
             $($name,)*
         }
@@ -55,6 +55,7 @@ atom_manager! {
     _NET_WM_STATE_HIDDEN,
     _NET_WM_STATE_MAXIMIZED_HORZ,
     _NET_WM_STATE_MAXIMIZED_VERT,
+    _NET_WM_STATE_MODAL,
     _NET_WM_WINDOW_TYPE,
 
     // Activation atoms.
@@ -85,13 +86,39 @@ atom_manager! {
     XdndDrop,
     XdndPosition,
     XdndStatus,
-    XdndActionPrivate,
+    XdndActionCopy,
+    XdndActionMove,
+    XdndActionLink,
+    XdndActionAsk,
     XdndSelection,
     XdndFinished,
     XdndTypeList,
+    XdndActionList,
     TextUriList: b"text/uri-list",
     None: b"None",
 
+    // XInput Valuator Labels
+    AbsPressure: b"Abs Pressure",
+    AbsTiltX: b"Abs Tilt X",
+    AbsTiltY: b"Abs Tilt Y",
+    AbsRotationZ: b"Abs Rotation Z",
+    AbsDistance: b"Abs Distance",
+    AbsMTPressure: b"Abs MT Pressure",
+    AbsMTTouchMajor: b"Abs MT Touch Major",
+    AbsMTTouchMinor: b"Abs MT Touch Minor",
+    AbsMTPositionX: b"Abs MT Position X",
+
+    // XInput Device Properties
+    DeviceProductID: b"Device Product ID",
+
+    // Selection Atoms
+    CLIPBOARD,
+    PRIMARY,
+    TARGETS,
+    INCR,
+    _WINIT_CLIPBOARD,
+    _WINIT_PRIMARY,
+
     // Miscellaneous Atoms
     _GTK_THEME_VARIANT,
     _MOTIF_WM_HINTS,
@@ -0,0 +1,776 @@
+//! Clipboard and primary selection handling.
+//!
+//! Selections are owned by a hidden window, which is also the requestor when the data is being
+//! read from other clients. See ICCCM section 2 for the details of the protocol.
+//!
+//! The same window owns the `XdndSelection` while the data is dragged out of one of our windows.
+//!
+//! Data which doesn't fit into a single request is sent with the `INCR` mechanism, one chunk per
+//! deletion of the property by the requestor. Transfers in both directions time out when the
+//! other client stops responding, so a misbehaving owner can't block the later requests.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::xproto::{self, ConnectionExt as _},
+};
+
+use super::{atoms::*, CookieResultExt, X11Error, XConnection};
+use crate::{
+    clipboard::{ClipboardContent, ClipboardEvent, ClipboardKind, TEXT_MIME_TYPE},
+    event_loop::AsyncRequestSerial,
+};
+
+/// Amount of data to read from the property in one request, in 32-bit chunks.
+const READ_CHUNK_SIZE: u32 = u32::MAX / 4;
+
+/// Time to wait for the other client before giving up on a transfer.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) struct Clipboard {
+    xconn: Arc<XConnection>,
+
+    /// The window which owns the selections and receives the data from other clients.
+    window: xproto::Window,
+
+    clipboard: SelectionState,
+    primary: SelectionState,
+
+    /// The content which is being dragged out of one of our windows.
+    drag: Option<ClipboardContent>,
+
+    /// Interned MIME types.
+    atoms: HashMap<String, xproto::Atom>,
+
+    /// The outgoing incremental transfers.
+    transfers: Vec<IncrTransfer>,
+}
+
+#[derive(Default)]
+struct SelectionState {
+    /// The content which is offered to other clients, if we own the selection.
+    content: Option<ClipboardContent>,
+
+    /// The pending requests, the first one is in flight.
+    requests: VecDeque<Request>,
+
+    /// The data received so far when the transfer is incremental.
+    incr: Option<Vec<u8>>,
+
+    /// The target requested for the request in flight.
+    target: xproto::Atom,
+
+    /// The time at which the request in flight fails, unless the owner responds.
+    deadline: Option<Instant>,
+}
+
+/// The data being sent to another client in chunks.
+struct IncrTransfer {
+    requestor: xproto::Window,
+    property: xproto::Atom,
+    target: xproto::Atom,
+    data: Arc<[u8]>,
+    offset: usize,
+
+    /// Our event mask on the requestor, restored once the transfer is done.
+    event_mask: xproto::EventMask,
+
+    deadline: Instant,
+}
+
+struct Request {
+    serial: AsyncRequestSerial,
+    kind: RequestKind,
+}
+
+enum RequestKind {
+    Data(String),
+    MimeTypes,
+}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, root: xproto::Window) -> Result<Self, X11Error> {
+        let conn = xconn.xcb_connection();
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            xproto::WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &xproto::CreateWindowAux::new().event_mask(xproto::EventMask::PROPERTY_CHANGE),
+        )?
+        .ignore_error();
+
+        Ok(Self {
+            xconn,
+            window,
+            clipboard: Default::default(),
+            primary: Default::default(),
+            drag: None,
+            atoms: Default::default(),
+            transfers: Vec::new(),
+        })
+    }
+
+    /// The window used for the selection transfers.
+    #[inline]
+    pub fn window(&self) -> xproto::Window {
+        self.window
+    }
+
+    /// Offer the content through the selection.
+    ///
+    /// Returns whether the ownership of the selection was acquired.
+    pub fn set_content(
+        &mut self,
+        kind: ClipboardKind,
+        content: ClipboardContent,
+    ) -> Result<bool, X11Error> {
+        let selection = self.selection_atom(kind);
+        let conn = self.xconn.xcb_connection();
+
+        // Empty content releases the selection.
+        let owner = if content.is_empty() {
+            x11rb::NONE
+        } else {
+            self.window
+        };
+        conn.set_selection_owner(owner, selection, self.xconn.timestamp())?
+            .ignore_error();
+
+        if conn.get_selection_owner(selection)?.reply()?.owner != owner {
+            return Ok(false);
+        }
+
+        // Intern the atoms upfront, so they're ready once requests arrive.
+        for mime_type in content.advertised_mime_types() {
+            self.intern(&mime_type)?;
+        }
+
+        self.state_mut(kind).content = (!content.is_empty()).then_some(content);
+        Ok(true)
+    }
+
+    /// Offer the dragged content through the `XdndSelection`.
+    ///
+    /// Returns the atoms of the MIME types, in the order of preference.
+    pub fn set_drag_content(
+        &mut self,
+        content: Option<ClipboardContent>,
+    ) -> Result<Vec<xproto::Atom>, X11Error> {
+        let conn = self.xconn.xcb_connection();
+        let owner = if content.is_some() {
+            self.window
+        } else {
+            x11rb::NONE
+        };
+        conn.set_selection_owner(
+            owner,
+            self.xconn.atoms()[XdndSelection],
+            self.xconn.timestamp(),
+        )?
+        .ignore_error();
+
+        let mut types = Vec::new();
+        for mime_type in content
+            .iter()
+            .flat_map(|content| content.advertised_mime_types())
+        {
+            types.push(self.intern(&mime_type)?);
+        }
+
+        self.drag = content;
+        Ok(types)
+    }
+
+    pub fn request_data(
+        &mut self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, X11Error> {
+        self.request(kind, RequestKind::Data(mime_type.to_owned()))
+    }
+
+    pub fn request_mime_types(
+        &mut self,
+        kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, X11Error> {
+        self.request(kind, RequestKind::MimeTypes)
+    }
+
+    fn request(
+        &mut self,
+        kind: ClipboardKind,
+        request: RequestKind,
+    ) -> Result<AsyncRequestSerial, X11Error> {
+        let serial = AsyncRequestSerial::get();
+        self.state_mut(kind).requests.push_back(Request {
+            serial,
+            kind: request,
+        });
+
+        if self.state(kind).requests.len() == 1 {
+            self.convert_selection(kind)?;
+        }
+
+        Ok(serial)
+    }
+
+    /// Ask the selection owner to convert the selection for the first pending request.
+    fn convert_selection(&mut self, kind: ClipboardKind) -> Result<(), X11Error> {
+        let target = match self.state(kind).requests.front() {
+            Some(Request {
+                kind: RequestKind::Data(mime_type),
+                ..
+            }) => {
+                // Legacy X11 clients don't know about MIME types for text.
+                if mime_type == TEXT_MIME_TYPE {
+                    self.xconn.atoms()[UTF8_STRING]
+                } else {
+                    let mime_type = mime_type.clone();
+                    self.intern(&mime_type)?
+                }
+            }
+            Some(Request {
+                kind: RequestKind::MimeTypes,
+                ..
+            }) => self.xconn.atoms()[TARGETS],
+            None => {
+                self.state_mut(kind).deadline = None;
+                return Ok(());
+            }
+        };
+
+        self.xconn
+            .xcb_connection()
+            .convert_selection(
+                self.window,
+                self.selection_atom(kind),
+                target,
+                self.property_atom(kind),
+                x11rb::CURRENT_TIME,
+            )?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        let state = self.state_mut(kind);
+        state.target = target;
+        state.deadline = Some(Instant::now() + TRANSFER_TIMEOUT);
+
+        Ok(())
+    }
+
+    /// Handle the `SelectionNotify` event for our window.
+    pub fn handle_selection_notify(
+        &mut self,
+        selection: xproto::Atom,
+        target: xproto::Atom,
+        property: xproto::Atom,
+    ) -> Vec<ClipboardEvent> {
+        let kind = match self.selection_kind(selection) {
+            Some(kind) => kind,
+            None => return Vec::new(),
+        };
+
+        // Ignore the late replies to the requests which have timed out.
+        let state = self.state(kind);
+        if state.deadline.is_none() || state.incr.is_some() || state.target != target {
+            return Vec::new();
+        }
+
+        if property == x11rb::NONE {
+            return self.finish_request(kind, None);
+        }
+
+        let (ty, data) = match self.read_property(property) {
+            Ok(reply) => reply,
+            Err(err) => {
+                warn!("Failed to read the selection data: {}", err);
+                return self.finish_request(kind, None);
+            }
+        };
+
+        if ty == self.xconn.atoms()[INCR] {
+            // Deleting the property by reading it starts the transfer.
+            let state = self.state_mut(kind);
+            state.incr = Some(Vec::new());
+            state.deadline = Some(Instant::now() + TRANSFER_TIMEOUT);
+            return Vec::new();
+        }
+
+        self.finish_request(kind, Some(data))
+    }
+
+    /// Handle the `PropertyNotify` event for any window.
+    pub fn handle_property_notify(
+        &mut self,
+        window: xproto::Window,
+        property: xproto::Atom,
+        state: xproto::Property,
+    ) -> Vec<ClipboardEvent> {
+        if window != self.window {
+            if state == xproto::Property::DELETE {
+                if let Err(err) = self.continue_transfer(window, property) {
+                    warn!("Failed to send the incremental selection data: {}", err);
+                }
+            }
+            return Vec::new();
+        }
+
+        let kind = if property == self.property_atom(ClipboardKind::Clipboard) {
+            ClipboardKind::Clipboard
+        } else if property == self.property_atom(ClipboardKind::Primary) {
+            ClipboardKind::Primary
+        } else {
+            return Vec::new();
+        };
+
+        if state != xproto::Property::NEW_VALUE || self.state(kind).incr.is_none() {
+            return Vec::new();
+        }
+
+        match self.read_property(property) {
+            Ok((_, chunk)) if chunk.is_empty() => {
+                let data = self.state_mut(kind).incr.take();
+                self.finish_request(kind, data)
+            }
+            Ok((_, chunk)) => {
+                let state = self.state_mut(kind);
+                state.incr.as_mut().unwrap().extend(chunk);
+                state.deadline = Some(Instant::now() + TRANSFER_TIMEOUT);
+                Vec::new()
+            }
+            Err(err) => {
+                warn!("Failed to read the incremental selection data: {}", err);
+                self.state_mut(kind).incr = None;
+                self.finish_request(kind, None)
+            }
+        }
+    }
+
+    /// Handle the `SelectionClear` event for our window.
+    pub fn handle_selection_clear(&mut self, selection: xproto::Atom) -> Option<ClipboardEvent> {
+        let kind = self.selection_kind(selection)?;
+        self.state_mut(kind)
+            .content
+            .take()
+            .map(|_| ClipboardEvent::ContentLost(kind))
+    }
+
+    /// Handle the `SelectionRequest` event from another client.
+    pub fn handle_selection_request(
+        &mut self,
+        requestor: xproto::Window,
+        selection: xproto::Atom,
+        target: xproto::Atom,
+        property: xproto::Atom,
+        time: xproto::Timestamp,
+    ) -> Result<(), X11Error> {
+        // Obsolete clients may set property to `None`.
+        let property = if property == x11rb::NONE {
+            target
+        } else {
+            property
+        };
+
+        let content = match self.selection_kind(selection) {
+            Some(kind) => self.state(kind).content.clone(),
+            None if selection == self.xconn.atoms()[XdndSelection] => self.drag.clone(),
+            None => None,
+        };
+
+        let replied = match content {
+            Some(content) => self.write_selection(&content, requestor, target, property)?,
+            None => false,
+        };
+
+        let event = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time,
+            requestor,
+            selection,
+            target,
+            property: if replied { property } else { x11rb::NONE },
+        };
+
+        self.xconn
+            .xcb_connection()
+            .send_event(false, requestor, xproto::EventMask::NO_EVENT, event)?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
+    /// Write the `content` for the `target` into the `property` of `requestor`.
+    ///
+    /// Returns whether the target was available.
+    fn write_selection(
+        &mut self,
+        content: &ClipboardContent,
+        requestor: xproto::Window,
+        target: xproto::Atom,
+        property: xproto::Atom,
+    ) -> Result<bool, X11Error> {
+        let atoms = self.xconn.atoms();
+        if target == atoms[TARGETS] {
+            let mut targets = vec![atoms[TARGETS]];
+            for mime_type in content.advertised_mime_types() {
+                targets.push(self.intern(&mime_type)?);
+            }
+
+            self.xconn
+                .change_property(
+                    requestor,
+                    property,
+                    xproto::AtomEnum::ATOM.into(),
+                    xproto::PropMode::REPLACE,
+                    &targets,
+                )?
+                .ignore_error();
+            return Ok(true);
+        }
+
+        let mime_type = match self.atoms.iter().find(|(_, atom)| **atom == target) {
+            Some((mime_type, _)) => mime_type.clone(),
+            None => return Ok(false),
+        };
+
+        let data = match content.resolve(&mime_type) {
+            Some(data) => data,
+            None => return Ok(false),
+        };
+
+        let max_size = self.xconn.xcb_connection().maximum_request_bytes();
+        if data.len() + 32 > max_size {
+            self.start_transfer(data, requestor, target, property)?;
+            return Ok(true);
+        }
+
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                target,
+                xproto::PropMode::REPLACE,
+                &data,
+            )?
+            .ignore_error();
+
+        Ok(true)
+    }
+
+    /// Announce the incremental transfer of `data`, which is sent once the requestor deletes
+    /// the property.
+    fn start_transfer(
+        &mut self,
+        data: Arc<[u8]>,
+        requestor: xproto::Window,
+        target: xproto::Atom,
+        property: xproto::Atom,
+    ) -> Result<(), X11Error> {
+        // A new request for the same property replaces the previous transfer.
+        self.transfers
+            .retain(|transfer| (transfer.requestor, transfer.property) != (requestor, property));
+
+        let conn = self.xconn.xcb_connection();
+        let event_mask = match self.transfers.iter().find(|t| t.requestor == requestor) {
+            Some(transfer) => transfer.event_mask,
+            None => {
+                conn.get_window_attributes(requestor)?
+                    .reply()?
+                    .your_event_mask
+            }
+        };
+        conn.change_window_attributes(
+            requestor,
+            &xproto::ChangeWindowAttributesAux::new()
+                .event_mask(event_mask | xproto::EventMask::PROPERTY_CHANGE),
+        )?
+        .ignore_error();
+
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                self.xconn.atoms()[INCR],
+                xproto::PropMode::REPLACE,
+                &[data.len() as u32],
+            )?
+            .ignore_error();
+
+        self.transfers.push(IncrTransfer {
+            requestor,
+            property,
+            target,
+            data,
+            offset: 0,
+            event_mask,
+            deadline: Instant::now() + TRANSFER_TIMEOUT,
+        });
+
+        Ok(())
+    }
+
+    /// Send the next chunk of the transfer once the requestor has deleted the property.
+    fn continue_transfer(
+        &mut self,
+        requestor: xproto::Window,
+        property: xproto::Atom,
+    ) -> Result<(), X11Error> {
+        let index = match self
+            .transfers
+            .iter()
+            .position(|t| (t.requestor, t.property) == (requestor, property))
+        {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        let chunk_size = self.xconn.xcb_connection().maximum_request_bytes() / 4;
+        let transfer = &mut self.transfers[index];
+        let end = transfer.data.len().min(transfer.offset + chunk_size);
+
+        // The empty chunk, written once all the data was sent, ends the transfer.
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                transfer.target,
+                xproto::PropMode::REPLACE,
+                &transfer.data[transfer.offset..end],
+            )?
+            .ignore_error();
+
+        if transfer.offset == end {
+            let transfer = self.transfers.swap_remove(index);
+            self.finish_transfer(&transfer)?;
+        } else {
+            transfer.offset = end;
+            transfer.deadline = Instant::now() + TRANSFER_TIMEOUT;
+        }
+
+        self.xconn.flush_requests()?;
+        Ok(())
+    }
+
+    /// Restore our event mask on the requestor, unless other transfers are still using it.
+    fn finish_transfer(&self, transfer: &IncrTransfer) -> Result<(), X11Error> {
+        if self
+            .transfers
+            .iter()
+            .any(|t| t.requestor == transfer.requestor)
+        {
+            return Ok(());
+        }
+
+        self.xconn
+            .xcb_connection()
+            .change_window_attributes(
+                transfer.requestor,
+                &xproto::ChangeWindowAttributesAux::new().event_mask(transfer.event_mask),
+            )?
+            .ignore_error();
+        Ok(())
+    }
+
+    /// The earliest time at which a transfer times out.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let requests = [&self.clipboard, &self.primary]
+            .into_iter()
+            .filter_map(|state| state.deadline);
+        let transfers = self.transfers.iter().map(|transfer| transfer.deadline);
+        requests.chain(transfers).min()
+    }
+
+    /// Abort the transfers which have timed out, failing the requests in flight.
+    pub fn expire(&mut self, now: Instant) -> Vec<ClipboardEvent> {
+        let mut events = Vec::new();
+        for kind in [ClipboardKind::Clipboard, ClipboardKind::Primary] {
+            if self
+                .state(kind)
+                .deadline
+                .map_or(false, |deadline| deadline <= now)
+            {
+                warn!("The owner of the selection didn't respond in time");
+                self.state_mut(kind).incr = None;
+                events.extend(self.finish_request(kind, None));
+            }
+        }
+
+        let (expired, transfers) = std::mem::take(&mut self.transfers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|transfer| transfer.deadline <= now);
+        self.transfers = transfers;
+        for transfer in expired {
+            warn!("The requestor of the selection didn't respond in time");
+            if let Err(err) = self.finish_transfer(&transfer) {
+                warn!("Failed to finish the selection transfer: {}", err);
+            }
+        }
+
+        events
+    }
+
+    /// Complete the first pending request and start the next one.
+    fn finish_request(
+        &mut self,
+        kind: ClipboardKind,
+        data: Option<Vec<u8>>,
+    ) -> Vec<ClipboardEvent> {
+        let request = match self.state_mut(kind).requests.pop_front() {
+            Some(request) => request,
+            None => return Vec::new(),
+        };
+
+        let event = match (request.kind, data) {
+            (RequestKind::Data(mime_type), Some(data)) => ClipboardEvent::Data {
+                serial: request.serial,
+                kind,
+                mime_type,
+                data,
+            },
+            (RequestKind::MimeTypes, Some(data)) => ClipboardEvent::MimeTypes {
+                serial: request.serial,
+                kind,
+                mime_types: self.atom_names(&data),
+            },
+            (_, None) => ClipboardEvent::RequestFailed {
+                serial: request.serial,
+                kind,
+            },
+        };
+
+        let mut events = vec![event];
+
+        // Start the next request, failing everything if the connection is broken.
+        if let Err(err) = self.convert_selection(kind) {
+            warn!("Failed to request the selection: {}", err);
+            events.extend(self.state_mut(kind).requests.drain(..).map(|request| {
+                ClipboardEvent::RequestFailed {
+                    serial: request.serial,
+                    kind,
+                }
+            }));
+        }
+
+        events
+    }
+
+    /// Read and delete the property from our window.
+    fn read_property(&self, property: xproto::Atom) -> Result<(xproto::Atom, Vec<u8>), X11Error> {
+        let reply = self
+            .xconn
+            .xcb_connection()
+            .get_property(
+                true,
+                self.window,
+                property,
+                xproto::AtomEnum::ANY,
+                0,
+                READ_CHUNK_SIZE,
+            )?
+            .reply()?;
+
+        Ok((reply.type_, reply.value))
+    }
+
+    /// Resolve the names of the atoms stored in the 32-bit property data.
+    fn atom_names(&mut self, data: &[u8]) -> Vec<String> {
+        let conn = self.xconn.xcb_connection();
+        let cookies: Vec<_> = data
+            .chunks_exact(4)
+            .map(|atom| u32::from_ne_bytes(atom.try_into().unwrap()))
+            .filter(|&atom| atom != x11rb::NONE)
+            .filter_map(|atom| Some((atom, conn.get_atom_name(atom).ok()?)))
+            .collect();
+
+        let mut names = Vec::with_capacity(cookies.len());
+        for (atom, cookie) in cookies {
+            if let Ok(reply) = cookie.reply() {
+                let name = String::from_utf8_lossy(&reply.name).into_owned();
+                self.atoms.insert(name.clone(), atom);
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    fn intern(&mut self, name: &str) -> Result<xproto::Atom, X11Error> {
+        if let Some(&atom) = self.atoms.get(name) {
+            return Ok(atom);
+        }
+
+        let atom = self
+            .xconn
+            .xcb_connection()
+            .intern_atom(false, name.as_bytes())?
+            .reply()?
+            .atom;
+        self.atoms.insert(name.to_owned(), atom);
+        Ok(atom)
+    }
+
+    fn selection_kind(&self, selection: xproto::Atom) -> Option<ClipboardKind> {
+        let atoms = self.xconn.atoms();
+        if selection == atoms[CLIPBOARD] {
+            Some(ClipboardKind::Clipboard)
+        } else if selection == atoms[PRIMARY] {
+            Some(ClipboardKind::Primary)
+        } else {
+            None
+        }
+    }
+
+    fn selection_atom(&self, kind: ClipboardKind) -> xproto::Atom {
+        let atoms = self.xconn.atoms();
+        match kind {
+            ClipboardKind::Clipboard => atoms[CLIPBOARD],
+            ClipboardKind::Primary => atoms[PRIMARY],
+        }
+    }
+
+    fn property_atom(&self, kind: ClipboardKind) -> xproto::Atom {
+        let atoms = self.xconn.atoms();
+        match kind {
+            ClipboardKind::Clipboard => atoms[_WINIT_CLIPBOARD],
+            ClipboardKind::Primary => atoms[_WINIT_PRIMARY],
+        }
+    }
+
+    fn state(&self, kind: ClipboardKind) -> &SelectionState {
+        match kind {
+            ClipboardKind::Clipboard => &self.clipboard,
+            ClipboardKind::Primary => &self.primary,
+        }
+    }
+
+    fn state_mut(&mut self, kind: ClipboardKind) -> &mut SelectionState {
+        match kind {
+            ClipboardKind::Clipboard => &mut self.clipboard,
+            ClipboardKind::Primary => &mut self.primary,
+        }
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        self.xconn
+            .xcb_connection()
+            .destroy_window(self.window)
+            .expect_then_ignore_error("Failed to destroy the clipboard window");
+    }
+}
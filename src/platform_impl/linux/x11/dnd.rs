@@ -1,44 +1,36 @@
 use std::{
-    io,
+    collections::{HashMap, VecDeque},
     os::raw::*,
-    path::{Path, PathBuf},
-    str::Utf8Error,
+    path::PathBuf,
     sync::Arc,
 };
 
-use percent_encoding::percent_decode;
-use x11rb::protocol::xproto::{self, ConnectionExt};
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::xproto::{self, ConnectionExt},
+};
+
+use crate::{
+    dnd::{DndAction, DragSourceEvent},
+    dpi::PhysicalPosition,
+    event_loop::AsyncRequestSerial,
+    icon::RgbaIcon,
+    platform_impl::platform::common::dnd::{self, DndDataParseError, URI_LIST_MIME_TYPE},
+};
 
 use super::{
     atoms::{AtomName::None as DndNone, *},
     util, CookieResultExt, X11Error, XConnection,
 };
 
-#[derive(Debug, Clone, Copy)]
-pub enum DndState {
-    Accepted,
-    Rejected,
-}
-
-#[derive(Debug)]
-pub enum DndDataParseError {
-    EmptyData,
-    InvalidUtf8(Utf8Error),
-    HostnameSpecified(String),
-    UnexpectedProtocol(String),
-    UnresolvablePath(io::Error),
-}
-
-impl From<Utf8Error> for DndDataParseError {
-    fn from(e: Utf8Error) -> Self {
-        DndDataParseError::InvalidUtf8(e)
-    }
-}
-
-impl From<io::Error> for DndDataParseError {
-    fn from(e: io::Error) -> Self {
-        DndDataParseError::UnresolvablePath(e)
-    }
+/// The request for the dragged data.
+pub struct DataRequest {
+    /// The serial of the request, `None` for the list of files requested by us.
+    pub serial: Option<AsyncRequestSerial>,
+    /// The requested MIME type.
+    pub mime_type: String,
+    /// The atom of the MIME type.
+    target: xproto::Atom,
 }
 
 pub(crate) struct Dnd {
@@ -46,10 +38,21 @@ pub(crate) struct Dnd {
     // Populated by XdndEnter event handler
     pub version: Option<c_long>,
     pub type_list: Option<Vec<xproto::Atom>>,
+    pub target_window: Option<xproto::Window>,
     // Populated by XdndPosition event handler
     pub source_window: Option<xproto::Window>,
+    pub position: Option<PhysicalPosition<f64>>,
+    pub time: xproto::Timestamp,
+    // The action sent to the source, `None` when the drop is rejected
+    pub action: Option<DndAction>,
+    // Populated by XdndDrop event handler
+    pub dropped: bool,
+    // Answered by SelectionNotify events, in order
+    requests: VecDeque<DataRequest>,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
+    // Cache of the MIME type atoms
+    atoms: HashMap<String, xproto::Atom>,
 }
 
 impl Dnd {
@@ -58,36 +61,101 @@ impl Dnd {
             xconn,
             version: None,
             type_list: None,
+            target_window: None,
             source_window: None,
+            position: None,
+            time: x11rb::CURRENT_TIME,
+            action: None,
+            dropped: false,
+            requests: VecDeque::new(),
             result: None,
+            atoms: HashMap::new(),
         })
     }
 
     pub fn reset(&mut self) {
         self.version = None;
         self.type_list = None;
+        self.target_window = None;
         self.source_window = None;
+        self.position = None;
+        self.time = x11rb::CURRENT_TIME;
+        self.action = None;
+        self.dropped = false;
+        self.requests.clear();
         self.result = None;
     }
 
+    /// Whether the type list contains the list of files.
+    pub fn has_files(&self) -> bool {
+        let atoms = self.xconn.atoms();
+        self.type_list
+            .as_ref()
+            .map_or(false, |type_list| type_list.contains(&atoms[TextUriList]))
+    }
+
+    /// Whether some of the requested data hasn't arrived yet.
+    pub fn has_pending_requests(&self) -> bool {
+        !self.requests.is_empty()
+    }
+
+    /// Resolve the MIME types from the type list.
+    pub fn mime_types(&mut self) -> Vec<String> {
+        let type_list = self.type_list.clone().unwrap_or_default();
+        let conn = self.xconn.xcb_connection();
+        let cookies: Vec<_> = type_list
+            .into_iter()
+            .filter(|&atom| atom != x11rb::NONE)
+            .filter_map(|atom| Some((atom, conn.get_atom_name(atom).ok()?)))
+            .collect();
+
+        let mut mime_types = Vec::with_capacity(cookies.len());
+        for (atom, cookie) in cookies {
+            if let Ok(reply) = cookie.reply() {
+                let name = String::from_utf8_lossy(&reply.name).into_owned();
+                self.atoms.insert(name.clone(), atom);
+                mime_types.push(name);
+            }
+        }
+
+        mime_types
+    }
+
+    /// Change the action and notify the source right away.
+    pub fn set_action(&mut self, action: Option<DndAction>) -> Result<(), X11Error> {
+        self.action = action;
+        match (self.target_window, self.source_window) {
+            (Some(this_window), Some(source_window)) if !self.dropped => unsafe {
+                self.send_status(this_window, source_window, action)?;
+                self.xconn.flush_requests()?;
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
     pub unsafe fn send_status(
         &self,
         this_window: xproto::Window,
         target_window: xproto::Window,
-        state: DndState,
+        action: Option<DndAction>,
     ) -> Result<(), X11Error> {
         let atoms = self.xconn.atoms();
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
-            DndState::Rejected => (0, atoms[DndNone]),
-        };
+        let accepted = action.is_some() as u32;
         self.xconn
             .send_client_msg(
                 target_window,
                 target_window,
                 atoms[XdndStatus] as _,
                 None,
-                [this_window, accepted, 0, 0, action as _],
+                [
+                    this_window,
+                    accepted,
+                    0,
+                    0,
+                    action_atom(&self.xconn, action),
+                ],
             )?
             .ignore_error();
 
@@ -98,20 +166,28 @@ impl Dnd {
         &self,
         this_window: xproto::Window,
         target_window: xproto::Window,
-        state: DndState,
+        action: Option<DndAction>,
     ) -> Result<(), X11Error> {
         let atoms = self.xconn.atoms();
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
-            DndState::Rejected => (0, atoms[DndNone]),
-        };
+        let accepted = action.is_some() as u32;
+        // The `ask` action isn't final, the data is copied once the drop is accepted.
+        let action = action.map(|action| match action {
+            DndAction::Ask => DndAction::Copy,
+            action => action,
+        });
         self.xconn
             .send_client_msg(
                 target_window,
                 target_window,
                 atoms[XdndFinished] as _,
                 None,
-                [this_window, accepted, action as _, 0, 0],
+                [
+                    this_window,
+                    accepted,
+                    action_atom(&self.xconn, action),
+                    0,
+                    0,
+                ],
             )?
             .ignore_error();
 
@@ -130,55 +206,679 @@ impl Dnd {
         )
     }
 
-    pub unsafe fn convert_selection(&self, window: xproto::Window, time: xproto::Timestamp) {
+    /// Request the data in the given MIME type, which is answered with `SelectionNotify`.
+    pub fn request(
+        &mut self,
+        window: xproto::Window,
+        serial: Option<AsyncRequestSerial>,
+        mime_type: &str,
+    ) -> Result<(), X11Error> {
+        let target = if mime_type == URI_LIST_MIME_TYPE {
+            self.xconn.atoms()[TextUriList]
+        } else if let Some(&atom) = self.atoms.get(mime_type) {
+            atom
+        } else {
+            let atom = self
+                .xconn
+                .xcb_connection()
+                .intern_atom(false, mime_type.as_bytes())?
+                .reply()?
+                .atom;
+            self.atoms.insert(mime_type.to_owned(), atom);
+            atom
+        };
+
+        self.requests.push_back(DataRequest {
+            serial,
+            mime_type: mime_type.to_owned(),
+            target,
+        });
+
+        if self.requests.len() == 1 {
+            unsafe { self.convert_selection(window, target) };
+            self.xconn.flush_requests()?;
+        }
+
+        Ok(())
+    }
+
+    unsafe fn convert_selection(&self, window: xproto::Window, target: xproto::Atom) {
         let atoms = self.xconn.atoms();
         self.xconn
             .xcb_connection()
             .convert_selection(
                 window,
                 atoms[XdndSelection],
-                atoms[TextUriList],
+                target,
                 atoms[XdndSelection],
-                time,
+                self.time,
             )
             .expect_then_ignore_error("Failed to send XdndSelection event")
     }
 
+    /// Complete the oldest request, returning it along with its data on success.
+    pub fn handle_selection_notify(
+        &mut self,
+        window: xproto::Window,
+        property: xproto::Atom,
+    ) -> Option<(DataRequest, Option<Vec<c_uchar>>)> {
+        let request = self.requests.pop_front()?;
+        let data = if property == x11rb::NONE {
+            None
+        } else {
+            unsafe { self.read_data(window, request.target) }.ok()
+        };
+
+        // Ask for the next data.
+        if let Some(next) = self.requests.front() {
+            unsafe { self.convert_selection(window, next.target) };
+        }
+
+        Some((request, data))
+    }
+
     pub unsafe fn read_data(
         &self,
         window: xproto::Window,
+        target: xproto::Atom,
     ) -> Result<Vec<c_uchar>, util::GetPropertyError> {
         let atoms = self.xconn.atoms();
-        self.xconn
-            .get_property(window, atoms[XdndSelection], atoms[TextUriList])
+        match self
+            .xconn
+            .get_property(window, atoms[XdndSelection], target)
+        {
+            // The type of the data isn't always the same as the requested target.
+            Err(util::GetPropertyError::TypeMismatch(actual_type)) => {
+                self.xconn
+                    .get_property(window, atoms[XdndSelection], actual_type)
+            }
+            result => result,
+        }
     }
 
     pub fn parse_data(&self, data: &mut [c_uchar]) -> Result<Vec<PathBuf>, DndDataParseError> {
-        if !data.is_empty() {
-            let mut path_list = Vec::new();
-            let decoded = percent_decode(data).decode_utf8()?.into_owned();
-            for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
-                // The format is specified as protocol://host/path
-                // However, it's typically simply protocol:///path
-                let path_str = if uri.starts_with("file://") {
-                    let path_str = uri.replace("file://", "");
-                    if !path_str.starts_with('/') {
-                        // A hostname is specified
-                        // Supporting this case is beyond the scope of my mental health
-                        return Err(DndDataParseError::HostnameSpecified(path_str));
-                    }
-                    path_str
-                } else {
-                    // Only the file protocol is supported
-                    return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
+        dnd::parse_uri_list(data)
+    }
+}
+
+/// The gap between the cursor and the drag icon, so the icon isn't found as the drop target.
+const DRAG_ICON_OFFSET: i16 = 8;
+
+/// The version of the XDND protocol we speak as the source.
+const XDND_VERSION: u32 = 5;
+
+/// The drag of the data out of one of our windows.
+pub(crate) struct DragSource {
+    xconn: Arc<XConnection>,
+    /// The window which owns the `XdndSelection` and receives the messages from the target.
+    window: xproto::Window,
+    drag: Option<Drag>,
+}
+
+struct Drag {
+    /// The window the data is dragged out of.
+    origin: xproto::Window,
+    /// The atoms of the offered MIME types, in the order of preference.
+    types: Vec<xproto::Atom>,
+    /// The actions the target could choose from, in the order of preference.
+    actions: Vec<DndAction>,
+    icon: Option<DragIcon>,
+    target: Option<DragTarget>,
+    /// Whether the data was dropped, and we're waiting for `XdndFinished`.
+    dropped: bool,
+}
+
+struct DragTarget {
+    window: xproto::Window,
+    version: u32,
+    /// The action accepted by the target, `None` when the drop is rejected.
+    action: Option<DndAction>,
+    /// Whether we're waiting for `XdndStatus`.
+    waiting_status: bool,
+    /// The position to send once the status arrives.
+    pending_position: Option<(i16, i16, xproto::Timestamp)>,
+}
+
+struct DragIcon {
+    window: xproto::Window,
+    pixmap: xproto::Pixmap,
+    colormap: Option<xproto::Colormap>,
+    mapped: bool,
+}
+
+impl DragSource {
+    pub fn new(xconn: Arc<XConnection>, window: xproto::Window) -> Self {
+        DragSource {
+            xconn,
+            window,
+            drag: None,
+        }
+    }
+
+    /// Whether the data is being dragged, and wasn't dropped yet.
+    pub fn is_active(&self) -> bool {
+        self.drag.as_ref().map_or(false, |drag| !drag.dropped)
+    }
+
+    /// Start the drag, the previous one must be cancelled beforehand.
+    ///
+    /// The `actions` must not be empty.
+    pub fn start(
+        &mut self,
+        origin: xproto::Window,
+        types: Vec<xproto::Atom>,
+        actions: Vec<DndAction>,
+        icon: Option<&RgbaIcon>,
+    ) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+
+        self.xconn
+            .change_property(
+                self.window,
+                atoms[XdndTypeList],
+                xproto::AtomEnum::ATOM.into(),
+                xproto::PropMode::REPLACE,
+                &types,
+            )?
+            .ignore_error();
+        let action_atoms: Vec<_> = actions
+            .iter()
+            .map(|&action| action_atom(&self.xconn, Some(action)))
+            .collect();
+        self.xconn
+            .change_property(
+                self.window,
+                atoms[XdndActionList],
+                xproto::AtomEnum::ATOM.into(),
+                xproto::PropMode::REPLACE,
+                &action_atoms,
+            )?
+            .ignore_error();
+
+        let icon = match icon {
+            Some(icon) => DragIcon::new(&self.xconn, icon)?,
+            None => None,
+        };
+
+        self.drag = Some(Drag {
+            origin,
+            types,
+            actions,
+            icon,
+            target: None,
+            dropped: false,
+        });
+
+        self.xconn.flush_requests()?;
+        Ok(())
+    }
+
+    /// Cancel the drag, returning the window it was started from.
+    pub fn cancel(&mut self) -> Option<xproto::Window> {
+        let mut drag = self.drag.take()?;
+        if let Some(target) = drag.target.take() {
+            if !drag.dropped {
+                if let Err(err) = self.send_leave(&target) {
+                    warn!("Failed to send `XdndLeave` message: {}", err);
+                }
+            }
+        }
+
+        if let Some(icon) = drag.icon.take() {
+            icon.destroy(&self.xconn);
+        }
+
+        Some(drag.origin)
+    }
+
+    /// Handle the pointer motion, in the root window coordinates.
+    pub fn handle_motion(
+        &mut self,
+        root_x: i16,
+        root_y: i16,
+        time: xproto::Timestamp,
+    ) -> Result<(), X11Error> {
+        if !self.is_active() {
+            return Ok(());
+        }
+
+        let target = self.find_target(root_x, root_y)?;
+        let mut drag = self.drag.take().unwrap();
+
+        if let Some(icon) = drag.icon.as_mut() {
+            icon.move_to(
+                &self.xconn,
+                root_x.saturating_add(DRAG_ICON_OFFSET),
+                root_y.saturating_add(DRAG_ICON_OFFSET),
+            )?;
+        }
+
+        let current = drag.target.as_ref().map(|target| target.window);
+        if current != target.map(|(window, _)| window) {
+            if let Some(target) = drag.target.take() {
+                self.send_leave(&target)?;
+            }
+
+            if let Some((window, version)) = target {
+                let target = DragTarget {
+                    window,
+                    version: version.min(XDND_VERSION),
+                    action: None,
+                    waiting_status: false,
+                    pending_position: None,
                 };
+                self.send_enter(&target, &drag.types)?;
+                drag.target = Some(target);
+            }
+        }
+
+        if let Some(target) = drag.target.as_mut() {
+            if target.waiting_status {
+                target.pending_position = Some((root_x, root_y, time));
+            } else {
+                self.send_position(target, drag.actions[0], root_x, root_y, time)?;
+            }
+        }
+
+        self.drag = Some(drag);
+        self.xconn.flush_requests()?;
+        Ok(())
+    }
+
+    /// Handle the release of the mouse button, dropping the data.
+    pub fn handle_release(
+        &mut self,
+        time: xproto::Timestamp,
+    ) -> Result<Option<(xproto::Window, DragSourceEvent)>, X11Error> {
+        if !self.is_active() {
+            return Ok(None);
+        }
 
-                let path = Path::new(&path_str).canonicalize()?;
-                path_list.push(path);
+        let drag = self.drag.as_mut().unwrap();
+        let (target, action) = match drag.target.as_ref() {
+            Some(target) => match target.action {
+                Some(action) => (target.window, action),
+                None => {
+                    return Ok(self
+                        .cancel()
+                        .map(|origin| (origin, DragSourceEvent::Cancelled)))
+                }
+            },
+            None => {
+                return Ok(self
+                    .cancel()
+                    .map(|origin| (origin, DragSourceEvent::Cancelled)))
             }
-            Ok(path_list)
+        };
+
+        drag.dropped = true;
+        if let Some(icon) = drag.icon.take() {
+            icon.destroy(&self.xconn);
+        }
+
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .send_client_msg(
+                target,
+                target,
+                atoms[XdndDrop],
+                None,
+                [self.window, 0, time, 0, 0],
+            )?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(Some((drag.origin, DragSourceEvent::Dropped { action })))
+    }
+
+    /// Handle the `XdndStatus` message from the target.
+    pub fn handle_status(
+        &mut self,
+        target_window: xproto::Window,
+        accepted: bool,
+        action: xproto::Atom,
+    ) -> Result<(), X11Error> {
+        let action = self.action_from_atom(action);
+        let preferred = match self.drag.as_ref() {
+            Some(drag) => drag.actions[0],
+            None => return Ok(()),
+        };
+
+        let pending_position = match self.drag.as_mut().and_then(|drag| drag.target.as_mut()) {
+            Some(target) if target.window == target_window => {
+                // The targets speaking the older versions may not report the action.
+                target.action = accepted.then(|| action.unwrap_or(preferred));
+                target.waiting_status = false;
+                target.pending_position.take()
+            }
+            _ => return Ok(()),
+        };
+
+        if let Some((root_x, root_y, time)) = pending_position {
+            let mut drag = self.drag.take().unwrap();
+            let target = drag.target.as_mut().unwrap();
+            let result = self.send_position(target, preferred, root_x, root_y, time);
+            self.drag = Some(drag);
+            result?;
+            self.xconn.flush_requests()?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle the `XdndFinished` message from the target, ending the drag.
+    pub fn handle_finished(
+        &mut self,
+        target_window: xproto::Window,
+        accepted: bool,
+        action: xproto::Atom,
+    ) -> Option<(xproto::Window, DragSourceEvent)> {
+        let action = self.action_from_atom(action);
+        let drag = self.drag.as_ref()?;
+        let target = drag.target.as_ref()?;
+        if !drag.dropped || target.window != target_window {
+            return None;
+        }
+
+        // The targets speaking the older versions don't report the outcome.
+        let event = if target.version < 5 {
+            DragSourceEvent::Finished {
+                action: target.action.unwrap_or(drag.actions[0]),
+            }
+        } else if accepted {
+            DragSourceEvent::Finished {
+                action: action.or(target.action).unwrap_or(drag.actions[0]),
+            }
+        } else {
+            DragSourceEvent::Cancelled
+        };
+
+        let origin = drag.origin;
+        self.drag = None;
+        Some((origin, event))
+    }
+
+    /// Find the XDND aware window under the cursor, along with its version of the protocol.
+    fn find_target(
+        &self,
+        root_x: i16,
+        root_y: i16,
+    ) -> Result<Option<(xproto::Window, u32)>, X11Error> {
+        let conn = self.xconn.xcb_connection();
+        let atoms = self.xconn.atoms();
+        let mut window = self.xconn.default_root().root;
+        let (mut x, mut y) = (root_x, root_y);
+        loop {
+            let reply = conn.translate_coordinates(window, window, x, y)?.reply()?;
+            let child = reply.child;
+            if child == x11rb::NONE {
+                return Ok(None);
+            }
+
+            let reply = conn.translate_coordinates(window, child, x, y)?.reply()?;
+            (x, y) = (reply.dst_x, reply.dst_y);
+            window = child;
+
+            // The window may be gone already.
+            let version = self
+                .xconn
+                .get_property::<xproto::Atom>(
+                    window,
+                    atoms[XdndAware],
+                    xproto::AtomEnum::ATOM.into(),
+                )
+                .ok()
+                .and_then(|version| version.first().copied());
+
+            if let Some(version) = version {
+                return Ok(Some((window, version)));
+            }
+        }
+    }
+
+    fn send_enter(&self, target: &DragTarget, types: &[xproto::Atom]) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        let more_types = (types.len() > 3) as u32;
+        let mut data = [self.window, (target.version << 24) | more_types, 0, 0, 0];
+        for (slot, &ty) in data[2..].iter_mut().zip(types) {
+            *slot = ty;
+        }
+
+        self.xconn
+            .send_client_msg(target.window, target.window, atoms[XdndEnter], None, data)?
+            .ignore_error();
+        Ok(())
+    }
+
+    fn send_leave(&self, target: &DragTarget) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .send_client_msg(
+                target.window,
+                target.window,
+                atoms[XdndLeave],
+                None,
+                [self.window, 0, 0, 0, 0],
+            )?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+        Ok(())
+    }
+
+    fn send_position(
+        &self,
+        target: &mut DragTarget,
+        action: DndAction,
+        root_x: i16,
+        root_y: i16,
+        time: xproto::Timestamp,
+    ) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        let position = ((root_x as u16 as u32) << 16) | root_y as u16 as u32;
+        self.xconn
+            .send_client_msg(
+                target.window,
+                target.window,
+                atoms[XdndPosition],
+                None,
+                [
+                    self.window,
+                    0,
+                    position,
+                    time,
+                    action_atom(&self.xconn, Some(action)),
+                ],
+            )?
+            .ignore_error();
+        target.waiting_status = true;
+        Ok(())
+    }
+
+    fn action_from_atom(&self, atom: xproto::Atom) -> Option<DndAction> {
+        let atoms = self.xconn.atoms();
+        if atom == atoms[XdndActionCopy] {
+            Some(DndAction::Copy)
+        } else if atom == atoms[XdndActionMove] {
+            Some(DndAction::Move)
+        } else if atom == atoms[XdndActionLink] {
+            Some(DndAction::Link)
+        } else if atom == atoms[XdndActionAsk] {
+            Some(DndAction::Ask)
+        } else {
+            None
+        }
+    }
+}
+
+impl DragIcon {
+    /// Create the window showing the icon, `None` when the icon can't be shown.
+    fn new(xconn: &XConnection, icon: &RgbaIcon) -> Result<Option<Self>, X11Error> {
+        let conn = xconn.xcb_connection();
+        let setup = conn.setup();
+        let screen = xconn.default_root();
+
+        // Prefer the visual with the alpha channel.
+        let argb_visual = screen
+            .allowed_depths
+            .iter()
+            .filter(|depth| depth.depth == 32)
+            .flat_map(|depth| depth.visuals.iter())
+            .find(|visual| visual.class == xproto::VisualClass::TRUE_COLOR)
+            .map(|visual| visual.visual_id);
+        let (depth, visual) = match argb_visual {
+            Some(visual) => (32, visual),
+            None => (screen.root_depth, screen.root_visual),
+        };
+
+        let bits_per_pixel = setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == depth)
+            .map(|format| format.bits_per_pixel);
+        if bits_per_pixel != Some(32) {
+            warn!(
+                "The drag icon isn't supported with the depth of {} bits",
+                depth
+            );
+            return Ok(None);
+        }
+
+        // The pixels are premultiplied, as expected by the compositors.
+        let little_endian = setup.image_byte_order == xproto::ImageOrder::LSB_FIRST;
+        let data: Vec<u8> = icon
+            .rgba
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+                let premultiply = |channel: u8| (channel as u32 * a as u32 / 255) as u8;
+                let pixel = u32::from_be_bytes([a, premultiply(r), premultiply(g), premultiply(b)]);
+                if little_endian {
+                    pixel.to_le_bytes()
+                } else {
+                    pixel.to_be_bytes()
+                }
+            })
+            .collect();
+
+        if data.len() + 24 > conn.maximum_request_bytes() {
+            warn!("The drag icon of {} bytes is too large", data.len());
+            return Ok(None);
+        }
+
+        let (width, height) = (icon.width as u16, icon.height as u16);
+        let pixmap = conn.generate_id()?;
+        conn.create_pixmap(depth, pixmap, screen.root, width, height)?
+            .ignore_error();
+
+        let gc = conn.generate_id()?;
+        conn.create_gc(gc, pixmap, &xproto::CreateGCAux::new())?
+            .ignore_error();
+        conn.put_image(
+            xproto::ImageFormat::Z_PIXMAP,
+            pixmap,
+            gc,
+            width,
+            height,
+            0,
+            0,
+            0,
+            depth,
+            &data,
+        )?
+        .ignore_error();
+        conn.free_gc(gc)?.ignore_error();
+
+        let colormap = if argb_visual.is_some() {
+            let colormap = conn.generate_id()?;
+            conn.create_colormap(xproto::ColormapAlloc::NONE, colormap, screen.root, visual)?
+                .ignore_error();
+            Some(colormap)
         } else {
-            Err(DndDataParseError::EmptyData)
+            None
+        };
+
+        let mut aux = xproto::CreateWindowAux::new()
+            .override_redirect(1)
+            .background_pixmap(pixmap)
+            .border_pixel(0);
+        if let Some(colormap) = colormap {
+            aux = aux.colormap(colormap);
+        }
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            depth,
+            window,
+            screen.root,
+            0,
+            0,
+            width,
+            height,
+            0,
+            xproto::WindowClass::INPUT_OUTPUT,
+            visual,
+            &aux,
+        )?
+        .ignore_error();
+
+        let atoms = xconn.atoms();
+        xconn
+            .change_property(
+                window,
+                atoms[_NET_WM_WINDOW_TYPE],
+                xproto::AtomEnum::ATOM.into(),
+                xproto::PropMode::REPLACE,
+                &[atoms[_NET_WM_WINDOW_TYPE_DND]],
+            )?
+            .ignore_error();
+
+        Ok(Some(DragIcon {
+            window,
+            pixmap,
+            colormap,
+            mapped: false,
+        }))
+    }
+
+    fn move_to(&mut self, xconn: &XConnection, x: i16, y: i16) -> Result<(), X11Error> {
+        let conn = xconn.xcb_connection();
+        conn.configure_window(
+            self.window,
+            &xproto::ConfigureWindowAux::new()
+                .x(x as i32)
+                .y(y as i32)
+                .stack_mode(xproto::StackMode::ABOVE),
+        )?
+        .ignore_error();
+
+        if !self.mapped {
+            conn.map_window(self.window)?.ignore_error();
+            self.mapped = true;
+        }
+
+        Ok(())
+    }
+
+    fn destroy(self, xconn: &XConnection) {
+        let conn = xconn.xcb_connection();
+        conn.destroy_window(self.window)
+            .expect_then_ignore_error("Failed to destroy the drag icon window");
+        conn.free_pixmap(self.pixmap)
+            .expect_then_ignore_error("Failed to free the drag icon pixmap");
+        if let Some(colormap) = self.colormap {
+            conn.free_colormap(colormap)
+                .expect_then_ignore_error("Failed to free the drag icon colormap");
         }
     }
 }
+
+fn action_atom(xconn: &XConnection, action: Option<DndAction>) -> xproto::Atom {
+    let atoms = xconn.atoms();
+    match action {
+        Some(DndAction::Copy) => atoms[XdndActionCopy],
+        Some(DndAction::Move) => atoms[XdndActionMove],
+        Some(DndAction::Link) => atoms[XdndActionLink],
+        Some(DndAction::Ask) => atoms[XdndActionAsk],
+        None => atoms[DndNone],
+    }
+}
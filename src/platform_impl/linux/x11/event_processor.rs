@@ -1,36 +1,51 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    slice,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use libc::{c_char, c_int, c_long, c_ulong};
 
+use x11rb::protocol::present;
 use x11rb::protocol::xproto::{self, ConnectionExt as _};
 use x11rb::x11_utils::Serialize;
 
 use super::{
-    atoms::*, ffi, get_xtarget, mkdid, mkwid, monitor, util, CookieResultExt, Device, DeviceId,
-    DeviceInfo, Dnd, DndState, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow,
-    WindowId, XExtension,
+    atoms::*, dnd::DataRequest, ffi, get_xtarget, mkdid, mkwid, monitor, util, CookieResultExt,
+    Device, DeviceId, DeviceInfo, GenericEventCookie, ImeReceiver, ScrollOrientation,
+    UnownedWindow, WindowId, XExtension,
 };
 
 use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventReceiver, ImeRequest};
 use crate::{
+    device::SeatId,
+    dnd::{DndAction, DndEvent, DragSourceEvent},
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, ElementState, Event, Ime, RawKeyEvent, TouchPhase, WindowEvent},
+    event::{
+        DeviceEvent, ElementState, Event, Ime, PenTool, PointerButtons, PresentationFeedback,
+        RawKeyEvent, TouchPhase, WindowEvent,
+    },
     event_loop::EventLoopWindowTarget as RootELW,
     keyboard::ModifiersState,
-    platform_impl::platform::common::{keymap, xkb_state::KbdState},
+    platform_impl::platform::common::{
+        dnd::URI_LIST_MIME_TYPE, keymap, timestamp, xkb_state::KbdState,
+    },
 };
 
 /// The X11 documentation states: "Keycodes lie in the inclusive range `[8, 255]`".
 const KEYCODE_OFFSET: u8 = 8;
 
 pub(super) struct EventProcessor<T: 'static> {
-    pub(super) dnd: Dnd,
     pub(super) ime_receiver: ImeReceiver,
     pub(super) ime_event_receiver: ImeEventReceiver,
     pub(super) randr_event_offset: c_int,
     pub(super) devices: RefCell<HashMap<DeviceId, Device>>,
     pub(super) xi2ext: XExtension,
     pub(super) xkbext: XExtension,
+    pub(super) present_opcode: Option<c_int>,
     pub(super) target: Rc<RootELW<T>>,
     pub(super) kb_state: KbdState,
     // Number of touch events currently in progress
@@ -41,18 +56,107 @@ pub(super) struct EventProcessor<T: 'static> {
     // Used to detect key repeats.
     pub(super) held_key_press: Option<u32>,
     pub(super) first_touch: Option<u64>,
-    // Currently focused window belonging to this process
+    // The latest scale of the touchpad pinch in progress, relative to its beginning.
+    pub(super) pinch_scale: f64,
+    // Currently focused window belonging to this process, which gets the core keyboard events
     pub(super) active_window: Option<xproto::Window>,
+    // The seats holding the keyboard focus of each window, when there are several with MPX
+    pub(super) focused_seats: HashMap<xproto::Window, Vec<SeatId>>,
     pub(super) is_composing: bool,
 }
 
 impl<T: 'static> EventProcessor<T> {
+    /// Notify the source that the drop is finished, and deliver the dropped files.
+    fn finish_drop<F>(&self, window: xproto::Window, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        let mut dnd = wt.dnd.borrow_mut();
+        if let Some(source_window) = dnd.source_window {
+            unsafe {
+                dnd.send_finished(window, source_window, dnd.action)
+                    .expect("Failed to send `XdndFinished` message.");
+            }
+        }
+
+        let path_list = match dnd.result.take() {
+            Some(Ok(path_list)) => path_list,
+            _ => Vec::new(),
+        };
+        dnd.reset();
+        drop(dnd);
+
+        for path in path_list {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::DroppedFile(path),
+            });
+        }
+    }
+
+    /// Deliver the outcome of the drag started from one of our windows.
+    fn drag_source_event<F>(&self, origin: xproto::Window, event: DragSourceEvent, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        // The data is no longer needed once the drag is over.
+        if !matches!(event, DragSourceEvent::Dropped { .. }) {
+            get_xtarget(&self.target).end_drag();
+        }
+
+        callback(Event::WindowEvent {
+            window_id: mkwid(origin),
+            event: WindowEvent::DragSource(event),
+        });
+    }
+
+    /// Report the presentation of a frame, if the window asked for it.
+    fn process_present_event<F>(&self, cookie: &ffi::XGenericEventCookie, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let event = match util::parse_present_complete(cookie) {
+            Some(event) if event.kind == present::CompleteKind::PIXMAP => event,
+            _ => return,
+        };
+
+        let request = self
+            .with_window(event.window, |window| {
+                window
+                    .take_presentation_feedback()
+                    .then(|| FeedbackRequest::new(window))
+            })
+            .flatten();
+        // The window is gone, or didn't ask for the feedback of this frame.
+        let refresh = match request {
+            Some(request) => request.refresh,
+            None => return,
+        };
+
+        // The frames are skipped when a newer one is presented for the same refresh.
+        let feedback = if event.mode == present::CompleteMode::SKIP {
+            PresentationFeedback::Discarded
+        } else {
+            PresentationFeedback::Presented {
+                timestamp: timestamp::from_micros(event.ust),
+                refresh,
+                sequence: Some(event.msc),
+            }
+        };
+
+        callback(Event::WindowEvent {
+            window_id: mkwid(event.window),
+            event: WindowEvent::PresentationFeedback(feedback),
+        });
+    }
+
     pub(super) fn init_device(&self, device: c_int) {
         let wt = get_xtarget(&self.target);
         let mut devices = self.devices.borrow_mut();
         if let Some(info) = DeviceInfo::get(&wt.xconn, device) {
             for info in info.iter() {
-                devices.insert(DeviceId(info.deviceid), Device::new(info));
+                devices.insert(DeviceId(info.deviceid), Device::new(info, wt.xconn.atoms()));
             }
         }
     }
@@ -182,7 +286,10 @@ impl<T: 'static> EventProcessor<T> {
                     let source_window = client_msg.data.get_long(0) as xproto::Window;
                     let flags = client_msg.data.get_long(1);
                     let version = flags >> 24;
-                    self.dnd.version = Some(version);
+                    let mut dnd = wt.dnd.borrow_mut();
+                    dnd.reset();
+                    dnd.version = Some(version);
+                    dnd.target_window = Some(window);
                     let has_more_types = flags - (flags & (c_long::max_value() - 1)) == 1;
                     if !has_more_types {
                         let type_list = vec![
@@ -190,19 +297,17 @@ impl<T: 'static> EventProcessor<T> {
                             client_msg.data.get_long(3) as xproto::Atom,
                             client_msg.data.get_long(4) as xproto::Atom,
                         ];
-                        self.dnd.type_list = Some(type_list);
-                    } else if let Ok(more_types) = unsafe { self.dnd.get_type_list(source_window) }
-                    {
-                        self.dnd.type_list = Some(more_types);
+                        dnd.type_list = Some(type_list);
+                    } else if let Ok(more_types) = unsafe { dnd.get_type_list(source_window) } {
+                        dnd.type_list = Some(more_types);
                     }
                 } else if client_msg.message_type == atoms[XdndPosition] as c_ulong {
-                    // This event occurs every time the mouse moves while a file's being dragged
-                    // over our window. We emit HoveredFile in response; while the macOS backend
-                    // does that upon a drag entering, XDND doesn't have access to the actual drop
-                    // data until this event. For parity with other platforms, we only emit
-                    // `HoveredFile` the first time, though if winit's API is later extended to
-                    // supply position updates with `HoveredFile` or another event, implementing
-                    // that here would be trivial.
+                    // This event occurs every time the mouse moves while something's being
+                    // dragged over our window. We emit `DndEvent::Entered` the first time and
+                    // `DndEvent::Moved` afterwards. For the list of files we also emit
+                    // HoveredFile once their paths arrive; while the macOS backend does that upon
+                    // a drag entering, XDND doesn't have access to the actual drop data until this
+                    // event.
 
                     let source_window = client_msg.data.get_long(0) as xproto::Window;
 
@@ -210,82 +315,144 @@ impl<T: 'static> EventProcessor<T> {
                     // where `shift = mem::size_of::<c_short>() * 8`
                     // Note that coordinates are in "desktop space", not "window space"
                     // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
+                    let packed_coordinates = client_msg.data.get_long(2);
+                    let x = (packed_coordinates >> 16) as i16;
+                    let y = packed_coordinates as i16;
+                    let position = wt
+                        .xconn
+                        .xcb_connection()
+                        .translate_coordinates(wt.root, window, x, y)
+                        .ok()
+                        .and_then(|cookie| cookie.reply().ok())
+                        .map(|reply| PhysicalPosition::new(reply.dst_x as f64, reply.dst_y as f64))
+                        .unwrap_or_default();
+
+                    let mut dnd = wt.dnd.borrow_mut();
 
                     // By our own state flow, `version` should never be `None` at this point.
-                    let version = self.dnd.version.unwrap_or(5);
+                    let version = dnd.version.unwrap_or(5);
 
                     // Action is specified in versions 2 and up, though we don't need it anyway.
                     //let action = client_msg.data.get_long(4);
 
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                        type_list.contains(&atoms[TextUriList])
+                    let time = if version >= 1 {
+                        client_msg.data.get_long(3) as xproto::Timestamp
                     } else {
-                        false
+                        // In version 0, time isn't specified
+                        x11rb::CURRENT_TIME
                     };
 
-                    if accepted {
-                        self.dnd.source_window = Some(source_window);
-                        unsafe {
-                            if self.dnd.result.is_none() {
-                                let time = if version >= 1 {
-                                    client_msg.data.get_long(3) as xproto::Timestamp
-                                } else {
-                                    // In version 0, time isn't specified
-                                    x11rb::CURRENT_TIME
-                                };
+                    // Log this timestamp.
+                    wt.xconn.set_timestamp(time);
 
-                                // Log this timestamp.
-                                wt.xconn.set_timestamp(time);
+                    dnd.time = time;
+                    dnd.source_window = Some(source_window);
+                    dnd.target_window = Some(window);
 
-                                // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
+                    let event = if dnd.position.replace(position).is_none() {
+                        // Accept the files by default.
+                        if dnd.has_files() {
+                            dnd.action = Some(DndAction::Copy);
+
+                            // This results in the `SelectionNotify` event below
+                            if let Err(err) = dnd.request(window, None, URI_LIST_MIME_TYPE) {
+                                warn!("Failed to request the dropped files: {err}");
                             }
-                            self.dnd
-                                .send_status(window, source_window, DndState::Accepted)
-                                .expect("Failed to send `XdndStatus` message.");
                         }
-                    } else {
-                        unsafe {
-                            self.dnd
-                                .send_status(window, source_window, DndState::Rejected)
-                                .expect("Failed to send `XdndStatus` message.");
+
+                        DndEvent::Entered {
+                            mime_types: dnd.mime_types(),
+                            position,
                         }
-                        self.dnd.reset();
+                    } else {
+                        DndEvent::Moved { position }
+                    };
+                    drop(dnd);
+
+                    // The response could be changed while handling the event.
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragAndDrop(event),
+                    });
+
+                    let dnd = wt.dnd.borrow();
+                    unsafe {
+                        dnd.send_status(window, source_window, dnd.action)
+                            .expect("Failed to send `XdndStatus` message.");
                     }
                 } else if client_msg.message_type == atoms[XdndDrop] as c_ulong {
-                    let (source_window, state) = if let Some(source_window) = self.dnd.source_window
-                    {
-                        if let Some(Ok(ref path_list)) = self.dnd.result {
-                            for path in path_list {
-                                callback(Event::WindowEvent {
-                                    window_id,
-                                    event: WindowEvent::DroppedFile(path.clone()),
-                                });
-                            }
+                    let source_window = client_msg.data.get_long(0) as xproto::Window;
+                    let mut dnd = wt.dnd.borrow_mut();
+                    dnd.source_window = Some(source_window);
+                    if let Some(action) = dnd.action {
+                        dnd.dropped = true;
+                        let position = dnd.position.unwrap_or_default();
+                        drop(dnd);
+
+                        // The data is requested while handling the event.
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Dropped { position, action }),
+                        });
+
+                        if !wt.dnd.borrow().has_pending_requests() {
+                            self.finish_drop(window, &mut callback);
                         }
-                        (source_window, DndState::Accepted)
                     } else {
-                        // `source_window` won't be part of our DND state if we already rejected the drop in our
-                        // `XdndPosition` handler.
-                        let source_window = client_msg.data.get_long(0) as xproto::Window;
-                        (source_window, DndState::Rejected)
-                    };
-                    unsafe {
-                        self.dnd
-                            .send_finished(window, source_window, state)
-                            .expect("Failed to send `XdndFinished` message.");
+                        // The drop was rejected in our `XdndPosition` handler.
+                        unsafe {
+                            dnd.send_finished(window, source_window, None)
+                                .expect("Failed to send `XdndFinished` message.");
+                        }
+                        dnd.reset();
+                        drop(dnd);
+
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Left),
+                        });
+                    }
+                } else if client_msg.message_type == atoms[XdndStatus] as c_ulong {
+                    let target = client_msg.data.get_long(0) as xproto::Window;
+                    let accepted = client_msg.data.get_long(1) & 1 != 0;
+                    let action = client_msg.data.get_long(4) as xproto::Atom;
+                    let result = wt
+                        .drag_source
+                        .borrow_mut()
+                        .handle_status(target, accepted, action);
+                    if let Err(err) = result {
+                        warn!("Failed to send `XdndPosition` message: {}", err);
+                    }
+                } else if client_msg.message_type == atoms[XdndFinished] as c_ulong {
+                    let target = client_msg.data.get_long(0) as xproto::Window;
+                    let accepted = client_msg.data.get_long(1) & 1 != 0;
+                    let action = client_msg.data.get_long(2) as xproto::Atom;
+                    let result = wt
+                        .drag_source
+                        .borrow_mut()
+                        .handle_finished(target, accepted, action);
+                    if let Some((origin, event)) = result {
+                        self.drag_source_event(origin, event, &mut callback);
                     }
-                    self.dnd.reset();
                 } else if client_msg.message_type == atoms[XdndLeave] as c_ulong {
-                    self.dnd.reset();
+                    let entered = {
+                        let mut dnd = wt.dnd.borrow_mut();
+                        let entered = dnd.position.is_some();
+                        dnd.reset();
+                        entered
+                    };
+
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::HoveredFileCancelled,
                     });
+
+                    if entered {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragAndDrop(DndEvent::Left),
+                        });
+                    }
                 }
             }
 
@@ -298,24 +465,127 @@ impl<T: 'static> EventProcessor<T> {
                 // Set the timestamp.
                 wt.xconn.set_timestamp(xsel.time as xproto::Timestamp);
 
-                if xsel.property == atoms[XdndSelection] as c_ulong {
-                    let mut result = None;
-
+                if window == wt.clipboard.borrow().window() {
+                    let events = wt.clipboard.borrow_mut().handle_selection_notify(
+                        xsel.selection as xproto::Atom,
+                        xsel.target as xproto::Atom,
+                        xsel.property as xproto::Atom,
+                    );
+                    for event in events {
+                        callback(Event::Clipboard(event));
+                    }
+                } else if xsel.selection == atoms[XdndSelection] as c_ulong {
                     // This is where we receive data from drag and drop
-                    if let Ok(mut data) = unsafe { self.dnd.read_data(window) } {
-                        let parse_result = self.dnd.parse_data(&mut data);
-                        if let Ok(ref path_list) = parse_result {
-                            for path in path_list {
-                                callback(Event::WindowEvent {
-                                    window_id,
-                                    event: WindowEvent::HoveredFile(path.clone()),
-                                });
+                    let response = wt
+                        .dnd
+                        .borrow_mut()
+                        .handle_selection_notify(window, xsel.property as xproto::Atom);
+
+                    match response {
+                        // The list of files requested by us.
+                        Some((DataRequest { serial: None, .. }, Some(mut data))) => {
+                            let parse_result = wt.dnd.borrow().parse_data(&mut data);
+                            if let Ok(ref path_list) = parse_result {
+                                for path in path_list {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::HoveredFile(path.clone()),
+                                    });
+                                }
                             }
+                            wt.dnd.borrow_mut().result = Some(parse_result);
+                        }
+                        Some((DataRequest { serial: None, .. }, None)) => (),
+                        Some((
+                            DataRequest {
+                                serial: Some(serial),
+                                mime_type,
+                                ..
+                            },
+                            data,
+                        )) => {
+                            let event = match data {
+                                Some(data) => DndEvent::Data {
+                                    serial,
+                                    mime_type,
+                                    data,
+                                },
+                                None => DndEvent::RequestFailed { serial },
+                            };
+
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragAndDrop(event),
+                            });
                         }
-                        result = Some(parse_result);
+                        None => (),
                     }
 
-                    self.dnd.result = result;
+                    let finished = {
+                        let dnd = wt.dnd.borrow();
+                        dnd.dropped && !dnd.has_pending_requests()
+                    };
+                    if finished {
+                        self.finish_drop(window, &mut callback);
+                    }
+                }
+            }
+
+            ffi::SelectionRequest => {
+                let xsel: &ffi::XSelectionRequestEvent = xev.as_ref();
+
+                wt.xconn.set_timestamp(xsel.time as xproto::Timestamp);
+
+                let mut clipboard = wt.clipboard.borrow_mut();
+                if xsel.owner as xproto::Window == clipboard.window() {
+                    if let Err(err) = clipboard.handle_selection_request(
+                        xsel.requestor as xproto::Window,
+                        xsel.selection as xproto::Atom,
+                        xsel.target as xproto::Atom,
+                        xsel.property as xproto::Atom,
+                        xsel.time as xproto::Timestamp,
+                    ) {
+                        warn!("Failed to reply to the selection request: {}", err);
+                    }
+                }
+            }
+
+            ffi::SelectionClear => {
+                let xsel: &ffi::XSelectionClearEvent = xev.as_ref();
+
+                let event = {
+                    let mut clipboard = wt.clipboard.borrow_mut();
+                    if xsel.window as xproto::Window == clipboard.window() {
+                        clipboard.handle_selection_clear(xsel.selection as xproto::Atom)
+                    } else {
+                        None
+                    }
+                };
+
+                if let Some(event) = event {
+                    callback(Event::Clipboard(event));
+                }
+            }
+
+            ffi::PropertyNotify => {
+                let xprop: &ffi::XPropertyEvent = xev.as_ref();
+
+                wt.xconn.set_timestamp(xprop.time as xproto::Timestamp);
+
+                let state = if xprop.state == ffi::PropertyNewValue {
+                    xproto::Property::NEW_VALUE
+                } else {
+                    xproto::Property::DELETE
+                };
+                // The requestors of the incremental transfers are also watched by the clipboard.
+                let events = wt.clipboard.borrow_mut().handle_property_notify(
+                    xprop.window as xproto::Window,
+                    xprop.atom as xproto::Atom,
+                    state,
+                );
+
+                for event in events {
+                    callback(Event::Clipboard(event));
                 }
             }
 
@@ -567,15 +837,40 @@ impl<T: 'static> EventProcessor<T> {
             }
 
             // Note that in compose/pre-edit sequences, we'll always receive KeyRelease events
+            ffi::ButtonPress => {
+                // The core button events are only received by the popups grabbing the pointer,
+                // for the clicks outside of the windows of the application, which dismiss them.
+                let xbev: &ffi::XButtonEvent = xev.as_ref();
+                let window = xbev.window as xproto::Window;
+                if self
+                    .with_window(window, |window| window.dismiss_popup())
+                    .unwrap_or(false)
+                {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(window),
+                        event: WindowEvent::PopupDismissed,
+                    });
+                }
+            }
+
             ty @ ffi::KeyPress | ty @ ffi::KeyRelease => {
                 let xkev: &mut ffi::XKeyEvent = xev.as_mut();
 
                 // Set the timestamp.
                 wt.xconn.set_timestamp(xkev.time as xproto::Timestamp);
 
-                let window = match self.active_window {
-                    Some(window) => window,
-                    None => return,
+                // The popups grabbing the keyboard get the keys, without being focused.
+                let popup = xkev.window as xproto::Window;
+                let window = if self
+                    .with_window(popup, |window| window.has_popup_grab())
+                    .unwrap_or(false)
+                {
+                    popup
+                } else {
+                    match self.active_window {
+                        Some(window) => window,
+                        None => return,
+                    }
                 };
 
                 let window_id = mkwid(window);
@@ -619,7 +914,12 @@ impl<T: 'static> EventProcessor<T> {
                 };
 
                 if keycode != 0 && !self.is_composing {
-                    let event = self.kb_state.process_key_event(keycode, state, repeat);
+                    let event = self.kb_state.process_key_event(
+                        keycode,
+                        state,
+                        repeat,
+                        timestamp::from_millis(xkev.time as u32),
+                    );
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::KeyboardInput {
@@ -655,6 +955,10 @@ impl<T: 'static> EventProcessor<T> {
                     return;
                 };
                 let xev = &guard.cookie;
+                if Some(xev.extension) == self.present_opcode {
+                    self.process_present_event(xev, &mut callback);
+                    return;
+                }
                 if self.xi2ext.opcode != xev.extension {
                     return;
                 }
@@ -663,10 +967,10 @@ impl<T: 'static> EventProcessor<T> {
                     ElementState::{Pressed, Released},
                     MouseButton::{Back, Forward, Left, Middle, Other, Right},
                     MouseScrollDelta::LineDelta,
-                    Touch,
+                    PenButton, PenEvent, Pointer, PointerKind, PointerPhase, ScrollSource, Touch,
                     WindowEvent::{
                         AxisMotion, CursorEntered, CursorLeft, CursorMoved, Focused, MouseInput,
-                        MouseWheel,
+                        MouseWheel, PenInput,
                     },
                 };
 
@@ -679,6 +983,26 @@ impl<T: 'static> EventProcessor<T> {
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
 
+                        // Releasing the button drops the data dragged out of our window.
+                        if xev.evtype == ffi::XI_ButtonRelease {
+                            let result = wt
+                                .drag_source
+                                .borrow_mut()
+                                .handle_release(xev.time as xproto::Timestamp);
+                            let outcome = match result {
+                                Ok(outcome) => outcome,
+                                Err(err) => {
+                                    warn!("Failed to drop the dragged data: {}", err);
+                                    let origin = wt.drag_source.borrow_mut().cancel();
+                                    origin.map(|origin| (origin, DragSourceEvent::Cancelled))
+                                }
+                            };
+
+                            if let Some((origin, event)) = outcome {
+                                self.drag_source_event(origin, event, &mut callback);
+                            }
+                        }
+
                         if (xev.flags & ffi::XIPointerEmulated) != 0 {
                             // Deliver multi-touch events instead of emulated mouse events.
                             return;
@@ -689,6 +1013,33 @@ impl<T: 'static> EventProcessor<T> {
                         } else {
                             Released
                         };
+
+                        // The tip and the barrel buttons of pens are reported as buttons.
+                        let pen = self
+                            .devices
+                            .borrow()
+                            .get(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_ref())
+                            .map(|pen| (pen.tool, pen.axes()));
+                        let is_pen = pen.is_some();
+                        let pen_event = match xev.detail as u32 {
+                            _ if !is_pen => None,
+                            ffi::Button1 => Some(PenEvent::Tip { state }),
+                            ffi::Button2 => Some(PenEvent::Button {
+                                button: PenButton::Primary,
+                                state,
+                            }),
+                            ffi::Button3 => Some(PenEvent::Button {
+                                button: PenButton::Secondary,
+                                state,
+                            }),
+                            4..=7 => None,
+                            x => Some(PenEvent::Button {
+                                button: PenButton::Other(x as u16),
+                                state,
+                            }),
+                        };
+
                         match xev.detail as u32 {
                             ffi::Button1 => callback(Event::WindowEvent {
                                 window_id,
@@ -696,6 +1047,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Left,
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
                             ffi::Button2 => callback(Event::WindowEvent {
@@ -704,6 +1056,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Middle,
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
                             ffi::Button3 => callback(Event::WindowEvent {
@@ -712,6 +1065,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Right,
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
 
@@ -732,6 +1086,18 @@ impl<T: 'static> EventProcessor<T> {
                                                 _ => unreachable!(),
                                             },
                                             phase: TouchPhase::Moved,
+                                            // Horizontal scroll buttons come from tilting the wheel.
+                                            source: Some(match xev.detail {
+                                                4 | 5 => ScrollSource::Wheel,
+                                                _ => ScrollSource::WheelTilt,
+                                            }),
+                                            value120: Some(match xev.detail {
+                                                4 => (0, 120),
+                                                5 => (0, -120),
+                                                6 => (120, 0),
+                                                _ => (-120, 0),
+                                            }),
+                                            timestamp: event_time(xev.time),
                                         },
                                     });
                                 }
@@ -743,6 +1109,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Back,
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
                             9 => callback(Event::WindowEvent {
@@ -751,6 +1118,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Forward,
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
 
@@ -760,9 +1128,46 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Other(x as u16),
+                                    timestamp: event_time(xev.time),
                                 },
                             }),
                         }
+
+                        if let Some(event) = pen_event {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: PenInput { device_id, event },
+                            });
+                        }
+
+                        if !(4..=7).contains(&xev.detail) {
+                            // The button state of the event is the one before the press.
+                            let buttons = pointer_buttons(&xev.buttons);
+                            let button = pointer_button(xev.detail as u32);
+                            let (phase, buttons) = match state {
+                                Pressed => (PointerPhase::Down, buttons | button),
+                                Released => (PointerPhase::Up, buttons - button),
+                            };
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Pointer(Pointer {
+                                    device_id,
+                                    pointer_id: 0,
+                                    kind: if is_pen {
+                                        PointerKind::Pen
+                                    } else {
+                                        PointerKind::Mouse
+                                    },
+                                    phase,
+                                    position: PhysicalPosition::new(xev.event_x, xev.event_y),
+                                    buttons: pen_buttons(pen.map(|(tool, _)| tool), buttons),
+                                    pressure: pen.and_then(|(_, axes)| axes.pressure),
+                                    tilt: pen.and_then(|(_, axes)| axes.tilt),
+                                    size: None,
+                                    timestamp: event_time(xev.time),
+                                }),
+                            });
+                        }
                     }
                     ffi::XI_Motion => {
                         let xev: &ffi::XIDeviceEvent = unsafe { &*(xev.data as *const _) };
@@ -770,6 +1175,16 @@ impl<T: 'static> EventProcessor<T> {
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
 
+                        // The data dragged out of our window follows the cursor.
+                        let result = wt.drag_source.borrow_mut().handle_motion(
+                            xev.root_x as i16,
+                            xev.root_y as i16,
+                            xev.time as xproto::Timestamp,
+                        );
+                        if let Err(err) = result {
+                            warn!("Failed to move the dragged data: {}", err);
+                        }
+
                         let device_id = mkdid(xev.deviceid);
                         let window = xev.event as xproto::Window;
                         let window_id = mkwid(window);
@@ -787,6 +1202,7 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    timestamp: event_time(xev.time),
                                 },
                             });
                         } else if cursor_moved.is_none() {
@@ -812,6 +1228,9 @@ impl<T: 'static> EventProcessor<T> {
                             for i in 0..xev.valuators.mask_len * 8 {
                                 if ffi::XIMaskIsSet(mask, i) {
                                     let x = unsafe { *value };
+                                    if let Some(pen) = physical_device.pen.as_mut() {
+                                        pen.update(i, x);
+                                    }
                                     if let Some(&mut (_, ref mut info)) = physical_device
                                         .scroll_axes
                                         .iter_mut()
@@ -819,6 +1238,8 @@ impl<T: 'static> EventProcessor<T> {
                                     {
                                         let delta = (x - info.position) / info.increment;
                                         info.position = x;
+                                        // The increment is the distance of one wheel detent.
+                                        let value120 = (-delta * 120.0).round() as i32;
                                         events.push(Event::WindowEvent {
                                             window_id,
                                             event: MouseWheel {
@@ -833,6 +1254,12 @@ impl<T: 'static> EventProcessor<T> {
                                                     }
                                                 },
                                                 phase: TouchPhase::Moved,
+                                                source: None,
+                                                value120: Some(match info.orientation {
+                                                    ScrollOrientation::Horizontal => (value120, 0),
+                                                    ScrollOrientation::Vertical => (0, value120),
+                                                }),
+                                                timestamp: event_time(xev.time),
                                             },
                                         });
                                     } else {
@@ -848,6 +1275,73 @@ impl<T: 'static> EventProcessor<T> {
                                     value = unsafe { value.offset(1) };
                                 }
                             }
+
+                            // The pens enter the window with their first motion.
+                            let mut pen_entered = false;
+                            if let Some(pen) = physical_device.pen.as_mut() {
+                                if pen.window != Some(window) {
+                                    pen.window = Some(window);
+                                    pen_entered = true;
+                                    events.push(Event::WindowEvent {
+                                        window_id,
+                                        event: PenInput {
+                                            device_id,
+                                            event: PenEvent::Entered { tool: pen.tool },
+                                        },
+                                    });
+                                }
+
+                                events.push(Event::WindowEvent {
+                                    window_id,
+                                    event: PenInput {
+                                        device_id,
+                                        event: PenEvent::Moved {
+                                            position: PhysicalPosition::new(
+                                                xev.event_x,
+                                                xev.event_y,
+                                            ),
+                                            axes: pen.axes(),
+                                        },
+                                    },
+                                });
+                            }
+
+                            let pen = physical_device.pen.as_ref();
+                            if cursor_moved == Some(true) || pen.is_some() {
+                                let axes = pen.map(|pen| pen.axes()).unwrap_or_default();
+                                let phases: &[PointerPhase] = if pen_entered {
+                                    &[PointerPhase::Entered, PointerPhase::Moved]
+                                } else {
+                                    &[PointerPhase::Moved]
+                                };
+                                for &phase in phases {
+                                    events.push(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::Pointer(Pointer {
+                                            device_id,
+                                            pointer_id: 0,
+                                            kind: if pen.is_some() {
+                                                PointerKind::Pen
+                                            } else {
+                                                PointerKind::Mouse
+                                            },
+                                            phase,
+                                            position: PhysicalPosition::new(
+                                                xev.event_x,
+                                                xev.event_y,
+                                            ),
+                                            buttons: pen_buttons(
+                                                pen.map(|pen| pen.tool),
+                                                pointer_buttons(&xev.buttons),
+                                            ),
+                                            pressure: axes.pressure,
+                                            tilt: axes.tilt,
+                                            size: None,
+                                            timestamp: event_time(xev.time),
+                                        }),
+                                    });
+                                }
+                            }
                         }
                         for event in events {
                             callback(event);
@@ -864,6 +1358,13 @@ impl<T: 'static> EventProcessor<T> {
                         let window_id = mkwid(window);
                         let device_id = mkdid(xev.deviceid);
 
+                        // Pens enter the window with their first motion, see `XI_Motion`.
+                        let is_pen = self
+                            .devices
+                            .borrow()
+                            .get(&DeviceId(xev.sourceid))
+                            .map_or(false, |device| device.pen.is_some());
+
                         if let Some(all_info) = DeviceInfo::get(&wt.xconn, ffi::XIAllDevices) {
                             let mut devices = self.devices.borrow_mut();
                             for device_info in all_info.iter() {
@@ -885,7 +1386,10 @@ impl<T: 'static> EventProcessor<T> {
                         if self.window_exists(window) {
                             callback(Event::WindowEvent {
                                 window_id,
-                                event: CursorEntered { device_id },
+                                event: CursorEntered {
+                                    device_id,
+                                    timestamp: event_time(xev.time),
+                                },
                             });
 
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
@@ -895,8 +1399,27 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    timestamp: event_time(xev.time),
                                 },
                             });
+
+                            if !is_pen {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::Pointer(Pointer {
+                                        device_id,
+                                        pointer_id: 0,
+                                        kind: PointerKind::Mouse,
+                                        phase: PointerPhase::Entered,
+                                        position,
+                                        buttons: pointer_buttons(&xev.buttons),
+                                        pressure: None,
+                                        tilt: None,
+                                        size: None,
+                                        timestamp: event_time(xev.time),
+                                    }),
+                                });
+                            }
                         }
                     }
                     ffi::XI_Leave => {
@@ -909,13 +1432,55 @@ impl<T: 'static> EventProcessor<T> {
                         // Leave, FocusIn, and FocusOut can be received by a window that's already
                         // been destroyed, which the user presumably doesn't want to deal with.
                         let window_closed = !self.window_exists(window);
+
+                        // The pen leaves along with the cursor.
+                        let pen_left = self
+                            .devices
+                            .borrow_mut()
+                            .get_mut(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_mut())
+                            .filter(|pen| pen.window == Some(window))
+                            .map(|pen| pen.window = None)
+                            .is_some();
+
                         if !window_closed {
+                            if pen_left {
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(window),
+                                    event: PenInput {
+                                        device_id: mkdid(xev.deviceid),
+                                        event: PenEvent::Left,
+                                    },
+                                });
+                            }
+
                             callback(Event::WindowEvent {
                                 window_id: mkwid(window),
                                 event: CursorLeft {
                                     device_id: mkdid(xev.deviceid),
+                                    timestamp: event_time(xev.time),
                                 },
                             });
+
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::Pointer(Pointer {
+                                    device_id: mkdid(xev.deviceid),
+                                    pointer_id: 0,
+                                    kind: if pen_left {
+                                        PointerKind::Pen
+                                    } else {
+                                        PointerKind::Mouse
+                                    },
+                                    phase: PointerPhase::Left,
+                                    position: PhysicalPosition::new(xev.event_x, xev.event_y),
+                                    buttons: pointer_buttons(&xev.buttons),
+                                    pressure: None,
+                                    tilt: None,
+                                    size: None,
+                                    timestamp: event_time(xev.time),
+                                }),
+                            });
                         }
                     }
                     ffi::XI_FocusIn => {
@@ -930,9 +1495,31 @@ impl<T: 'static> EventProcessor<T> {
                             .focus(xev.event)
                             .expect("Failed to focus input context");
 
-                        if self.active_window != Some(window) {
-                            self.active_window = Some(window);
+                        // The deviceid for this event is for a keyboard instead of a pointer,
+                        // so we have to do a little extra work.
+                        let pointer_id = self
+                            .devices
+                            .borrow()
+                            .get(&DeviceId(xev.deviceid))
+                            .map(|device| device.attachment)
+                            .unwrap_or(2);
+
+                        // The master pointer identifies the seat.
+                        let seat = SeatId(pointer_id as u64);
+                        if self.window_exists(window) {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::SeatFocused {
+                                    seat,
+                                    focused: true,
+                                },
+                            });
+                        }
+
+                        self.active_window = Some(window);
 
+                        // The window is focused as long as any seat focuses it.
+                        if set_seat_focus(&mut self.focused_seats, window, seat, true) {
                             wt.update_listen_device_events(true);
 
                             let window_id = mkwid(window);
@@ -956,20 +1543,12 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                             }
 
-                            // The deviceid for this event is for a keyboard instead of a pointer,
-                            // so we have to do a little extra work.
-                            let pointer_id = self
-                                .devices
-                                .borrow()
-                                .get(&DeviceId(xev.deviceid))
-                                .map(|device| device.attachment)
-                                .unwrap_or(2);
-
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: CursorMoved {
                                     device_id: mkdid(pointer_id),
                                     position,
+                                    timestamp: event_time(xev.time),
                                 },
                             });
 
@@ -979,6 +1558,7 @@ impl<T: 'static> EventProcessor<T> {
                                 window_id,
                                 ElementState::Pressed,
                                 &mut self.kb_state,
+                                timestamp::from_millis(xev.time as u32),
                                 &mut callback,
                             );
                         }
@@ -990,6 +1570,22 @@ impl<T: 'static> EventProcessor<T> {
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
 
+                        let pointer_id = self
+                            .devices
+                            .borrow()
+                            .get(&DeviceId(xev.deviceid))
+                            .map(|device| device.attachment)
+                            .unwrap_or(2);
+                        let seat = SeatId(pointer_id as u64);
+
+                        // The window loses the focus once the last seat leaves it, the core
+                        // keyboard events then go to a window still focused by another seat.
+                        let focus_changed =
+                            set_seat_focus(&mut self.focused_seats, window, seat, false);
+                        if focus_changed && self.active_window == Some(window) {
+                            self.active_window = self.focused_seats.keys().next().copied();
+                        }
+
                         if !self.window_exists(window) {
                             return;
                         }
@@ -999,10 +1595,18 @@ impl<T: 'static> EventProcessor<T> {
                             .unfocus(xev.event)
                             .expect("Failed to unfocus input context");
 
-                        if self.active_window.take() == Some(window) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::SeatFocused {
+                                seat,
+                                focused: false,
+                            },
+                        });
+
+                        if focus_changed {
                             let window_id = mkwid(window);
 
-                            wt.update_listen_device_events(false);
+                            wt.update_listen_device_events(self.active_window.is_some());
 
                             // Issue key release events for all pressed keys
                             Self::handle_pressed_keys(
@@ -1010,6 +1614,7 @@ impl<T: 'static> EventProcessor<T> {
                                 window_id,
                                 ElementState::Released,
                                 &mut self.kb_state,
+                                timestamp::from_millis(xev.time as u32),
                                 &mut callback,
                             );
                             // Clear this so detecting key repeats is consistently handled when the
@@ -1052,6 +1657,36 @@ impl<T: 'static> EventProcessor<T> {
                             let id = xev.detail as u64;
                             let location = PhysicalPosition::new(xev.event_x, xev.event_y);
 
+                            // Read the pressure and the contact size from the valuators.
+                            let (force, contact_size) = {
+                                let mut devices = self.devices.borrow_mut();
+                                match devices
+                                    .get_mut(&DeviceId(xev.sourceid))
+                                    .and_then(|device| device.touch.as_mut())
+                                {
+                                    Some(touch) => {
+                                        let mask = unsafe {
+                                            slice::from_raw_parts(
+                                                xev.valuators.mask,
+                                                xev.valuators.mask_len as usize,
+                                            )
+                                        };
+                                        let mut value = xev.valuators.values;
+                                        for i in 0..xev.valuators.mask_len * 8 {
+                                            if ffi::XIMaskIsSet(mask, i) {
+                                                touch.update(i, unsafe { *value });
+                                                value = unsafe { value.offset(1) };
+                                            }
+                                        }
+
+                                        let screen_width =
+                                            wt.xconn.default_root().width_in_pixels as f64;
+                                        (touch.force(), touch.contact_size(screen_width))
+                                    }
+                                    None => (None, None),
+                                }
+                            };
+
                             // Mouse cursor position changes when touch events are received.
                             // Only the first concurrently active touch ID moves the mouse cursor.
                             if is_first_touch(&mut self.first_touch, &mut self.num_touch, id, phase)
@@ -1061,6 +1696,7 @@ impl<T: 'static> EventProcessor<T> {
                                     event: WindowEvent::CursorMoved {
                                         device_id: mkdid(util::VIRTUAL_CORE_POINTER.into()),
                                         position: location.cast(),
+                                        timestamp: event_time(xev.time),
                                     },
                                 });
                             }
@@ -1071,13 +1707,126 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id: mkdid(xev.deviceid),
                                     phase,
                                     location,
-                                    force: None, // TODO
+                                    force,
+                                    contact_size,
                                     id,
+                                    timestamp: event_time(xev.time),
+                                }),
+                            });
+
+                            let (phase, buttons) = match phase {
+                                TouchPhase::Started => {
+                                    (PointerPhase::Down, PointerButtons::PRIMARY)
+                                }
+                                TouchPhase::Moved => (PointerPhase::Moved, PointerButtons::PRIMARY),
+                                _ => (PointerPhase::Up, PointerButtons::empty()),
+                            };
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Pointer(Pointer {
+                                    device_id: mkdid(xev.deviceid),
+                                    pointer_id: id,
+                                    kind: PointerKind::Touch,
+                                    phase,
+                                    position: location,
+                                    buttons,
+                                    pressure: force.map(|force| force.normalized()),
+                                    tilt: None,
+                                    size: contact_size,
+                                    timestamp: event_time(xev.time),
                                 }),
                             })
                         }
                     }
 
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { &*(xev.data as *const _) };
+
+                        // Set the timestamp.
+                        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+
+                        let window = xev.event as xproto::Window;
+                        if !self.window_exists(window) {
+                            return;
+                        }
+
+                        // The scale is relative to the beginning of the pinch, but the angle is
+                        // clockwise relative to the previous event.
+                        let (phase, magnify, rotate) = match xev.evtype {
+                            ffi::XI_GesturePinchBegin => {
+                                self.pinch_scale = 1.0;
+                                (TouchPhase::Started, 0.0, 0.0)
+                            }
+                            ffi::XI_GesturePinchUpdate => {
+                                let magnify = xev.scale - self.pinch_scale;
+                                self.pinch_scale = xev.scale;
+                                (TouchPhase::Moved, magnify, -xev.delta_angle)
+                            }
+                            _ if xev.flags & ffi::XIGesturePinchEventCancelled != 0 => {
+                                (TouchPhase::Cancelled, 0.0, 0.0)
+                            }
+                            _ => (TouchPhase::Ended, 0.0, 0.0),
+                        };
+
+                        let window_id = mkwid(window);
+                        let device_id = mkdid(xev.deviceid);
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::TouchpadMagnify {
+                                device_id,
+                                delta: magnify,
+                                phase,
+                                timestamp: event_time(xev.time),
+                            },
+                        });
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::TouchpadRotate {
+                                device_id,
+                                delta: rotate as f32,
+                                phase,
+                                timestamp: event_time(xev.time),
+                            },
+                        });
+                    }
+
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { &*(xev.data as *const _) };
+
+                        // Set the timestamp.
+                        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+
+                        let window = xev.event as xproto::Window;
+                        if !self.window_exists(window) {
+                            return;
+                        }
+
+                        let phase = match xev.evtype {
+                            ffi::XI_GestureSwipeBegin => TouchPhase::Started,
+                            ffi::XI_GestureSwipeUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGestureSwipeEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::TouchpadSwipe {
+                                device_id: mkdid(xev.deviceid),
+                                delta: PhysicalPosition::new(xev.delta_x, xev.delta_y),
+                                // The detail is the number of touches of the gesture.
+                                fingers: xev.detail as u32,
+                                phase,
+                                timestamp: event_time(xev.time),
+                            },
+                        });
+                    }
+
                     ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => {
                         let xev: &ffi::XIRawEvent = unsafe { &*(xev.data as *const _) };
 
@@ -1087,6 +1836,7 @@ impl<T: 'static> EventProcessor<T> {
                         if xev.flags & ffi::XIPointerEmulated == 0 {
                             callback(Event::DeviceEvent {
                                 device_id: mkdid(xev.deviceid),
+                                timestamp: event_time(xev.time),
                                 event: DeviceEvent::Button {
                                     button: xev.detail as u32,
                                     state: match xev.evtype {
@@ -1130,6 +1880,7 @@ impl<T: 'static> EventProcessor<T> {
                                 }
                                 callback(Event::DeviceEvent {
                                     device_id: did,
+                                    timestamp: event_time(xev.time),
                                     event: DeviceEvent::Motion {
                                         axis: i as u32,
                                         value: x,
@@ -1141,12 +1892,14 @@ impl<T: 'static> EventProcessor<T> {
                         if mouse_delta != (0.0, 0.0) {
                             callback(Event::DeviceEvent {
                                 device_id: did,
+                                timestamp: event_time(xev.time),
                                 event: DeviceEvent::MouseMotion { delta: mouse_delta },
                             });
                         }
                         if scroll_delta != (0.0, 0.0) {
                             callback(Event::DeviceEvent {
                                 device_id: did,
+                                timestamp: event_time(xev.time),
                                 event: DeviceEvent::MouseWheel {
                                     delta: LineDelta(scroll_delta.0, scroll_delta.1),
                                 },
@@ -1174,6 +1927,7 @@ impl<T: 'static> EventProcessor<T> {
 
                         callback(Event::DeviceEvent {
                             device_id,
+                            timestamp: event_time(xev.time),
                             event: DeviceEvent::Key(RawKeyEvent {
                                 physical_key,
                                 state,
@@ -1194,12 +1948,14 @@ impl<T: 'static> EventProcessor<T> {
                                 self.init_device(info.deviceid);
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid),
+                                    timestamp: event_time(xev.time),
                                     event: DeviceEvent::Added,
                                 });
                             } else if 0 != info.flags & (ffi::XISlaveRemoved | ffi::XIMasterRemoved)
                             {
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid),
+                                    timestamp: event_time(xev.time),
                                     event: DeviceEvent::Removed,
                                 });
                                 let mut devices = self.devices.borrow_mut();
@@ -1393,6 +2149,7 @@ impl<T: 'static> EventProcessor<T> {
         window_id: crate::window::WindowId,
         state: ElementState,
         kb_state: &mut KbdState,
+        timestamp: Instant,
         callback: &mut F,
     ) where
         F: FnMut(Event<'_, T>),
@@ -1407,7 +2164,7 @@ impl<T: 'static> EventProcessor<T> {
             .filter(|k| *k >= KEYCODE_OFFSET)
         {
             let keycode = keycode as u32;
-            let event = kb_state.process_key_event(keycode, state, false);
+            let event = kb_state.process_key_event(keycode, state, false, timestamp);
             callback(Event::WindowEvent {
                 window_id,
                 event: WindowEvent::KeyboardInput {
@@ -1439,3 +2196,80 @@ fn is_first_touch(first: &mut Option<u64>, num: &mut u32, id: u64, phase: TouchP
 
     *first == Some(id)
 }
+
+/// The time of an X event, in milliseconds.
+fn event_time(time: ffi::Time) -> Option<Instant> {
+    Some(timestamp::from_millis(time as u32))
+}
+
+/// Maps an X button to the pointer buttons.
+fn pointer_button(button: u32) -> PointerButtons {
+    match button {
+        ffi::Button1 => PointerButtons::PRIMARY,
+        ffi::Button2 => PointerButtons::AUXILIARY,
+        ffi::Button3 => PointerButtons::SECONDARY,
+        8 => PointerButtons::BACK,
+        9 => PointerButtons::FORWARD,
+        _ => PointerButtons::empty(),
+    }
+}
+
+/// Reads the buttons held before an XInput2 event.
+fn pointer_buttons(state: &ffi::XIButtonState) -> PointerButtons {
+    let mask = unsafe { slice::from_raw_parts(state.mask, state.mask_len as usize) };
+    (0..state.mask_len * 8)
+        .filter(|&button| ffi::XIMaskIsSet(mask, button))
+        .fold(PointerButtons::empty(), |buttons, button| {
+            buttons | pointer_button(button as u32)
+        })
+}
+
+/// The presentation feedback requested by a window.
+struct FeedbackRequest {
+    /// The refresh interval of the monitor of the window, `None` when its refresh rate is
+    /// unknown.
+    refresh: Option<Duration>,
+}
+
+impl FeedbackRequest {
+    fn new(window: &UnownedWindow) -> Self {
+        let refresh = window
+            .current_monitor()
+            .refresh_rate_millihertz()
+            .filter(|&refresh| refresh != 0)
+            .map(|refresh| Duration::from_nanos(1_000_000_000_000 / refresh as u64));
+        Self { refresh }
+    }
+}
+
+/// Mark that the keyboard focus of the seat has entered or left the window, returning whether
+/// the focus of the window as a whole has changed.
+fn set_seat_focus(
+    focused_seats: &mut HashMap<xproto::Window, Vec<SeatId>>,
+    window: xproto::Window,
+    seat: SeatId,
+    focused: bool,
+) -> bool {
+    let seats = focused_seats.entry(window).or_default();
+    let had_focus = !seats.is_empty();
+    seats.retain(|focused_seat| *focused_seat != seat);
+    if focused {
+        seats.push(seat);
+    }
+
+    let has_focus = !seats.is_empty();
+    if !has_focus {
+        focused_seats.remove(&window);
+    }
+
+    had_focus != has_focus
+}
+
+/// The tip of an eraser is reported as the eraser button.
+fn pen_buttons(tool: Option<PenTool>, mut buttons: PointerButtons) -> PointerButtons {
+    if tool == Some(PenTool::Eraser) && buttons.contains(PointerButtons::PRIMARY) {
+        buttons.remove(PointerButtons::PRIMARY);
+        buttons.insert(PointerButtons::ERASER);
+    }
+    buttons
+}
@@ -2,6 +2,7 @@
 
 mod activation;
 mod atoms;
+mod clipboard;
 mod dnd;
 mod event_processor;
 pub mod ffi;
@@ -57,18 +58,31 @@ use x11rb::{
 };
 
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::{Dnd, DragSource},
     event_processor::EventProcessor,
     ime::{Ime, ImeCreationError, ImeReceiver, ImeRequest, ImeSender},
 };
-use super::common::xkb_state::KbdState;
+use super::common::{
+    dnd::DragRequest,
+    event_sources::{EventSources, Waker},
+    modal,
+    xkb_state::KbdState,
+};
 use crate::{
-    error::OsError as RootOsError,
-    event::{Event, StartCause},
-    event_loop::{ControlFlow, DeviceEvents, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    clipboard::{ClipboardContent, ClipboardKind},
+    device::{DeviceCapabilities, DeviceInfo as RootDeviceInfo, DeviceKind, SeatId},
+    dnd::{DndAction, DragSourceEvent},
+    error::{ExternalError, OsError as RootOsError},
+    event::{Event, Force, PenAxes, PenTool, StartCause},
+    event_loop::{
+        AsyncRequestSerial, ControlFlow, DeviceEvents, EventLoopClosed,
+        EventLoopWindowTarget as RootELW, SourceId,
+    },
+    platform::pump_events::PumpStatus,
     platform_impl::{
         platform::{sticky_exit_callback, WindowId},
-        PlatformSpecificWindowBuilderAttributes,
+        OsError, PlatformSpecificWindowBuilderAttributes,
     },
     window::WindowAttributes,
 };
@@ -82,10 +96,16 @@ pub struct EventLoopWindowTarget<T> {
     ime_sender: ImeSender,
     root: xproto::Window,
     ime: RefCell<Ime>,
+    clipboard: RefCell<Clipboard>,
+    dnd: RefCell<Dnd>,
+    drag_source: RefCell<DragSource>,
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: Sender<WindowId>,
     activation_sender: Sender<ActivationToken>,
+    drag_sender: Sender<(WindowId, DragRequest)>,
     device_events: Cell<DeviceEvents>,
+    xinput_gestures: bool,
+    event_sources: EventSources<EventLoopState<T>>,
     _marker: ::std::marker::PhantomData<T>,
 }
 
@@ -100,9 +120,25 @@ pub struct EventLoop<T: 'static> {
 
     /// Dispatcher for redraw events.
     redraw_dispatcher: Dispatcher<'static, Channel<WindowId>, EventLoopState<T>>,
+
+    /// The state of the running loop, `None` until it's started.
+    pump_state: Option<PumpState>,
+}
+
+type ActivationToken = (WindowId, AsyncRequestSerial);
+
+struct IterationResult {
+    deadline: Option<Instant>,
+    timeout: Option<Duration>,
+    wait_start: Instant,
 }
 
-type ActivationToken = (WindowId, crate::event_loop::AsyncRequestSerial);
+/// The state of the loop kept between the calls to `pump_events`.
+struct PumpState {
+    control_flow: ControlFlow,
+    cause: StartCause,
+    iter_result: IterationResult,
+}
 
 struct EventLoopState<T> {
     /// Incoming user events.
@@ -113,16 +149,24 @@ struct EventLoopState<T> {
 
     /// Incoming activation tokens.
     activation_tokens: VecDeque<ActivationToken>,
+
+    /// Incoming requests to start the drag.
+    drag_requests: VecDeque<(WindowId, DragRequest)>,
+
+    /// The event sources of the application which are ready.
+    source_events: VecDeque<SourceId>,
 }
 
 pub struct EventLoopProxy<T: 'static> {
     user_sender: Sender<T>,
+    waker: Arc<Waker>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
             user_sender: self.user_sender.clone(),
+            waker: self.waker.clone(),
         }
     }
 }
@@ -135,8 +179,10 @@ impl<T: 'static> EventLoop<T> {
         let wm_delete_window = atoms[WM_DELETE_WINDOW];
         let net_wm_ping = atoms[_NET_WM_PING];
 
-        let dnd = Dnd::new(Arc::clone(&xconn))
-            .expect("Failed to call XInternAtoms when initializing drag and drop");
+        let dnd = RefCell::new(
+            Dnd::new(Arc::clone(&xconn))
+                .expect("Failed to call XInternAtoms when initializing drag and drop"),
+        );
 
         let (ime_sender, ime_receiver) = mpsc::channel();
         let (ime_event_sender, ime_event_receiver) = mpsc::channel();
@@ -171,6 +217,17 @@ impl<T: 'static> EventLoop<T> {
             result.expect("Failed to set input method destruction callback")
         });
 
+        let clipboard = RefCell::new(
+            Clipboard::new(Arc::clone(&xconn), root)
+                .expect("Failed to create the clipboard window"),
+        );
+
+        // The clipboard window also takes part in the drags from our windows.
+        let drag_source = RefCell::new(DragSource::new(
+            Arc::clone(&xconn),
+            clipboard.borrow().window(),
+        ));
+
         let randr_event_offset = xconn
             .select_xrandr_input(root as ffi::Window)
             .expect("Failed to query XRandR extension");
@@ -223,9 +280,13 @@ impl<T: 'static> EventLoop<T> {
             ext
         };
 
-        unsafe {
+        let present_opcode = xconn.init_present();
+
+        // Ask for XInput 2.4 to get the touchpad gestures, the server replies with the version
+        // it supports.
+        let xinput_gestures = unsafe {
             let mut xinput_major_ver = ffi::XI_2_Major;
-            let mut xinput_minor_ver = ffi::XI_2_Minor;
+            let mut xinput_minor_ver = ffi::XI_2_4_Minor;
             if (xconn.xinput2.XIQueryVersion)(
                 xconn.display,
                 &mut xinput_major_ver,
@@ -236,7 +297,9 @@ impl<T: 'static> EventLoop<T> {
                     "X server has XInput extension {xinput_major_ver}.{xinput_minor_ver} but does not support XInput2",
                 );
             }
-        }
+
+            (xinput_major_ver, xinput_minor_ver) >= (ffi::XI_2_Major, ffi::XI_2_4_Minor)
+        };
 
         xconn.update_cached_wm_info(root);
 
@@ -295,11 +358,35 @@ impl<T: 'static> EventLoop<T> {
             .register_dispatcher(activation_tokens.clone())
             .expect("Failed to register the activation token channel with the event loop");
 
+        // Create a channel for the requests to start the drag.
+        let (drag_sender, drag_channel) = channel();
+
+        let drag_requests =
+            Dispatcher::<_, EventLoopState<T>>::new(drag_channel, |ev, _, state| {
+                if let ChanResult::Msg(request) = ev {
+                    state.drag_requests.push_back(request);
+                }
+            });
+        handle
+            .register_dispatcher(drag_requests.clone())
+            .expect("Failed to register the drag channel with the event loop");
+
+        // Create the event sources of the application.
+        let event_sources = EventSources::new(
+            handle.clone(),
+            Some(xconn.xcb_connection().as_raw_fd()),
+            |state: &mut EventLoopState<T>, source| state.source_events.push_back(source),
+        )
+        .expect("Failed to create the event sources");
+
         let kb_state =
             KbdState::from_x11_xkb(xconn.xcb_connection().get_raw_xcb_connection()).unwrap();
 
         let window_target = EventLoopWindowTarget {
             ime,
+            clipboard,
+            dnd,
+            drag_source,
             root,
             windows: Default::default(),
             _marker: ::std::marker::PhantomData,
@@ -309,7 +396,10 @@ impl<T: 'static> EventLoop<T> {
             net_wm_ping,
             redraw_sender,
             activation_sender: activation_token_sender,
+            drag_sender,
             device_events: Default::default(),
+            xinput_gestures,
+            event_sources,
         };
 
         // Set initial device event filter.
@@ -322,18 +412,20 @@ impl<T: 'static> EventLoop<T> {
 
         let event_processor = EventProcessor {
             target: target.clone(),
-            dnd,
             devices: Default::default(),
             randr_event_offset,
             ime_receiver,
             ime_event_receiver,
             xi2ext,
             xkbext,
+            present_opcode,
             kb_state,
             num_touch: 0,
             held_key_press: None,
             first_touch: None,
+            pinch_scale: 1.0,
             active_window: None,
+            focused_seats: HashMap::new(),
             is_composing: false,
         };
 
@@ -344,7 +436,7 @@ impl<T: 'static> EventLoop<T> {
             .select_xinput_events(
                 root,
                 ffi::XIAllDevices as _,
-                x11rb::protocol::xinput::XIEventMask::HIERARCHY,
+                &[x11rb::protocol::xinput::XIEventMask::HIERARCHY],
             )
             .expect_then_ignore_error("Failed to register for XInput2 device hotplug events");
 
@@ -364,10 +456,13 @@ impl<T: 'static> EventLoop<T> {
             user_sender,
             target,
             redraw_dispatcher,
+            pump_state: None,
             state: EventLoopState {
                 user_events: VecDeque::new(),
                 redraw_events: VecDeque::new(),
                 activation_tokens: VecDeque::new(),
+                drag_requests: VecDeque::new(),
+                source_events: VecDeque::new(),
             },
         }
     }
@@ -375,9 +470,14 @@ impl<T: 'static> EventLoop<T> {
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         EventLoopProxy {
             user_sender: self.user_sender.clone(),
+            waker: get_xtarget(&self.target).event_sources.waker().clone(),
         }
     }
 
+    pub fn as_raw_fd(&self) -> RawFd {
+        get_xtarget(&self.target).event_sources.as_raw_fd()
+    }
+
     pub(crate) fn window_target(&self) -> &RootELW<T> {
         &self.target
     }
@@ -386,224 +486,343 @@ impl<T: 'static> EventLoop<T> {
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        struct IterationResult {
-            deadline: Option<Instant>,
-            timeout: Option<Duration>,
-            wait_start: Instant,
-        }
-        fn single_iteration<T, F>(
-            this: &mut EventLoop<T>,
-            control_flow: &mut ControlFlow,
-            cause: &mut StartCause,
-            callback: &mut F,
-        ) -> IterationResult
-        where
-            F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
-        {
-            sticky_exit_callback(
-                crate::event::Event::NewEvents(*cause),
-                &this.target,
-                control_flow,
-                callback,
-            );
+        // Start the loop anew.
+        self.pump_state = None;
 
-            // NB: For consistency all platforms must emit a 'resumed' event even though X11
-            // applications don't themselves have a formal suspend/resume lifecycle.
-            if *cause == StartCause::Init {
-                sticky_exit_callback(
-                    crate::event::Event::Resumed,
-                    &this.target,
-                    control_flow,
-                    callback,
-                );
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                break code;
             }
+        }
+    }
 
-            // Process all pending events
-            this.drain_events(callback, control_flow);
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let PumpState {
+            mut control_flow,
+            mut cause,
+            iter_result,
+        } = match self.pump_state.take() {
+            Some(pump_state) => pump_state,
+            None => {
+                // Run the initial loop iteration.
+                let mut control_flow = ControlFlow::default();
+                let mut cause = StartCause::Init;
+                let iter_result =
+                    self.single_iteration(&mut control_flow, &mut cause, &mut callback);
+                return self.finish_pump(control_flow, cause, iter_result, &mut callback);
+            }
+        };
 
-            // Empty activation tokens.
-            while let Some((window_id, serial)) = this.state.activation_tokens.pop_front() {
-                let token = this
-                    .event_processor
-                    .with_window(window_id.0 as xproto::Window, |window| {
-                        window.generate_activation_token()
-                    });
+        if let ControlFlow::ExitWithCode(_) = control_flow {
+            return self.finish_pump(control_flow, cause, iter_result, &mut callback);
+        }
 
-                match token {
-                    Some(Ok(token)) => sticky_exit_callback(
-                        crate::event::Event::WindowEvent {
-                            window_id: crate::window::WindowId(window_id),
-                            event: crate::event::WindowEvent::ActivationTokenDone {
-                                serial,
-                                token: crate::window::ActivationToken::_new(token),
-                            },
-                        },
-                        &this.target,
-                        control_flow,
-                        callback,
-                    ),
-                    Some(Err(e)) => {
-                        log::error!("Failed to get activation token: {}", e);
-                    }
-                    None => {}
-                }
-            }
+        let has_pending = self.has_pending();
+        if !has_pending {
+            // Wait until the next event, the deadline, or the timeout of the caller. The time
+            // left until the deadline is measured anew, since the caller may have spent some of
+            // it between the calls.
+            let wait = match iter_result.deadline {
+                Some(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+                None => iter_result.timeout,
+            };
+            let timeout = match (wait, timeout) {
+                (Some(wait), Some(timeout)) => Some(wait.min(timeout)),
+                (wait, timeout) => wait.or(timeout),
+            };
 
-            // Empty the user event buffer
+            // Wake up in time to fail the clipboard transfers which have stalled.
+            let clipboard_timeout = get_xtarget(&self.target)
+                .clipboard
+                .borrow()
+                .next_deadline()
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            let timeout = match (timeout, clipboard_timeout) {
+                (Some(timeout), Some(clipboard)) => Some(timeout.min(clipboard)),
+                (timeout, clipboard) => timeout.or(clipboard),
+            };
+            if let Err(error) = self
+                .event_loop
+                .dispatch(timeout, &mut self.state)
+                .map_err(std::io::Error::from)
             {
-                while let Some(event) = this.state.user_events.pop_front() {
-                    sticky_exit_callback(
-                        crate::event::Event::UserEvent(event),
-                        &this.target,
-                        control_flow,
-                        callback,
-                    );
-                }
-            }
-            // send MainEventsCleared
-            {
-                sticky_exit_callback(
-                    crate::event::Event::MainEventsCleared,
-                    &this.target,
-                    control_flow,
-                    callback,
-                );
+                control_flow = ControlFlow::ExitWithCode(error.raw_os_error().unwrap_or(1));
+                return self.finish_pump(control_flow, cause, iter_result, &mut callback);
             }
 
-            // Quickly dispatch all redraw events to avoid buffering them.
-            while let Ok(event) = this.redraw_dispatcher.as_source_mut().try_recv() {
-                this.state.redraw_events.push_back(event);
+            let waiting = match control_flow {
+                ControlFlow::Wait => true,
+                ControlFlow::WaitUntil(deadline) => Instant::now() < deadline,
+                _ => false,
+            };
+            if waiting && !self.has_pending() {
+                // We don't go straight into executing the event loop iteration, we instead check
+                // again if there's any pending event. We must do this because during the
+                // execution of the iteration we sometimes wake the calloop waker, and if the
+                // waker is already awaken before we call poll(), then poll doesn't block, but it
+                // returns immediately. This caused the event loop to run continuously even if the
+                // control_flow was `Wait`
+                return self.finish_pump(control_flow, cause, iter_result, &mut callback);
             }
+        }
 
-            // Empty the redraw requests
-            {
-                let mut windows = HashSet::new();
+        let wait_cancelled = iter_result
+            .deadline
+            .map_or(false, |deadline| Instant::now() < deadline);
 
-                // Empty the channel.
+        if wait_cancelled {
+            cause = StartCause::WaitCancelled {
+                start: iter_result.wait_start,
+                requested_resume: iter_result.deadline,
+            };
+        }
 
-                while let Some(window_id) = this.state.redraw_events.pop_front() {
-                    windows.insert(window_id);
-                }
+        let iter_result = self.single_iteration(&mut control_flow, &mut cause, &mut callback);
+        self.finish_pump(control_flow, cause, iter_result, &mut callback)
+    }
+
+    /// Keep the state for the next call to `pump_events`, unless the loop has exited.
+    fn finish_pump<F>(
+        &mut self,
+        mut control_flow: ControlFlow,
+        cause: StartCause,
+        iter_result: IterationResult,
+        callback: &mut F,
+    ) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if let ControlFlow::ExitWithCode(code) = control_flow {
+            callback(
+                crate::event::Event::LoopDestroyed,
+                &self.target,
+                &mut control_flow,
+            );
+            PumpStatus::Exit(code)
+        } else {
+            self.pump_state = Some(PumpState {
+                control_flow,
+                cause,
+                iter_result,
+            });
+            PumpStatus::Continue
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        let clipboard_expired = get_xtarget(&self.target)
+            .clipboard
+            .borrow()
+            .next_deadline()
+            .map_or(false, |deadline| deadline <= Instant::now());
+
+        self.event_processor.poll()
+            || clipboard_expired
+            || !self.state.user_events.is_empty()
+            || !self.state.redraw_events.is_empty()
+            || !self.state.source_events.is_empty()
+    }
 
-                for window_id in windows {
-                    let window_id = crate::window::WindowId(window_id);
-                    sticky_exit_callback(
-                        Event::RedrawRequested(window_id),
-                        &this.target,
-                        control_flow,
-                        callback,
-                    );
+    fn single_iteration<F>(
+        &mut self,
+        control_flow: &mut ControlFlow,
+        cause: &mut StartCause,
+        callback: &mut F,
+    ) -> IterationResult
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        sticky_exit_callback(
+            crate::event::Event::NewEvents(*cause),
+            &self.target,
+            control_flow,
+            callback,
+        );
+
+        // NB: For consistency all platforms must emit a 'resumed' event even though X11
+        // applications don't themselves have a formal suspend/resume lifecycle.
+        if *cause == StartCause::Init {
+            sticky_exit_callback(
+                crate::event::Event::Resumed,
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Process all pending events
+        self.drain_events(callback, control_flow);
+
+        // Fail the clipboard requests which have timed out.
+        let events = get_xtarget(&self.target)
+            .clipboard
+            .borrow_mut()
+            .expire(Instant::now());
+        for event in events {
+            sticky_exit_callback(
+                crate::event::Event::Clipboard(event),
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
+
+        // Empty activation tokens.
+        while let Some((window_id, serial)) = self.state.activation_tokens.pop_front() {
+            let token = self
+                .event_processor
+                .with_window(window_id.0 as xproto::Window, |window| {
+                    window.generate_activation_token()
+                });
+
+            match token {
+                Some(Ok(token)) => sticky_exit_callback(
+                    crate::event::Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: crate::event::WindowEvent::ActivationTokenDone {
+                            serial,
+                            token: crate::window::ActivationToken::_new(token),
+                        },
+                    },
+                    &self.target,
+                    control_flow,
+                    callback,
+                ),
+                Some(Err(e)) => {
+                    log::error!("Failed to get activation token: {}", e);
                 }
+                None => {}
             }
-            // send RedrawEventsCleared
-            {
+        }
+
+        // Start the requested drags.
+        while let Some((window_id, request)) = self.state.drag_requests.pop_front() {
+            let events = get_xtarget(&self.target).start_drag(window_id, request);
+            for (window_id, event) in events {
                 sticky_exit_callback(
-                    crate::event::Event::RedrawEventsCleared,
-                    &this.target,
+                    crate::event::Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: crate::event::WindowEvent::DragSource(event),
+                    },
+                    &self.target,
                     control_flow,
                     callback,
                 );
             }
+        }
 
-            let start = Instant::now();
-            let (deadline, timeout);
+        // Empty the ready event sources.
+        while let Some(source) = self.state.source_events.pop_front() {
+            sticky_exit_callback(
+                crate::event::Event::SourceReady(source),
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
 
-            match control_flow {
-                ControlFlow::ExitWithCode(_) => {
-                    return IterationResult {
-                        wait_start: start,
-                        deadline: None,
-                        timeout: None,
-                    };
-                }
-                ControlFlow::Poll => {
-                    *cause = StartCause::Poll;
-                    deadline = None;
-                    timeout = Some(Duration::from_millis(0));
-                }
-                ControlFlow::Wait => {
-                    *cause = StartCause::WaitCancelled {
-                        start,
-                        requested_resume: None,
-                    };
-                    deadline = None;
-                    timeout = None;
-                }
-                ControlFlow::WaitUntil(wait_deadline) => {
-                    *cause = StartCause::ResumeTimeReached {
-                        start,
-                        requested_resume: *wait_deadline,
-                    };
-                    timeout = if *wait_deadline > start {
-                        Some(*wait_deadline - start)
-                    } else {
-                        Some(Duration::from_millis(0))
-                    };
-                    deadline = Some(*wait_deadline);
-                }
+        // Empty the user event buffer
+        {
+            while let Some(event) = self.state.user_events.pop_front() {
+                sticky_exit_callback(
+                    crate::event::Event::UserEvent(event),
+                    &self.target,
+                    control_flow,
+                    callback,
+                );
             }
+        }
+        // send MainEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::MainEventsCleared,
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
 
-            IterationResult {
-                wait_start: start,
-                deadline,
-                timeout,
-            }
+        // Quickly dispatch all redraw events to avoid buffering them.
+        while let Ok(event) = self.redraw_dispatcher.as_source_mut().try_recv() {
+            self.state.redraw_events.push_back(event);
         }
 
-        let mut control_flow = ControlFlow::default();
-        let mut cause = StartCause::Init;
+        // Empty the redraw requests
+        {
+            let mut windows = HashSet::new();
 
-        // run the initial loop iteration
-        let mut iter_result = single_iteration(self, &mut control_flow, &mut cause, &mut callback);
+            // Empty the channel.
 
-        let exit_code = loop {
-            if let ControlFlow::ExitWithCode(code) = control_flow {
-                break code;
+            while let Some(window_id) = self.state.redraw_events.pop_front() {
+                windows.insert(window_id);
             }
-            let has_pending = self.event_processor.poll()
-                || !self.state.user_events.is_empty()
-                || !self.state.redraw_events.is_empty();
-            if !has_pending {
-                // Wait until
-                if let Err(error) = self
-                    .event_loop
-                    .dispatch(iter_result.timeout, &mut self.state)
-                    .map_err(std::io::Error::from)
-                {
-                    break error.raw_os_error().unwrap_or(1);
-                }
 
-                if control_flow == ControlFlow::Wait {
-                    // We don't go straight into executing the event loop iteration, we instead go
-                    // to the start of this loop and check again if there's any pending event. We
-                    // must do this because during the execution of the iteration we sometimes wake
-                    // the calloop waker, and if the waker is already awaken before we call poll(),
-                    // then poll doesn't block, but it returns immediately. This caused the event
-                    // loop to run continuously even if the control_flow was `Wait`
-                    continue;
-                }
+            for window_id in windows {
+                let window_id = crate::window::WindowId(window_id);
+                sticky_exit_callback(
+                    Event::RedrawRequested(window_id),
+                    &self.target,
+                    control_flow,
+                    callback,
+                );
             }
+        }
+        // send RedrawEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::RedrawEventsCleared,
+                &self.target,
+                control_flow,
+                callback,
+            );
+        }
 
-            let wait_cancelled = iter_result
-                .deadline
-                .map_or(false, |deadline| Instant::now() < deadline);
+        let start = Instant::now();
+        let (deadline, timeout);
 
-            if wait_cancelled {
-                cause = StartCause::WaitCancelled {
-                    start: iter_result.wait_start,
-                    requested_resume: iter_result.deadline,
+        match control_flow {
+            ControlFlow::ExitWithCode(_) => {
+                return IterationResult {
+                    wait_start: start,
+                    deadline: None,
+                    timeout: None,
                 };
             }
+            ControlFlow::Poll => {
+                *cause = StartCause::Poll;
+                deadline = None;
+                timeout = Some(Duration::from_millis(0));
+            }
+            ControlFlow::Wait => {
+                *cause = StartCause::WaitCancelled {
+                    start,
+                    requested_resume: None,
+                };
+                deadline = None;
+                timeout = None;
+            }
+            ControlFlow::WaitUntil(wait_deadline) => {
+                *cause = StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: *wait_deadline,
+                };
+                timeout = if *wait_deadline > start {
+                    Some(*wait_deadline - start)
+                } else {
+                    Some(Duration::from_millis(0))
+                };
+                deadline = Some(*wait_deadline);
+            }
+        }
 
-            iter_result = single_iteration(self, &mut control_flow, &mut cause, &mut callback);
-        };
-
-        callback(
-            crate::event::Event::LoopDestroyed,
-            &self.target,
-            &mut control_flow,
-        );
-        exit_code
+        IterationResult {
+            wait_start: start,
+            deadline,
+            timeout,
+        }
     }
 
     pub fn run<F>(mut self, callback: F) -> !
@@ -632,7 +851,7 @@ impl<T: 'static> EventLoop<T> {
                     &mut |event, window_target, control_flow| {
                         if let Event::RedrawRequested(crate::window::WindowId(wid)) = event {
                             wt.redraw_sender.send(wid).unwrap();
-                        } else {
+                        } else if !wt.is_input_blocked(&event) {
                             callback(event, window_target, control_flow);
                         }
                     },
@@ -645,12 +864,24 @@ impl<T: 'static> EventLoop<T> {
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(wayland_platform)]
         _ => unreachable!(),
     }
 }
 
 impl<T> EventLoopWindowTarget<T> {
+    /// Whether the event is input for a window blocked by a modal dialog.
+    fn is_input_blocked(&self, event: &Event<'_, T>) -> bool {
+        match event {
+            Event::WindowEvent { window_id, event } if modal::is_user_input(event) => self
+                .windows
+                .borrow()
+                .get(&window_id.0)
+                .and_then(Weak::upgrade)
+                .map_or(false, |window| window.is_input_disabled()),
+            _ => false,
+        }
+    }
+
     /// Returns the `XConnection` of this events loop.
     #[inline]
     pub(crate) fn x_connection(&self) -> &Arc<XConnection> {
@@ -661,6 +892,23 @@ impl<T> EventLoopWindowTarget<T> {
         self.device_events.set(allowed);
     }
 
+    pub fn available_devices(&self) -> Vec<RootDeviceInfo> {
+        match DeviceInfo::get(&self.xconn, ffi::XIAllDevices) {
+            Some(all_info) => all_info
+                .iter()
+                .filter(|info| info.enabled != 0)
+                .map(|info| Device::describe(info, &all_info, &self.xconn))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn device_seat(&self, device_id: DeviceId) -> Option<SeatId> {
+        let all_info = DeviceInfo::get(&self.xconn, ffi::XIAllDevices)?;
+        let info = all_info.iter().find(|info| info.deviceid == device_id.0)?;
+        Device::seat(info, &all_info)
+    }
+
     /// Update the device event based on window focus.
     pub fn update_listen_device_events(&self, focus: bool) {
         let device_events = self.device_events.get() == DeviceEvents::Always
@@ -676,10 +924,115 @@ impl<T> EventLoopWindowTarget<T> {
         }
 
         self.xconn
-            .select_xinput_events(self.root, ffi::XIAllMasterDevices as _, mask)
+            .select_xinput_events(self.root, ffi::XIAllMasterDevices as _, &[mask])
             .expect_then_ignore_error("Failed to update device event filter");
     }
 
+    pub fn set_clipboard_content(
+        &self,
+        kind: ClipboardKind,
+        content: ClipboardContent,
+    ) -> Result<(), ExternalError> {
+        match self.clipboard.borrow_mut().set_content(kind, content) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to acquire the selection ownership"
+            )))),
+            Err(err) => Err(ExternalError::Os(os_error!(OsError::XError(err.into())))),
+        }
+    }
+
+    pub fn request_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        self.clipboard
+            .borrow_mut()
+            .request_data(kind, mime_type)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn request_clipboard_mime_types(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<AsyncRequestSerial, ExternalError> {
+        self.clipboard
+            .borrow_mut()
+            .request_mime_types(kind)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn accept_drop(&self, _mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        let mut dnd = self.dnd.borrow_mut();
+        if dnd.target_window.is_none() {
+            return Err(no_drag_error());
+        }
+
+        dnd.set_action(Some(action))
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn reject_drop(&self) -> Result<(), ExternalError> {
+        let mut dnd = self.dnd.borrow_mut();
+        if dnd.target_window.is_none() {
+            return Err(no_drag_error());
+        }
+
+        dnd.set_action(None)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))
+    }
+
+    pub fn request_drop_data(&self, mime_type: &str) -> Result<AsyncRequestSerial, ExternalError> {
+        let mut dnd = self.dnd.borrow_mut();
+        let window = dnd.target_window.ok_or_else(no_drag_error)?;
+        let serial = AsyncRequestSerial::get();
+        dnd.request(window, Some(serial), mime_type)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))?;
+        Ok(serial)
+    }
+
+    /// Start dragging the data out of the window, returning the events for the ended drags.
+    fn start_drag(
+        &self,
+        window_id: WindowId,
+        request: DragRequest,
+    ) -> Vec<(WindowId, DragSourceEvent)> {
+        let mut events = Vec::new();
+        if let Some(origin) = self.drag_source.borrow_mut().cancel() {
+            events.push((WindowId(origin as _), DragSourceEvent::Cancelled));
+        }
+
+        let icon = request.icon.as_ref().map(|icon| &icon.inner);
+        let result = self
+            .clipboard
+            .borrow_mut()
+            .set_drag_content(Some(request.content))
+            .and_then(|types| {
+                self.drag_source.borrow_mut().start(
+                    window_id.0 as xproto::Window,
+                    types,
+                    request.actions,
+                    icon,
+                )
+            });
+
+        if let Err(err) = result {
+            warn!("Failed to start the drag: {}", err);
+            self.end_drag();
+            events.push((window_id, DragSourceEvent::Cancelled));
+        }
+
+        events
+    }
+
+    /// Stop offering the dragged data once the drag has ended.
+    fn end_drag(&self) {
+        if let Err(err) = self.clipboard.borrow_mut().set_drag_content(None) {
+            warn!("Failed to release the drag and drop selection: {}", err);
+        }
+    }
+
     pub fn raw_display_handle(&self) -> raw_window_handle::RawDisplayHandle {
         let mut display_handle = XlibDisplayHandle::empty();
         display_handle.display = self.xconn.display as *mut _;
@@ -688,11 +1041,37 @@ impl<T> EventLoopWindowTarget<T> {
     }
 }
 
+impl<T: 'static> EventLoopWindowTarget<T> {
+    pub fn register_fd(&self, fd: RawFd) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_fd(fd)
+    }
+
+    pub fn register_timer(
+        &self,
+        deadline: Instant,
+        interval: Option<Duration>,
+    ) -> Result<SourceId, ExternalError> {
+        self.event_sources.register_timer(deadline, interval)
+    }
+
+    pub fn unregister_source(&self, source: SourceId) {
+        self.event_sources.unregister_source(source)
+    }
+}
+
+fn no_drag_error() -> ExternalError {
+    ExternalError::Os(os_error!(OsError::XMisc(
+        "no drag and drop session is active"
+    )))
+}
+
 impl<T: 'static> EventLoopProxy<T> {
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
         self.user_sender
             .send(event)
-            .map_err(|e| EventLoopClosed(e.0))
+            .map_err(|e| EventLoopClosed(e.0))?;
+        self.waker.ping();
+        Ok(())
     }
 }
 
@@ -776,6 +1155,9 @@ impl Drop for Window {
         let window = self.deref();
         let xconn = &window.xconn;
 
+        // The owner of a modal dialog is unblocked once the dialog is gone.
+        window.set_owner_disabled(false);
+
         if let Ok(c) = xconn
             .xcb_connection()
             .destroy_window(window.id().0 as xproto::Window)
@@ -954,8 +1336,12 @@ fn mkdid(w: c_int) -> crate::event::DeviceId {
 
 #[derive(Debug)]
 struct Device {
-    _name: String,
+    name: String,
     scroll_axes: Vec<(i32, ScrollAxis)>,
+    // The state of the pen, if the device is one.
+    pen: Option<Pen>,
+    // The valuators of the touchscreen, if the device is one.
+    touch: Option<TouchValuators>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: c_int,
@@ -974,16 +1360,207 @@ enum ScrollOrientation {
     Horizontal,
 }
 
+#[derive(Debug, Clone)]
+struct Pen {
+    tool: PenTool,
+    pressure: Option<Valuator>,
+    tilt_x: Option<Valuator>,
+    tilt_y: Option<Valuator>,
+    rotation: Option<Valuator>,
+    distance: Option<Valuator>,
+    // The window the pen is over.
+    window: Option<xproto::Window>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Valuator {
+    number: i32,
+    min: f64,
+    max: f64,
+    value: f64,
+}
+
+impl Valuator {
+    // Finds the valuator with the given label.
+    fn find(valuators: &[&ffi::XIValuatorClassInfo], label: xproto::Atom) -> Option<Self> {
+        valuators
+            .iter()
+            .find(|info| info.label as xproto::Atom == label)
+            .map(|info| Valuator {
+                number: info.number,
+                min: info.min,
+                max: info.max,
+                value: info.value,
+            })
+    }
+
+    // Updates the valuator with the given number among `valuators`.
+    fn update(valuators: &mut [&mut Option<Valuator>], number: i32, value: f64) {
+        if let Some(valuator) = valuators
+            .iter_mut()
+            .filter_map(|valuator| valuator.as_mut())
+            .find(|valuator| valuator.number == number)
+        {
+            valuator.value = value;
+        }
+    }
+
+    // The value mapped to `0.0..=1.0`.
+    fn normalized(&self) -> f64 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TouchValuators {
+    pressure: Option<Valuator>,
+    major: Option<Valuator>,
+    minor: Option<Valuator>,
+    // The horizontal position, whose range is used to scale the contact size to the screen.
+    position_x: Option<Valuator>,
+}
+
+impl TouchValuators {
+    // Gathers the valuators of a touchscreen reporting its pressure or contact size.
+    fn new(atoms: &Atoms, valuators: &[&ffi::XIValuatorClassInfo]) -> Option<Self> {
+        let valuator = |label: AtomName| Valuator::find(valuators, atoms[label]);
+        let touch = TouchValuators {
+            pressure: valuator(AbsMTPressure),
+            major: valuator(AbsMTTouchMajor),
+            minor: valuator(AbsMTTouchMinor),
+            position_x: valuator(AbsMTPositionX),
+        };
+
+        if touch.pressure.is_some() || touch.major.is_some() {
+            Some(touch)
+        } else {
+            None
+        }
+    }
+
+    fn update(&mut self, number: i32, value: f64) {
+        let mut valuators = [&mut self.pressure, &mut self.major, &mut self.minor];
+        Valuator::update(&mut valuators, number, value);
+    }
+
+    fn force(&self) -> Option<Force> {
+        self.pressure
+            .as_ref()
+            .map(|pressure| Force::Normalized(pressure.normalized()))
+    }
+
+    // The contact size in pixels, given the width of the screen.
+    fn contact_size(&self, screen_width: f64) -> Option<(f64, f64)> {
+        // The touch axes are in the units of the position axes.
+        let position_x = self.position_x.as_ref()?;
+        if position_x.max <= position_x.min {
+            return None;
+        }
+        let scale = screen_width / (position_x.max - position_x.min);
+
+        let major = self.major.as_ref()?.value.max(0.0) * scale;
+        let minor = match &self.minor {
+            Some(minor) => minor.value.max(0.0) * scale,
+            None => major,
+        };
+        Some((major, minor))
+    }
+}
+
+impl Pen {
+    // Builds the pen from the valuators of a tablet tool with a pressure axis.
+    fn new(name: &str, atoms: &Atoms, valuators: &[&ffi::XIValuatorClassInfo]) -> Option<Self> {
+        let valuator = |label: AtomName| Valuator::find(valuators, atoms[label]);
+
+        let pressure = Some(valuator(AbsPressure)?);
+        let has_tilt = valuator(AbsTiltX).is_some() || valuator(AbsTiltY).is_some();
+        if !is_tablet_tool(name, has_tilt) {
+            return None;
+        }
+        let tool = if name.to_lowercase().contains("eraser") {
+            PenTool::Eraser
+        } else {
+            PenTool::Pen
+        };
+
+        Some(Pen {
+            tool,
+            pressure,
+            tilt_x: valuator(AbsTiltX),
+            tilt_y: valuator(AbsTiltY),
+            rotation: valuator(AbsRotationZ),
+            distance: valuator(AbsDistance),
+            window: None,
+        })
+    }
+
+    // Updates the valuator with the given number, if it's one of the pen's.
+    fn update(&mut self, number: i32, value: f64) {
+        let mut valuators = [
+            &mut self.pressure,
+            &mut self.tilt_x,
+            &mut self.tilt_y,
+            &mut self.rotation,
+            &mut self.distance,
+        ];
+        Valuator::update(&mut valuators, number, value);
+    }
+
+    fn axes(&self) -> PenAxes {
+        // The tilt is reported in degrees, but the ranges of the drivers differ, so map them.
+        let tilt = |valuator: &Valuator| (valuator.normalized() * 2.0 - 1.0) * 90.0;
+        PenAxes {
+            pressure: self.pressure.as_ref().map(Valuator::normalized),
+            tilt: match (&self.tilt_x, &self.tilt_y) {
+                (Some(x), Some(y)) => Some((tilt(x), tilt(y))),
+                _ => None,
+            },
+            rotation: self
+                .rotation
+                .as_ref()
+                .map(|valuator| valuator.normalized() * 360.0),
+            distance: self.distance.as_ref().map(Valuator::normalized),
+        }
+    }
+}
+
+// Whether the device with a pressure axis is a tablet tool, rather than a touchpad or a mouse
+// reporting the pressure. Tools without tilt are recognized by the naming of the tablet drivers,
+// e.g. "Wacom Intuos Pro M Pen stylus".
+fn is_tablet_tool(name: &str, has_tilt: bool) -> bool {
+    has_tilt
+        || name.split(|c: char| !c.is_alphanumeric()).any(|word| {
+            ["stylus", "eraser", "pen", "wacom"]
+                .iter()
+                .any(|tool| word.eq_ignore_ascii_case(tool))
+        })
+}
+
 impl Device {
-    fn new(info: &ffi::XIDeviceInfo) -> Self {
+    fn new(info: &ffi::XIDeviceInfo, atoms: &Atoms) -> Self {
         let name = unsafe { CStr::from_ptr(info.name).to_string_lossy() };
         let mut scroll_axes = Vec::new();
+        let mut pen = None;
+        let mut touch = None;
+        let mut is_touchscreen = false;
 
         if Device::physical_device(info) {
-            // Identify scroll axes
+            let mut valuators = Vec::new();
+
+            // Identify scroll axes, and gather the valuators
             for class_ptr in Device::classes(info) {
                 let class = unsafe { &**class_ptr };
-                if class._type == ffi::XIScrollClass {
+                if class._type == ffi::XIValuatorClass {
+                    valuators.push(unsafe {
+                        mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIValuatorClassInfo>(class)
+                    });
+                } else if class._type == ffi::XITouchClass {
+                    is_touchscreen = true;
+                } else if class._type == ffi::XIScrollClass {
                     let info = unsafe {
                         mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIScrollClassInfo>(class)
                     };
@@ -1001,17 +1578,120 @@ impl Device {
                     ));
                 }
             }
+
+            // Identify pens, which report their pressure. Touchscreens and touchpads may report
+            // the pressure too, but have a touch class.
+            let is_slave = info._use == ffi::XISlavePointer || info._use == ffi::XIFloatingSlave;
+            if is_slave && !is_touchscreen {
+                pen = Pen::new(&name, atoms, &valuators);
+            }
+
+            if is_touchscreen {
+                touch = TouchValuators::new(atoms, &valuators);
+            }
         }
 
         let mut device = Device {
-            _name: name.into_owned(),
+            name: name.into_owned(),
             scroll_axes,
+            pen,
+            touch,
             attachment: info.attachment,
         };
         device.reset_scroll_position(info);
         device
     }
 
+    /// Describe the device for `EventLoopWindowTarget::available_devices`.
+    fn describe(
+        info: &ffi::XIDeviceInfo,
+        all_info: &[ffi::XIDeviceInfo],
+        xconn: &XConnection,
+    ) -> RootDeviceInfo {
+        use xinput::ConnectionExt as _;
+
+        let device = Device::new(info, xconn.atoms());
+
+        let mut capabilities = DeviceCapabilities::empty();
+        for class_ptr in Device::classes(info) {
+            capabilities |= match unsafe { (**class_ptr)._type } {
+                ffi::XIKeyClass => DeviceCapabilities::KEYS,
+                ffi::XIButtonClass => DeviceCapabilities::BUTTONS,
+                ffi::XIValuatorClass => DeviceCapabilities::MOTION,
+                ffi::XIScrollClass => DeviceCapabilities::SCROLL,
+                ffi::XITouchClass => DeviceCapabilities::TOUCH,
+                _ => DeviceCapabilities::empty(),
+            };
+        }
+        if let Some(pen) = &device.pen {
+            capabilities.set(DeviceCapabilities::PRESSURE, pen.pressure.is_some());
+            capabilities.set(
+                DeviceCapabilities::TILT,
+                pen.tilt_x.is_some() && pen.tilt_y.is_some(),
+            );
+        }
+        if let Some(touch) = &device.touch {
+            capabilities.set(DeviceCapabilities::PRESSURE, touch.pressure.is_some());
+        }
+
+        let kind = match info._use {
+            _ if device.pen.is_some() => DeviceKind::Tablet,
+            _ if capabilities.contains(DeviceCapabilities::TOUCH) => DeviceKind::Touchscreen,
+            ffi::XIMasterKeyboard | ffi::XISlaveKeyboard => DeviceKind::Keyboard,
+            ffi::XIMasterPointer | ffi::XISlavePointer => DeviceKind::Pointer,
+            // Floating devices are told apart by their classes.
+            _ if capabilities.contains(DeviceCapabilities::MOTION) => DeviceKind::Pointer,
+            _ => DeviceKind::Keyboard,
+        };
+
+        // The input drivers set the vendor and product ids of the kernel device.
+        let ids = xconn
+            .xcb_connection()
+            .xinput_xi_get_property(
+                info.deviceid as u16,
+                false,
+                xconn.atoms()[DeviceProductID],
+                xproto::AtomEnum::INTEGER.into(),
+                0,
+                2,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| match reply.items {
+                xinput::XIGetPropertyItems::Data32(ids) if ids.len() == 2 => {
+                    Some((ids[0] as u16, ids[1] as u16))
+                }
+                _ => None,
+            });
+
+        RootDeviceInfo {
+            id: mkdid(info.deviceid),
+            name: device.name,
+            kind,
+            vendor_id: ids.map(|(vendor_id, _)| vendor_id),
+            product_id: ids.map(|(_, product_id)| product_id),
+            capabilities,
+            seat: Device::seat(info, all_info),
+        }
+    }
+
+    /// The seat of the device, identified by its master pointer since MPX pairs each master
+    /// pointer with a master keyboard.
+    fn seat(info: &ffi::XIDeviceInfo, all_info: &[ffi::XIDeviceInfo]) -> Option<SeatId> {
+        let master_pointer = match info._use {
+            ffi::XIMasterPointer => info.deviceid,
+            ffi::XIMasterKeyboard | ffi::XISlavePointer => info.attachment,
+            ffi::XISlaveKeyboard => {
+                all_info
+                    .iter()
+                    .find(|master| master.deviceid == info.attachment)?
+                    .attachment
+            }
+            _ => return None,
+        };
+        Some(SeatId(master_pointer as u64))
+    }
+
     fn reset_scroll_position(&mut self, info: &ffi::XIDeviceInfo) {
         if Device::physical_device(info) {
             for class_ptr in Device::classes(info) {
@@ -1049,3 +1729,18 @@ impl Device {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tablet_tools_are_recognized() {
+        assert!(is_tablet_tool("Wacom Intuos Pro M Pen stylus", false));
+        assert!(is_tablet_tool("Wacom Intuos Pro M Pen eraser", false));
+        assert!(is_tablet_tool("HUION Huion Tablet_H640P Pen (0)", false));
+        assert!(is_tablet_tool("Generic Tablet", true));
+        assert!(!is_tablet_tool("SynPS/2 Synaptics TouchPad", false));
+        assert!(!is_tablet_tool("OpenPointer Mouse", false));
+    }
+}
@@ -1,24 +1,137 @@
 use std::ffi::CString;
+use std::sync::Arc;
 
 use x11rb::connection::Connection;
+use x11rb::protocol::xinput::{self, ConnectionExt as _};
 
+use crate::cursor::CustomCursor;
 use crate::window::CursorIcon;
 
 use super::*;
 
 impl XConnection {
     pub fn set_cursor_icon(&self, window: xproto::Window, cursor: Option<CursorIcon>) {
-        let cursor = *self
+        let cursor = self.cached_cursor(cursor);
+
+        self.update_cursor(window, cursor)
+            .expect("Failed to set cursor");
+    }
+
+    /// Set the cursor of the master pointer on the window, the pointer falling back to the cursor
+    /// of the window with `None`.
+    pub fn set_device_cursor_icon(
+        &self,
+        window: xproto::Window,
+        device: xinput::DeviceId,
+        cursor: Option<CursorIcon>,
+    ) -> Result<(), X11Error> {
+        let cursor = match cursor {
+            Some(cursor) => self.cached_cursor(Some(cursor)),
+            None => x11rb::NONE as ffi::Cursor,
+        };
+
+        self.xcb_connection()
+            .xinput_xi_change_cursor(window, cursor as xproto::Cursor, device)?
+            .ignore_error();
+
+        self.xcb_connection().flush()?;
+        Ok(())
+    }
+
+    fn cached_cursor(&self, cursor: Option<CursorIcon>) -> ffi::Cursor {
+        *self
             .cursor_cache
             .lock()
             .unwrap()
             .entry(cursor)
-            .or_insert_with(|| self.get_cursor(cursor));
+            .or_insert_with(|| self.get_cursor(cursor))
+    }
+
+    pub fn set_custom_cursor(&self, window: xproto::Window, cursor: &CustomCursor) {
+        let cursor = {
+            let mut cache = self.custom_cursor_cache.lock().unwrap();
+
+            // Free the cursors which can't be set anymore, the server keeps them alive while
+            // they're still in use. This also keeps a new cursor allocated at the address of a
+            // dropped one from matching its stale entry.
+            cache.retain(|(image, cursor)| {
+                let alive = image.strong_count() > 0;
+                if !alive {
+                    unsafe { (self.xlib.XFreeCursor)(self.display, *cursor) };
+                }
+                alive
+            });
+
+            match cache
+                .iter()
+                .find(|(image, _)| image.as_ptr() == Arc::as_ptr(&cursor.inner))
+            {
+                Some((_, cursor)) => *cursor,
+                None => match self.create_custom_cursor(cursor) {
+                    Some(xcursor) => {
+                        cache.push((Arc::downgrade(&cursor.inner), xcursor));
+                        xcursor
+                    }
+                    None => {
+                        warn!("Failed to create the custom cursor, using the default one");
+                        drop(cache);
+                        self.cached_cursor(Some(CursorIcon::Default))
+                    }
+                },
+            }
+        };
 
         self.update_cursor(window, cursor)
             .expect("Failed to set cursor");
     }
 
+    /// Create the cursor from the frames, `None` when Xcursor fails to allocate it.
+    fn create_custom_cursor(&self, cursor: &CustomCursor) -> Option<ffi::Cursor> {
+        let frames = &cursor.inner.frames;
+        unsafe {
+            // The frames are animated by Xcursor when there are several of them.
+            let images = (self.xcursor.XcursorImagesCreate)(frames.len() as c_int);
+            if images.is_null() {
+                return None;
+            }
+
+            for frame in frames {
+                let image = &frame.image;
+                let xcursor_image =
+                    (self.xcursor.XcursorImageCreate)(image.width as c_int, image.height as c_int);
+                if xcursor_image.is_null() {
+                    (self.xcursor.XcursorImagesDestroy)(images);
+                    return None;
+                }
+
+                (*xcursor_image).xhot = cursor.inner.hotspot_x;
+                (*xcursor_image).yhot = cursor.inner.hotspot_y;
+                (*xcursor_image).delay = frame.delay.as_millis() as u32;
+
+                // Xcursor expects premultiplied ARGB pixels.
+                let pixels = std::slice::from_raw_parts_mut(
+                    (*xcursor_image).pixels,
+                    (image.width * image.height) as usize,
+                );
+                for (pixel, rgba) in pixels.iter_mut().zip(image.rgba.chunks_exact(4)) {
+                    let [r, g, b, a] = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                    let premultiply = |channel: u8| (channel as u32 * a as u32 / 255) as u8;
+                    *pixel =
+                        u32::from_be_bytes([a, premultiply(r), premultiply(g), premultiply(b)]);
+                }
+
+                // The images are owned by the list from now on.
+                *(*images).images.add((*images).nimage as usize) = xcursor_image;
+                (*images).nimage += 1;
+            }
+
+            let cursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, images);
+            (self.xcursor.XcursorImagesDestroy)(images);
+
+            (cursor != 0).then_some(cursor)
+        }
+    }
+
     fn create_empty_cursor(&self) -> ffi::Cursor {
         let data = 0;
         let pixmap = unsafe {
@@ -89,6 +89,26 @@ impl WindowType {
     }
 }
 
+/// Initial X window state. Maps directly to
+/// [`_NET_WM_STATE`](https://specifications.freedesktop.org/wm-spec/wm-spec-1.5.html).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    /// The window is a modal dialog box for its `WM_TRANSIENT_FOR` window.
+    Modal,
+}
+
+impl WindowState {
+    pub(crate) fn as_atom(&self, xconn: &Arc<XConnection>) -> xproto::Atom {
+        use self::WindowState::*;
+        let atom_name = match *self {
+            Modal => _NET_WM_STATE_MODAL,
+        };
+
+        let atoms = xconn.atoms();
+        atoms[atom_name]
+    }
+}
+
 pub struct MotifHints {
     hints: MwmHints,
 }
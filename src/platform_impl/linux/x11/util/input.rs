@@ -41,14 +41,14 @@ impl XConnection {
         &self,
         window: xproto::Window,
         device_id: u16,
-        mask: xinput::XIEventMask,
+        mask: &[xinput::XIEventMask],
     ) -> Result<VoidCookie<'_>, X11Error> {
         self.xcb_connection()
             .xinput_xi_select_events(
                 window,
                 &[xinput::EventMask {
                     deviceid: device_id,
-                    mask: vec![mask],
+                    mask: mask.to_vec(),
                 }],
             )
             .map_err(Into::into)
@@ -9,12 +9,15 @@ mod icon;
 mod input;
 pub mod keys;
 pub(crate) mod memory;
+mod popup;
+mod present;
 mod randr;
 mod window_property;
 mod wm;
 
 pub use self::{
-    client_msg::*, geometry::*, hint::*, icon::*, input::*, randr::*, window_property::*, wm::*,
+    client_msg::*, geometry::*, hint::*, icon::*, input::*, popup::*, present::*, randr::*,
+    window_property::*, wm::*,
 };
 
 use std::{
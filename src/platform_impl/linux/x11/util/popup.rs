@@ -0,0 +1,203 @@
+use crate::window::{PopupAdjustment, PopupAnchor};
+
+/// A side of the anchor rectangle, or a direction from it, along one axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Start,
+    Center,
+    End,
+}
+
+impl Side {
+    fn flip(self) -> Self {
+        match self {
+            Side::Start => Side::End,
+            Side::Center => Side::Center,
+            Side::End => Side::Start,
+        }
+    }
+}
+
+/// The sides of the anchor along the x and y axes.
+fn sides(anchor: PopupAnchor) -> (Side, Side) {
+    match anchor {
+        PopupAnchor::Center => (Side::Center, Side::Center),
+        PopupAnchor::Top => (Side::Center, Side::Start),
+        PopupAnchor::Bottom => (Side::Center, Side::End),
+        PopupAnchor::Left => (Side::Start, Side::Center),
+        PopupAnchor::Right => (Side::End, Side::Center),
+        PopupAnchor::TopLeft => (Side::Start, Side::Start),
+        PopupAnchor::TopRight => (Side::End, Side::Start),
+        PopupAnchor::BottomLeft => (Side::Start, Side::End),
+        PopupAnchor::BottomRight => (Side::End, Side::End),
+    }
+}
+
+/// The placement along one axis, with the start and length of the segments.
+struct Axis {
+    anchor: (i32, i32),
+    bounds: (i32, i32),
+    flip: bool,
+    slide: bool,
+    resize: bool,
+}
+
+impl Axis {
+    fn position(&self, anchor: Side, gravity: Side, len: i32) -> i32 {
+        let point = match anchor {
+            Side::Start => self.anchor.0,
+            Side::Center => self.anchor.0 + self.anchor.1 / 2,
+            Side::End => self.anchor.0 + self.anchor.1,
+        };
+
+        match gravity {
+            Side::Start => point - len,
+            Side::Center => point - len / 2,
+            Side::End => point,
+        }
+    }
+
+    fn is_constrained(&self, pos: i32, len: i32) -> bool {
+        pos < self.bounds.0 || pos + len > self.bounds.0 + self.bounds.1
+    }
+
+    fn place(&self, anchor: Side, gravity: Side, mut len: i32) -> (i32, i32) {
+        let mut pos = self.position(anchor, gravity, len);
+
+        if self.flip && self.is_constrained(pos, len) {
+            // The popup is only flipped when it then fits.
+            let flipped = self.position(anchor.flip(), gravity.flip(), len);
+            if !self.is_constrained(flipped, len) {
+                pos = flipped;
+            }
+        }
+
+        if self.slide && self.is_constrained(pos, len) {
+            // The start of the popup is kept visible when it's too large.
+            pos = pos.min(self.bounds.0 + self.bounds.1 - len);
+            pos = pos.max(self.bounds.0);
+        }
+
+        if self.resize && self.is_constrained(pos, len) {
+            let start = pos.max(self.bounds.0);
+            let end = (pos + len).min(self.bounds.0 + self.bounds.1);
+            if end > start {
+                pos = start;
+                len = end - start;
+            }
+        }
+
+        (pos, len)
+    }
+}
+
+/// Place a popup of the given size next to the anchor rectangle, moving it as allowed by the
+/// adjustment to keep it within the bounds, like the compositors do for the `xdg_positioner`.
+pub fn place_popup(
+    (anchor_position, anchor_size): ((i32, i32), (u32, u32)),
+    anchor: PopupAnchor,
+    gravity: PopupAnchor,
+    adjustment: PopupAdjustment,
+    size: (u32, u32),
+    (bounds_position, bounds_size): ((i32, i32), (u32, u32)),
+) -> ((i32, i32), (u32, u32)) {
+    let (anchor_x, anchor_y) = sides(anchor);
+    let (gravity_x, gravity_y) = sides(gravity);
+
+    let x_axis = Axis {
+        anchor: (anchor_position.0, anchor_size.0 as i32),
+        bounds: (bounds_position.0, bounds_size.0 as i32),
+        flip: adjustment.contains(PopupAdjustment::FLIP_X),
+        slide: adjustment.contains(PopupAdjustment::SLIDE_X),
+        resize: adjustment.contains(PopupAdjustment::RESIZE_X),
+    };
+    let y_axis = Axis {
+        anchor: (anchor_position.1, anchor_size.1 as i32),
+        bounds: (bounds_position.1, bounds_size.1 as i32),
+        flip: adjustment.contains(PopupAdjustment::FLIP_Y),
+        slide: adjustment.contains(PopupAdjustment::SLIDE_Y),
+        resize: adjustment.contains(PopupAdjustment::RESIZE_Y),
+    };
+
+    let (x, width) = x_axis.place(anchor_x, gravity_x, size.0 as i32);
+    let (y, height) = y_axis.place(anchor_y, gravity_y, size.1 as i32);
+    ((x, y), (width as u32, height as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: ((i32, i32), (u32, u32)) = ((0, 0), (1000, 800));
+
+    #[test]
+    fn places_popup_from_anchor_point() {
+        let placed = place_popup(
+            ((100, 100), (50, 20)),
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            PopupAdjustment::empty(),
+            (200, 300),
+            SCREEN,
+        );
+        assert_eq!(placed, ((100, 120), (200, 300)));
+
+        let placed = place_popup(
+            ((100, 100), (50, 20)),
+            PopupAnchor::Center,
+            PopupAnchor::Center,
+            PopupAdjustment::empty(),
+            (200, 300),
+            SCREEN,
+        );
+        assert_eq!(placed, ((25, -40), (200, 300)));
+    }
+
+    #[test]
+    fn flips_only_when_popup_fits() {
+        // Below the anchor doesn't fit, but above does.
+        let placed = place_popup(
+            ((100, 700), (50, 20)),
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            PopupAdjustment::FLIP_Y,
+            (200, 300),
+            SCREEN,
+        );
+        assert_eq!(placed, ((100, 400), (200, 300)));
+
+        // Neither fits, the popup stays where it was.
+        let placed = place_popup(
+            ((100, 400), (50, 20)),
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            PopupAdjustment::FLIP_Y,
+            (200, 700),
+            SCREEN,
+        );
+        assert_eq!(placed, ((100, 420), (200, 700)));
+    }
+
+    #[test]
+    fn slides_then_resizes() {
+        let placed = place_popup(
+            ((900, 100), (50, 20)),
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            PopupAdjustment::SLIDE_X,
+            (200, 300),
+            SCREEN,
+        );
+        assert_eq!(placed, ((800, 120), (200, 300)));
+
+        let placed = place_popup(
+            ((100, 600), (50, 20)),
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            PopupAdjustment::RESIZE_Y,
+            (200, 300),
+            SCREEN,
+        );
+        assert_eq!(placed, ((100, 620), (200, 180)));
+    }
+}
@@ -0,0 +1,104 @@
+use std::{ptr, slice};
+
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::present::{self, ConnectionExt as _};
+use x11rb::x11_utils::TryParse;
+
+use super::*;
+
+impl XConnection {
+    /// Set up the Present extension, returning its opcode if it's available.
+    ///
+    /// Xlib doesn't know the events of the extension, so it's taught to keep them as generic
+    /// event cookies whose data are the raw events.
+    pub fn init_present(&self) -> Option<c_int> {
+        let conn = self.xcb_connection();
+        let info = conn
+            .extension_information(present::X11_EXTENSION_NAME)
+            .ok()
+            .flatten()?;
+        conn.present_query_version(1, 0).ok()?.reply().ok()?;
+
+        unsafe {
+            (self.xlib.XESetWireToEventCookie)(
+                self.display,
+                info.major_opcode as c_int,
+                Some(present_wire_to_cookie),
+            );
+        }
+
+        Some(info.major_opcode as c_int)
+    }
+
+    /// Select the completion of the presentations on the window, returning the id of the
+    /// selection, or `None` if the extension is missing.
+    pub fn select_present_complete(
+        &self,
+        window: xproto::Window,
+    ) -> Result<Option<present::Event>, X11Error> {
+        let conn = self.xcb_connection();
+        if conn
+            .extension_information(present::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let event = conn.generate_id()?;
+        conn.present_select_input(event, window, present::EventMask::COMPLETE_NOTIFY)?
+            .ignore_error();
+        Ok(Some(event))
+    }
+}
+
+/// Parse the `CompleteNotify` event kept in a cookie by [`present_wire_to_cookie`].
+pub fn parse_present_complete(
+    cookie: &ffi::XGenericEventCookie,
+) -> Option<present::CompleteNotifyEvent> {
+    if cookie.data.is_null() || cookie.evtype != present::COMPLETE_NOTIFY_EVENT as c_int {
+        return None;
+    }
+
+    let event = unsafe {
+        slice::from_raw_parts(
+            cookie.data as *const u8,
+            generic_event_len(cookie.data as *const u8),
+        )
+    };
+    present::CompleteNotifyEvent::try_parse(event)
+        .ok()
+        .map(|(event, _)| event)
+}
+
+/// The length in bytes of a generic event, which has 4 byte units past its first 32 bytes.
+unsafe fn generic_event_len(event: *const u8) -> usize {
+    32 + 4 * ptr::read_unaligned(event.add(4) as *const u32) as usize
+}
+
+/// Copy a generic event of the Present extension in a cookie, in place of the decoding done
+/// by the libraries of the other extensions.
+unsafe extern "C" fn present_wire_to_cookie(
+    display: *mut ffi::Display,
+    cookie: *mut ffi::XGenericEventCookie,
+    wire: *mut ffi::xEvent,
+) -> c_int {
+    let wire = wire as *const u8;
+    let len = generic_event_len(wire);
+
+    // The data are freed by `XFreeEventData`, with `free`.
+    let data = libc::malloc(len);
+    if data.is_null() {
+        return ffi::False;
+    }
+    ptr::copy_nonoverlapping(wire, data as *mut u8, len);
+
+    let cookie = &mut *cookie;
+    cookie.type_ = (*wire & 0x7f) as c_int;
+    cookie.serial = ptr::read_unaligned(wire.add(2) as *const u16) as c_ulong;
+    cookie.send_event = (*wire & 0x80 != 0) as ffi::Bool;
+    cookie.display = display;
+    cookie.extension = *wire.add(1) as c_int;
+    cookie.evtype = ptr::read_unaligned(wire.add(8) as *const u16) as c_int;
+    cookie.data = data;
+    ffi::True
+}
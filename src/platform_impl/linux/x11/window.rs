@@ -1,11 +1,14 @@
 use std::{
     cmp, env,
     ffi::CString,
-    mem::{replace, MaybeUninit},
+    mem::{replace, take, MaybeUninit},
     os::raw::*,
     path::Path,
     ptr, slice,
-    sync::{Arc, Mutex, MutexGuard},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, Weak,
+    },
 };
 
 use libc;
@@ -17,14 +20,19 @@ use x11rb::{
 };
 use x11rb::{
     properties::{WmHints, WmSizeHints, WmSizeHintsSpecification},
-    protocol::xinput,
+    protocol::{present, xinput},
 };
 
 use crate::{
+    clipboard::ClipboardContent,
+    cursor::CustomCursor,
+    device::SeatId,
+    dnd::DndAction,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event_loop::AsyncRequestSerial,
     platform_impl::{
+        platform::common::{cursor::SelectedCursor, dnd::DragRequest, event_sources::Waker},
         x11::{atoms::*, MonitorHandle as X11MonitorHandle, X11Error},
         Fullscreen, MonitorHandle as PlatformMonitorHandle, OsError,
         PlatformSpecificWindowBuilderAttributes, VideoMode as PlatformVideoMode,
@@ -118,14 +126,30 @@ pub(crate) struct UnownedWindow {
     xwindow: xproto::Window,            // never changes
     root: xproto::Window,               // never changes
     screen_id: i32,                     // never changes
-    cursor: Mutex<CursorIcon>,
+    cursor: Mutex<SelectedCursor>,
     cursor_grabbed_mode: Mutex<CursorGrabMode>,
     #[allow(clippy::mutex_atomic)]
     cursor_visible: Mutex<bool>,
+    seat_cursors: Mutex<Vec<(SeatId, CursorIcon)>>,
+    seat_grabs: Mutex<Vec<SeatId>>,
+    // The selection of the Present events, made on the first feedback request.
+    present_event: Mutex<Option<present::Event>>,
+    // The number of presentations to report.
+    pending_presentation_feedback: AtomicUsize,
+    grabs_popup_input: bool, // never changes
+    // Whether the popup is grabbing the input, until it's dismissed.
+    popup_grabbed: AtomicBool,
+    modal_owner: Option<Weak<UnownedWindow>>, // never changes
+    // Whether the modal dialog is blocking the input of its owner.
+    owner_disabled: AtomicBool,
+    // The number of modal dialogs blocking the input of the window.
+    modal_dialogs: AtomicUsize,
     ime_sender: Mutex<ImeSender>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: Sender<WindowId>,
     activation_sender: Sender<super::ActivationToken>,
+    drag_sender: Sender<(WindowId, DragRequest)>,
+    waker: Arc<Waker>,
 }
 
 impl UnownedWindow {
@@ -153,8 +177,36 @@ impl UnownedWindow {
             None => event_loop.root,
         };
 
+        // Popups are placed next to their parent, on its monitor.
+        let popup = window_attrs.popup.and_then(|(parent, placement)| {
+            let parent = event_loop
+                .windows
+                .borrow()
+                .get(&parent.0)
+                .and_then(Weak::upgrade);
+            if parent.is_none() {
+                warn!("The parent of the popup doesn't exist, creating a regular window");
+            }
+            Some((parent?, placement))
+        });
+
+        // Dialogs are kept above their owner.
+        let owner = window_attrs.owner.and_then(|owner| {
+            let owner = event_loop
+                .windows
+                .borrow()
+                .get(&owner.0)
+                .and_then(Weak::upgrade);
+            if owner.is_none() {
+                warn!("The owner of the dialog doesn't exist");
+            }
+            owner
+        });
+
         let mut monitors = xconn.available_monitors();
-        let guessed_monitor = if monitors.is_empty() {
+        let guessed_monitor = if let Some((parent, _)) = popup.as_ref() {
+            parent.current_monitor()
+        } else if monitors.is_empty() {
             X11MonitorHandle::dummy()
         } else {
             xconn
@@ -184,7 +236,7 @@ impl UnownedWindow {
             .min_inner_size
             .map(|size| size.to_physical::<u32>(scale_factor).into());
 
-        let position = window_attrs
+        let mut position = window_attrs
             .position
             .map(|position| position.to_physical::<i32>(scale_factor));
 
@@ -212,6 +264,33 @@ impl UnownedWindow {
             dimensions
         };
 
+        let dimensions = if let Some((parent, placement)) = popup.as_ref() {
+            let (parent_x, parent_y) = parent.inner_position_physical();
+            let anchor_position: (i32, i32) = placement
+                .anchor_position
+                .to_physical::<i32>(scale_factor)
+                .into();
+            let anchor_size = placement.anchor_size.to_physical::<u32>(scale_factor);
+            let (popup_position, dimensions) = util::place_popup(
+                (
+                    (parent_x + anchor_position.0, parent_y + anchor_position.1),
+                    anchor_size.into(),
+                ),
+                placement.anchor,
+                placement.gravity,
+                placement.adjustment,
+                dimensions,
+                (
+                    guessed_monitor.position().into(),
+                    guessed_monitor.size().into(),
+                ),
+            );
+            position = Some(popup_position.into());
+            dimensions
+        } else {
+            dimensions
+        };
+
         let screen_id = match pl_attribs.screen_id {
             Some(id) => id,
             None => xconn.default_screen_index() as c_int,
@@ -258,7 +337,7 @@ impl UnownedWindow {
 
             aux = aux.event_mask(event_mask).border_pixel(0);
 
-            if pl_attribs.override_redirect {
+            if pl_attribs.override_redirect || popup.is_some() {
                 aux = aux.override_redirect(true as u32);
             }
 
@@ -316,10 +395,26 @@ impl UnownedWindow {
             cursor: Default::default(),
             cursor_grabbed_mode: Mutex::new(CursorGrabMode::None),
             cursor_visible: Mutex::new(true),
+            seat_cursors: Default::default(),
+            seat_grabs: Default::default(),
+            present_event: Default::default(),
+            pending_presentation_feedback: Default::default(),
+            grabs_popup_input: popup
+                .as_ref()
+                .map_or(false, |(_, placement)| placement.grab),
+            popup_grabbed: Default::default(),
+            modal_owner: owner
+                .as_ref()
+                .filter(|_| window_attrs.modal)
+                .map(Arc::downgrade),
+            owner_disabled: Default::default(),
+            modal_dialogs: Default::default(),
             ime_sender: Mutex::new(event_loop.ime_sender.clone()),
             shared_state: SharedState::new(guessed_monitor, &window_attrs),
             redraw_sender: event_loop.redraw_sender.clone(),
             activation_sender: event_loop.activation_sender.clone(),
+            drag_sender: event_loop.drag_sender.clone(),
+            waker: event_loop.event_sources.waker().clone(),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -382,7 +477,31 @@ impl UnownedWindow {
                 flusher.ignore_error()
             }
 
-            leap!(window.set_window_types(pl_attribs.x11_window_types)).ignore_error();
+            if let Some((parent, placement)) = popup.as_ref() {
+                let window_type = if placement.grab {
+                    util::WindowType::PopupMenu
+                } else {
+                    util::WindowType::Tooltip
+                };
+                leap!(window.set_window_types(vec![window_type], &[])).ignore_error();
+                leap!(window.set_transient_for(parent.xwindow)).ignore_error();
+            } else if let Some(owner) = owner.as_ref() {
+                // Owned windows are dialogs, unless they were given another type.
+                let window_types = if pl_attribs.x11_window_types == [util::WindowType::Normal] {
+                    vec![util::WindowType::Dialog]
+                } else {
+                    pl_attribs.x11_window_types
+                };
+                let window_states: &[_] = if window.modal_owner.is_some() {
+                    &[util::WindowState::Modal]
+                } else {
+                    &[]
+                };
+                leap!(window.set_window_types(window_types, window_states)).ignore_error();
+                leap!(window.set_transient_for(owner.xwindow)).ignore_error();
+            } else {
+                leap!(window.set_window_types(pl_attribs.x11_window_types, &[])).ignore_error();
+            }
 
             // Set size hints.
             let mut min_inner_size = window_attrs
@@ -462,6 +581,11 @@ impl UnownedWindow {
                     &xproto::ConfigureWindowAux::new().stack_mode(xproto::StackMode::ABOVE)
                 ))
                 .ignore_error();
+
+                if window.grabs_popup_input {
+                    window.grab_popup_input();
+                }
+                window.set_owner_disabled(true);
             }
 
             // Attempt to make keyboard input repeat detectable
@@ -490,8 +614,30 @@ impl UnownedWindow {
                 | xinput::XIEventMask::TOUCH_BEGIN
                 | xinput::XIEventMask::TOUCH_UPDATE
                 | xinput::XIEventMask::TOUCH_END;
-            leap!(xconn.select_xinput_events(window.xwindow, ffi::XIAllMasterDevices as u16, mask))
-                .ignore_error();
+            let mut masks = vec![mask];
+
+            // The gestures don't fit in the first word of the mask.
+            if event_loop.xinput_gestures {
+                masks.push(xinput::XIEventMask::default());
+                for event in [
+                    ffi::XI_GesturePinchBegin,
+                    ffi::XI_GesturePinchUpdate,
+                    ffi::XI_GesturePinchEnd,
+                    ffi::XI_GestureSwipeBegin,
+                    ffi::XI_GestureSwipeUpdate,
+                    ffi::XI_GestureSwipeEnd,
+                ] {
+                    let word = &mut masks[event as usize / 32];
+                    *word = (u32::from(*word) | 1 << (event % 32)).into();
+                }
+            }
+
+            leap!(xconn.select_xinput_events(
+                window.xwindow,
+                ffi::XIAllMasterDevices as u16,
+                &masks
+            ))
+            .ignore_error();
 
             {
                 let result = event_loop
@@ -579,23 +725,45 @@ impl UnownedWindow {
         }
     }
 
+    /// Sets the window types along with the initial states that go with them.
+    ///
+    /// This must be called before the window is mapped. The states are appended to
+    /// `_NET_WM_STATE` rather than replacing it, so that other initial states survive.
     fn set_window_types(
         &self,
         window_types: Vec<util::WindowType>,
+        window_states: &[util::WindowState],
     ) -> Result<VoidCookie<'_>, X11Error> {
         let atoms = self.xconn.atoms();
         let hint_atom = atoms[_NET_WM_WINDOW_TYPE];
-        let atoms: Vec<_> = window_types
+        let state_atom = atoms[_NET_WM_STATE];
+        let type_atoms: Vec<_> = window_types
             .iter()
             .map(|t| t.as_atom(&self.xconn))
             .collect();
 
+        if !window_states.is_empty() {
+            let state_atoms: Vec<_> = window_states
+                .iter()
+                .map(|s| s.as_atom(&self.xconn))
+                .collect();
+            self.xconn
+                .change_property(
+                    self.xwindow,
+                    state_atom,
+                    xproto::Atom::from(xproto::AtomEnum::ATOM),
+                    xproto::PropMode::APPEND,
+                    &state_atoms,
+                )?
+                .ignore_error();
+        }
+
         self.xconn.change_property(
             self.xwindow,
             hint_atom,
             xproto::Atom::from(xproto::AtomEnum::ATOM),
             xproto::PropMode::REPLACE,
-            &atoms,
+            &type_atoms,
         )
     }
 
@@ -740,7 +908,6 @@ impl UnownedWindow {
                         (None, monitor)
                     }
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(wayland_platform)]
                     _ => unreachable!(),
                 };
 
@@ -1098,6 +1265,11 @@ impl UnownedWindow {
                 .flush_requests()
                 .expect("Failed to call XMapRaised");
             shared_state.visibility = Visibility::YesWait;
+
+            if self.grabs_popup_input {
+                self.grab_popup_input();
+            }
+            self.set_owner_disabled(true);
         } else {
             self.xconn
                 .xcb_connection()
@@ -1107,6 +1279,10 @@ impl UnownedWindow {
                 .flush_requests()
                 .expect("Failed to call XUnmapWindow");
             shared_state.visibility = Visibility::No;
+
+            // The grabs end along with the visibility of the window.
+            self.popup_grabbed.store(false, Ordering::Relaxed);
+            self.set_owner_disabled(false);
         }
     }
 
@@ -1115,6 +1291,103 @@ impl UnownedWindow {
         Some(self.shared_state_lock().visibility == Visibility::Yes)
     }
 
+    /// Grab the pointer and the keyboard for the popup, such that it gets the keys, and that the
+    /// clicks outside of the windows of the application are reported to it.
+    fn grab_popup_input(&self) {
+        let conn = self.xconn.xcb_connection();
+        let pointer_status = conn
+            .grab_pointer(
+                true,
+                self.xwindow,
+                xproto::EventMask::BUTTON_PRESS | xproto::EventMask::BUTTON_RELEASE,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                x11rb::CURRENT_TIME,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.status);
+        let keyboard_status = conn
+            .grab_keyboard(
+                false,
+                self.xwindow,
+                x11rb::CURRENT_TIME,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.status);
+
+        if pointer_status != Some(xproto::GrabStatus::SUCCESS)
+            || keyboard_status != Some(xproto::GrabStatus::SUCCESS)
+        {
+            warn!("Failed to grab the input for the popup");
+        }
+
+        self.popup_grabbed.store(
+            pointer_status == Some(xproto::GrabStatus::SUCCESS),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Whether the window is a popup grabbing the input.
+    pub(crate) fn has_popup_grab(&self) -> bool {
+        self.popup_grabbed.load(Ordering::Relaxed)
+    }
+
+    /// Release the grabs of the popup, returning whether it was grabbing the input.
+    pub(crate) fn dismiss_popup(&self) -> bool {
+        if !self.popup_grabbed.swap(false, Ordering::Relaxed) {
+            return false;
+        }
+
+        let conn = self.xconn.xcb_connection();
+        conn.ungrab_pointer(x11rb::CURRENT_TIME)
+            .expect_then_ignore_error("Failed to call `xcb_ungrab_pointer`");
+        conn.ungrab_keyboard(x11rb::CURRENT_TIME)
+            .expect_then_ignore_error("Failed to call `xcb_ungrab_keyboard`");
+        self.xconn
+            .flush_requests()
+            .expect("Failed to release the grabs of the popup");
+        true
+    }
+
+    fn set_transient_for(&self, owner: xproto::Window) -> Result<VoidCookie<'_>, X11Error> {
+        self.xconn.change_property(
+            self.xwindow,
+            xproto::Atom::from(xproto::AtomEnum::WM_TRANSIENT_FOR),
+            xproto::Atom::from(xproto::AtomEnum::WINDOW),
+            xproto::PropMode::REPLACE,
+            &[owner],
+        )
+    }
+
+    /// Block or unblock the input of the owner of the modal dialog.
+    pub(crate) fn set_owner_disabled(&self, disabled: bool) {
+        let owner = match self.modal_owner.as_ref().and_then(Weak::upgrade) {
+            Some(owner) => owner,
+            None => return,
+        };
+
+        if self.owner_disabled.swap(disabled, Ordering::Relaxed) == disabled {
+            return;
+        }
+
+        if disabled {
+            owner.modal_dialogs.fetch_add(1, Ordering::Relaxed);
+        } else {
+            owner.modal_dialogs.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the input of the window is blocked by a modal dialog.
+    pub(crate) fn is_input_disabled(&self) -> bool {
+        self.modal_dialogs.load(Ordering::Relaxed) > 0
+    }
+
     fn update_cached_frame_extents(&self) {
         let extents = self
             .xconn
@@ -1428,20 +1701,105 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let old_cursor = replace(&mut *self.cursor.lock().unwrap(), cursor);
+        self.set_cursor(SelectedCursor::Named(cursor));
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        self.set_cursor(SelectedCursor::Custom(cursor));
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_icon(
+        &self,
+        seat: SeatId,
+        cursor: CursorIcon,
+    ) -> Result<(), ExternalError> {
+        let mut seat_cursors = self.seat_cursors.lock().unwrap();
+        seat_cursors.retain(|(cursor_seat, _)| *cursor_seat != seat);
+        seat_cursors.push((seat, cursor));
+
+        // The hidden cursor of the window applies to all the seats.
+        #[allow(clippy::mutex_atomic)]
+        if *self.cursor_visible.lock().unwrap() {
+            self.xconn
+                .set_device_cursor_icon(self.xwindow, seat.0 as xinput::DeviceId, Some(cursor))
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))?;
+        }
+
+        Ok(())
+    }
+
+    /// Make the pointers of the seats fall back to the cursor of the window, or set their own.
+    fn update_seat_cursors(&self, seat_cursors: &[(SeatId, CursorIcon)], show: bool) {
+        for &(seat, cursor) in seat_cursors {
+            let cursor = show.then_some(cursor);
+            let _ =
+                self.xconn
+                    .set_device_cursor_icon(self.xwindow, seat.0 as xinput::DeviceId, cursor);
+        }
+    }
+
+    fn set_cursor(&self, cursor: SelectedCursor) {
+        // The cursors of the seats are replaced by the one of the window.
+        let seat_cursors = take(&mut *self.seat_cursors.lock().unwrap());
+        self.update_seat_cursors(&seat_cursors, false);
+
+        let old_cursor = replace(&mut *self.cursor.lock().unwrap(), cursor.clone());
         #[allow(clippy::mutex_atomic)]
         if cursor != old_cursor && *self.cursor_visible.lock().unwrap() {
-            self.xconn.set_cursor_icon(self.xwindow, Some(cursor));
+            self.show_cursor(&cursor);
+        }
+    }
+
+    fn show_cursor(&self, cursor: &SelectedCursor) {
+        match cursor {
+            SelectedCursor::Named(icon) => self.xconn.set_cursor_icon(self.xwindow, Some(*icon)),
+            SelectedCursor::Custom(cursor) => self.xconn.set_custom_cursor(self.xwindow, cursor),
         }
     }
 
     #[inline]
     pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         let mut grabbed_lock = self.cursor_grabbed_mode.lock().unwrap();
-        if mode == *grabbed_lock {
+
+        // The grabs of the seats are replaced by the one of the window.
+        let seat_grabs = take(&mut *self.seat_grabs.lock().unwrap());
+        if mode == *grabbed_lock && seat_grabs.is_empty() {
             return Ok(());
         }
 
+        for seat in seat_grabs {
+            let _ = self.with_seat_pointer(seat, || self.grab_pointer(CursorGrabMode::None));
+        }
+
+        let result = self.grab_pointer(mode);
+        if result.is_ok() {
+            *grabbed_lock = mode;
+        }
+
+        result
+    }
+
+    #[inline]
+    pub fn set_seat_cursor_grab(
+        &self,
+        seat: SeatId,
+        mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        let result = self.with_seat_pointer(seat, || self.grab_pointer(mode))?;
+        if result.is_ok() {
+            let mut seat_grabs = self.seat_grabs.lock().unwrap();
+            if !seat_grabs.contains(&seat) {
+                seat_grabs.push(seat);
+            }
+        }
+
+        result
+    }
+
+    /// Grab the pointer with the core requests, which apply to the ClientPointer.
+    fn grab_pointer(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         // We ungrab before grabbing to prevent passive grabs from causing `AlreadyGrabbed`.
         // Therefore, this is common to both codepaths.
         self.xconn
@@ -1449,7 +1807,7 @@ impl UnownedWindow {
             .ungrab_pointer(x11rb::CURRENT_TIME)
             .expect_then_ignore_error("Failed to call `xcb_ungrab_pointer`");
 
-        let result = match mode {
+        match mode {
             CursorGrabMode::None => self.xconn.flush_requests().map_err(|err| {
                 ExternalError::Os(os_error!(OsError::XError(X11Error::Xlib(err).into())))
             }),
@@ -1501,16 +1859,42 @@ impl UnownedWindow {
                 }
                 .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
             }
-            CursorGrabMode::Locked => {
-                return Err(ExternalError::NotSupported(NotSupportedError::new()));
-            }
-        };
-
-        if result.is_ok() {
-            *grabbed_lock = mode;
+            CursorGrabMode::Locked => Err(ExternalError::NotSupported(NotSupportedError::new())),
         }
+    }
 
-        result
+    /// Run the closure with the master pointer of the seat as the ClientPointer, such that the
+    /// core pointer requests apply to it.
+    fn with_seat_pointer<R>(
+        &self,
+        seat: SeatId,
+        f: impl FnOnce() -> R,
+    ) -> Result<R, ExternalError> {
+        use xinput::ConnectionExt as _;
+
+        let map_err = |err: X11Error| ExternalError::Os(os_error!(OsError::XError(err.into())));
+        let conn = self.xconn.xcb_connection();
+
+        let client_pointer = conn
+            .xinput_xi_get_client_pointer(x11rb::NONE)
+            .map_err(|err| map_err(err.into()))?
+            .reply()
+            .map_err(|err| map_err(err.into()))?;
+
+        // The request fails when the seat doesn't exist anymore.
+        conn.xinput_xi_set_client_pointer(x11rb::NONE, seat.0 as xinput::DeviceId)
+            .map_err(|err| map_err(err.into()))?
+            .check()
+            .map_err(|err| map_err(err.into()))?;
+
+        let result = f();
+
+        conn.xinput_xi_set_client_pointer(x11rb::NONE, client_pointer.deviceid)
+            .map_err(|err| map_err(err.into()))?
+            .ignore_error();
+        conn.flush().map_err(|err| map_err(err.into()))?;
+
+        Ok(result)
     }
 
     #[inline]
@@ -1520,14 +1904,15 @@ impl UnownedWindow {
         if visible == *visible_lock {
             return;
         }
-        let cursor = if visible {
-            Some(*self.cursor.lock().unwrap())
-        } else {
-            None
-        };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow, cursor);
+        if visible {
+            let cursor = self.cursor.lock().unwrap().clone();
+            self.show_cursor(&cursor);
+        } else {
+            self.xconn.set_cursor_icon(self.xwindow, None);
+        }
+        self.update_seat_cursors(&self.seat_cursors.lock().unwrap(), visible);
     }
 
     #[inline]
@@ -1732,9 +2117,24 @@ impl UnownedWindow {
         self.activation_sender
             .send((self.id(), serial))
             .expect("activation token channel should never be closed");
+        self.waker.ping();
         Ok(serial)
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        content: ClipboardContent,
+        actions: &[DndAction],
+        icon: Option<Icon>,
+    ) -> Result<(), ExternalError> {
+        self.drag_sender
+            .send((self.id(), DragRequest::new(content, actions, icon)))
+            .expect("drag channel should never be closed");
+        self.waker.ping();
+        Ok(())
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.xwindow as _)
@@ -1745,6 +2145,39 @@ impl UnownedWindow {
         self.redraw_sender
             .send(WindowId(self.xwindow as _))
             .unwrap();
+        self.waker.ping();
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        // The redraws aren't paced on X11.
+    }
+
+    #[inline]
+    pub fn request_presentation_feedback(&self) -> Result<(), ExternalError> {
+        let mut present_event = self.present_event.lock().unwrap();
+        if present_event.is_none() {
+            *present_event = self
+                .xconn
+                .select_present_complete(self.xwindow)
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err.into()))))?;
+            if present_event.is_none() {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()));
+            }
+        }
+
+        self.pending_presentation_feedback
+            .fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Take one of the pending presentation feedback requests, if any.
+    pub(crate) fn take_presentation_feedback(&self) -> bool {
+        self.pending_presentation_feedback
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+                pending.checked_sub(1)
+            })
+            .is_ok()
     }
 
     #[inline]
@@ -4,10 +4,11 @@ use std::{
     fmt, ptr,
     sync::{
         atomic::{AtomicU32, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, Weak,
     },
 };
 
+use crate::cursor::CursorImage;
 use crate::window::CursorIcon;
 
 use super::{atoms::Atoms, ffi};
@@ -40,6 +41,7 @@ pub(crate) struct XConnection {
 
     pub latest_error: Mutex<Option<XError>>,
     pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
+    pub custom_cursor_cache: Mutex<Vec<(Weak<CursorImage>, ffi::Cursor)>>,
 }
 
 unsafe impl Send for XConnection {}
@@ -104,6 +106,7 @@ impl XConnection {
             timestamp: AtomicU32::new(0),
             latest_error: Mutex::new(None),
             cursor_cache: Default::default(),
+            custom_cursor_cache: Default::default(),
         })
     }
 
@@ -93,6 +93,7 @@ fn queue_device_event(event: DeviceEvent) {
     let event = Event::DeviceEvent {
         device_id: DEVICE_ID,
         event,
+        timestamp: None,
     };
     AppState::queue_event(EventWrapper::StaticEvent(event));
 }
@@ -43,6 +43,19 @@ pub struct KeyEventExtra {
     pub key_without_modifiers: Key,
 }
 
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        key_without_modifiers: Key,
+        text_with_all_modifiers: Option<SmolStr>,
+    ) -> Self {
+        Self {
+            text_with_all_modifiers,
+            key_without_modifiers,
+        }
+    }
+}
+
 impl KeyEventExtModifierSupplement for KeyEvent {
     fn text_with_all_modifiers(&self) -> Option<&str> {
         self.platform_specific
@@ -201,6 +214,7 @@ pub(crate) fn create_key_event(
         repeat: is_repeat,
         state,
         text,
+        timestamp: None,
         platform_specific: KeyEventExtra {
             key_without_modifiers,
             text_with_all_modifiers,
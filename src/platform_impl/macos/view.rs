@@ -656,6 +656,7 @@ declare_class!(
             trace_scope!("mouseEntered:");
             self.queue_event(WindowEvent::CursorEntered {
                 device_id: DEVICE_ID,
+                timestamp: None,
             });
         }
 
@@ -665,6 +666,7 @@ declare_class!(
 
             self.queue_event(WindowEvent::CursorLeft {
                 device_id: DEVICE_ID,
+                timestamp: None,
             });
         }
 
@@ -713,6 +715,9 @@ declare_class!(
                 device_id: DEVICE_ID,
                 delta,
                 phase,
+                source: None,
+                value120: None,
+                timestamp: None,
             });
         }
 
@@ -732,6 +737,7 @@ declare_class!(
                 device_id: DEVICE_ID,
                 delta: event.magnification(),
                 phase,
+                timestamp: None,
             });
         }
 
@@ -760,6 +766,7 @@ declare_class!(
                 device_id: DEVICE_ID,
                 delta: event.rotation(),
                 phase,
+                timestamp: None,
             });
         }
 
@@ -829,6 +836,7 @@ impl WinitView {
         let event = Event::DeviceEvent {
             device_id: DEVICE_ID,
             event,
+            timestamp: None,
         };
         AppState::queue_event(EventWrapper::StaticEvent(event));
     }
@@ -1019,6 +1027,7 @@ impl WinitView {
             device_id: DEVICE_ID,
             state: button_state,
             button,
+            timestamp: None,
         });
     }
 
@@ -1048,6 +1057,7 @@ impl WinitView {
         self.queue_event(WindowEvent::CursorMoved {
             device_id: DEVICE_ID,
             position: logical_position.to_physical(self.scale_factor()),
+            timestamp: None,
         });
     }
 }
@@ -339,6 +339,7 @@ impl<T: 'static> EventLoop<T> {
                                 state: element_state(pressed),
                                 repeat: false,
                                 text: None,
+                                timestamp: None,
 
                                 platform_specific: KeyEventExtra {},
                             },
@@ -371,6 +372,7 @@ impl<T: 'static> EventLoop<T> {
                     event: event::WindowEvent::CursorMoved {
                         device_id: event::DeviceId(DeviceId),
                         position: (x, y).into(),
+                        timestamp: None,
                     },
                 });
             }
@@ -386,6 +388,7 @@ impl<T: 'static> EventLoop<T> {
                             device_id: event::DeviceId(DeviceId),
                             state,
                             button,
+                            timestamp: None,
                         },
                     });
                 }
@@ -397,6 +400,9 @@ impl<T: 'static> EventLoop<T> {
                         device_id: event::DeviceId(DeviceId),
                         delta: event::MouseScrollDelta::LineDelta(x as f32, y as f32),
                         phase: event::TouchPhase::Moved,
+                        source: None,
+                        value120: None,
+                        timestamp: None,
                     },
                 });
             }
@@ -434,6 +440,7 @@ impl<T: 'static> EventLoop<T> {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::CursorEntered {
                             device_id: event::DeviceId(DeviceId),
+                            timestamp: None,
                         },
                     });
                 } else {
@@ -441,6 +448,7 @@ impl<T: 'static> EventLoop<T> {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::CursorLeft {
                             device_id: event::DeviceId(DeviceId),
+                            timestamp: None,
                         },
                     });
                 }
@@ -254,3 +254,13 @@ impl VideoMode {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct KeyEventExtra {}
+
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        _key_without_modifiers: crate::keyboard::Key,
+        _text_with_all_modifiers: Option<smol_str::SmolStr>,
+    ) -> Self {
+        Self {}
+    }
+}
@@ -261,6 +261,7 @@ impl<T: 'static> Shared<T> {
                             button: button.to_id(),
                             state,
                         },
+                        timestamp: None,
                     });
 
                     return;
@@ -279,6 +280,7 @@ impl<T: 'static> Shared<T> {
                             axis: 0,
                             value: delta.x,
                         },
+                        timestamp: None,
                     });
 
                     let y_motion = (delta.y != 0.0).then_some(Event::DeviceEvent {
@@ -287,6 +289,7 @@ impl<T: 'static> Shared<T> {
                             axis: 1,
                             value: delta.y,
                         },
+                        timestamp: None,
                     });
 
                     x_motion
@@ -297,6 +300,7 @@ impl<T: 'static> Shared<T> {
                             event: DeviceEvent::MouseMotion {
                                 delta: (delta.x, delta.y),
                             },
+                            timestamp: None,
                         }))
                 }));
             }),
@@ -315,6 +319,7 @@ impl<T: 'static> Shared<T> {
                     runner.send_event(Event::DeviceEvent {
                         device_id: RootDeviceId(DeviceId(0)),
                         event: DeviceEvent::MouseWheel { delta },
+                        timestamp: None,
                     });
                 }
             }),
@@ -339,6 +344,7 @@ impl<T: 'static> Shared<T> {
                         button: button.to_id(),
                         state: ElementState::Pressed,
                     },
+                    timestamp: None,
                 });
             }),
         ));
@@ -362,6 +368,7 @@ impl<T: 'static> Shared<T> {
                         button: button.to_id(),
                         state: ElementState::Released,
                     },
+                    timestamp: None,
                 });
             }),
         ));
@@ -380,6 +387,7 @@ impl<T: 'static> Shared<T> {
                         physical_key: backend::event::key_code(&event),
                         state: ElementState::Pressed,
                     }),
+                    timestamp: None,
                 });
             }),
         ));
@@ -398,6 +406,7 @@ impl<T: 'static> Shared<T> {
                         physical_key: backend::event::key_code(&event),
                         state: ElementState::Released,
                     }),
+                    timestamp: None,
                 });
             }),
         ));
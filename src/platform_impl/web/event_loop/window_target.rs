@@ -146,6 +146,7 @@ impl<T> EventLoopWindowTarget<T> {
                         physical_key,
                         state: ElementState::Pressed,
                     }),
+                    timestamp: None,
                 });
 
                 runner.send_events(
@@ -162,6 +163,7 @@ impl<T> EventLoopWindowTarget<T> {
                                     location,
                                     state: ElementState::Pressed,
                                     repeat,
+                                    timestamp: None,
                                     platform_specific: KeyEventExtra,
                                 },
                                 is_synthetic: false,
@@ -193,6 +195,7 @@ impl<T> EventLoopWindowTarget<T> {
                         physical_key,
                         state: ElementState::Pressed,
                     }),
+                    timestamp: None,
                 });
 
                 runner.send_events(
@@ -209,6 +212,7 @@ impl<T> EventLoopWindowTarget<T> {
                                     location,
                                     state: ElementState::Released,
                                     repeat,
+                                    timestamp: None,
                                     platform_specific: KeyEventExtra,
                                 },
                                 is_synthetic: false,
@@ -241,6 +245,7 @@ impl<T> EventLoopWindowTarget<T> {
                     window_id: RootWindowId(id),
                     event: WindowEvent::CursorLeft {
                         device_id: RootDeviceId(DeviceId(pointer_id)),
+                        timestamp: None,
                     },
                 });
 
@@ -270,6 +275,7 @@ impl<T> EventLoopWindowTarget<T> {
                     window_id: RootWindowId(id),
                     event: WindowEvent::CursorEntered {
                         device_id: RootDeviceId(DeviceId(pointer_id)),
+                        timestamp: None,
                     },
                 });
 
@@ -322,6 +328,7 @@ impl<T> EventLoopWindowTarget<T> {
                                         axis: 0,
                                         value: delta.x,
                                     },
+                                    timestamp: None,
                                 });
 
                                 let y_motion = (delta.y != 0.0).then_some(Event::DeviceEvent {
@@ -330,6 +337,7 @@ impl<T> EventLoopWindowTarget<T> {
                                         axis: 1,
                                         value: delta.y,
                                     },
+                                    timestamp: None,
                                 });
 
                                 x_motion.into_iter().chain(y_motion).chain(iter::once(
@@ -338,6 +346,7 @@ impl<T> EventLoopWindowTarget<T> {
                                         event: DeviceEvent::MouseMotion {
                                             delta: (delta.x, delta.y),
                                         },
+                                        timestamp: None,
                                     },
                                 ))
                             });
@@ -348,6 +357,7 @@ impl<T> EventLoopWindowTarget<T> {
                                     event: WindowEvent::CursorMoved {
                                         device_id,
                                         position,
+                                        timestamp: None,
                                     },
                                 },
                             ))
@@ -379,7 +389,9 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Moved,
                                 force: Some(force),
+                                contact_size: None,
                                 location,
+                                timestamp: None,
                             }),
                         },
                     )));
@@ -419,6 +431,7 @@ impl<T> EventLoopWindowTarget<T> {
                             button: button.to_id(),
                             state,
                         },
+                        timestamp: None,
                     });
 
                     // A chorded button event may come in without any prior CursorMoved events,
@@ -430,6 +443,7 @@ impl<T> EventLoopWindowTarget<T> {
                             event: WindowEvent::CursorMoved {
                                 device_id,
                                 position,
+                                timestamp: None,
                             },
                         },
                         Event::WindowEvent {
@@ -438,6 +452,7 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id,
                                 state,
                                 button,
+                                timestamp: None,
                             },
                         },
                     ]));
@@ -481,6 +496,7 @@ impl<T> EventLoopWindowTarget<T> {
                             button: button.to_id(),
                             state: ElementState::Pressed,
                         },
+                        timestamp: None,
                     });
 
                     // A mouse down event may come in without any prior CursorMoved events,
@@ -492,6 +508,7 @@ impl<T> EventLoopWindowTarget<T> {
                             event: WindowEvent::CursorMoved {
                                 device_id,
                                 position,
+                                timestamp: None,
                             },
                         },
                         Event::WindowEvent {
@@ -500,6 +517,7 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id,
                                 state: ElementState::Pressed,
                                 button,
+                                timestamp: None,
                             },
                         },
                     ]));
@@ -526,7 +544,9 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Started,
                                 force: Some(force),
+                                contact_size: None,
                                 location,
+                                timestamp: None,
                             }),
                         },
                     )))
@@ -574,6 +594,7 @@ impl<T> EventLoopWindowTarget<T> {
                             button: button.to_id(),
                             state: ElementState::Pressed,
                         },
+                        timestamp: None,
                     });
 
                     // A mouse up event may come in without any prior CursorMoved events,
@@ -585,6 +606,7 @@ impl<T> EventLoopWindowTarget<T> {
                             event: WindowEvent::CursorMoved {
                                 device_id,
                                 position,
+                                timestamp: None,
                             },
                         },
                         Event::WindowEvent {
@@ -593,6 +615,7 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id,
                                 state: ElementState::Released,
                                 button,
+                                timestamp: None,
                             },
                         },
                     ]));
@@ -622,7 +645,9 @@ impl<T> EventLoopWindowTarget<T> {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Ended,
                                 force: Some(force),
+                                contact_size: None,
                                 location,
+                                timestamp: None,
                             }),
                         },
                     )));
@@ -647,6 +672,7 @@ impl<T> EventLoopWindowTarget<T> {
                 let device_event = runner.device_events().then_some(Event::DeviceEvent {
                     device_id: RootDeviceId(DeviceId(pointer_id)),
                     event: DeviceEvent::MouseWheel { delta },
+                    timestamp: None,
                 });
 
                 runner.send_events(modifiers_changed.into_iter().chain(device_event).chain(
@@ -656,6 +682,9 @@ impl<T> EventLoopWindowTarget<T> {
                             device_id: RootDeviceId(DeviceId(pointer_id)),
                             delta,
                             phase: TouchPhase::Moved,
+                            source: None,
+                            value120: None,
+                            timestamp: None,
                         },
                     }),
                 ));
@@ -672,11 +701,21 @@ impl<T> EventLoopWindowTarget<T> {
                     device_id: RootDeviceId(DeviceId(device_id)),
                     phase: TouchPhase::Cancelled,
                     force: Some(force),
+                    contact_size: None,
                     location,
+                    timestamp: None,
                 }),
             });
         });
 
+        let runner = self.runner.clone();
+        canvas.on_pointer(move |pointer| {
+            runner.send_event(Event::WindowEvent {
+                window_id: RootWindowId(id),
+                event: WindowEvent::Pointer(pointer),
+            });
+        });
+
         let runner = self.runner.clone();
         canvas.on_dark_mode(move |is_dark_mode| {
             let theme = if is_dark_mode {
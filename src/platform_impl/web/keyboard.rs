@@ -5,6 +5,16 @@ use crate::keyboard::{Key, KeyCode, NativeKey, NativeKeyCode};
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub(crate) struct KeyEventExtra;
 
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        _key_without_modifiers: Key,
+        _text_with_all_modifiers: Option<SmolStr>,
+    ) -> Self {
+        Self
+    }
+}
+
 impl Key {
     pub(crate) fn from_key_attribute_value(kav: &str) -> Self {
         match kav {
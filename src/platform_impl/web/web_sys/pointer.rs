@@ -1,9 +1,16 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use super::canvas::Common;
 use super::event;
 use super::event_handle::EventListenerHandle;
 use crate::dpi::PhysicalPosition;
-use crate::event::{Force, MouseButton};
+use crate::event::{
+    DeviceId as RootDeviceId, Force, MouseButton, Pointer, PointerButtons, PointerKind,
+    PointerPhase,
+};
 use crate::keyboard::ModifiersState;
+use crate::platform_impl::DeviceId;
 
 use event::ButtonsState;
 use web_sys::PointerEvent;
@@ -16,6 +23,7 @@ pub(super) struct PointerHandler {
     on_pointer_press: Option<EventListenerHandle<dyn FnMut(PointerEvent)>>,
     on_pointer_release: Option<EventListenerHandle<dyn FnMut(PointerEvent)>>,
     on_touch_cancel: Option<EventListenerHandle<dyn FnMut(PointerEvent)>>,
+    on_pointer: Vec<EventListenerHandle<dyn FnMut(PointerEvent)>>,
 }
 
 impl PointerHandler {
@@ -27,6 +35,7 @@ impl PointerHandler {
             on_pointer_press: None,
             on_pointer_release: None,
             on_touch_cancel: None,
+            on_pointer: Vec::new(),
         }
     }
 
@@ -274,6 +283,78 @@ impl PointerHandler {
         ));
     }
 
+    pub fn on_pointer<F>(&mut self, canvas_common: &Common, handler: F)
+    where
+        F: 'static + FnMut(Pointer),
+    {
+        let handler = Rc::new(RefCell::new(handler));
+        self.on_pointer = [
+            ("pointerover", PointerPhase::Entered),
+            ("pointerdown", PointerPhase::Down),
+            ("pointermove", PointerPhase::Moved),
+            ("pointerup", PointerPhase::Up),
+            ("pointerout", PointerPhase::Left),
+            ("pointercancel", PointerPhase::Cancelled),
+        ]
+        .into_iter()
+        .map(|(event_name, phase)| {
+            let window = canvas_common.window.clone();
+            let handler = handler.clone();
+            canvas_common.add_event(event_name, move |event: PointerEvent| {
+                let kind = match event.pointer_type().as_str() {
+                    "touch" => PointerKind::Touch,
+                    "pen" => PointerKind::Pen,
+                    _ => PointerKind::Mouse,
+                };
+                let buttons = PointerButtons::from_bits_truncate(event.buttons() as u32);
+
+                // Chorded buttons are reported with move events.
+                // https://www.w3.org/TR/pointerevents3/#chorded-button-interactions
+                let button = match event.button() {
+                    0 => PointerButtons::PRIMARY,
+                    1 => PointerButtons::AUXILIARY,
+                    2 => PointerButtons::SECONDARY,
+                    3 => PointerButtons::BACK,
+                    4 => PointerButtons::FORWARD,
+                    5 => PointerButtons::ERASER,
+                    _ => PointerButtons::empty(),
+                };
+                let phase = match phase {
+                    PointerPhase::Moved if buttons.contains(button) && !button.is_empty() => {
+                        PointerPhase::Down
+                    }
+                    PointerPhase::Moved if !button.is_empty() => PointerPhase::Up,
+                    phase => phase,
+                };
+
+                // The mouse only has a pressure and a size emulated from the buttons.
+                let scale = super::scale_factor(&window);
+                let (pressure, size) = match kind {
+                    PointerKind::Mouse => (None, None),
+                    _ => (
+                        Some(event.pressure() as f64),
+                        Some((event.width() as f64 * scale, event.height() as f64 * scale)),
+                    ),
+                };
+
+                (handler.borrow_mut())(Pointer {
+                    device_id: RootDeviceId(DeviceId(event.pointer_id())),
+                    pointer_id: event.pointer_id() as u64,
+                    kind,
+                    phase,
+                    position: event::mouse_position(&event).to_physical(scale),
+                    buttons,
+                    pressure,
+                    tilt: (kind == PointerKind::Pen)
+                        .then(|| (event.tilt_x() as f64, event.tilt_y() as f64)),
+                    size,
+                    timestamp: None,
+                })
+            })
+        })
+        .collect();
+    }
+
     pub fn remove_listeners(&mut self) {
         self.on_cursor_leave = None;
         self.on_cursor_enter = None;
@@ -281,5 +362,6 @@ impl PointerHandler {
         self.on_pointer_press = None;
         self.on_pointer_release = None;
         self.on_touch_cancel = None;
+        self.on_pointer.clear();
     }
 }
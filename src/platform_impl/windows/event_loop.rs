@@ -1413,6 +1413,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     window_id: RootWindowId(WindowId(window)),
                     event: CursorEntered {
                         device_id: DEVICE_ID,
+                        timestamp: None,
                     },
                 });
 
@@ -1445,6 +1446,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     event: CursorMoved {
                         device_id: DEVICE_ID,
                         position,
+                        timestamp: None,
                     },
                 });
             }
@@ -1465,6 +1467,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 window_id: RootWindowId(WindowId(window)),
                 event: CursorLeft {
                     device_id: DEVICE_ID,
+                    timestamp: None,
                 },
             });
 
@@ -1486,6 +1489,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     delta: LineDelta(0.0, value),
                     phase: TouchPhase::Moved,
+                    source: None,
+                    value120: None,
+                    timestamp: None,
                 },
             });
 
@@ -1507,6 +1513,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     delta: LineDelta(value, 0.0),
                     phase: TouchPhase::Moved,
+                    source: None,
+                    value120: None,
+                    timestamp: None,
                 },
             });
 
@@ -1540,6 +1549,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Left,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1560,6 +1570,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Left,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1580,6 +1591,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Right,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1600,6 +1612,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Right,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1620,6 +1633,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Middle,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1640,6 +1654,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Middle,
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1666,6 +1681,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                         2 => Forward,
                         _ => Other(xbutton),
                     },
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1692,6 +1708,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                         2 => Forward,
                         _ => Other(xbutton),
                     },
+                    timestamp: None,
                 },
             });
             result = ProcResult::Value(0);
@@ -1747,8 +1764,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             },
                             location,
                             force: None, // WM_TOUCH doesn't support pressure information
+                            contact_size: None,
                             id: input.dwID as u64,
                             device_id: DEVICE_ID,
+                            timestamp: None,
                         }),
                     });
                 }
@@ -1888,8 +1907,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             },
                             location,
                             force,
+                            contact_size: None,
                             id: pointer_info.pointerId as u64,
                             device_id: DEVICE_ID,
+                            timestamp: None,
                         }),
                     });
                 }
@@ -2305,6 +2326,7 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
             userdata.send_event(Event::DeviceEvent {
                 device_id: wrap_device_id(lparam as u32),
                 event,
+                timestamp: None,
             });
 
             0
@@ -2398,6 +2420,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 0, value: x },
+                    timestamp: None,
                 });
             }
 
@@ -2405,6 +2428,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 1, value: y },
+                    timestamp: None,
                 });
             }
 
@@ -2412,6 +2436,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: MouseMotion { delta: (x, y) },
+                    timestamp: None,
                 });
             }
         }
@@ -2427,6 +2452,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 event: MouseWheel {
                     delta: LineDelta(0.0, delta),
                 },
+                timestamp: None,
             });
         }
 
@@ -2441,6 +2467,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: Button { button, state },
+                    timestamp: None,
                 });
             }
         }
@@ -2550,6 +2577,7 @@ unsafe fn handle_raw_input<T: 'static>(userdata: &ThreadMsgTargetData<T>, data:
                 physical_key: code,
                 state,
             }),
+            timestamp: None,
         });
     }
 }
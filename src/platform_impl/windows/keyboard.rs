@@ -662,6 +662,7 @@ impl PartialKeyEventInfo {
             location: self.location,
             state: self.key_state,
             repeat: self.is_repeat,
+            timestamp: None,
             platform_specific: KeyEventExtra {
                 text_with_all_modifers: char_with_all_modifiers,
                 key_without_modifiers: self.key_without_modifiers,
@@ -90,6 +90,19 @@ pub struct KeyEventExtra {
     pub key_without_modifiers: Key,
 }
 
+impl KeyEventExtra {
+    #[cfg(feature = "record")]
+    pub(crate) fn new(
+        key_without_modifiers: Key,
+        text_with_all_modifiers: Option<SmolStr>,
+    ) -> Self {
+        Self {
+            text_with_all_modifers: text_with_all_modifiers,
+            key_without_modifiers,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct WindowId(HWND);
 unsafe impl Send for WindowId {}
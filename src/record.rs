@@ -0,0 +1,948 @@
+//! Recording the events to a file, and replaying them into the event loop callback.
+//!
+//! Bugs depending on the input and its timing are hard to reproduce. A [`Recorder`] serializes
+//! the events received by the application with their timestamps, and a [`Replayer`] feeds the
+//! recorded session back into the event loop callback later, to reproduce the bug or to turn it
+//! into a test, for example with the headless backend on Linux.
+//!
+//! Both wrap the event handler given to [`EventLoop::run`]:
+//!
+//! ```no_run
+//! use winit::event::{Event, WindowEvent};
+//! use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
+//! use winit::record::{Recorder, Replayer};
+//! use winit::window::Window;
+//!
+//! let event_loop = EventLoop::new();
+//! let window = Window::new(&event_loop).unwrap();
+//!
+//! let event_handler =
+//!     move |event: Event<'_, ()>, _: &EventLoopWindowTarget<()>, control_flow: &mut ControlFlow| {
+//!         if let Event::WindowEvent {
+//!             event: WindowEvent::CloseRequested,
+//!             window_id,
+//!         } = event
+//!         {
+//!             if window_id == window.id() {
+//!                 control_flow.set_exit();
+//!             }
+//!         }
+//!     };
+//!
+//! if let Ok(path) = std::env::var("REPLAY") {
+//!     let replayer = Replayer::open(path).unwrap();
+//!     event_loop.run(replayer.wrap(event_handler));
+//! } else {
+//!     let recorder = Recorder::create("session.jsonl").unwrap();
+//!     event_loop.run(recorder.wrap(event_handler));
+//! }
+//! ```
+//!
+//! The file holds an event per line in JSON, with the time elapsed since the start of the
+//! recording. Only the [`WindowEvent`]s and [`DeviceEvent`]s are recorded, the other events are
+//! produced by the event loop during the replay, with some exceptions:
+//!
+//! - [`WindowEvent::ActivationTokenDone`], [`WindowEvent::DragAndDrop`],
+//!   [`WindowEvent::DragSource`] and [`WindowEvent::PresentationFeedback`] refer to requests of
+//!   the application, and aren't recorded.
+//! - The [`DeviceId`]s aren't recorded, and the replayed events all use [`DeviceId::dummy`].
+//! - The [`KeyEvent`]s are replayed with the text and key of the [`KeyEventExtModifierSupplement`]
+//!   when recorded on a platform supporting it, and with the ones of the event otherwise.
+//! - The size set in [`WindowEvent::ScaleFactorChanged`] isn't applied to the window.
+//! - The timestamps of the events are shifted to the start of the replay.
+//!
+//! [`EventLoop::run`]: crate::event_loop::EventLoop::run
+//! [`DeviceId`]: crate::event::DeviceId
+//! [`DeviceId::dummy`]: crate::event::DeviceId::dummy
+//! [`KeyEventExtModifierSupplement`]: crate::platform::modifier_supplement::KeyEventExtModifierSupplement
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use smol_str::SmolStr;
+
+use crate::device::SeatId;
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::event::{
+    AxisId, ButtonId, DeviceEvent, DeviceId, ElementState, Event, Force, Ime, KeyEvent,
+    MouseButton, MouseScrollDelta, PenEvent, Pointer, PointerButtons, PointerKind, PointerPhase,
+    RawKeyEvent, ScrollSource, Touch, TouchPhase, WindowEvent,
+};
+use crate::event_loop::{ControlFlow, EventLoopWindowTarget};
+use crate::keyboard::{Key, KeyCode, KeyLocation, ModifiersState};
+use crate::platform_impl;
+use crate::window::{Theme, WindowId};
+
+/// Records the events to a file, with their timestamps.
+///
+/// The windows are identified by the order in which they first appear in the events of the
+/// event loop, including the ones which aren't recorded such as [`Event::RedrawRequested`], so
+/// that the session can be replayed with other windows, see [`Replayer::set_window`].
+pub struct Recorder {
+    writer: Box<dyn Write>,
+    start: Option<Instant>,
+    windows: Vec<WindowId>,
+    pending_iteration: bool,
+}
+
+impl Recorder {
+    /// Record the events to the file at `path`, which is created or truncated.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Record the events to `writer`.
+    pub fn new<W: Write + 'static>(writer: W) -> Self {
+        Self {
+            writer: Box::new(writer),
+            start: None,
+            windows: Vec::new(),
+            pending_iteration: false,
+        }
+    }
+
+    /// Record the event, if it's one that can be replayed.
+    ///
+    /// The timestamps are relative to the first event given to the recorder. The writer is
+    /// flushed after each event, so that the events leading to a crash are in the file.
+    pub fn record<T>(&mut self, event: &Event<'_, T>) -> io::Result<()> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let time = start.elapsed();
+        if let Some(window_id) = event_window(event) {
+            self.window_index(window_id);
+        }
+
+        let event = match event {
+            Event::WindowEvent { window_id, event } => {
+                match RecordedWindowEvent::from_event(event, start) {
+                    Some(event) => RecordedEvent::WindowEvent {
+                        window: self.window_index(*window_id),
+                        event,
+                    },
+                    None => return Ok(()),
+                }
+            }
+            Event::DeviceEvent {
+                event, timestamp, ..
+            } => RecordedEvent::DeviceEvent {
+                event: RecordedDeviceEvent::from_event(event),
+                timestamp: offset(*timestamp, start),
+            },
+            // The end of an iteration is only marked when it had some events, to replay them in
+            // a single iteration as well.
+            Event::MainEventsCleared if self.pending_iteration => RecordedEvent::MainEventsCleared,
+            _ => return Ok(()),
+        };
+
+        self.pending_iteration = !matches!(event, RecordedEvent::MainEventsCleared);
+        serde_json::to_writer(&mut self.writer, &Record { time, event })?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    /// Wrap the event handler, recording the events before passing them to it.
+    ///
+    /// The errors while recording are logged, and the recording goes on.
+    pub fn wrap<T, F>(
+        mut self,
+        mut event_handler: F,
+    ) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        move |event, target, control_flow| {
+            if let Err(err) = self.record(&event) {
+                warn!("Failed to record the event: {err}");
+            }
+            event_handler(event, target, control_flow)
+        }
+    }
+
+    fn window_index(&mut self, window_id: WindowId) -> usize {
+        match self.windows.iter().position(|id| *id == window_id) {
+            Some(index) => index,
+            None => {
+                self.windows.push(window_id);
+                self.windows.len() - 1
+            }
+        }
+    }
+}
+
+/// Replays the events of a session recorded with a [`Recorder`].
+///
+/// The recorded events are delivered right before [`Event::MainEventsCleared`], the events
+/// recorded during an iteration of the event loop being replayed within a single iteration as
+/// well. By default, they're delivered once the time elapsed since the first event given to the
+/// replayer reaches the one elapsed during the recording, waking up the event loop when needed.
+pub struct Replayer {
+    iterations: VecDeque<Iteration>,
+    realtime: bool,
+    start: Option<Instant>,
+    windows: HashMap<usize, WindowId>,
+    live_windows: Vec<WindowId>,
+}
+
+impl Replayer {
+    /// Replay the session recorded in the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Replay the session recorded in `reader`.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut iterations = VecDeque::new();
+        let mut iteration = Iteration::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let Record { time, event } = serde_json::from_str(&line)?;
+            iteration.time = time;
+            match event {
+                RecordedEvent::MainEventsCleared => {
+                    iterations.push_back(std::mem::take(&mut iteration))
+                }
+                event => iteration.events.push(event),
+            }
+        }
+
+        // The recording may have been cut short in the middle of an iteration.
+        if !iteration.events.is_empty() {
+            iterations.push_back(iteration);
+        }
+
+        Ok(Self {
+            iterations,
+            realtime: true,
+            start: None,
+            windows: HashMap::new(),
+            live_windows: Vec::new(),
+        })
+    }
+
+    /// Whether to honor the timing of the recording, which is the default.
+    ///
+    /// Otherwise, the events of a recorded iteration are replayed on each iteration of the event
+    /// loop, which is kept polling until the end of the session.
+    pub fn set_realtime(&mut self, realtime: bool) {
+        self.realtime = realtime;
+    }
+
+    /// Replay the events of the `index`th recorded window into the window `window_id`.
+    ///
+    /// By default, the `index`th recorded window is matched with the `index`th window to appear
+    /// in the events of the event loop. The events of a recorded window without a match are
+    /// dropped.
+    pub fn set_window(&mut self, index: usize, window_id: WindowId) {
+        self.windows.insert(index, window_id);
+    }
+
+    /// True once all the recorded events were replayed.
+    pub fn is_finished(&self) -> bool {
+        self.iterations.is_empty()
+    }
+
+    /// Wrap the event handler, replaying the recorded events into it along with the ones of the
+    /// event loop.
+    pub fn wrap<T, F>(
+        mut self,
+        mut event_handler: F,
+    ) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        move |event, target, control_flow| {
+            let start = *self.start.get_or_insert_with(Instant::now);
+
+            if let Some(window_id) = event_window(&event) {
+                if !self.live_windows.contains(&window_id) {
+                    self.live_windows.push(window_id);
+                }
+            }
+
+            if let Event::MainEventsCleared = event {
+                while let Some(iteration) = self.next_iteration(start) {
+                    for event in iteration.events {
+                        self.replay(event, start, target, control_flow, &mut event_handler);
+                    }
+
+                    if !self.realtime {
+                        break;
+                    }
+                }
+            }
+
+            event_handler(event, target, control_flow);
+
+            if let Some(iteration) = self.iterations.front() {
+                match *control_flow {
+                    ControlFlow::ExitWithCode(_) | ControlFlow::Poll => (),
+                    _ if !self.realtime => control_flow.set_poll(),
+                    ControlFlow::Wait => control_flow.set_wait_until(start + iteration.time),
+                    ControlFlow::WaitUntil(deadline) => {
+                        control_flow.set_wait_until(deadline.min(start + iteration.time))
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_iteration(&mut self, start: Instant) -> Option<Iteration> {
+        let iteration = self.iterations.front()?;
+        if self.realtime && start.elapsed() < iteration.time {
+            return None;
+        }
+
+        self.iterations.pop_front()
+    }
+
+    fn replay<T, F>(
+        &self,
+        event: RecordedEvent,
+        start: Instant,
+        target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        event_handler: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        // SAFETY: The id is only given to the event handler, and never passed into winit.
+        let device_id = unsafe { DeviceId::dummy() };
+
+        let (window, event) = match event {
+            RecordedEvent::WindowEvent { window, event } => (window, event),
+            RecordedEvent::DeviceEvent { event, timestamp } => {
+                let event = Event::DeviceEvent {
+                    device_id,
+                    event: event.into_event(),
+                    timestamp: timestamp.map(|timestamp| start + timestamp),
+                };
+                return event_handler(event, target, control_flow);
+            }
+            RecordedEvent::MainEventsCleared => return,
+        };
+
+        let window_id = match self
+            .windows
+            .get(&window)
+            .or_else(|| self.live_windows.get(window))
+        {
+            Some(window_id) => *window_id,
+            None => {
+                warn!("Dropping an event of the recorded window {window} without a match");
+                return;
+            }
+        };
+
+        let mut new_inner_size = PhysicalSize::default();
+        let event = event.into_event(device_id, start, &mut new_inner_size);
+        event_handler(
+            Event::WindowEvent { window_id, event },
+            target,
+            control_flow,
+        )
+    }
+}
+
+/// The window of an event of the event loop, numbering the windows the same way when recording
+/// and replaying.
+fn event_window<T>(event: &Event<'_, T>) -> Option<WindowId> {
+    match event {
+        Event::WindowEvent { window_id, .. } | Event::RedrawRequested(window_id) => {
+            Some(*window_id)
+        }
+        _ => None,
+    }
+}
+
+/// The timestamp of an event, relative to the start of the recording.
+fn offset(timestamp: Option<Instant>, start: Instant) -> Option<Duration> {
+    timestamp.map(|timestamp| timestamp.saturating_duration_since(start))
+}
+
+#[derive(Default)]
+struct Iteration {
+    time: Duration,
+    events: Vec<RecordedEvent>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    time: Duration,
+    event: RecordedEvent,
+}
+
+#[derive(Serialize, Deserialize)]
+enum RecordedEvent {
+    WindowEvent {
+        window: usize,
+        event: RecordedWindowEvent,
+    },
+    DeviceEvent {
+        event: RecordedDeviceEvent,
+        timestamp: Option<Duration>,
+    },
+    MainEventsCleared,
+}
+
+/// The [`WindowEvent`]s which can be replayed, without their device ids.
+#[derive(Serialize, Deserialize)]
+enum RecordedWindowEvent {
+    Resized(PhysicalSize<u32>),
+    Moved(PhysicalPosition<i32>),
+    CloseRequested,
+    Destroyed,
+    DroppedFile(PathBuf),
+    HoveredFile(PathBuf),
+    HoveredFileCancelled,
+    Focused(bool),
+    SeatFocused {
+        seat: u64,
+        focused: bool,
+    },
+    KeyboardInput {
+        event: RecordedKeyEvent,
+        is_synthetic: bool,
+    },
+    ModifiersChanged(ModifiersState),
+    Ime(Ime),
+    CursorMoved {
+        position: PhysicalPosition<f64>,
+        timestamp: Option<Duration>,
+    },
+    CursorEntered {
+        timestamp: Option<Duration>,
+    },
+    CursorLeft {
+        timestamp: Option<Duration>,
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+        source: Option<ScrollSource>,
+        value120: Option<(i32, i32)>,
+        timestamp: Option<Duration>,
+    },
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+        timestamp: Option<Duration>,
+    },
+    TouchpadMagnify {
+        delta: f64,
+        phase: TouchPhase,
+        timestamp: Option<Duration>,
+    },
+    SmartMagnify,
+    TouchpadRotate {
+        delta: f32,
+        phase: TouchPhase,
+        timestamp: Option<Duration>,
+    },
+    TouchpadSwipe {
+        delta: PhysicalPosition<f64>,
+        fingers: u32,
+        phase: TouchPhase,
+        timestamp: Option<Duration>,
+    },
+    TouchpadHold {
+        fingers: u32,
+        phase: TouchPhase,
+        timestamp: Option<Duration>,
+    },
+    TouchpadPressure {
+        pressure: f32,
+        stage: i64,
+    },
+    AxisMotion {
+        axis: AxisId,
+        value: f64,
+    },
+    Touch {
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+        force: Option<Force>,
+        contact_size: Option<(f64, f64)>,
+        id: u64,
+        timestamp: Option<Duration>,
+    },
+    PenInput {
+        event: PenEvent,
+    },
+    Pointer {
+        pointer_id: u64,
+        kind: PointerKind,
+        phase: PointerPhase,
+        position: PhysicalPosition<f64>,
+        /// The bits of the `PointerButtons`.
+        buttons: u32,
+        pressure: Option<f64>,
+        tilt: Option<(f64, f64)>,
+        size: Option<(f64, f64)>,
+        timestamp: Option<Duration>,
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+    ThemeChanged(Theme),
+    Occluded(bool),
+    PopupDismissed,
+}
+
+impl RecordedWindowEvent {
+    fn from_event(event: &WindowEvent<'_>, start: Instant) -> Option<Self> {
+        let event = match event {
+            WindowEvent::ActivationTokenDone { .. }
+            | WindowEvent::DragAndDrop(_)
+            | WindowEvent::DragSource(_)
+            | WindowEvent::PresentationFeedback(_) => return None,
+            WindowEvent::Resized(size) => Self::Resized(*size),
+            WindowEvent::Moved(position) => Self::Moved(*position),
+            WindowEvent::CloseRequested => Self::CloseRequested,
+            WindowEvent::Destroyed => Self::Destroyed,
+            WindowEvent::DroppedFile(path) => Self::DroppedFile(path.clone()),
+            WindowEvent::HoveredFile(path) => Self::HoveredFile(path.clone()),
+            WindowEvent::HoveredFileCancelled => Self::HoveredFileCancelled,
+            WindowEvent::Focused(focused) => Self::Focused(*focused),
+            WindowEvent::SeatFocused { seat, focused } => Self::SeatFocused {
+                seat: seat.0,
+                focused: *focused,
+            },
+            WindowEvent::KeyboardInput {
+                event,
+                is_synthetic,
+                ..
+            } => Self::KeyboardInput {
+                event: RecordedKeyEvent::from_event(event, start),
+                is_synthetic: *is_synthetic,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Self::ModifiersChanged(modifiers.state()),
+            WindowEvent::Ime(ime) => Self::Ime(ime.clone()),
+            WindowEvent::CursorMoved {
+                position,
+                timestamp,
+                ..
+            } => Self::CursorMoved {
+                position: *position,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::CursorEntered { timestamp, .. } => Self::CursorEntered {
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::CursorLeft { timestamp, .. } => Self::CursorLeft {
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::MouseWheel {
+                delta,
+                phase,
+                source,
+                value120,
+                timestamp,
+                ..
+            } => Self::MouseWheel {
+                delta: *delta,
+                phase: *phase,
+                source: *source,
+                value120: *value120,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::MouseInput {
+                state,
+                button,
+                timestamp,
+                ..
+            } => Self::MouseInput {
+                state: *state,
+                button: *button,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::TouchpadMagnify {
+                delta,
+                phase,
+                timestamp,
+                ..
+            } => Self::TouchpadMagnify {
+                delta: *delta,
+                phase: *phase,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::SmartMagnify { .. } => Self::SmartMagnify,
+            WindowEvent::TouchpadRotate {
+                delta,
+                phase,
+                timestamp,
+                ..
+            } => Self::TouchpadRotate {
+                delta: *delta,
+                phase: *phase,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::TouchpadSwipe {
+                delta,
+                fingers,
+                phase,
+                timestamp,
+                ..
+            } => Self::TouchpadSwipe {
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::TouchpadHold {
+                fingers,
+                phase,
+                timestamp,
+                ..
+            } => Self::TouchpadHold {
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: offset(*timestamp, start),
+            },
+            WindowEvent::TouchpadPressure {
+                pressure, stage, ..
+            } => Self::TouchpadPressure {
+                pressure: *pressure,
+                stage: *stage,
+            },
+            WindowEvent::AxisMotion { axis, value, .. } => Self::AxisMotion {
+                axis: *axis,
+                value: *value,
+            },
+            WindowEvent::Touch(touch) => Self::Touch {
+                phase: touch.phase,
+                location: touch.location,
+                force: touch.force,
+                contact_size: touch.contact_size,
+                id: touch.id,
+                timestamp: offset(touch.timestamp, start),
+            },
+            WindowEvent::PenInput { event, .. } => Self::PenInput { event: *event },
+            WindowEvent::Pointer(pointer) => Self::Pointer {
+                pointer_id: pointer.pointer_id,
+                kind: pointer.kind,
+                phase: pointer.phase,
+                position: pointer.position,
+                buttons: pointer.buttons.bits(),
+                pressure: pointer.pressure,
+                tilt: pointer.tilt,
+                size: pointer.size,
+                timestamp: offset(pointer.timestamp, start),
+            },
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => Self::ScaleFactorChanged {
+                scale_factor: *scale_factor,
+                new_inner_size: **new_inner_size,
+            },
+            WindowEvent::ThemeChanged(theme) => Self::ThemeChanged(*theme),
+            WindowEvent::Occluded(occluded) => Self::Occluded(*occluded),
+            WindowEvent::PopupDismissed => Self::PopupDismissed,
+        };
+
+        Some(event)
+    }
+
+    fn into_event(
+        self,
+        device_id: DeviceId,
+        start: Instant,
+        size: &mut PhysicalSize<u32>,
+    ) -> WindowEvent<'_> {
+        match self {
+            Self::Resized(size) => WindowEvent::Resized(size),
+            Self::Moved(position) => WindowEvent::Moved(position),
+            Self::CloseRequested => WindowEvent::CloseRequested,
+            Self::Destroyed => WindowEvent::Destroyed,
+            Self::DroppedFile(path) => WindowEvent::DroppedFile(path),
+            Self::HoveredFile(path) => WindowEvent::HoveredFile(path),
+            Self::HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+            Self::Focused(focused) => WindowEvent::Focused(focused),
+            Self::SeatFocused { seat, focused } => WindowEvent::SeatFocused {
+                seat: SeatId(seat),
+                focused,
+            },
+            Self::KeyboardInput {
+                event,
+                is_synthetic,
+            } => WindowEvent::KeyboardInput {
+                device_id,
+                event: event.into_event(start),
+                is_synthetic,
+            },
+            Self::ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers.into()),
+            Self::Ime(ime) => WindowEvent::Ime(ime),
+            Self::CursorMoved {
+                position,
+                timestamp,
+            } => WindowEvent::CursorMoved {
+                device_id,
+                position,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::CursorEntered { timestamp } => WindowEvent::CursorEntered {
+                device_id,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::CursorLeft { timestamp } => WindowEvent::CursorLeft {
+                device_id,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::MouseWheel {
+                delta,
+                phase,
+                source,
+                value120,
+                timestamp,
+            } => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                source,
+                value120,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::MouseInput {
+                state,
+                button,
+                timestamp,
+            } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::TouchpadMagnify {
+                delta,
+                phase,
+                timestamp,
+            } => WindowEvent::TouchpadMagnify {
+                device_id,
+                delta,
+                phase,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::SmartMagnify => WindowEvent::SmartMagnify { device_id },
+            Self::TouchpadRotate {
+                delta,
+                phase,
+                timestamp,
+            } => WindowEvent::TouchpadRotate {
+                device_id,
+                delta,
+                phase,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::TouchpadSwipe {
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            } => WindowEvent::TouchpadSwipe {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::TouchpadHold {
+                fingers,
+                phase,
+                timestamp,
+            } => WindowEvent::TouchpadHold {
+                device_id,
+                fingers,
+                phase,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            },
+            Self::TouchpadPressure { pressure, stage } => WindowEvent::TouchpadPressure {
+                device_id,
+                pressure,
+                stage,
+            },
+            Self::AxisMotion { axis, value } => WindowEvent::AxisMotion {
+                device_id,
+                axis,
+                value,
+            },
+            Self::Touch {
+                phase,
+                location,
+                force,
+                contact_size,
+                id,
+                timestamp,
+            } => WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location,
+                force,
+                contact_size,
+                id,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            }),
+            Self::PenInput { event } => WindowEvent::PenInput { device_id, event },
+            Self::Pointer {
+                pointer_id,
+                kind,
+                phase,
+                position,
+                buttons,
+                pressure,
+                tilt,
+                size,
+                timestamp,
+            } => WindowEvent::Pointer(Pointer {
+                device_id,
+                pointer_id,
+                kind,
+                phase,
+                position,
+                buttons: PointerButtons::from_bits_truncate(buttons),
+                pressure,
+                tilt,
+                size,
+                timestamp: timestamp.map(|timestamp| start + timestamp),
+            }),
+            Self::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => {
+                *size = new_inner_size;
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size: size,
+                }
+            }
+            Self::ThemeChanged(theme) => WindowEvent::ThemeChanged(theme),
+            Self::Occluded(occluded) => WindowEvent::Occluded(occluded),
+            Self::PopupDismissed => WindowEvent::PopupDismissed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedKeyEvent {
+    physical_key: KeyCode,
+    logical_key: Key,
+    text: Option<SmolStr>,
+    location: KeyLocation,
+    state: ElementState,
+    repeat: bool,
+    timestamp: Option<Duration>,
+    key_without_modifiers: Key,
+    text_with_all_modifiers: Option<SmolStr>,
+}
+
+impl RecordedKeyEvent {
+    fn from_event(event: &KeyEvent, start: Instant) -> Self {
+        #[cfg(any(windows_platform, macos_platform, x11_platform, wayland_platform))]
+        let (key_without_modifiers, text_with_all_modifiers) = {
+            use crate::platform::modifier_supplement::KeyEventExtModifierSupplement;
+            (
+                event.key_without_modifiers(),
+                event.text_with_all_modifiers().map(SmolStr::new),
+            )
+        };
+        #[cfg(not(any(windows_platform, macos_platform, x11_platform, wayland_platform)))]
+        let (key_without_modifiers, text_with_all_modifiers) =
+            (event.logical_key.clone(), event.text.clone());
+
+        Self {
+            physical_key: event.physical_key,
+            logical_key: event.logical_key.clone(),
+            text: event.text.clone(),
+            location: event.location,
+            state: event.state,
+            repeat: event.repeat,
+            timestamp: offset(event.timestamp, start),
+            key_without_modifiers,
+            text_with_all_modifiers,
+        }
+    }
+
+    fn into_event(self, start: Instant) -> KeyEvent {
+        KeyEvent {
+            physical_key: self.physical_key,
+            logical_key: self.logical_key,
+            text: self.text,
+            location: self.location,
+            state: self.state,
+            repeat: self.repeat,
+            timestamp: self.timestamp.map(|timestamp| start + timestamp),
+            platform_specific: platform_impl::KeyEventExtra::new(
+                self.key_without_modifiers,
+                self.text_with_all_modifiers,
+            ),
+        }
+    }
+}
+
+/// The [`DeviceEvent`]s, which can all be replayed.
+#[derive(Serialize, Deserialize)]
+enum RecordedDeviceEvent {
+    Added,
+    Removed,
+    MouseMotion {
+        delta: (f64, f64),
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+    },
+    Motion {
+        axis: AxisId,
+        value: f64,
+    },
+    Button {
+        button: ButtonId,
+        state: ElementState,
+    },
+    Key(RawKeyEvent),
+    Text {
+        codepoint: char,
+    },
+}
+
+impl RecordedDeviceEvent {
+    fn from_event(event: &DeviceEvent) -> Self {
+        match event {
+            DeviceEvent::Added => Self::Added,
+            DeviceEvent::Removed => Self::Removed,
+            DeviceEvent::MouseMotion { delta } => Self::MouseMotion { delta: *delta },
+            DeviceEvent::MouseWheel { delta } => Self::MouseWheel { delta: *delta },
+            DeviceEvent::Motion { axis, value } => Self::Motion {
+                axis: *axis,
+                value: *value,
+            },
+            DeviceEvent::Button { button, state } => Self::Button {
+                button: *button,
+                state: *state,
+            },
+            DeviceEvent::Key(event) => Self::Key(event.clone()),
+            DeviceEvent::Text { codepoint } => Self::Text {
+                codepoint: *codepoint,
+            },
+        }
+    }
+
+    fn into_event(self) -> DeviceEvent {
+        match self {
+            Self::Added => DeviceEvent::Added,
+            Self::Removed => DeviceEvent::Removed,
+            Self::MouseMotion { delta } => DeviceEvent::MouseMotion { delta },
+            Self::MouseWheel { delta } => DeviceEvent::MouseWheel { delta },
+            Self::Motion { axis, value } => DeviceEvent::Motion { axis, value },
+            Self::Button { button, state } => DeviceEvent::Button { button, state },
+            Self::Key(event) => DeviceEvent::Key(event),
+            Self::Text { codepoint } => DeviceEvent::Text { codepoint },
+        }
+    }
+}
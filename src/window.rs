@@ -6,6 +6,9 @@ use raw_window_handle::{
 };
 
 use crate::{
+    clipboard::ClipboardContent,
+    device::SeatId,
+    dnd::DndAction,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError},
     event_loop::EventLoopWindowTarget,
@@ -13,6 +16,7 @@ use crate::{
     platform_impl,
 };
 
+pub use crate::cursor::{BadCursor, CursorFrame, CustomCursor};
 pub use crate::icon::{BadIcon, Icon};
 
 #[doc(inline)]
@@ -143,6 +147,9 @@ pub struct WindowAttributes {
     pub window_level: WindowLevel,
     pub parent_window: Option<RawWindowHandle>,
     pub active: bool,
+    pub popup: Option<(WindowId, PopupPlacement)>,
+    pub owner: Option<WindowId>,
+    pub modal: bool,
 }
 
 impl Default for WindowAttributes {
@@ -168,6 +175,9 @@ impl Default for WindowAttributes {
             content_protected: false,
             parent_window: None,
             active: true,
+            popup: None,
+            owner: None,
+            modal: false,
         }
     }
 }
@@ -240,6 +250,8 @@ impl WindowBuilder {
     ///   the specifics of the Window Manager.
     /// - **X11:** The top left corner of the window, the window's "outer"
     ///   position.
+    /// - **Wayland:** The position of child windows relative to their parent, ignored for the
+    ///   other windows.
     /// - **Others:** Ignored.
     #[inline]
     pub fn with_position<P: Into<Position>>(mut self, position: P) -> Self {
@@ -445,13 +457,74 @@ impl WindowBuilder {
     /// to the client area of its parent window. For more information, see
     /// <https://docs.microsoft.com/en-us/windows/win32/winmsg/window-features#child-windows>
     /// - **X11**: A child window is confined to the client area of its parent window.
-    /// - **Android / iOS / Wayland / Web:** Unsupported.
+    /// - **Wayland:** A child window is a subsurface of its parent, which must be a window of the
+    ///   same event loop. Its position, stacking and synchronization are applied when the parent
+    ///   is redrawn, see [`WindowExtWayland`](crate::platform::wayland::WindowExtWayland). Child
+    ///   windows don't receive the keyboard focus.
+    /// - **Android / iOS / Web:** Unsupported.
     #[inline]
     pub unsafe fn with_parent_window(mut self, parent_window: Option<RawWindowHandle>) -> Self {
         self.window.parent_window = parent_window;
         self
     }
 
+    /// Build a popup, like a menu or a tooltip, attached to the `parent` window.
+    ///
+    /// The popup has no decorations and isn't managed by the window manager. It's placed next to
+    /// a rectangle of its parent as described by the `placement`, and the position of the
+    /// builder is ignored. A [`WindowEvent::PopupDismissed`] is sent when the system closes the
+    /// popup, and the popups must be dropped before their parent.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Created as an `xdg_popup`. Grabbing the input requires a recent input
+    ///   event, like the click opening a menu, otherwise the popup is dismissed right away.
+    /// - **X11:** Created as an override-redirect window, transient for its parent, which grabs
+    ///   the pointer and the keyboard.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported, a regular window is
+    ///   created instead.
+    ///
+    /// [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+    #[inline]
+    pub fn with_popup(mut self, parent: WindowId, placement: PopupPlacement) -> Self {
+        self.window.popup = Some((parent, placement));
+        self
+    }
+
+    /// Build a dialog owned by the `owner` window.
+    ///
+    /// The dialog is kept above its owner, and isn't shown in the taskbar.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets `WM_TRANSIENT_FOR` and the `_NET_WM_WINDOW_TYPE_DIALOG` type, unless other
+    ///   types were requested with
+    ///   [`WindowBuilderExtX11::with_x11_window_type`](crate::platform::x11::WindowBuilderExtX11::with_x11_window_type).
+    /// - **Wayland:** Sets the parent of the `xdg_toplevel`, the owner must be a top-level window.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    #[inline]
+    pub fn with_owner(mut self, owner: WindowId) -> Self {
+        self.window.owner = Some(owner);
+        self
+    }
+
+    /// Sets whether the dialog is modal, blocking the input of its owner while it's shown.
+    ///
+    /// The keyboard, mouse, touch and pen events of the owner are dropped until the dialog is
+    /// hidden or dropped, except for the releases, so the press which opened the dialog doesn't
+    /// stay held. This requires an owner, see [`WindowBuilder::with_owner`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Also sets the `_NET_WM_STATE_MODAL` state.
+    /// - **Wayland:** The compositor isn't told that the dialog is modal.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    #[inline]
+    pub fn with_modal(mut self, modal: bool) -> Self {
+        self.window.modal = modal;
+        self
+    }
+
     /// Builds the window.
     ///
     /// Possible causes of error include denied permission, incompatible system, and lack of memory.
@@ -548,6 +621,48 @@ impl Window {
         self.window.request_redraw()
     }
 
+    /// Notify the windowing system that the window is about to be presented to.
+    ///
+    /// Call it right before submitting the buffer of a frame, like with `eglSwapBuffers`, such
+    /// that the [`Event::RedrawRequested`] events are paced to the rate the compositor shows the
+    /// frames at. The redraws are held back until the compositor is ready for a new frame, and
+    /// aren't emitted at all while the window is hidden.
+    ///
+    /// A buffer must be presented after this call, otherwise the redraws stop.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requests a frame callback on the surface.
+    /// - **Windows / macOS / X11 / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`Event::RedrawRequested`]: crate::event::Event::RedrawRequested
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        #[cfg(any(x11_platform, wayland_platform))]
+        self.window.pre_present_notify();
+    }
+
+    /// Request to be told when the next frame of the window is shown on the screen.
+    ///
+    /// Call it before presenting the frame, and a [`WindowEvent::PresentationFeedback`] is sent
+    /// once the frame is shown or discarded, with the time it was shown at and the refresh
+    /// interval of the monitor, which lets the application time its next frames precisely.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `wp_presentation` protocol.
+    /// - **X11:** Requires the Present extension.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`WindowEvent::PresentationFeedback`]: crate::event::WindowEvent::PresentationFeedback
+    #[inline]
+    pub fn request_presentation_feedback(&self) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.window.request_presentation_feedback();
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     /// Reset the dead key state of the keyboard.
     ///
     /// This is useful when a dead key is bound to trigger an action. Then
@@ -578,7 +693,9 @@ impl Window {
     ///   window's [safe area] in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport. _Note: this returns the
     ///    same value as [`Window::outer_position`]._
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the position of child windows relative to their parent, and
+    ///   [`NotSupportedError`] for the other windows.
+    /// - **Android:** Always returns [`NotSupportedError`].
     ///
     /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
     #[inline]
@@ -601,7 +718,9 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the position of child windows relative to their parent, and
+    ///   [`NotSupportedError`] for the other windows.
+    /// - **Android:** Always returns [`NotSupportedError`].
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         self.window.outer_position()
@@ -631,7 +750,9 @@ impl Window {
     ///   window in the screen space coordinate system.
     /// - **Web:** Sets the top-left coordinates relative to the viewport. Doesn't account for CSS
     ///   [`transform`].
-    /// - **Android / Wayland:** Unsupported.
+    /// - **Wayland:** Only moves child windows, relative to their parent. The position is applied
+    ///   when the parent is redrawn.
+    /// - **Android:** Unsupported.
     ///
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
     #[inline]
@@ -763,7 +884,7 @@ impl Window {
     ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / Wayland / Windows / Orbital:** Always returns [`None`].
+    /// - **iOS / Android / Web / Windows / Orbital:** Always returns [`None`].
     #[inline]
     pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
         self.window.resize_increments()
@@ -777,7 +898,9 @@ impl Window {
     /// ## Platform-specific
     ///
     /// - **macOS:** Increments are converted to logical size and then macOS rounds them to whole numbers.
-    /// - **Wayland / Windows:** Not implemented.
+    /// - **Wayland:** The size is snapped to the increments starting from the minimum inner size,
+    ///   unless the window is maximized, fullscreen or tiled.
+    /// - **Windows:** Not implemented.
     /// - **iOS / Android / Web / Orbital:** Unsupported.
     #[inline]
     pub fn set_resize_increments<S: Into<Size>>(&self, increments: Option<S>) {
@@ -1208,6 +1331,8 @@ impl Window {
 impl Window {
     /// Modifies the cursor icon of the window.
     ///
+    /// The cursor icons set with [`Window::set_seat_cursor_icon`] are replaced as well.
+    ///
     /// ## Platform-specific
     ///
     /// - **iOS / Android / Orbital:** Unsupported.
@@ -1216,6 +1341,45 @@ impl Window {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Modifies the cursor icon of the window for the pointer of the given seat only.
+    ///
+    /// The icon is kept until the cursor of the whole window is set again, with
+    /// [`Window::set_cursor_icon`] or [`Window::set_custom_cursor`]. The cursor stays hidden on
+    /// all the seats with [`Window::set_cursor_visible`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_seat_cursor_icon(
+        &self,
+        _seat: SeatId,
+        _cursor: CursorIcon,
+    ) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.window.set_seat_cursor_icon(_seat, _cursor);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    /// Modifies the cursor of the window to the given [`CustomCursor`].
+    ///
+    /// Animated cursors loop over their frames for as long as they're shown. The cursor is
+    /// replaced again with [`Window::set_cursor_icon`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only the first frame of animated cursors is shown when the X server doesn't
+    ///   support animated cursors. The default cursor is shown when the cursor can't be created.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {
+        #[cfg(any(x11_platform, wayland_platform))]
+        self.window.set_custom_cursor(_cursor.clone());
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
     /// ```no_run
@@ -1241,6 +1405,8 @@ impl Window {
 
     /// Set grabbing [mode]([`CursorGrabMode`]) on the cursor preventing it from leaving the window.
     ///
+    /// The grabbing modes set with [`Window::set_seat_cursor_grab`] are replaced as well.
+    ///
     /// # Example
     ///
     /// First try confining the cursor, and if that fails, try locking it instead.
@@ -1259,6 +1425,30 @@ impl Window {
         self.window.set_cursor_grab(mode)
     }
 
+    /// Set grabbing [mode]([`CursorGrabMode`]) on the cursor of the given seat only.
+    ///
+    /// The mode is kept until the one of the whole window is set again with
+    /// [`Window::set_cursor_grab`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** [`CursorGrabMode::Locked`] isn't supported, like with
+    ///   [`Window::set_cursor_grab`].
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_seat_cursor_grab(
+        &self,
+        _seat: SeatId,
+        _mode: CursorGrabMode,
+    ) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.window.set_seat_cursor_grab(_seat, _mode);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     /// Modifies the cursor's visibility.
     ///
     /// If `false`, this will hide the cursor. If `true`, this will show the cursor.
@@ -1306,6 +1496,37 @@ impl Window {
         self.window.drag_resize_window(direction)
     }
 
+    /// Starts dragging the `content` out of the window, until the mouse button is released.
+    ///
+    /// The data is offered in all the MIME types of the `content`, and only transferred once the
+    /// target requests it. The `actions` are the ones the target is allowed to choose from, in the
+    /// order of preference, and the optional `icon` is shown under the cursor while dragging. The
+    /// outcome of the drag is reported with [`WindowEvent::DragSource`].
+    ///
+    /// There's no guarantee that this will work unless a mouse button was pressed immediately
+    /// before this function is called.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** [`DndAction::Link`] isn't supported, and is treated as [`DndAction::Copy`].
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::DragSource`]: crate::event::WindowEvent::DragSource
+    #[inline]
+    pub fn start_drag(
+        &self,
+        _content: ClipboardContent,
+        _actions: &[DndAction],
+        _icon: Option<Icon>,
+    ) -> Result<(), ExternalError> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        return self.window.start_drag(_content, _actions, _icon);
+
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     /// Modifies whether the window catches cursor events.
     ///
     /// If `true`, the window will catch the cursor events. If `false`, events are passed through
@@ -1516,6 +1737,85 @@ bitflags! {
     }
 }
 
+/// Where a popup is placed relative to its parent, see [`WindowBuilder::with_popup`].
+///
+/// The popup is attached to a rectangle of the parent, like an item of a menu bar or the
+/// position of the cursor, and extends from its [`anchor`](Self::anchor) point in the direction
+/// of the [`gravity`](Self::gravity). The popup is moved as allowed by the
+/// [`adjustment`](Self::adjustment) when it doesn't fit on the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PopupPlacement {
+    /// The top-left corner of the rectangle the popup is attached to, relative to the client
+    /// area of the parent.
+    pub anchor_position: Position,
+
+    /// The size of the rectangle the popup is attached to.
+    pub anchor_size: Size,
+
+    /// The point of the rectangle the popup is attached to.
+    pub anchor: PopupAnchor,
+
+    /// The direction the popup extends to from the anchor point.
+    pub gravity: PopupAnchor,
+
+    /// How the popup is moved when it doesn't fit on the screen.
+    pub adjustment: PopupAdjustment,
+
+    /// Whether the popup takes the pointer and keyboard input, and is dismissed when clicking
+    /// outside of the windows of the application, like a menu. Tooltips don't grab the input.
+    pub grab: bool,
+}
+
+impl PopupPlacement {
+    /// A popup grabbing the input, extending down and to the right from the bottom-left corner
+    /// of the rectangle, like a drop-down menu.
+    pub fn new<P: Into<Position>, S: Into<Size>>(anchor_position: P, anchor_size: S) -> Self {
+        Self {
+            anchor_position: anchor_position.into(),
+            anchor_size: anchor_size.into(),
+            anchor: PopupAnchor::BottomLeft,
+            gravity: PopupAnchor::BottomRight,
+            adjustment: PopupAdjustment::SLIDE_X | PopupAdjustment::FLIP_Y,
+            grab: true,
+        }
+    }
+}
+
+/// A point of a rectangle, or a direction from it, see [`PopupPlacement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupAnchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+bitflags! {
+    /// The ways a popup may be moved to fit on the screen, see [`PopupPlacement`].
+    ///
+    /// The popup is first flipped, then slid, and then resized, along each axis.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PopupAdjustment: u32 {
+        /// Move the popup along the x axis until it fits.
+        const SLIDE_X = 1 << 0;
+        /// Move the popup along the y axis until it fits.
+        const SLIDE_Y = 1 << 1;
+        /// Mirror the anchor and the gravity along the x axis, if the popup then fits.
+        const FLIP_X = 1 << 2;
+        /// Mirror the anchor and the gravity along the y axis, if the popup then fits.
+        const FLIP_Y = 1 << 3;
+        /// Shrink the popup along the x axis to the part fitting on the screen.
+        const RESIZE_X = 1 << 4;
+        /// Shrink the popup along the y axis to the part fitting on the screen.
+        const RESIZE_Y = 1 << 5;
+    }
+}
+
 /// A window level groups windows with respect to their z-position.
 ///
 /// The relative ordering between windows in different window levels is fixed.
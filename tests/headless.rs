@@ -0,0 +1,277 @@
+#![cfg(any(x11_platform, wayland_platform))]
+
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use winit::device::DeviceKind;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, Ime, StartCause, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopBuilder};
+use winit::keyboard::{Key, KeyCode};
+use winit::platform::event_sources::EventLoopWindowTargetExtEventSources;
+use winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopWindowTargetExtHeadless};
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+#[cfg(all(wayland_platform, not(x11_platform)))]
+use winit::platform::wayland::EventLoopBuilderExtWayland;
+#[cfg(x11_platform)]
+use winit::platform::x11::EventLoopBuilderExtX11;
+use winit::window::{CursorGrabMode, Window};
+
+fn window_events(event_loop: &mut EventLoop<()>) -> Vec<WindowEvent<'static>> {
+    let mut events = Vec::new();
+    event_loop.pump_events(Some(Duration::ZERO), |event, _, _| {
+        if let Event::WindowEvent { event, .. } = event {
+            events.extend(event.to_static());
+        }
+    });
+    events
+}
+
+#[test]
+fn headless_window() {
+    let mut event_loop = EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build();
+    assert!(event_loop.is_headless());
+
+    let window = Window::new(&event_loop).unwrap();
+    let input = event_loop.headless_input().unwrap();
+    window_events(&mut event_loop);
+
+    // The setters take effect right away.
+    assert_eq!(
+        window.request_inner_size(PhysicalSize::new(640, 480)),
+        Some(PhysicalSize::new(640, 480))
+    );
+    window.set_maximized(true);
+    assert_eq!(window.inner_size(), PhysicalSize::new(1920, 1080));
+    window.set_maximized(false);
+    assert_eq!(
+        window_events(&mut event_loop),
+        [
+            WindowEvent::Resized(PhysicalSize::new(640, 480)),
+            WindowEvent::Resized(PhysicalSize::new(1920, 1080)),
+            WindowEvent::Resized(PhysicalSize::new(640, 480)),
+        ]
+    );
+
+    // The injected input flows through the event loop.
+    input.keyboard_input(
+        window.id(),
+        KeyCode::KeyA,
+        Key::Character("a".into()),
+        Some("a"),
+        ElementState::Pressed,
+    );
+    match &window_events(&mut event_loop)[..] {
+        [WindowEvent::KeyboardInput { event, .. }] => {
+            assert_eq!(event.physical_key, KeyCode::KeyA);
+            assert_eq!(event.text.as_deref(), Some("a"));
+            assert!(event.timestamp.is_some());
+        }
+        events => panic!("unexpected events: {events:?}"),
+    }
+
+    // The grab of the cursor is honored.
+    window.set_cursor_grab(CursorGrabMode::Confined).unwrap();
+    input.cursor_moved(window.id(), PhysicalPosition::new(1000., 10.));
+    assert!(matches!(
+        &window_events(&mut event_loop)[..],
+        [
+            WindowEvent::CursorEntered { .. },
+            WindowEvent::CursorMoved { position, .. },
+        ] if *position == PhysicalPosition::new(640., 10.)
+    ));
+
+    // The input method events are only delivered once IME is allowed.
+    input.ime(window.id(), Ime::Commit("a".into()));
+    window.set_ime_allowed(true);
+    input.ime(window.id(), Ime::Commit("b".into()));
+    assert_eq!(
+        window_events(&mut event_loop),
+        [
+            WindowEvent::Ime(Ime::Enabled),
+            WindowEvent::Ime(Ime::Commit("b".into())),
+        ]
+    );
+
+    // The injected input comes from the listed devices.
+    let kinds: Vec<_> = event_loop
+        .available_devices()
+        .map(|device| device.kind())
+        .collect();
+    assert_eq!(kinds, [DeviceKind::Keyboard, DeviceKind::Pointer]);
+
+    drop(window);
+    assert_eq!(window_events(&mut event_loop), [WindowEvent::Destroyed]);
+
+    // The event loop can be created again once dropped.
+    drop(event_loop);
+    let event_loop = EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build();
+    assert!(event_loop.is_headless());
+}
+
+#[test]
+fn parallel_event_loops() {
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            thread::spawn(|| {
+                let mut event_loop = EventLoopBuilder::new()
+                    .with_headless()
+                    .with_any_thread(true)
+                    .build();
+                let window = Window::new(&event_loop).unwrap();
+                let input = event_loop.headless_input().unwrap();
+                window_events(&mut event_loop);
+
+                // Each event loop only sees the input of its own windows.
+                input.focus(window.id(), true);
+                window_events(&mut event_loop)
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), [WindowEvent::Focused(true)]);
+    }
+}
+
+fn headless_event_loop() -> EventLoop<()> {
+    EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build()
+}
+
+#[test]
+fn pump_events_without_events() {
+    let mut event_loop = headless_event_loop();
+    let proxy = event_loop.create_proxy();
+    let _window = Window::new(&event_loop).unwrap();
+    event_loop.pump_events(Some(Duration::ZERO), |_, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+    });
+
+    // Nothing is dispatched until there's an event.
+    let mut count = 0;
+    for timeout in [Duration::ZERO, Duration::from_millis(10)] {
+        event_loop.pump_events(Some(timeout), |_, _, _| count += 1);
+    }
+    assert_eq!(count, 0);
+
+    proxy.send_event(()).unwrap();
+    let mut user_events = 0;
+    event_loop.pump_events(Some(Duration::ZERO), |event, _, _| {
+        if let Event::UserEvent(()) = event {
+            user_events += 1;
+        }
+    });
+    assert_eq!(user_events, 1);
+}
+
+#[test]
+fn pump_events_until_deadline() {
+    let mut event_loop = headless_event_loop();
+    let deadline = Instant::now() + Duration::from_millis(50);
+    let set_deadline = |control_flow: &mut ControlFlow| {
+        *control_flow = ControlFlow::WaitUntil(deadline);
+    };
+    event_loop.pump_events(Some(Duration::ZERO), |_, _, control_flow| {
+        set_deadline(control_flow)
+    });
+
+    // The time spent between the calls counts towards the deadline, and nothing runs before it.
+    let mut resumed = None;
+    while resumed.is_none() {
+        event_loop.pump_events(Some(Duration::ZERO), |event, _, control_flow| {
+            if let Event::NewEvents(cause) = event {
+                resumed = Some(cause);
+            }
+            set_deadline(control_flow);
+        });
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(Instant::now() >= deadline);
+    assert!(matches!(
+        resumed,
+        Some(StartCause::ResumeTimeReached { requested_resume, .. }) if requested_resume == deadline
+    ));
+}
+
+#[test]
+fn pump_events_exits_once() {
+    let mut event_loop = headless_event_loop();
+    let mut iterations = 0;
+    let mut destroyed = 0;
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        statuses.push(
+            event_loop.pump_events(Some(Duration::ZERO), |event, _, control_flow| match event {
+                Event::RedrawEventsCleared => {
+                    iterations += 1;
+                    if iterations == 2 {
+                        *control_flow = ControlFlow::ExitWithCode(3);
+                    }
+                }
+                Event::LoopDestroyed => destroyed += 1,
+                _ => (),
+            }),
+        );
+    }
+
+    assert_eq!(
+        statuses,
+        [
+            PumpStatus::Continue,
+            PumpStatus::Exit(3),
+            PumpStatus::Continue
+        ]
+    );
+    assert_eq!(destroyed, 1);
+}
+
+#[test]
+fn event_sources() {
+    let mut event_loop = headless_event_loop();
+    let (reader, mut writer) = UnixStream::pair().unwrap();
+    let fd_source = event_loop.register_fd(reader.as_raw_fd()).unwrap();
+    let timer_source = event_loop
+        .register_timer(Instant::now() + Duration::from_millis(10), None)
+        .unwrap();
+    writer.write_all(&[0]).unwrap();
+
+    let mut ready = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while !ready.contains(&timer_source) && Instant::now() < deadline {
+        event_loop.pump_events(Some(Duration::from_millis(50)), |event, _, _| {
+            if let Event::SourceReady(source) = event {
+                ready.push(source);
+            }
+        });
+    }
+    assert!(ready.contains(&fd_source));
+    assert_eq!(
+        ready
+            .iter()
+            .filter(|&&source| source == timer_source)
+            .count(),
+        1
+    );
+
+    // The file descriptor isn't reported anymore once it's unregistered.
+    event_loop.unregister_source(fd_source);
+    ready.clear();
+    event_loop.pump_events(Some(Duration::ZERO), |event, _, _| {
+        if let Event::SourceReady(source) = event {
+            ready.push(source);
+        }
+    });
+    assert!(ready.is_empty());
+}
@@ -0,0 +1,161 @@
+#![cfg(all(feature = "record", any(x11_platform, wayland_platform)))]
+
+use std::thread;
+use std::time::Duration;
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, Event, WindowEvent};
+use winit::event_loop::{EventLoop, EventLoopBuilder};
+use winit::keyboard::{Key, KeyCode};
+use winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopWindowTargetExtHeadless};
+use winit::platform::pump_events::EventLoopExtPumpEvents;
+#[cfg(all(wayland_platform, not(x11_platform)))]
+use winit::platform::wayland::EventLoopBuilderExtWayland;
+#[cfg(x11_platform)]
+use winit::platform::x11::EventLoopBuilderExtX11;
+use winit::record::{Recorder, Replayer};
+use winit::window::Window;
+
+fn headless_event_loop() -> EventLoop<()> {
+    EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build()
+}
+
+#[test]
+fn record_and_replay() {
+    let path = std::env::temp_dir().join(format!("winit-record-{}.jsonl", std::process::id()));
+
+    let mut event_loop = headless_event_loop();
+    let window = Window::new(&event_loop).unwrap();
+    let input = event_loop.headless_input().unwrap();
+
+    let mut recorded = Vec::new();
+    let mut handler =
+        Recorder::create(&path)
+            .unwrap()
+            .wrap(|event: Event<'_, ()>, _: &_, _: &mut _| {
+                if let Event::WindowEvent { event, .. } = event {
+                    recorded.extend(event.to_static());
+                }
+            });
+
+    input.cursor_moved(window.id(), PhysicalPosition::new(10., 20.));
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    input.keyboard_input(
+        window.id(),
+        KeyCode::KeyA,
+        Key::Character("a".into()),
+        Some("a"),
+        ElementState::Pressed,
+    );
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    drop(handler);
+    drop(window);
+    drop(event_loop);
+
+    // The session is replayed into another window, as fast as possible.
+    let mut event_loop = headless_event_loop();
+    let window = Window::new(&event_loop).unwrap();
+    let mut replayer = Replayer::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    replayer.set_realtime(false);
+    replayer.set_window(0, window.id());
+
+    let mut replayed = Vec::new();
+    let mut handler = replayer.wrap(|event: Event<'_, ()>, _: &_, _: &mut _| {
+        if let Event::WindowEvent { window_id, event } = event {
+            assert_eq!(window_id, window.id());
+            replayed.extend(event.to_static());
+        }
+    });
+    for _ in 0..2 {
+        event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    }
+    drop(handler);
+
+    assert_eq!(recorded.len(), 3);
+    assert_eq!(recorded.len(), replayed.len());
+    for (recorded, replayed) in recorded.iter().zip(&replayed) {
+        match (recorded, replayed) {
+            (
+                WindowEvent::KeyboardInput {
+                    event: recorded, ..
+                },
+                WindowEvent::KeyboardInput {
+                    event: replayed, ..
+                },
+            ) => {
+                // The timestamps are shifted to the start of the replay.
+                assert!(replayed.timestamp.is_some());
+                let mut replayed = replayed.clone();
+                replayed.timestamp = recorded.timestamp;
+                assert_eq!(*recorded, replayed);
+            }
+            (
+                WindowEvent::CursorMoved {
+                    position: recorded, ..
+                },
+                WindowEvent::CursorMoved {
+                    position: replayed, ..
+                },
+            ) => assert_eq!(recorded, replayed),
+            (WindowEvent::CursorEntered { .. }, WindowEvent::CursorEntered { .. }) => (),
+            events => panic!("unexpected events: {events:?}"),
+        }
+    }
+}
+
+#[test]
+fn replay_with_redraw_only_window() {
+    let path =
+        std::env::temp_dir().join(format!("winit-record-redraw-{}.jsonl", std::process::id()));
+
+    // The first window only appears through `RedrawRequested`, which isn't recorded.
+    let mut event_loop = headless_event_loop();
+    let redrawn = Window::new(&event_loop).unwrap();
+    let window = Window::new(&event_loop).unwrap();
+    let input = event_loop.headless_input().unwrap();
+
+    let mut handler = Recorder::create(&path)
+        .unwrap()
+        .wrap(|_: Event<'_, ()>, _: &_, _: &mut _| ());
+    redrawn.request_redraw();
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    window.request_redraw();
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    thread::sleep(Duration::from_millis(50));
+    input.cursor_moved(window.id(), PhysicalPosition::new(10., 20.));
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    drop(handler);
+    drop((redrawn, window));
+    drop(event_loop);
+
+    // The windows are matched in the order in which they appear by default.
+    let mut event_loop = headless_event_loop();
+    let redrawn = Window::new(&event_loop).unwrap();
+    let window = Window::new(&event_loop).unwrap();
+    let replayer = Replayer::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut replayed = Vec::new();
+    let mut handler = replayer.wrap(|event: Event<'_, ()>, _: &_, _: &mut _| {
+        if let Event::WindowEvent {
+            window_id,
+            event: WindowEvent::CursorMoved { .. },
+        } = event
+        {
+            replayed.push(window_id);
+        }
+    });
+    redrawn.request_redraw();
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    window.request_redraw();
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    thread::sleep(Duration::from_millis(60));
+    event_loop.pump_events(Some(Duration::ZERO), &mut handler);
+    drop(handler);
+
+    assert_eq!(replayed, [window.id()]);
+}
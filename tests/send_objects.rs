@@ -23,3 +23,9 @@ fn ids_send() {
     needs_send::<winit::event::DeviceId>();
     needs_send::<winit::monitor::MonitorHandle>();
 }
+
+#[test]
+fn custom_cursor_send() {
+    // ensures that `winit::CustomCursor` implements `Send`
+    needs_send::<winit::window::CustomCursor>();
+}
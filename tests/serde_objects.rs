@@ -2,8 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 use winit::{
+    clipboard::ClipboardKind,
+    dnd::{DndAction, DragSourceEvent},
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
-    event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase},
+    event::{
+        ElementState, MouseButton, MouseScrollDelta, PenAxes, PenButton, PenEvent, PenTool,
+        TouchPhase,
+    },
     keyboard::{Key, KeyCode, KeyLocation, ModifiersState},
     window::CursorIcon,
 };
@@ -22,10 +27,17 @@ fn events_serde() {
     needs_serde::<ElementState>();
     needs_serde::<MouseButton>();
     needs_serde::<MouseScrollDelta>();
+    needs_serde::<PenEvent>();
+    needs_serde::<PenTool>();
+    needs_serde::<PenAxes>();
+    needs_serde::<PenButton>();
     needs_serde::<Key>();
     needs_serde::<KeyCode>();
     needs_serde::<KeyLocation>();
     needs_serde::<ModifiersState>();
+    needs_serde::<ClipboardKind>();
+    needs_serde::<DndAction>();
+    needs_serde::<DragSourceEvent>();
 }
 
 #[test]
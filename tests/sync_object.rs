@@ -6,3 +6,9 @@ fn window_sync() {
     // ensures that `winit::Window` implements `Sync`
     needs_sync::<winit::window::Window>();
 }
+
+#[test]
+fn custom_cursor_sync() {
+    // ensures that `winit::CustomCursor` implements `Sync`
+    needs_sync::<winit::window::CustomCursor>();
+}